@@ -0,0 +1,208 @@
+//! Measure the size and execution units of programs, to compare them
+//! against each other or against an earlier run.
+
+use serde::{Deserialize, Serialize};
+use uplc::{
+    ast::{DeBruijn, NamedDeBruijn, PlutusVersion, Program},
+    data::PlutusData,
+    machine::cost_model::ExBudget,
+};
+
+/// A program evaluated with a set of arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub program: String,
+    /// The hex encoded CBOR data the program was applied to.
+    pub arguments: Vec<String>,
+    /// Length in bytes of the program flat encoded, without it's
+    /// arguments.
+    pub size: usize,
+    pub cpu: i64,
+    pub mem: i64,
+    /// Why evaluation failed, the units are then the ones consumed
+    /// until the failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A measurement along with it's change from the baseline, in
+/// percents.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    #[serde(flatten)]
+    pub measurement: Measurement,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_delta: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_delta: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_delta: Option<f64>,
+}
+
+pub fn measure(
+    name: &str,
+    program: &Program<DeBruijn>,
+    arguments: &[(String, PlutusData)],
+    version: PlutusVersion,
+) -> anyhow::Result<Measurement> {
+    let size = program.to_flat_with_version(version)?.len();
+
+    let applied: Program<NamedDeBruijn> = arguments
+        .iter()
+        .fold(program.clone(), |program, (_, data)| {
+            program.apply_data(data.clone())
+        })
+        .into();
+
+    let result = applied.eval_with_version(version, ExBudget::default());
+
+    Ok(Measurement {
+        program: name.to_string(),
+        arguments: arguments.iter().map(|(hex, _)| hex.clone()).collect(),
+        size,
+        cpu: result.budget.cpu,
+        mem: result.budget.mem,
+        error: result.term.err().map(|error| error.to_string()),
+    })
+}
+
+/// Pair each measurement with the baseline measurement of the same
+/// program and arguments, if there is one.
+pub fn compare(measurements: Vec<Measurement>, baseline: &[Measurement]) -> Vec<Comparison> {
+    measurements
+        .into_iter()
+        .map(|measurement| {
+            let before = baseline.iter().find(|before| {
+                before.program == measurement.program && before.arguments == measurement.arguments
+            });
+
+            let delta = |field: fn(&Measurement) -> i64| {
+                before.and_then(|before| percent(field(before), field(&measurement)))
+            };
+
+            Comparison {
+                size_delta: delta(|m| m.size as i64),
+                cpu_delta: delta(|m| m.cpu),
+                mem_delta: delta(|m| m.mem),
+                measurement,
+            }
+        })
+        .collect()
+}
+
+fn percent(before: i64, after: i64) -> Option<f64> {
+    if before == 0 {
+        None
+    } else {
+        Some((after - before) as f64 * 100.0 / before as f64)
+    }
+}
+
+/// Lay the comparisons out as a table, one row per measurement.
+pub fn table(comparisons: &[Comparison]) -> String {
+    let cell = |value: i64, delta: Option<f64>| match delta {
+        Some(delta) => format!("{value} ({delta:+.2}%)"),
+        None => value.to_string(),
+    };
+
+    let header = ["program", "arguments", "size", "cpu", "mem", "error"].map(String::from);
+
+    let rows: Vec<[String; 6]> = comparisons
+        .iter()
+        .map(|comparison| {
+            let measurement = &comparison.measurement;
+
+            [
+                measurement.program.clone(),
+                measurement.arguments.join(" "),
+                cell(measurement.size as i64, comparison.size_delta),
+                cell(measurement.cpu, comparison.cpu_delta),
+                cell(measurement.mem, comparison.mem_delta),
+                // errors can span lines, which would break the table
+                measurement
+                    .error
+                    .as_deref()
+                    .and_then(|error| error.lines().next())
+                    .unwrap_or_default()
+                    .to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|title| title.len());
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use uplc::{
+        ast::{DeBruijn, PlutusVersion, Program},
+        data::PlutusData,
+        parser,
+    };
+
+    use super::{compare, measure, table, Measurement};
+
+    fn program(code: &str) -> Program<DeBruijn> {
+        parser::program(code).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn measures_and_compares() {
+        let identity = program("(program 1.0.0 (lam x x))");
+        let failing = program("(program 1.0.0 (lam x (error)))");
+
+        let arguments = [(
+            hex::encode(PlutusData::Integer(1.into()).to_cbor()),
+            PlutusData::Integer(1.into()),
+        )];
+
+        let before = measure("id", &identity, &arguments, PlutusVersion::V2).unwrap();
+
+        assert_eq!(before.arguments, vec!["01".to_string()]);
+        assert!(before.cpu > 0 && before.mem > 0);
+        assert_eq!(before.error, None);
+
+        let after = Measurement {
+            cpu: before.cpu / 2,
+            ..before.clone()
+        };
+
+        let failed = measure("fail", &failing, &arguments, PlutusVersion::V2).unwrap();
+
+        assert!(failed.error.is_some());
+
+        let comparisons = compare(vec![after, failed], &[before]);
+
+        assert_eq!(comparisons[0].size_delta, Some(0.0));
+        assert_eq!(comparisons[0].cpu_delta, Some(-50.0));
+        assert_eq!(comparisons[1].cpu_delta, None);
+
+        let table = table(&comparisons);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("program  arguments  size"));
+        assert!(lines[1].contains("(-50.00%)"));
+        assert!(lines[2].starts_with("fail"));
+    }
+}
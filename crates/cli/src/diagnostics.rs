@@ -0,0 +1,145 @@
+//! Project errors as diagnostics editors and CI can read, one JSON
+//! object per line, see `aiken check --diagnostics json-lines`.
+
+use std::{path::PathBuf, str::FromStr};
+
+use miette::{Diagnostic as _, LabeledSpan, Severity};
+use serde::Serialize;
+
+use aiken_project::error::Error;
+
+/// How `aiken check` prints what it found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    Human,
+    JsonLines,
+}
+
+impl FromStr for DiagnosticFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "human" => Ok(DiagnosticFormat::Human),
+            "json-lines" => Ok(DiagnosticFormat::JsonLines),
+            _ => Err(format!(
+                "unknown diagnostics format {format}, expected human or json-lines"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: &'static str,
+    pub code: Option<String>,
+    pub message: String,
+    pub path: Option<PathBuf>,
+    /// Where the problem is, the first label of the error
+    pub span: Option<Span>,
+    /// The other places the error points at
+    pub related: Vec<Span>,
+    pub help: Option<String>,
+}
+
+/// A byte range of a source file, with the 1-based line and column
+/// it starts at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub label: Option<String>,
+}
+
+impl Diagnostic {
+    /// A project error bundles the errors of a whole module, each of
+    /// them becomes a diagnostic of it's own.
+    pub fn from_error(error: &Error) -> Vec<Diagnostic> {
+        let path = error.path().map(PathBuf::from);
+        let src = error.src().unwrap_or_default();
+
+        match error.related() {
+            Some(related) => related
+                .map(|inner| Diagnostic::new(inner, path.clone(), src))
+                .collect(),
+            None => vec![Diagnostic::new(error, path, src)],
+        }
+    }
+
+    fn new(diagnostic: &dyn miette::Diagnostic, path: Option<PathBuf>, src: &str) -> Self {
+        let mut spans = diagnostic
+            .labels()
+            .into_iter()
+            .flatten()
+            .map(|label| Span::new(&label, src));
+
+        let span = spans.next();
+
+        Diagnostic {
+            severity: match diagnostic.severity().unwrap_or(Severity::Error) {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Advice => "advice",
+            },
+            code: diagnostic.code().map(|code| code.to_string()),
+            message: diagnostic.to_string(),
+            path,
+            span,
+            related: spans.collect(),
+            help: diagnostic.help().map(|help| help.to_string()),
+        }
+    }
+}
+
+impl Span {
+    fn new(label: &LabeledSpan, src: &str) -> Self {
+        let start = label.offset();
+
+        // spans past the end, e.g. at an unexpected end of input,
+        // point at the last position
+        let mut end = start.min(src.len());
+
+        while !src.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let before = &src[..end];
+
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+        Span {
+            start,
+            end: start + label.len(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            label: label.label().map(String::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn span_position() {
+        let src = "fn a() {\n  é + x\n}";
+
+        let span = Span::new(&LabeledSpan::new(Some("here".into()), 16, 1), src);
+
+        assert_eq!(
+            span,
+            Span {
+                start: 16,
+                end: 17,
+                line: 2,
+                column: 7,
+                label: Some("here".to_string()),
+            }
+        );
+
+        assert_eq!(Span::new(&LabeledSpan::new(None, 100, 0), src).line, 3);
+    }
+}
@@ -1,17 +1,116 @@
-use std::path::PathBuf;
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use diagnostics::DiagnosticFormat;
+use uplc::{address::Network, ast::PlutusVersion};
+
+pub mod bench;
+pub mod diagnostics;
+pub mod repl;
 
 /// Cardano smart contract toolchain
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 #[clap(propagate_version = true)]
 pub enum Cli {
+    /// Compile the validators of an Aiken project
+    Build {
+        /// The project's root, containing `aiken.toml`
+        #[clap(short, long, default_value = ".")]
+        directory: PathBuf,
+        /// Simplify the compiled validators
+        #[clap(long)]
+        optimize: bool,
+        /// Compile the validators again with the options recorded in
+        /// `plutus.json` and check they are the same, without writing
+        /// anything
+        #[clap(long, conflicts_with = "optimize")]
+        verify: bool,
+    },
+    /// Parse and type check every module of an Aiken project,
+    /// reporting all the errors found
+    Check {
+        /// The project's root, containing `aiken.toml`
+        #[clap(short, long, default_value = ".")]
+        directory: PathBuf,
+        /// How errors are printed: human, or json-lines for one JSON
+        /// object per diagnostic on stdout
+        #[clap(long, default_value = "human")]
+        diagnostics: DiagnosticFormat,
+    },
+    /// Commands for the validators of a built project's blueprint
+    #[clap(subcommand)]
+    Blueprint(BlueprintCommand),
+    /// Commands for the build results cached under `.aiken/cache/`
+    #[clap(subcommand)]
+    Cache(CacheCommand),
+    /// Run the language server, for editors to talk to over stdin
+    /// and stdout
+    Lsp,
     /// A subcommand for working with Untyped Plutus Core
     #[clap(subcommand)]
     Uplc(UplcCommand),
 }
 
+/// Commands for the validators of a built project's blueprint
+#[derive(Subcommand)]
+pub enum BlueprintCommand {
+    /// Print the hash of a validator
+    Hash(Selection),
+    /// Print the address of a spending validator, without a staking part
+    Address {
+        #[clap(flatten)]
+        selection: Selection,
+        /// The network to build the address for, mainnet or testnet
+        #[clap(short, long, default_value = "mainnet")]
+        network: Network,
+    },
+    /// Print the policy id of a minting validator
+    Policy(Selection),
+    /// Apply a validator to parameters, printing the hex encoded CBOR
+    /// of the applied script and it's hash
+    Apply {
+        #[clap(flatten)]
+        selection: Selection,
+        /// Hex encoded CBOR data to apply the validator to, in order
+        arguments: Vec<String>,
+    },
+}
+
+/// Commands for the build results cached under `.aiken/cache/`
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Print how many entries of each kind are cached and their size
+    Info {
+        /// The project's root, containing `aiken.toml`
+        #[clap(short, long, default_value = ".")]
+        directory: PathBuf,
+    },
+    /// Remove every cached entry
+    Clear {
+        /// The project's root, containing `aiken.toml`
+        #[clap(short, long, default_value = ".")]
+        directory: PathBuf,
+    },
+}
+
+/// Which validator of which project a blueprint command is about.
+#[derive(Args)]
+pub struct Selection {
+    /// The project's root, containing `plutus.json`
+    #[clap(short, long, default_value = ".")]
+    pub directory: PathBuf,
+    /// `module.function`, or either part alone when it's unique. Can
+    /// be left out when the project has a single validator
+    #[clap(short, long)]
+    pub validator: Option<String>,
+}
+
 /// Commands for working with Untyped Plutus Core
 #[derive(Subcommand)]
 pub enum UplcCommand {
@@ -22,13 +121,295 @@ pub enum UplcCommand {
         print: bool,
         #[clap(short, long)]
         out: Option<String>,
+        /// Also write the original names, where they were and the
+        /// comments to a `.ann` sidecar file
+        #[clap(short, long)]
+        annotations: bool,
+        /// Read the input as Typed Plutus Core and erase it's types
+        #[clap(short, long)]
+        typed: bool,
+        /// Simplify the program before encoding it
+        #[clap(long)]
+        optimize: bool,
+        /// How variables are written: debruijn, as on chain, or
+        /// named-debruijn or name to keep their names
+        #[clap(long, default_value = "debruijn", conflicts_with = "annotations")]
+        naming: Naming,
+        /// The Plutus version the script is meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
     },
     /// Decode flat bytes to textual Untyped Plutus Core
     Unflat {
         input: PathBuf,
         #[clap(short, long)]
         print: bool,
+        /// How variables are written: debruijn, named-debruijn or name
+        #[clap(long, default_value = "debruijn", conflicts_with = "annotations")]
+        naming: Naming,
+        /// Restore original names and comments from a `.ann` sidecar file
+        #[clap(short, long)]
+        annotations: Option<PathBuf>,
+        /// Read the input as a hex encoded CBOR script, as found on chain
+        #[clap(short, long)]
+        cbor: bool,
+        /// Read the input as flat Typed Plutus Core with named
+        /// variables, as plutus-tx writes it, and erase it's types
+        #[clap(short, long, conflicts_with_all = &["cbor", "annotations"])]
+        typed: bool,
+        /// The Plutus version the script is meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+    },
+    /// Encode textual Untyped Plutus Core to another format, printing
+    /// the result
+    Encode {
+        input: PathBuf,
+        /// flat, cbor (hex encoded) or json
+        #[clap(long, default_value = "json")]
+        to: Format,
+    },
+    /// Decode a program to textual Untyped Plutus Core
+    Decode {
+        input: PathBuf,
+        /// flat, cbor (hex encoded) or json
+        #[clap(long, default_value = "json")]
+        from: Format,
+    },
+    /// Format an Untyped Plutus Core source file in place
+    Fmt {
+        input: PathBuf,
+        /// Don't write the file, exit with an error if it isn't formatted
+        #[clap(short, long)]
+        check: bool,
+        /// Also make sure every variable is bound by a lambda
+        #[clap(short, long)]
+        scopes: bool,
+    },
+    /// Report free variables and shadowed parameters in a program
+    Check {
+        input: PathBuf,
+        /// Read the input as flat bytes instead of text
+        #[clap(short, long)]
+        flat: bool,
+    },
+    /// Show where two programs differ, ignoring the names of lambda
+    /// parameters
+    Diff { left: PathBuf, right: PathBuf },
+    /// Evaluate an Untyped Plutus Core program
+    Eval {
+        script: PathBuf,
+        /// Read the script as flat bytes instead of text
+        #[clap(short, long)]
+        flat: bool,
+        /// Print the messages traced during evaluation
+        #[clap(short, long)]
+        trace: bool,
+        /// Where traces go, stderr or a file, so they aren't mixed up
+        /// with the result on stdout. Implies --trace
+        #[clap(long)]
+        trace_output: Option<TraceOutput>,
+        /// The Plutus version the script is meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+    },
+    /// Step through the evaluation of an Untyped Plutus Core program
+    Debug {
+        script: PathBuf,
+        /// Read the script as flat bytes instead of text
+        #[clap(short, long)]
+        flat: bool,
+        /// The Plutus version the script is meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+    },
+    /// Compute the hash a script is identified by on chain
+    Hash {
+        input: PathBuf,
+        /// Read the input as a hex encoded CBOR script, as found on chain
+        #[clap(short, long)]
+        cbor: bool,
+        /// The Plutus version the script is meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+        /// Print the script's address instead of it's hash
+        #[clap(short, long)]
+        address: bool,
+        /// The network to build the address for, mainnet or testnet
+        #[clap(short, long, default_value = "mainnet")]
+        network: Network,
+    },
+    /// Apply arguments to a parameterized script
+    Apply {
+        script: PathBuf,
+        /// Hex encoded CBOR data to apply the script to, in order
+        arguments: Vec<String>,
+        /// Read the script as a hex encoded CBOR script and print the
+        /// applied script the same way
+        #[clap(short, long)]
+        cbor: bool,
+    },
+    /// Compare the flat size and execution units of programs
+    Bench {
+        #[clap(required = true)]
+        scripts: Vec<PathBuf>,
+        /// Read the scripts as flat bytes instead of text
+        #[clap(short, long)]
+        flat: bool,
+        /// Comma separated hex encoded CBOR data to apply each script
+        /// to, repeat to evaluate them with several sets of arguments
+        #[clap(short, long)]
+        arguments: Vec<String>,
+        /// A JSON report of an earlier run to show changes against
+        #[clap(short, long)]
+        baseline: Option<PathBuf>,
+        /// Print the report as JSON instead of a table
+        #[clap(short, long)]
+        json: bool,
+        /// The Plutus version the scripts are meant for, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+    },
+    /// Report which builtins a program uses, how much of it's size is
+    /// constants and how deeply it's terms nest
+    Stats {
+        input: PathBuf,
+        /// Read the input as flat bytes instead of text
+        #[clap(short, long)]
+        flat: bool,
+        /// Print the report as JSON instead of a table
+        #[clap(short, long)]
+        json: bool,
     },
+    /// Run the test vectors under a directory laid out like the
+    /// Plutus conformance suite
+    Conformance { dir: PathBuf },
+    /// Evaluate terms interactively, binding results to names
+    Repl {
+        /// A file of lines to run first, e.g. a session saved with `:save`
+        #[clap(short, long)]
+        load: Option<PathBuf>,
+        /// The Plutus version terms are evaluated with, v1 or v2
+        #[clap(long, default_value = "v2")]
+        plutus_version: PlutusVersion,
+    },
+    /// Commands for working with transactions
+    #[clap(subcommand)]
+    Tx(TxCommand),
+}
+
+/// Commands for working with transactions
+#[derive(Subcommand)]
+pub enum TxCommand {
+    /// Evaluate the scripts of a transaction and report the execution
+    /// units each redeemer needs
+    Simulate {
+        /// A hex encoded CBOR transaction
+        input: PathBuf,
+        /// A hex encoded CBOR array of the inputs the transaction spends or references
+        raw_inputs: PathBuf,
+        /// A hex encoded CBOR array of the outputs those inputs point to
+        raw_outputs: PathBuf,
+        /// POSIX time in milliseconds of the slot `zero_slot`
+        #[clap(long, default_value_t = 1596059091000)]
+        zero_time: u64,
+        #[clap(long, default_value_t = 4492800)]
+        zero_slot: u64,
+        /// Length of a slot in milliseconds
+        #[clap(long, default_value_t = 1000)]
+        slot_length: u64,
+        /// Print the messages each script traced
+        #[clap(short, long)]
+        trace: bool,
+        /// Where traces go, stderr or a file, so they aren't mixed up
+        /// with the execution units on stdout. Implies --trace
+        #[clap(long)]
+        trace_output: Option<TraceOutput>,
+    },
+}
+
+/// The formats programs can be encoded to besides text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Flat,
+    Cbor,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "flat" => Ok(Format::Flat),
+            "cbor" => Ok(Format::Cbor),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "unknown format {format}, expected flat, cbor or json"
+            )),
+        }
+    }
+}
+
+/// How variables are written in flat encoded programs. Only debruijn
+/// indices are accepted on chain, the names are kept for debugging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Naming {
+    DeBruijn,
+    NamedDeBruijn,
+    Name,
+}
+
+impl FromStr for Naming {
+    type Err = String;
+
+    fn from_str(naming: &str) -> Result<Self, Self::Err> {
+        match naming.to_lowercase().as_str() {
+            "debruijn" => Ok(Naming::DeBruijn),
+            "named-debruijn" => Ok(Naming::NamedDeBruijn),
+            "name" => Ok(Naming::Name),
+            _ => Err(format!(
+                "unknown naming {naming}, expected debruijn, named-debruijn or name"
+            )),
+        }
+    }
+}
+
+/// Where traced messages are written, away from results on stdout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceOutput {
+    Stderr,
+    File(PathBuf),
+}
+
+impl TraceOutput {
+    /// Tracing without saying where goes to stderr.
+    pub fn new(trace: bool, output: Option<TraceOutput>) -> Option<Self> {
+        output.or(if trace {
+            Some(TraceOutput::Stderr)
+        } else {
+            None
+        })
+    }
+
+    pub fn writer(&self) -> io::Result<Box<dyn Write>> {
+        match self {
+            TraceOutput::Stderr => Ok(Box::new(io::stderr())),
+            TraceOutput::File(path) => Ok(Box::new(File::create(path)?)),
+        }
+    }
+}
+
+impl FromStr for TraceOutput {
+    type Err = String;
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        match output {
+            "stderr" => Ok(TraceOutput::Stderr),
+            "" => Err("expected stderr or a file".to_string()),
+            path => Ok(TraceOutput::File(PathBuf::from(path))),
+        }
+    }
 }
 
 impl Default for Cli {
@@ -36,3 +417,32 @@ impl Default for Cli {
         Self::parse()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::TraceOutput;
+
+    #[test]
+    fn trace_output() {
+        assert_eq!("stderr".parse(), Ok(TraceOutput::Stderr));
+        assert_eq!(
+            "traces.log".parse(),
+            Ok(TraceOutput::File(PathBuf::from("traces.log")))
+        );
+        assert!("".parse::<TraceOutput>().is_err());
+
+        assert_eq!(TraceOutput::new(false, None), None);
+        assert_eq!(TraceOutput::new(true, None), Some(TraceOutput::Stderr));
+
+        // an output implies tracing
+        let file = TraceOutput::File(PathBuf::from("traces.log"));
+
+        assert_eq!(
+            TraceOutput::new(false, Some(file.clone())),
+            Some(file.clone())
+        );
+        assert_eq!(TraceOutput::new(true, Some(file.clone())), Some(file));
+    }
+}
@@ -1,25 +1,201 @@
-use std::fs;
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    str::FromStr,
+};
 
 use uplc::{
-    ast::{DeBruijn, FakeNamedDeBruijn, Program},
-    parser,
+    annotations::Annotations,
+    ast::{DeBruijn, FakeNamedDeBruijn, Name, NamedDeBruijn, PlutusVersion, Program},
+    builtins::DefaultFunction,
+    conformance,
+    data::PlutusData,
+    machine::{
+        cost_model::{CostModels, ExBudget},
+        Machine, MachineState,
+    },
+    optimize::Pass,
+    parser::{self, Spans},
+    tx::{self, SlotConfig},
+    typed,
+};
+
+use aiken::{
+    bench,
+    diagnostics::{Diagnostic, DiagnosticFormat},
+    repl, BlueprintCommand, CacheCommand, Cli, Format, Naming, TraceOutput, TxCommand, UplcCommand,
 };
+use aiken_project::{Options, Project};
+
+fn main() {
+    if let Err(error) = run() {
+        report(error);
 
-use aiken::{Cli, UplcCommand};
+        std::process::exit(1);
+    }
+}
+
+fn report(error: anyhow::Error) {
+    // parse errors come with a snippet of the offending source
+    match error.downcast::<parser::Error>() {
+        Ok(error) => eprintln!("{:?}", miette::Report::new(error)),
+        Err(error) => match error.downcast::<aiken_project::error::Error>() {
+            Ok(error) => eprintln!("{:?}", miette::Report::new(error)),
+            Err(error) => eprintln!("Error: {:?}", error),
+        },
+    }
+}
 
-fn main() -> anyhow::Result<()> {
+fn run() -> anyhow::Result<()> {
     let args = Cli::default();
 
     match args {
+        Cli::Build {
+            directory,
+            optimize,
+            verify,
+        } => {
+            let project = Project::new(directory)?;
+
+            let artifacts = if verify {
+                project.verify()?
+            } else {
+                project.build(Options { optimize })?
+            };
+
+            for artifact in artifacts {
+                println!(
+                    "{}.{} {}",
+                    artifact.module,
+                    artifact.name,
+                    hex::encode(artifact.hash)
+                );
+            }
+        }
+        Cli::Check {
+            directory,
+            diagnostics,
+        } => {
+            let project = Project::new(directory)?;
+
+            let mut failed = false;
+
+            // errors are printed as soon as a module is checked
+            project.check_reporting(&mut |error| {
+                failed = true;
+
+                match diagnostics {
+                    DiagnosticFormat::Human => eprintln!("{:?}", miette::Report::new(error)),
+                    DiagnosticFormat::JsonLines => {
+                        for diagnostic in Diagnostic::from_error(&error) {
+                            println!(
+                                "{}",
+                                serde_json::to_string(&diagnostic)
+                                    .expect("diagnostics are plain data")
+                            );
+                        }
+                    }
+                }
+            });
+
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Cli::Blueprint(command) => {
+            let (selection, network) = match &command {
+                BlueprintCommand::Hash(selection) | BlueprintCommand::Policy(selection) => {
+                    (selection, None)
+                }
+                BlueprintCommand::Address { selection, network } => (selection, Some(*network)),
+                BlueprintCommand::Apply {
+                    selection,
+                    arguments,
+                } => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|argument| Ok(PlutusData::from_cbor(&hex::decode(argument)?)?))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+
+                    let applied = Project::new(selection.directory.clone())?
+                        .apply(selection.validator.as_deref(), &arguments)?;
+
+                    println!("{}", hex::encode(applied.cbor));
+                    println!("{}", hex::encode(applied.hash));
+
+                    return Ok(());
+                }
+            };
+
+            let blueprint = Project::new(selection.directory.clone())?.blueprint()?;
+
+            let validator = blueprint.select(selection.validator.as_deref())?;
+
+            match network {
+                Some(network) => {
+                    let hash = hex::decode(&validator.hash)?;
+
+                    println!("{}", uplc::address::script_address(&hash, network));
+                }
+                // a minting policy's id is it's hash
+                None => println!("{}", validator.hash),
+            }
+        }
+        Cli::Cache(command) => match command {
+            CacheCommand::Info { directory } => {
+                for usage in Project::new(directory)?.cache().usage()? {
+                    println!(
+                        "{:<8} {:>6} entries {:>10} bytes",
+                        usage.kind, usage.entries, usage.bytes
+                    );
+                }
+            }
+            CacheCommand::Clear { directory } => Project::new(directory)?.cache().clear()?,
+        },
+        Cli::Lsp => aiken_lsp::start()?,
         Cli::Uplc(uplc) => match uplc {
-            UplcCommand::Flat { input, print, out } => {
+            UplcCommand::Flat {
+                input,
+                print,
+                out,
+                annotations,
+                typed,
+                optimize,
+                naming,
+                plutus_version,
+            } => {
                 let code = std::fs::read_to_string(&input)?;
 
-                let program = parser::program(&code)?;
+                let (program, spans) = if typed {
+                    (parser::typed_program(&code)?.erase(), Spans::default())
+                } else {
+                    parser::program_with_spans(&code)?
+                };
+
+                // the optimized program's binders aren't the ones in the source
+                let (program, program_annotations) = if optimize {
+                    let program = program.optimize(&Pass::ALL, plutus_version);
+
+                    let program_annotations = Annotations {
+                        comments: spans.comments,
+                        ..Annotations::from_program(&program)
+                    };
 
-                let program = Program::<DeBruijn>::try_from(program)?;
+                    (program, program_annotations)
+                } else {
+                    let program_annotations = Annotations::from_source(&program, spans);
+
+                    (program, program_annotations)
+                };
 
-                let bytes = program.to_flat()?;
+                let bytes = match naming {
+                    Naming::DeBruijn => Program::<DeBruijn>::try_from(program)?
+                        .to_flat_with_version(plutus_version)?,
+                    Naming::NamedDeBruijn => Program::<NamedDeBruijn>::try_from(program)?
+                        .to_flat_with_version(plutus_version)?,
+                    Naming::Name => program.to_flat_with_version(plutus_version)?,
+                };
 
                 if print {
                     for (i, byte) in bytes.iter().enumerate() {
@@ -41,15 +217,502 @@ fn main() -> anyhow::Result<()> {
                     };
 
                     fs::write(&out_name, &bytes)?;
+
+                    if annotations {
+                        fs::write(format!("{}.ann", out_name), program_annotations.to_flat()?)?;
+                    }
                 }
             }
-            UplcCommand::Unflat { input, print } => {
-                let bytes = std::fs::read(&input)?;
+            UplcCommand::Unflat {
+                input,
+                print,
+                naming,
+                annotations,
+                cbor,
+                typed,
+                plutus_version,
+            } => {
+                let bytes = if cbor {
+                    let cbor_hex = std::fs::read_to_string(&input)?;
 
-                let program = Program::<FakeNamedDeBruijn>::from_flat(&bytes)?;
+                    hex::decode(cbor_hex.trim())?
+                } else {
+                    std::fs::read(&input)?
+                };
 
-                if print {
-                    println!("{:#?}", program);
+                if typed {
+                    let program = typed::Program::from_flat(&bytes)?.erase();
+
+                    program.check_version(plutus_version)?;
+
+                    if print {
+                        println!("{}", program);
+                    }
+                } else if naming != Naming::DeBruijn {
+                    let program = match naming {
+                        Naming::NamedDeBruijn if cbor => {
+                            Program::<NamedDeBruijn>::from_cbor_with_version(
+                                &bytes,
+                                plutus_version,
+                            )?
+                            .try_into()?
+                        }
+                        Naming::NamedDeBruijn => Program::<NamedDeBruijn>::from_flat_with_version(
+                            &bytes,
+                            plutus_version,
+                        )?
+                        .try_into()?,
+                        _ if cbor => {
+                            Program::<Name>::from_cbor_with_version(&bytes, plutus_version)?
+                        }
+                        _ => Program::<Name>::from_flat_with_version(&bytes, plutus_version)?,
+                    };
+
+                    if print {
+                        println!("{}", program);
+                    }
+                } else if let Some(annotations) = annotations {
+                    let annotation_bytes = std::fs::read(&annotations)?;
+
+                    let program_annotations = Annotations::from_flat(&annotation_bytes)?;
+
+                    let program = if cbor {
+                        Program::<DeBruijn>::from_cbor_with_version(&bytes, plutus_version)?
+                    } else {
+                        Program::<DeBruijn>::from_flat_with_version(&bytes, plutus_version)?
+                    };
+
+                    let program = program_annotations.restore(program)?;
+
+                    if print {
+                        println!("{}", program_annotations.to_pretty(&program));
+                    }
+                } else {
+                    let program = if cbor {
+                        Program::<FakeNamedDeBruijn>::from_cbor_with_version(
+                            &bytes,
+                            plutus_version,
+                        )?
+                    } else {
+                        Program::<FakeNamedDeBruijn>::from_flat_with_version(
+                            &bytes,
+                            plutus_version,
+                        )?
+                    };
+
+                    if print {
+                        println!("{}", program);
+                    }
+                }
+            }
+            UplcCommand::Encode { input, to } => {
+                let code = std::fs::read_to_string(&input)?;
+
+                let program = parser::program(&code)?;
+
+                match to {
+                    Format::Flat => {
+                        let bytes = Program::<DeBruijn>::try_from(program)?.to_flat()?;
+
+                        io::stdout().write_all(&bytes)?;
+                    }
+                    Format::Cbor => {
+                        let bytes = Program::<DeBruijn>::try_from(program)?.to_cbor()?;
+
+                        println!("{}", hex::encode(bytes));
+                    }
+                    Format::Json => println!("{}", serde_json::to_string_pretty(&program)?),
+                }
+            }
+            UplcCommand::Decode { input, from } => {
+                let program = match from {
+                    Format::Flat => {
+                        let bytes = std::fs::read(&input)?;
+
+                        let program: Program<NamedDeBruijn> =
+                            Program::<FakeNamedDeBruijn>::from_flat(&bytes)?.into();
+
+                        Program::<Name>::try_from(program)?
+                    }
+                    Format::Cbor => {
+                        let cbor_hex = std::fs::read_to_string(&input)?;
+
+                        let program: Program<NamedDeBruijn> =
+                            Program::<FakeNamedDeBruijn>::from_cbor(&hex::decode(
+                                cbor_hex.trim(),
+                            )?)?
+                            .into();
+
+                        Program::<Name>::try_from(program)?
+                    }
+                    Format::Json => {
+                        serde_json::from_str::<Program<Name>>(&std::fs::read_to_string(&input)?)?
+                    }
+                };
+
+                println!("{}", program.to_pretty());
+            }
+            UplcCommand::Fmt {
+                input,
+                check,
+                scopes,
+            } => {
+                let code = std::fs::read_to_string(&input)?;
+
+                let program = parser::program(&code)?;
+
+                if scopes {
+                    Program::<DeBruijn>::try_from(program.clone())?;
+                }
+
+                let formatted = format!("{}\n", program.to_pretty());
+
+                if check {
+                    if formatted != code {
+                        eprintln!("{} is not formatted", input.display());
+
+                        std::process::exit(1);
+                    }
+                } else if formatted != code {
+                    fs::write(&input, formatted)?;
+                }
+            }
+            UplcCommand::Check { input, flat } => {
+                let problems = if flat {
+                    let bytes = std::fs::read(&input)?;
+
+                    Program::<DeBruijn>::from_flat(&bytes)?.check()
+                } else {
+                    let code = std::fs::read_to_string(&input)?;
+
+                    parser::program(&code)?.check()
+                };
+
+                for problem in &problems {
+                    let level = if problem.is_warning() {
+                        "warning"
+                    } else {
+                        "error"
+                    };
+
+                    eprintln!("{}: {}", level, problem);
+                }
+
+                if problems.iter().any(|problem| !problem.is_warning()) {
+                    std::process::exit(1);
+                }
+            }
+            UplcCommand::Diff { left, right } => {
+                let left = parser::program(&std::fs::read_to_string(&left)?)?;
+                let right = parser::program(&std::fs::read_to_string(&right)?)?;
+
+                let differences = left.diff(&right);
+
+                if left.version != right.version {
+                    let version = |(major, minor, patch)| format!("{major}.{minor}.{patch}");
+
+                    println!("in the version");
+                    println!("- {}", version(left.version));
+                    println!("+ {}\n", version(right.version));
+                }
+
+                for difference in &differences {
+                    println!("{}", difference);
+                }
+
+                if left.version != right.version || !differences.is_empty() {
+                    std::process::exit(1);
+                }
+            }
+            UplcCommand::Eval {
+                script,
+                flat,
+                trace,
+                trace_output,
+                plutus_version,
+            } => {
+                let program = if flat {
+                    let bytes = std::fs::read(&script)?;
+
+                    let program = Program::<FakeNamedDeBruijn>::from_flat(&bytes)?;
+
+                    program.into()
+                } else {
+                    let code = std::fs::read_to_string(&script)?;
+
+                    let program = parser::program(&code)?;
+
+                    Program::<NamedDeBruijn>::try_from(program)?
+                };
+
+                let result = program.eval_with_version(plutus_version, ExBudget::default());
+
+                if let Some(output) = TraceOutput::new(trace, trace_output) {
+                    let mut writer = output.writer()?;
+
+                    for log in &result.logs {
+                        writeln!(writer, "{}", log)?;
+                    }
+                }
+
+                let term = Program::<Name>::try_from(Program {
+                    version: program.version,
+                    term: result.term?,
+                })?;
+
+                println!("{}", term.term.to_pretty());
+
+                println!(
+                    "\nCosts - mem: {} & cpu: {}",
+                    result.budget.mem, result.budget.cpu
+                );
+            }
+            UplcCommand::Debug {
+                script,
+                flat,
+                plutus_version,
+            } => {
+                let program = if flat {
+                    let bytes = std::fs::read(&script)?;
+
+                    let program = Program::<FakeNamedDeBruijn>::from_flat(&bytes)?;
+
+                    program.into()
+                } else {
+                    let code = std::fs::read_to_string(&script)?;
+
+                    let program = parser::program(&code)?;
+
+                    Program::<NamedDeBruijn>::try_from(program)?
+                };
+
+                debug(program, plutus_version)?;
+            }
+            UplcCommand::Hash {
+                input,
+                cbor,
+                plutus_version,
+                address,
+                network,
+            } => {
+                let program = if cbor {
+                    let cbor_hex = std::fs::read_to_string(&input)?;
+
+                    Program::<DeBruijn>::from_cbor(&hex::decode(cbor_hex.trim())?)?
+                } else {
+                    let code = std::fs::read_to_string(&input)?;
+
+                    Program::<DeBruijn>::try_from(parser::program(&code)?)?
+                };
+
+                program.check_version(plutus_version)?;
+
+                let hash = program.to_hash(plutus_version)?;
+
+                if address {
+                    println!("{}", uplc::address::script_address(&hash, network));
+                } else {
+                    println!("{}", hex::encode(hash));
+                }
+            }
+            UplcCommand::Apply {
+                script,
+                arguments,
+                cbor,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| Ok(PlutusData::from_cbor(&hex::decode(argument)?)?))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                if cbor {
+                    let cbor_hex = std::fs::read_to_string(&script)?;
+
+                    let program = Program::<DeBruijn>::from_cbor(&hex::decode(cbor_hex.trim())?)?;
+
+                    let program = arguments
+                        .into_iter()
+                        .fold(program, |program, data| program.apply_data(data));
+
+                    println!("{}", hex::encode(program.to_cbor()?));
+                } else {
+                    let code = std::fs::read_to_string(&script)?;
+
+                    let program = parser::program(&code)?;
+
+                    let program = arguments
+                        .into_iter()
+                        .fold(program, |program, data| program.apply_data(data));
+
+                    println!("{}", program.to_pretty());
+                }
+            }
+            UplcCommand::Bench {
+                scripts,
+                flat,
+                arguments,
+                baseline,
+                json,
+                plutus_version,
+            } => {
+                let argument_sets = arguments
+                    .iter()
+                    .map(|set| {
+                        set.split(',')
+                            .map(|argument| {
+                                let argument = argument.trim();
+                                let data = PlutusData::from_cbor(&hex::decode(argument)?)?;
+
+                                Ok((argument.to_string(), data))
+                            })
+                            .collect::<anyhow::Result<Vec<_>>>()
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                // without arguments each script is evaluated as is
+                let argument_sets = if argument_sets.is_empty() {
+                    vec![vec![]]
+                } else {
+                    argument_sets
+                };
+
+                let mut measurements = vec![];
+
+                for script in &scripts {
+                    let program = if flat {
+                        Program::<DeBruijn>::from_flat(&std::fs::read(script)?)?
+                    } else {
+                        let code = std::fs::read_to_string(script)?;
+
+                        Program::<DeBruijn>::try_from(parser::program(&code)?)?
+                    };
+
+                    for arguments in &argument_sets {
+                        measurements.push(bench::measure(
+                            &script.display().to_string(),
+                            &program,
+                            arguments,
+                            plutus_version,
+                        )?);
+                    }
+                }
+
+                let baseline: Vec<bench::Measurement> = match baseline {
+                    Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+                    None => vec![],
+                };
+
+                let comparisons = bench::compare(measurements, &baseline);
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&comparisons)?);
+                } else {
+                    println!("{}", bench::table(&comparisons));
+                }
+            }
+            UplcCommand::Stats { input, flat, json } => {
+                let program = if flat {
+                    Program::<DeBruijn>::from_flat(&std::fs::read(&input)?)?
+                } else {
+                    let code = std::fs::read_to_string(&input)?;
+
+                    Program::<DeBruijn>::try_from(parser::program(&code)?)?
+                };
+
+                let stats = program.stats()?;
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&stats)?);
+                } else {
+                    println!("{}", stats);
+                }
+            }
+            UplcCommand::Conformance { dir } => {
+                let vectors = conformance::discover(&dir)?;
+
+                let mut failed = 0;
+
+                // a vector that can't be run fails, without stopping the others
+                for path in &vectors {
+                    let outcome = match conformance::run(path) {
+                        Ok(conformance::Outcome::Passed) => continue,
+                        Ok(outcome) => outcome.to_string(),
+                        Err(error) => error.to_string(),
+                    };
+
+                    failed += 1;
+
+                    println!("FAIL {}\n{}\n", path.display(), outcome);
+                }
+
+                println!("{} passed, {} failed", vectors.len() - failed, failed);
+
+                if failed > 0 {
+                    std::process::exit(1);
+                }
+            }
+            UplcCommand::Repl {
+                load,
+                plutus_version,
+            } => repl(load, plutus_version)?,
+            UplcCommand::Tx(TxCommand::Simulate {
+                input,
+                raw_inputs,
+                raw_outputs,
+                zero_time,
+                zero_slot,
+                slot_length,
+                trace,
+                trace_output,
+            }) => {
+                let read_hex = |path| -> anyhow::Result<Vec<u8>> {
+                    let cbor_hex = std::fs::read_to_string(path)?;
+
+                    Ok(hex::decode(cbor_hex.trim())?)
+                };
+
+                let slot_config = SlotConfig {
+                    zero_time,
+                    zero_slot,
+                    slot_length,
+                };
+
+                let result = tx::eval_phase_two_raw(
+                    &read_hex(&input)?,
+                    &read_hex(&raw_inputs)?,
+                    &read_hex(&raw_outputs)?,
+                    &CostModels::default(),
+                    ExBudget::default(),
+                    &slot_config,
+                );
+
+                let mut traces = match TraceOutput::new(trace, trace_output) {
+                    Some(output) => Some(output.writer()?),
+                    None => None,
+                };
+
+                // a failing script's traces usually say why it failed
+                if let (Some(writer), Err(tx::Error::Machine(tag, index, _, logs))) =
+                    (&mut traces, &result)
+                {
+                    for log in logs {
+                        writeln!(writer, "{:?}[{}] {}", tag, index, log)?;
+                    }
+                }
+
+                for evaluated in result? {
+                    let redeemer = evaluated.redeemer;
+
+                    if let Some(writer) = &mut traces {
+                        for log in &evaluated.logs {
+                            writeln!(writer, "{:?}[{}] {}", redeemer.tag, redeemer.index, log)?;
+                        }
+                    }
+
+                    println!(
+                        "{:?}[{}] - mem: {} & cpu: {}",
+                        redeemer.tag, redeemer.index, redeemer.ex_units.mem, redeemer.ex_units.cpu
+                    );
                 }
             }
         },
@@ -57,3 +720,145 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+const DEBUG_HELP: &str = "\
+commands:
+  step, s [n]       make one transition, or n of them
+  continue, c       run until a breakpoint or the end
+  break, b <name>   stop once the builtin `name` is called, e.g. `b trace`
+  frames, f         show the continuation, innermost frame first
+  env, e            show the environment, closest variable first
+  quit, q           stop debugging";
+
+/// Evaluate `program` one transition at a time, reading commands from stdin.
+fn debug(program: Program<NamedDeBruijn>, version: PlutusVersion) -> anyhow::Result<()> {
+    let cost_model = CostModels::default().get(version).clone();
+
+    let mut machine = Machine::new(version, cost_model, ExBudget::default(), 200);
+
+    let mut state = machine.start(program.term)?;
+
+    println!("{}", DEBUG_HELP);
+    println!("\n{}", state);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+
+        let mut words = line.split_whitespace();
+
+        let steps = match (words.next(), words.next()) {
+            (Some("step" | "s"), count) => match count.map_or(Ok(1), usize::from_str) {
+                Ok(steps) => steps,
+                Err(error) => {
+                    println!("invalid count {}: {}", count.unwrap_or_default(), error);
+
+                    continue;
+                }
+            },
+            (Some("continue" | "c"), _) => usize::MAX,
+            (Some("break" | "b"), Some(name)) => {
+                match DefaultFunction::from_str(name) {
+                    Ok(fun) => machine.breakpoints.push(fun),
+                    Err(_) => println!("unknown builtin {}", name),
+                }
+
+                continue;
+            }
+            (Some("frames" | "f"), _) => {
+                for frame in machine.frames().iter().rev() {
+                    println!("{}", frame);
+                }
+
+                continue;
+            }
+            (Some("env" | "e"), _) => {
+                if let MachineState::Compute(env, _) = &state {
+                    for (index, value) in env.iter().enumerate() {
+                        println!("{}: {}", index + 1, value.to_term().to_pretty());
+                    }
+                }
+
+                continue;
+            }
+            (Some("quit" | "q"), _) => return Ok(()),
+            (None, _) => continue,
+            _ => {
+                println!("{}", DEBUG_HELP);
+
+                continue;
+            }
+        };
+
+        for _ in 0..steps {
+            let logged = machine.logs.len();
+
+            state = machine.step(state)?;
+
+            for log in &machine.logs[logged..] {
+                println!("trace: {}", log);
+            }
+
+            if let Some(call) = machine.breakpoint() {
+                println!("breakpoint: {}", call.to_pretty());
+
+                break;
+            }
+
+            if let MachineState::Done(_) = state {
+                break;
+            }
+        }
+
+        println!("{}", state);
+
+        if let MachineState::Done(_) = state {
+            let budget = ExBudget::default() - machine.ex_budget;
+
+            println!("\nCosts - mem: {} & cpu: {}", budget.mem, budget.cpu);
+
+            return Ok(());
+        }
+    }
+}
+
+/// Run the lines read from stdin in a session until `:quit`.
+fn repl(load: Option<PathBuf>, version: PlutusVersion) -> anyhow::Result<()> {
+    let mut session = repl::Session::new(version);
+
+    println!("{}", repl::HELP);
+
+    if let Some(path) = load {
+        println!("{}", session.run(&format!(":load {}", path.display()))?);
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+
+        match line.trim() {
+            ":quit" | ":q" => return Ok(()),
+            line => match session.run(line) {
+                Ok(output) if output.is_empty() => (),
+                Ok(output) => println!("{}", output),
+                Err(error) => report(error),
+            },
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! An interactive session evaluating terms, in which the results of
+//! evaluations can be bound to names and used in later terms.
+
+use std::fs;
+
+use anyhow::{anyhow, bail};
+use uplc::{
+    ast::{DeBruijn, Name, NamedDeBruijn, PlutusVersion, Program, Term},
+    machine::{
+        cost_model::{CostModels, ExBudget},
+        EvalResult,
+    },
+    parser,
+};
+
+pub const HELP: &str = "\
+<term>                 evaluate a term, it can use the names bound with :let
+:eval <term>           same as above
+:let <name> = <term>   evaluate a term and bind the result to a name
+:budget <term>         evaluate a term, printing the execution units it consumed
+:size <term>           print the length in bytes of a term flat encoded
+:bindings              list the bound names
+:save <file>           write the :let lines of the session to a file
+:load <file>           run the lines of a file, e.g. one written by :save
+:help                  print this message
+:quit                  end the session";
+
+/// The version of the programs terms are evaluated and encoded in.
+const VERSION: (usize, usize, usize) = (1, 0, 0);
+
+pub struct Session {
+    plutus_version: PlutusVersion,
+    /// What evaluating is priced with, the model of `plutus_version`.
+    cost_models: CostModels,
+    bindings: Vec<(String, Term<Name>)>,
+    /// The `:let` lines run so far, to save the session.
+    definitions: Vec<String>,
+}
+
+impl Session {
+    pub fn new(plutus_version: PlutusVersion) -> Self {
+        Session {
+            plutus_version,
+            cost_models: CostModels::default(),
+            bindings: Vec::new(),
+            definitions: Vec::new(),
+        }
+    }
+
+    /// Run a line of input, returning what to print.
+    pub fn run(&mut self, line: &str) -> anyhow::Result<String> {
+        let line = line.trim();
+
+        let (command, argument) = match line.strip_prefix(':') {
+            Some(command) => command
+                .split_once(char::is_whitespace)
+                .map_or((command, ""), |(command, argument)| {
+                    (command, argument.trim())
+                }),
+            None => ("eval", line),
+        };
+
+        match command {
+            _ if line.is_empty() => Ok(String::new()),
+            "eval" => {
+                let result = self.eval(argument)?;
+
+                let term = result.term?;
+
+                Ok(traces(&result.logs) + &named(term)?.to_pretty())
+            }
+            "let" => {
+                let (name, source) = argument
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected :let <name> = <term>"))?;
+
+                let name = name.trim();
+
+                if !matches!(parser::term(name), Ok(Term::Var(_))) {
+                    bail!("`{name}` isn't a valid name");
+                }
+
+                let result = self.eval(source)?;
+
+                let term = named(result.term?)?;
+
+                let output = traces(&result.logs) + &format!("{name} = {}", term.to_pretty());
+
+                self.bindings.retain(|(bound, _)| bound != name);
+                self.bindings.push((name.to_string(), term));
+                self.definitions.push(line.to_string());
+
+                Ok(output)
+            }
+            "budget" => {
+                let result = self.eval(argument)?;
+
+                let outcome = match result.term {
+                    Ok(term) => named(term)?.to_pretty(),
+                    Err(error) => format!("error: {error}"),
+                };
+
+                Ok(format!(
+                    "{}{outcome}\nCosts - mem: {} & cpu: {}",
+                    traces(&result.logs),
+                    result.budget.mem,
+                    result.budget.cpu
+                ))
+            }
+            "size" => {
+                let program: Program<DeBruijn> = self.program(argument)?.try_into()?;
+
+                let size = program.to_flat_with_version(self.plutus_version)?.len();
+
+                Ok(format!("{size} bytes"))
+            }
+            "bindings" => Ok(self
+                .bindings
+                .iter()
+                .map(|(name, term)| format!("{name} = {}", term.to_pretty()))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "save" => {
+                fs::write(argument, self.definitions.join("\n") + "\n")?;
+
+                Ok(format!(
+                    "saved {} definitions to {argument}",
+                    self.definitions.len()
+                ))
+            }
+            "load" => {
+                let lines = fs::read_to_string(argument)?;
+
+                let mut outputs = Vec::new();
+
+                for (index, line) in lines.lines().enumerate() {
+                    let output = self
+                        .run(line)
+                        .map_err(|error| error.context(format!("{argument}:{}", index + 1)))?;
+
+                    if !output.is_empty() {
+                        outputs.push(output);
+                    }
+                }
+
+                Ok(outputs.join("\n"))
+            }
+            "help" => Ok(HELP.to_string()),
+            _ => bail!("unknown command :{command}, see :help"),
+        }
+    }
+
+    fn eval(&self, source: &str) -> anyhow::Result<EvalResult> {
+        let program: Program<NamedDeBruijn> = self.program(source)?.try_into()?;
+
+        Ok(program.eval_with_cost_models(
+            self.plutus_version,
+            &self.cost_models,
+            ExBudget::default(),
+        ))
+    }
+
+    /// Parse a term, with the bound names in place of the free
+    /// variables with the same text.
+    fn program(&self, source: &str) -> anyhow::Result<Program<Name>> {
+        let term = parser::term(source)?;
+
+        Ok(Program {
+            version: VERSION,
+            term: self.expand(&term, &mut Vec::new())?,
+        })
+    }
+
+    fn expand<'a>(
+        &self,
+        term: &'a Term<Name>,
+        scope: &mut Vec<&'a str>,
+    ) -> anyhow::Result<Term<Name>> {
+        Ok(match term {
+            Term::Var(name) if !scope.contains(&name.text.as_str()) => self
+                .bindings
+                .iter()
+                .find(|(bound, _)| *bound == name.text)
+                .map(|(_, term)| term.clone())
+                .ok_or_else(|| anyhow!("unknown name `{}`", name.text))?,
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => {
+                scope.push(&parameter_name.text);
+
+                let body = self.expand(body, scope)?;
+
+                scope.pop();
+
+                Term::Lambda {
+                    parameter_name: parameter_name.clone(),
+                    body: body.into(),
+                }
+            }
+            Term::Delay(term) => Term::Delay(self.expand(term, scope)?.into()),
+            Term::Apply { function, argument } => Term::Apply {
+                function: self.expand(function, scope)?.into(),
+                argument: self.expand(argument, scope)?.into(),
+            },
+            Term::Force(term) => Term::Force(self.expand(term, scope)?.into()),
+            Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => term.clone(),
+        })
+    }
+}
+
+fn named(term: Term<NamedDeBruijn>) -> anyhow::Result<Term<Name>> {
+    let program: Program<Name> = Program {
+        version: VERSION,
+        term,
+    }
+    .try_into()?;
+
+    Ok(program.term)
+}
+
+fn traces(logs: &[String]) -> String {
+    logs.iter().map(|log| format!("trace: {log}\n")).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use uplc::ast::PlutusVersion;
+
+    use super::Session;
+
+    #[test]
+    fn bindings() {
+        let mut session = Session::new(PlutusVersion::V2);
+
+        assert_eq!(
+            session.run(":let id = [(lam x x) (lam y y)]").unwrap(),
+            "id = (lam y y)"
+        );
+        assert_eq!(
+            session
+                .run(":let one = [(builtin addInteger) (con integer 0) (con integer 1)]")
+                .unwrap(),
+            "one = (con integer 1)"
+        );
+
+        assert_eq!(session.run("[id one]").unwrap(), "(con integer 1)");
+        assert_eq!(session.run(":eval (lam one one)").unwrap(), "(lam one one)");
+        assert_eq!(session.run("").unwrap(), "");
+
+        assert!(session
+            .run(":let one = [(builtin trace) (con string \"hi\") two]")
+            .is_err());
+        assert_eq!(
+            session.run(":let one = (con integer 2)").unwrap(),
+            "one = (con integer 2)"
+        );
+        assert_eq!(
+            session.run(":bindings").unwrap(),
+            "id = (lam y y)\none = (con integer 2)"
+        );
+
+        assert!(session.run(":let (lam x x) = one").is_err());
+        assert!(session.run(":nope").is_err());
+    }
+
+    #[test]
+    fn budget_and_size() {
+        let mut session = Session::new(PlutusVersion::V2);
+
+        let output = session
+            .run(":budget (force [(force (builtin trace)) (con string \"hi\") (delay (error))])")
+            .unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "trace: hi");
+        assert!(lines[1].starts_with("error: "));
+        assert!(lines[2].starts_with("Costs - mem: "));
+
+        assert_eq!(session.run(":size (con integer 1)").unwrap(), "6 bytes");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("uplc_repl_session.uplc");
+        let path = path.to_str().unwrap();
+
+        let mut session = Session::new(PlutusVersion::V2);
+
+        session.run(":let one = (con integer 1)").unwrap();
+        session.run(":let pair = [(lam x (lam y x)) one]").unwrap();
+
+        assert_eq!(
+            session.run(&format!(":save {path}")).unwrap(),
+            format!("saved 2 definitions to {path}")
+        );
+
+        let mut loaded = Session::new(PlutusVersion::V2);
+
+        loaded.run(&format!(":load {path}")).unwrap();
+
+        assert_eq!(
+            loaded.run(":bindings").unwrap(),
+            session.run(":bindings").unwrap()
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
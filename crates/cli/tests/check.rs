@@ -0,0 +1,85 @@
+//! `aiken check --diagnostics json-lines` prints one JSON object per
+//! diagnostic on stdout.
+
+use std::{fs, path::PathBuf, process::Command};
+
+use serde_json::Value;
+
+/// A fresh project made of `files`.
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("aiken-check-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_dir_all(&root);
+
+    for (path, src) in files {
+        let path = root.join(path);
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        fs::write(path, src).unwrap();
+    }
+
+    root
+}
+
+const MANIFEST: &str = r#"
+name = "test"
+version = "0.0.0"
+plutus_version = "v2"
+"#;
+
+#[test]
+fn json_lines() {
+    let root = project(
+        "json_lines",
+        &[
+            ("aiken.toml", MANIFEST),
+            ("lib/broken.ak", "pub fn broken( { 1 }"),
+            ("validators/bad.ak", "pub fn spend() {\n  1 + True\n}"),
+        ],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aiken"))
+        .args(["check", "--diagnostics", "json-lines", "-d"])
+        .arg(&root)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+
+    let diagnostics: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+
+    for diagnostic in &diagnostics {
+        assert_eq!(diagnostic["severity"], "error");
+        assert!(diagnostic["message"].is_string());
+        assert!(diagnostic["code"].is_string());
+        assert!(diagnostic["related"].is_array());
+        assert!(diagnostic["span"]["start"].is_u64());
+        assert!(diagnostic["span"]["line"].is_u64());
+    }
+
+    let in_file = |file: &str| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic["path"].as_str().unwrap().ends_with(file))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(!in_file("broken.ak").is_empty());
+
+    // still reported although another module failed to parse
+    let bad = in_file("bad.ak");
+
+    assert_eq!(bad.len(), 1);
+    assert_eq!(bad[0]["code"], "aiken::check::could_not_unify");
+    assert_eq!(bad[0]["span"]["line"], 2);
+    assert_eq!(bad[0]["span"]["column"], 7);
+
+    fs::remove_dir_all(root).unwrap();
+}
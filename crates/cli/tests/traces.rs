@@ -0,0 +1,170 @@
+//! Traces go to stderr or the --trace-output file, never to stdout
+//! where scripts read results from.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use uplc::{
+    ast::{DeBruijn, PlutusVersion, Program},
+    parser,
+};
+
+const TRACING: &str = r#"
+(program 1.0.0
+    (lam redeemer (lam context
+        [(force (builtin trace)) (con string "hello") (con unit ())]
+    ))
+)
+"#;
+
+/// A fresh directory for the files of `name`.
+fn directory(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aiken-traces-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_dir_all(&dir);
+
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+fn aiken(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_aiken"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+
+    output
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[test]
+fn eval() {
+    let dir = directory("eval");
+
+    fs::write(
+        dir.join("script.uplc"),
+        r#"(program 1.0.0 [(force (builtin trace)) (con string "hello") (con integer 1)])"#,
+    )
+    .unwrap();
+
+    let output = aiken(&dir, &["uplc", "eval", "script.uplc", "--trace"]);
+
+    assert!(!text(&output.stdout).contains("hello"));
+    assert!(text(&output.stdout).contains("(con integer 1)"));
+    assert_eq!(text(&output.stderr), "hello\n");
+
+    let output = aiken(
+        &dir,
+        &[
+            "uplc",
+            "eval",
+            "script.uplc",
+            "--trace-output",
+            "traces.log",
+        ],
+    );
+
+    assert!(!text(&output.stdout).contains("hello"));
+    assert_eq!(text(&output.stderr), "");
+    assert_eq!(
+        fs::read_to_string(dir.join("traces.log")).unwrap(),
+        "hello\n"
+    );
+
+    let output = aiken(&dir, &["uplc", "eval", "script.uplc"]);
+
+    assert!(!text(&output.stdout).contains("hello"));
+    assert_eq!(text(&output.stderr), "");
+}
+
+#[test]
+fn tx_simulate() {
+    let dir = directory("tx-simulate");
+
+    let program: Program<DeBruijn> = parser::program(TRACING).unwrap().try_into().unwrap();
+
+    let script = program.to_cbor().unwrap();
+    let policy = program.to_hash(PlutusVersion::V2).unwrap();
+
+    let mut address = vec![0x61];
+    address.extend([0; 28]);
+
+    // a transaction minting with the tracing script
+    let mut e = minicbor::Encoder::new(Vec::new());
+
+    e.array(4).unwrap();
+
+    e.map(4).unwrap();
+    e.u8(0).unwrap().array(1).unwrap();
+    e.array(2).unwrap().bytes(&[0; 32]).unwrap().u8(0).unwrap();
+    e.u8(1).unwrap().array(0).unwrap();
+    e.u8(2).unwrap().u64(200_000).unwrap();
+    e.u8(9).unwrap().map(1).unwrap().bytes(&policy).unwrap();
+    e.map(1).unwrap().bytes(b"ab").unwrap().u8(1).unwrap();
+
+    e.map(2).unwrap();
+    e.u8(5).unwrap().array(1).unwrap();
+    e.array(4)
+        .unwrap()
+        .u8(1)
+        .unwrap()
+        .u8(0)
+        .unwrap()
+        .u8(0)
+        .unwrap();
+    e.array(2).unwrap().u8(0).unwrap().u8(0).unwrap();
+    e.u8(6).unwrap().array(1).unwrap().bytes(&script).unwrap();
+
+    e.bool(true).unwrap().null().unwrap();
+
+    fs::write(dir.join("tx"), hex::encode(e.writer())).unwrap();
+
+    let mut e = minicbor::Encoder::new(Vec::new());
+
+    e.array(1).unwrap();
+    e.array(2).unwrap().bytes(&[0; 32]).unwrap().u8(0).unwrap();
+
+    fs::write(dir.join("inputs"), hex::encode(e.writer())).unwrap();
+
+    let mut e = minicbor::Encoder::new(Vec::new());
+
+    e.array(1).unwrap();
+    e.array(2)
+        .unwrap()
+        .bytes(&address)
+        .unwrap()
+        .u64(2_000_000)
+        .unwrap();
+
+    fs::write(dir.join("outputs"), hex::encode(e.writer())).unwrap();
+
+    let simulate = ["uplc", "tx", "simulate", "tx", "inputs", "outputs"];
+
+    let output = aiken(&dir, &[&simulate[..], &["--trace"]].concat());
+
+    assert!(!text(&output.stdout).contains("hello"));
+    assert!(text(&output.stdout).starts_with("Mint[0] - mem: "));
+    assert_eq!(text(&output.stderr), "Mint[0] hello\n");
+
+    let output = aiken(
+        &dir,
+        &[&simulate[..], &["--trace-output", "traces.log"]].concat(),
+    );
+
+    assert!(!text(&output.stdout).contains("hello"));
+    assert_eq!(text(&output.stderr), "");
+    assert_eq!(
+        fs::read_to_string(dir.join("traces.log")).unwrap(),
+        "Mint[0] hello\n"
+    );
+}
@@ -1,6 +1,8 @@
 mod decoder;
 mod error;
 
+use num_bigint::BigInt;
+
 use crate::filler::Filler;
 
 pub use decoder::Decoder;
@@ -36,6 +38,12 @@ impl Decode<'_> for isize {
     }
 }
 
+impl Decode<'_> for BigInt {
+    fn decode(d: &mut Decoder) -> Result<Self, Error> {
+        d.big_integer()
+    }
+}
+
 impl Decode<'_> for usize {
     fn decode(d: &mut Decoder) -> Result<Self, Error> {
         d.word()
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use num_bigint::{BigInt, BigUint};
+
 use crate::{decode::Decode, zigzag};
 
 use super::Error;
@@ -7,17 +11,45 @@ pub struct Decoder<'b> {
     pub buffer: &'b [u8],
     pub used_bits: i64,
     pub pos: usize,
+    depth: usize,
+    max_depth: usize,
 }
 
 impl<'b> Decoder<'b> {
-    pub fn new(bytes: &'b [u8]) -> Decoder {
+    pub fn new(bytes: &'b [u8]) -> Decoder<'b> {
+        Decoder::with_max_depth(bytes, usize::MAX)
+    }
+
+    /// A decoder failing on values nested deeper than `max_depth`,
+    /// see `enter`.
+    pub fn with_max_depth(bytes: &'b [u8], max_depth: usize) -> Decoder<'b> {
         Decoder {
             buffer: bytes,
             pos: 0,
             used_bits: 0,
+            depth: 0,
+            max_depth,
         }
     }
 
+    /// Mark the start of a nested value, failing if it is nested too
+    /// deeply. Recursive `Decode` implementations call this so that
+    /// untrusted input can't overflow the stack, and `leave` once the
+    /// value is decoded.
+    pub fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= self.max_depth {
+            return Err(Error::MaxDepth(self.max_depth));
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
     /// Encode any type that implements [`Decode`].
     pub fn decode<T: Decode<'b>>(&mut self) -> Result<T, Error> {
         T::decode(self)
@@ -27,11 +59,12 @@ impl<'b> Decoder<'b> {
         Ok(zigzag::to_isize(self.word()?))
     }
 
+    pub fn big_integer(&mut self) -> Result<BigInt, Error> {
+        Ok(zigzag::to_bigint(self.big_word()?))
+    }
+
     pub fn bool(&mut self) -> Result<bool, Error> {
-        let current_byte = self.buffer[self.pos];
-        let b = 0 != (current_byte & (128 >> self.used_bits));
-        self.increment_buffer_by_bit();
-        Ok(b)
+        self.bit()
     }
 
     pub fn u8(&mut self) -> Result<u8, Error> {
@@ -43,6 +76,39 @@ impl<'b> Decoder<'b> {
         self.byte_array()
     }
 
+    /// Like `bytes` but borrowing them from the buffer when they are
+    /// written in a single chunk, i.e. are at most 255 bytes long.
+    /// Longer ones have to be joined, so are copied.
+    pub fn bytes_borrowed(&mut self) -> Result<Cow<'b, [u8]>, Error> {
+        self.filler()?;
+
+        if self.used_bits != 0 {
+            return Err(Error::BufferNotByteAligned);
+        }
+
+        self.ensure_bytes(1)?;
+
+        let chunk_length = self.buffer[self.pos] as usize;
+
+        if chunk_length == 0 {
+            self.pos += 1;
+
+            return Ok(Cow::Borrowed(&[]));
+        }
+
+        self.ensure_bytes(chunk_length + 2)?;
+
+        if self.buffer[self.pos + chunk_length + 1] != 0 {
+            return self.byte_array().map(Cow::Owned);
+        }
+
+        let bytes = &self.buffer[self.pos + 1..self.pos + 1 + chunk_length];
+
+        self.pos += chunk_length + 2;
+
+        Ok(Cow::Borrowed(bytes))
+    }
+
     pub fn char(&mut self) -> Result<char, Error> {
         let character = self.word()? as u32;
 
@@ -52,7 +118,7 @@ impl<'b> Decoder<'b> {
     pub fn string(&mut self) -> Result<String, Error> {
         let mut s = String::new();
         while self.bit()? {
-            s += &self.char()?.to_string();
+            s.push(self.char()?);
         }
         Ok(s)
     }
@@ -82,6 +148,21 @@ impl<'b> Decoder<'b> {
         Ok(final_word)
     }
 
+    pub fn big_word(&mut self) -> Result<BigUint, Error> {
+        let mut leading_bit = 1;
+        let mut final_word = BigUint::default();
+        let mut shl: usize = 0;
+        // continue looping if lead bit is 1 otherwise exit
+        while leading_bit > 0 {
+            let word8 = self.bits8(8)?;
+            let word7 = word8 & 127;
+            final_word |= BigUint::from(word7) << shl;
+            shl += 7;
+            leading_bit = word8 & 128;
+        }
+        Ok(final_word)
+    }
+
     pub fn decode_list_with<T: Decode<'b>>(
         &mut self,
         decoder_func: for<'r> fn(&'r mut Decoder) -> Result<T, Error>,
@@ -111,34 +192,45 @@ impl<'b> Decoder<'b> {
         Ok(b)
     }
 
+    /// Byte arrays are written in chunks of at most 255 bytes, each
+    /// prefixed by it's length and ended by an empty chunk. The chunks
+    /// are measured first so that they are copied once, into a vec of
+    /// the right size.
     fn byte_array(&mut self) -> Result<Vec<u8>, Error> {
         if self.used_bits != 0 {
             return Err(Error::BufferNotByteAligned);
         }
 
-        self.ensure_bytes(1)?;
+        let mut length = 0;
+        let mut end = self.pos;
 
-        let mut blk_len = self.buffer[self.pos];
+        loop {
+            self.ensure_bytes(end - self.pos + 1)?;
 
-        self.pos += 1;
+            let chunk_length = self.buffer[end] as usize;
 
-        let mut blk_array: Vec<u8> = Vec::new();
+            end += chunk_length + 1;
 
-        while blk_len != 0 {
-            self.ensure_bytes(blk_len as usize + 1)?;
+            if chunk_length == 0 {
+                break;
+            }
 
-            let decoded_array = &self.buffer[self.pos..self.pos + blk_len as usize];
+            length += chunk_length;
+        }
 
-            blk_array.extend(decoded_array);
+        let mut bytes = Vec::with_capacity(length);
 
-            self.pos += blk_len as usize;
+        while self.pos + 1 < end {
+            let chunk_length = self.buffer[self.pos] as usize;
 
-            blk_len = self.buffer[self.pos];
+            bytes.extend_from_slice(&self.buffer[self.pos + 1..self.pos + 1 + chunk_length]);
 
-            self.pos += 1
+            self.pos += chunk_length + 1;
         }
 
-        Ok(blk_array)
+        self.pos = end;
+
+        Ok(bytes)
     }
 
     // can decode up to a max of 8 bits
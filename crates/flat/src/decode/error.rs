@@ -16,6 +16,8 @@ pub enum Error {
     DecodeUtf8(#[from] std::string::FromUtf8Error),
     #[error("Decoding u32 to char {0}")]
     DecodeChar(u32),
+    #[error("Values are nested deeper than the maximum depth of {0}")]
+    MaxDepth(usize),
     #[error("{0}")]
     Message(String),
     #[error(transparent)]
@@ -1,6 +1,8 @@
 mod encoder;
 mod error;
 
+use num_bigint::BigInt;
+
 use crate::filler::Filler;
 
 pub use encoder::Encoder;
@@ -34,6 +36,14 @@ impl Encode for isize {
     }
 }
 
+impl Encode for BigInt {
+    fn encode(&self, e: &mut Encoder) -> Result<(), Error> {
+        e.big_integer(self);
+
+        Ok(())
+    }
+}
+
 impl Encode for usize {
     fn encode(&self, e: &mut Encoder) -> Result<(), Error> {
         e.word(*self);
@@ -1,3 +1,5 @@
+use num_bigint::{BigInt, BigUint};
+
 use crate::{encode::Encode, zigzag};
 
 use super::Error;
@@ -25,6 +27,11 @@ impl Encoder {
         }
     }
 
+    /// The number of bits encoded so far.
+    pub fn bits_written(&self) -> usize {
+        self.buffer.len() * 8 + self.used_bits as usize
+    }
+
     /// Encode any type that implements [`Encode`].
     pub fn encode<T: Encode>(&mut self, x: T) -> Result<&mut Self, Error> {
         x.encode(self)?;
@@ -79,6 +86,14 @@ impl Encoder {
         self
     }
 
+    pub fn big_integer(&mut self, i: &BigInt) -> &mut Self {
+        let i = zigzag::to_biguint(i);
+
+        self.big_word(i);
+
+        self
+    }
+
     pub fn char(&mut self, c: char) -> &mut Self {
         self.word(c as usize);
 
@@ -120,6 +135,25 @@ impl Encoder {
         self
     }
 
+    pub fn big_word(&mut self, c: BigUint) -> &mut Self {
+        let mut d = c;
+        loop {
+            let mut w = (d.iter_u32_digits().next().unwrap_or(0) & 127) as u8;
+            d >>= 7u8;
+
+            if d != BigUint::default() {
+                w |= 128;
+            }
+            self.bits(8, w);
+
+            if d == BigUint::default() {
+                break;
+            }
+        }
+
+        self
+    }
+
     pub fn encode_list_with(
         &mut self,
         list: Vec<u8>,
@@ -162,7 +196,7 @@ impl Encoder {
                     x if x > 0 => {
                         self.current_byte |= val << x;
                     }
-                    x if x == 0 => {
+                    0 => {
                         self.current_byte |= val;
                         self.next_word();
                     }
@@ -229,7 +263,7 @@ impl Encoder {
             return;
         }
 
-        self.buffer.extend(&arr[*src_ptr..blk_len]);
+        self.buffer.extend(&arr[*src_ptr..*src_ptr + blk_len]);
 
         *src_ptr += blk_len;
 
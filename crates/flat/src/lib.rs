@@ -37,7 +37,16 @@ pub fn decode<'b, T>(bytes: &'b [u8]) -> Result<T, de::Error>
 where
     T: de::Decode<'b>,
 {
-    let mut d = de::Decoder::new(bytes);
+    decode_with_max_depth(bytes, usize::MAX)
+}
+
+/// Like `decode` but failing on values nested deeper than
+/// `max_depth`, see `Decoder::enter`.
+pub fn decode_with_max_depth<'b, T>(bytes: &'b [u8], max_depth: usize) -> Result<T, de::Error>
+where
+    T: de::Decode<'b>,
+{
+    let mut d = de::Decoder::with_max_depth(bytes, max_depth);
 
     let value = d.decode()?;
 
@@ -65,4 +74,66 @@ mod test {
 
         assert_eq!(bytes, vec![0b00000011, 0b00000001]);
     }
+
+    #[test]
+    fn bytes_round_trip() {
+        for length in [0, 1, 255, 256, 600] {
+            let bytes: Vec<u8> = (0..length).map(|i| i as u8).collect();
+
+            let encoded = super::encode(&bytes).unwrap();
+            let decoded = super::decode::<Vec<u8>>(&encoded).unwrap();
+
+            assert_eq!(decoded, bytes);
+            assert_eq!(decoded.capacity(), length);
+
+            // missing the empty chunk at the end
+            assert!(super::decode::<Vec<u8>>(&encoded[..encoded.len() - 2]).is_err());
+        }
+    }
+
+    #[test]
+    fn bytes_borrowed() {
+        use std::borrow::Cow;
+
+        for length in [0, 1, 255, 256, 600] {
+            let bytes: Vec<u8> = (0..length).map(|i| i as u8).collect();
+
+            let encoded = super::encode(&bytes).unwrap();
+            let mut d = super::de::Decoder::new(&encoded);
+
+            match d.bytes_borrowed().unwrap() {
+                Cow::Borrowed(decoded) => {
+                    assert!(length <= 255);
+                    assert_eq!(decoded, bytes);
+                }
+                Cow::Owned(decoded) => {
+                    assert!(length > 255);
+                    assert_eq!(decoded, bytes);
+                }
+            }
+
+            d.decode::<super::filler::Filler>().unwrap();
+
+            // missing the empty chunk at the end
+            let mut d = super::de::Decoder::new(&encoded[..encoded.len() - 2]);
+
+            assert!(d.bytes_borrowed().is_err());
+        }
+    }
+
+    #[test]
+    fn big_integer_round_trip() {
+        let n: num_bigint::BigInt = "-340282366920938463463374607431768211457".parse().unwrap();
+
+        let bytes = super::encode(&n).unwrap();
+
+        assert_eq!(super::decode::<num_bigint::BigInt>(&bytes).unwrap(), n);
+
+        let small = num_bigint::BigInt::from(-12);
+
+        assert_eq!(
+            super::encode(&small).unwrap(),
+            super::encode(&-12_isize).unwrap()
+        );
+    }
 }
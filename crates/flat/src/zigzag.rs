@@ -1,3 +1,5 @@
+use num_bigint::{BigInt, BigUint};
+
 pub fn to_usize(x: isize) -> usize {
     let double_x = x << 1;
 
@@ -14,6 +16,27 @@ pub fn to_isize(u: usize) -> isize {
     (s >> 1) ^ -(s & 1)
 }
 
+pub fn to_biguint(x: &BigInt) -> BigUint {
+    let double_x: BigInt = x << 1;
+
+    if x.sign() == num_bigint::Sign::Minus {
+        (-double_x - 1u8).magnitude().clone()
+    } else {
+        double_x.magnitude().clone()
+    }
+}
+
+pub fn to_bigint(u: BigUint) -> BigInt {
+    let negative = u.bit(0);
+    let s = BigInt::from(u >> 1u8);
+
+    if negative {
+        -s - 1u8
+    } else {
+        s
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -24,4 +47,13 @@ mod test {
 
         assert_eq!(n, signed)
     }
+
+    #[test]
+    fn convert_big() {
+        let n: num_bigint::BigInt = "-123456789012345678901234567890".parse().unwrap();
+        let unsigned = super::to_biguint(&n);
+        let signed = super::to_bigint(unsigned);
+
+        assert_eq!(n, signed)
+    }
 }
@@ -0,0 +1,392 @@
+use std::{fmt::Display, rc::Rc};
+
+use miette::SourceSpan;
+use num_bigint::BigInt;
+
+use crate::{
+    expr::{TypedExpr, UntypedExpr},
+    tipo::{PatternConstructor, Type, TypeInfo},
+};
+
+/// A range of byte offsets in a module's source. Every node of
+/// the AST carries one so later phases can point at the code
+/// they are complaining about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// The smallest span covering both `self` and `other`.
+    pub fn union(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+impl chumsky::Span for Span {
+    type Context = ();
+    type Offset = usize;
+
+    fn new(_context: Self::Context, range: std::ops::Range<Self::Offset>) -> Self {
+        Span::new(range.start, range.end)
+    }
+
+    fn context(&self) -> Self::Context {}
+
+    fn start(&self) -> Self::Offset {
+        self.start
+    }
+
+    fn end(&self) -> Self::Offset {
+        self.end
+    }
+}
+
+impl From<Span> for SourceSpan {
+    fn from(span: Span) -> Self {
+        (span.start, span.end - span.start).into()
+    }
+}
+
+/// Library modules live under `lib/`, modules defining
+/// validators under `validators/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    Lib,
+    Validator,
+}
+
+/// A source file. `Info` is filled in by the type checker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module<Info, Definitions> {
+    /// The path of the module, like `aiken/list`.
+    pub name: String,
+    pub kind: ModuleKind,
+    pub definitions: Vec<Definitions>,
+    pub type_info: Info,
+}
+
+pub type UntypedModule = Module<(), UntypedDefinition>;
+pub type TypedModule = Module<TypeInfo, TypedDefinition>;
+
+impl<Info, Expr, T> Module<Info, Definition<T, Expr>> {
+    /// The modules this module imports, in the order they are used.
+    pub fn dependencies(&self) -> Vec<(String, Span)> {
+        self.definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Use(Use {
+                    module, location, ..
+                }) => Some((module.join("/"), *location)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Definition<T, Expr> {
+    Fn(Function<T, Expr>),
+    DataType(DataType),
+    Use(Use),
+}
+
+pub type UntypedDefinition = Definition<(), UntypedExpr>;
+pub type TypedDefinition = Definition<Rc<Type>, TypedExpr>;
+
+impl<T, Expr> Definition<T, Expr> {
+    pub fn location(&self) -> Span {
+        match self {
+            Definition::Fn(Function { location, .. })
+            | Definition::DataType(DataType { location, .. })
+            | Definition::Use(Use { location, .. }) => *location,
+        }
+    }
+}
+
+/// `pub fn name(arguments) -> Return { body }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function<T, Expr> {
+    pub location: Span,
+    pub public: bool,
+    pub name: String,
+    pub arguments: Vec<Arg<T>>,
+    pub return_annotation: Option<Annotation>,
+    pub return_type: T,
+    pub body: Expr,
+}
+
+pub type UntypedFunction = Function<(), UntypedExpr>;
+pub type TypedFunction = Function<Rc<Type>, TypedExpr>;
+
+/// A function argument, `T` is it's type once inferred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg<T> {
+    pub location: Span,
+    pub name: ArgName,
+    pub annotation: Option<Annotation>,
+    pub tipo: T,
+}
+
+pub type TypedArg = Arg<Rc<Type>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgName {
+    Named(String),
+    /// An argument starting with `_`, which can't be referred to.
+    Discard(String),
+}
+
+impl ArgName {
+    pub fn name(&self) -> &str {
+        match self {
+            ArgName::Named(name) | ArgName::Discard(name) => name,
+        }
+    }
+}
+
+/// `pub type Name(parameters) { constructors }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataType {
+    pub location: Span,
+    pub public: bool,
+    pub name: String,
+    pub parameters: Vec<String>,
+    pub constructors: Vec<RecordConstructor>,
+}
+
+/// `Name(arguments)` or `Name { label: Type, .. }` in a type definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordConstructor {
+    pub location: Span,
+    pub name: String,
+    pub arguments: Vec<RecordConstructorArg>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordConstructorArg {
+    pub location: Span,
+    pub label: Option<String>,
+    pub annotation: Annotation,
+}
+
+/// `use some/module.{unqualified} as name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Use {
+    pub location: Span,
+    pub module: Vec<String>,
+    pub as_name: Option<String>,
+    pub unqualified: Vec<UnqualifiedImport>,
+}
+
+impl Use {
+    /// The name the module is referred to by in the importing module.
+    pub fn local_name(&self) -> &str {
+        self.as_name
+            .as_deref()
+            .or_else(|| self.module.last().map(String::as_str))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnqualifiedImport {
+    pub location: Span,
+    pub name: String,
+    pub as_name: Option<String>,
+}
+
+impl UnqualifiedImport {
+    pub fn local_name(&self) -> &str {
+        self.as_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// A type as written in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    /// `Int`, `List(a)` or `module.Type`
+    Constructor {
+        location: Span,
+        module: Option<String>,
+        name: String,
+        arguments: Vec<Annotation>,
+    },
+    /// `fn(a, b) -> c`
+    Fn {
+        location: Span,
+        arguments: Vec<Annotation>,
+        ret: Box<Annotation>,
+    },
+    /// A type variable, `a`
+    Var { location: Span, name: String },
+}
+
+impl Annotation {
+    pub fn location(&self) -> Span {
+        match self {
+            Annotation::Constructor { location, .. }
+            | Annotation::Fn { location, .. }
+            | Annotation::Var { location, .. } => *location,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    // Boolean logic
+    And,
+    Or,
+
+    // Equality
+    Eq,
+    NotEq,
+
+    // Order comparison
+    LtInt,
+    LtEqInt,
+    GtEqInt,
+    GtInt,
+
+    // Maths
+    AddInt,
+    SubInt,
+    MultInt,
+    /// Rounds towards negative infinity, like `math.div`.
+    DivInt,
+    /// Has the sign of the divisor, like `math.mod`.
+    ModInt,
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+            BinOp::Eq => "==",
+            BinOp::NotEq => "!=",
+            BinOp::LtInt => "<",
+            BinOp::LtEqInt => "<=",
+            BinOp::GtEqInt => ">=",
+            BinOp::GtInt => ">",
+            BinOp::AddInt => "+",
+            BinOp::SubInt => "-",
+            BinOp::MultInt => "*",
+            BinOp::DivInt => "/",
+            BinOp::ModInt => "%",
+        };
+
+        write!(f, "{}", op)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    /// `!`
+    Not,
+    /// `-`
+    Negate,
+}
+
+/// `Constructor` is what the type checker resolves constructor
+/// patterns to, `Type` the type of the value being matched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern<Constructor, Type> {
+    Int {
+        location: Span,
+        value: BigInt,
+    },
+    String {
+        location: Span,
+        value: String,
+    },
+    Var {
+        location: Span,
+        name: String,
+        tipo: Type,
+    },
+    /// `_` or `_name`, matches anything without binding it.
+    Discard {
+        location: Span,
+        name: String,
+    },
+    Constructor {
+        location: Span,
+        module: Option<String>,
+        name: String,
+        arguments: Vec<Pattern<Constructor, Type>>,
+        constructor: Constructor,
+        tipo: Type,
+    },
+    /// `[a, b, ..tail]`
+    List {
+        location: Span,
+        elements: Vec<Pattern<Constructor, Type>>,
+        tail: Option<Box<Pattern<Constructor, Type>>>,
+        tipo: Type,
+    },
+}
+
+pub type UntypedPattern = Pattern<(), ()>;
+pub type TypedPattern = Pattern<PatternConstructor, Rc<Type>>;
+
+impl<C, T> Pattern<C, T> {
+    pub fn location(&self) -> Span {
+        match self {
+            Pattern::Int { location, .. }
+            | Pattern::String { location, .. }
+            | Pattern::Var { location, .. }
+            | Pattern::Discard { location, .. }
+            | Pattern::Constructor { location, .. }
+            | Pattern::List { location, .. } => *location,
+        }
+    }
+}
+
+/// How an assignment behaves when it's pattern doesn't match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentKind<Expr> {
+    /// `let`, the pattern has to match every value.
+    Let,
+    /// `expect`, failing with a generic trace.
+    Expect,
+    /// `expect pattern = value else default`, the rest of the block
+    /// is skipped and the block evaluates to `default`.
+    ExpectElse(Box<Expr>),
+    /// `expect pattern = value or fail "message"`, failing after
+    /// tracing `message`.
+    ExpectOrFail(String),
+}
+
+impl<Expr> AssignmentKind<Expr> {
+    pub fn is_let(&self) -> bool {
+        matches!(self, AssignmentKind::Let)
+    }
+}
+
+/// `pattern -> then` in a `when` expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause<Expr, Constructor, Type> {
+    pub location: Span,
+    pub pattern: Pattern<Constructor, Type>,
+    pub then: Expr,
+}
+
+pub type UntypedClause = Clause<UntypedExpr, (), ()>;
+pub type TypedClause = Clause<TypedExpr, PatternConstructor, Rc<Type>>;
+
+/// `condition { body }` in an `if` expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfBranch<Expr> {
+    pub location: Span,
+    pub condition: Expr,
+    pub body: Expr,
+}
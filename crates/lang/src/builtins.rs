@@ -0,0 +1,288 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use strum::IntoEnumIterator;
+use uplc::{ast as plutus, builtins::DefaultFunction};
+
+use crate::{
+    ast::{ModuleKind, Span},
+    tipo::{Type, TypeConstructor, TypeInfo, TypeVar, ValueConstructor, ValueConstructorVariant},
+};
+
+/// The name of the prelude module, whose types and values are in
+/// scope in every module without being imported.
+pub const PRELUDE: &str = "";
+
+/// The module exposing the Plutus builtins as functions.
+pub const BUILTIN: &str = "aiken/builtin";
+
+/// The module of integer functions, see `math`.
+pub const MATH: &str = "aiken/math";
+
+/// The types and constructors every module starts with.
+pub fn prelude() -> TypeInfo {
+    let mut prelude = TypeInfo {
+        name: PRELUDE.to_string(),
+        kind: ModuleKind::Lib,
+        types: HashMap::new(),
+        types_constructors: HashMap::new(),
+        values: HashMap::new(),
+    };
+
+    for (name, tipo) in [
+        ("Int", int()),
+        ("ByteArray", byte_array()),
+        ("String", string()),
+        ("Data", data()),
+        ("Bool", bool()),
+        ("Void", void()),
+    ] {
+        prelude
+            .types
+            .insert(name.to_string(), prelude_type(vec![], tipo));
+    }
+
+    let element = generic_var(0);
+    prelude.types.insert(
+        "List".to_string(),
+        prelude_type(vec![element.clone()], list(element)),
+    );
+
+    for (name, tag, constructors_count, tipo) in [
+        ("False", 0, 2, bool()),
+        ("True", 1, 2, bool()),
+        ("Void", 0, 1, void()),
+    ] {
+        prelude.values.insert(
+            name.to_string(),
+            ValueConstructor {
+                public: true,
+                variant: ValueConstructorVariant::Record {
+                    name: name.to_string(),
+                    module: PRELUDE.to_string(),
+                    arity: 0,
+                    tag,
+                    constructors_count,
+                    field_map: None,
+                    location: Span::default(),
+                },
+                tipo,
+            },
+        );
+    }
+
+    prelude.types_constructors.insert(
+        "Bool".to_string(),
+        vec!["False".to_string(), "True".to_string()],
+    );
+    prelude
+        .types_constructors
+        .insert("Void".to_string(), vec!["Void".to_string()]);
+
+    prelude
+}
+
+/// The `aiken/builtin` module: every builtin whose signature can be
+/// expressed with Aiken types, named in snake case. Those involving
+/// pairs are left out.
+pub fn plutus() -> TypeInfo {
+    let mut module = TypeInfo {
+        name: BUILTIN.to_string(),
+        kind: ModuleKind::Lib,
+        types: HashMap::new(),
+        types_constructors: HashMap::new(),
+        values: HashMap::new(),
+    };
+
+    for fun in DefaultFunction::iter() {
+        let signature = fun.signature();
+        let mut vars = HashMap::new();
+
+        let arguments: Option<Vec<Rc<Type>>> = signature
+            .arguments
+            .iter()
+            .map(|argument| from_plutus_type(argument, &mut vars))
+            .collect();
+
+        let (Some(arguments), Some(ret)) =
+            (arguments, from_plutus_type(&signature.result, &mut vars))
+        else {
+            continue;
+        };
+
+        let name = snake_case(&fun.to_string());
+
+        module.values.insert(
+            name.clone(),
+            ValueConstructor {
+                public: true,
+                variant: ValueConstructorVariant::ModuleFn {
+                    name,
+                    module: BUILTIN.to_string(),
+                    arity: arguments.len(),
+                    builtin: Some(fun),
+                    location: Span::default(),
+                },
+                tipo: function(arguments, ret),
+            },
+        );
+    }
+
+    module
+}
+
+/// The `aiken/math` module. The division functions are the Plutus
+/// builtins, which differ on negative numbers:
+///
+/// - `div` rounds towards negative infinity and `mod` has the sign of
+///   the divisor, `div(-7, 2) == -4` and `mod(-7, 2) == 1`. These are
+///   what the `/` and `%` operators do.
+/// - `quot` rounds towards zero and `rem` has the sign of the
+///   dividend, `quot(-7, 2) == -3` and `rem(-7, 2) == -1`.
+///
+/// All four fail when dividing by zero. `abs` and `pow` have no
+/// builtin and are generated, `pow` fails on a negative exponent.
+pub fn math() -> TypeInfo {
+    let mut module = TypeInfo {
+        name: MATH.to_string(),
+        kind: ModuleKind::Lib,
+        types: HashMap::new(),
+        types_constructors: HashMap::new(),
+        values: HashMap::new(),
+    };
+
+    for (name, arity, builtin) in [
+        ("div", 2, Some(DefaultFunction::DivideInteger)),
+        ("mod", 2, Some(DefaultFunction::ModInteger)),
+        ("quot", 2, Some(DefaultFunction::QuotientInteger)),
+        ("rem", 2, Some(DefaultFunction::RemainderInteger)),
+        ("abs", 1, None),
+        ("pow", 2, None),
+    ] {
+        module.values.insert(
+            name.to_string(),
+            ValueConstructor {
+                public: true,
+                variant: ValueConstructorVariant::ModuleFn {
+                    name: name.to_string(),
+                    module: MATH.to_string(),
+                    arity,
+                    builtin,
+                    location: Span::default(),
+                },
+                tipo: function(vec![int(); arity], int()),
+            },
+        );
+    }
+
+    module
+}
+
+/// Whether `builtin` divides it's first argument by it's second.
+pub fn is_division(builtin: DefaultFunction) -> bool {
+    matches!(
+        builtin,
+        DefaultFunction::DivideInteger
+            | DefaultFunction::ModInteger
+            | DefaultFunction::QuotientInteger
+            | DefaultFunction::RemainderInteger
+    )
+}
+
+fn from_plutus_type(
+    tipo: &plutus::Type,
+    vars: &mut HashMap<&'static str, Rc<Type>>,
+) -> Option<Rc<Type>> {
+    match tipo {
+        plutus::Type::Integer => Some(int()),
+        plutus::Type::ByteString => Some(byte_array()),
+        plutus::Type::String => Some(string()),
+        plutus::Type::Unit => Some(void()),
+        plutus::Type::Bool => Some(bool()),
+        plutus::Type::Data => Some(data()),
+        plutus::Type::List(element) => from_plutus_type(element, vars).map(list),
+        plutus::Type::Pair(_, _) => None,
+        plutus::Type::Var(name) => {
+            let id = vars.len() as u64;
+
+            Some(vars.entry(name).or_insert_with(|| generic_var(id)).clone())
+        }
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+fn prelude_type(parameters: Vec<Rc<Type>>, tipo: Rc<Type>) -> TypeConstructor {
+    TypeConstructor {
+        public: true,
+        location: Span::default(),
+        module: PRELUDE.to_string(),
+        parameters,
+        tipo,
+    }
+}
+
+fn prelude_app(name: &str, args: Vec<Rc<Type>>) -> Rc<Type> {
+    Rc::new(Type::App {
+        public: true,
+        module: PRELUDE.to_string(),
+        name: name.to_string(),
+        args,
+    })
+}
+
+pub fn int() -> Rc<Type> {
+    prelude_app("Int", vec![])
+}
+
+pub fn byte_array() -> Rc<Type> {
+    prelude_app("ByteArray", vec![])
+}
+
+pub fn string() -> Rc<Type> {
+    prelude_app("String", vec![])
+}
+
+pub fn data() -> Rc<Type> {
+    prelude_app("Data", vec![])
+}
+
+pub fn bool() -> Rc<Type> {
+    prelude_app("Bool", vec![])
+}
+
+pub fn void() -> Rc<Type> {
+    prelude_app("Void", vec![])
+}
+
+pub fn list(element: Rc<Type>) -> Rc<Type> {
+    prelude_app("List", vec![element])
+}
+
+pub fn function(args: Vec<Rc<Type>>, ret: Rc<Type>) -> Rc<Type> {
+    Rc::new(Type::Fn { args, ret })
+}
+
+pub fn generic_var(id: u64) -> Rc<Type> {
+    Rc::new(Type::Var {
+        tipo: Rc::new(RefCell::new(TypeVar::Generic { id })),
+    })
+}
+
+pub fn unbound_var(id: u64) -> Rc<Type> {
+    Rc::new(Type::Var {
+        tipo: Rc::new(RefCell::new(TypeVar::Unbound { id })),
+    })
+}
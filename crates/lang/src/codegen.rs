@@ -0,0 +1,1271 @@
+//! Turn type checked modules into Untyped Plutus Core.
+//!
+//! Each function is a curried lambda, functions without arguments
+//! take a single unit argument instead. A program binds every
+//! function the entry point uses, dependencies first, and
+//! recursive functions go through a self application.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use uplc::{
+    ast::{Constant, Name, Program, Term, Type as UplcType, Unique},
+    builtins::DefaultFunction,
+};
+
+use crate::{
+    ast::{ArgName, AssignmentKind, BinOp, Definition, Span, TypedFunction, TypedModule, UnOp},
+    builtins,
+    expr::TypedExpr,
+    tipo::{Type, TypeInfo, ValueConstructorVariant},
+};
+
+mod data;
+pub mod error;
+mod math;
+mod pattern;
+
+pub use error::Error;
+
+/// A function, by module and name.
+type Key = (String, String);
+
+pub struct CodeGenerator<'a> {
+    functions: HashMap<Key, &'a TypedFunction>,
+    plutus: TypeInfo,
+    scope: HashMap<String, Name>,
+    /// The names functions are bound to in the generated program.
+    referenced: HashMap<Key, Name>,
+    current: Option<Current>,
+    next_unique: isize,
+}
+
+/// The function being generated.
+struct Current {
+    key: Key,
+    /// Bound to the function itself when it is recursive.
+    this: Name,
+    recursive: bool,
+    dependencies: BTreeSet<Key>,
+}
+
+impl<'a> CodeGenerator<'a> {
+    pub fn new(modules: impl IntoIterator<Item = &'a TypedModule>) -> Self {
+        let functions = modules
+            .into_iter()
+            .flat_map(|module| {
+                module
+                    .definitions
+                    .iter()
+                    .filter_map(move |definition| match definition {
+                        Definition::Fn(function) => {
+                            Some(((module.name.clone(), function.name.clone()), function))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        CodeGenerator {
+            functions,
+            plutus: builtins::plutus(),
+            scope: HashMap::new(),
+            referenced: HashMap::new(),
+            current: None,
+            next_unique: 0,
+        }
+    }
+
+    /// The function `name` of `module`, with every function it uses
+    /// bound around it.
+    pub fn generate(&mut self, module: &str, name: &str) -> Result<Term<Name>, Error> {
+        let root = (module.to_string(), name.to_string());
+
+        if !self.functions.contains_key(&root) {
+            return Err(Error::UnknownFunction {
+                module: module.to_string(),
+                name: name.to_string(),
+            });
+        }
+
+        let mut definitions = BTreeMap::new();
+        let mut queue = vec![root.clone()];
+
+        while let Some(key) = queue.pop() {
+            if definitions.contains_key(&key) {
+                continue;
+            }
+
+            let (term, dependencies) = self.function(&key)?;
+
+            queue.extend(dependencies.iter().cloned());
+            definitions.insert(key, (term, dependencies));
+        }
+
+        let mut order = vec![];
+        self.sort(&root, &definitions, &mut vec![], &mut order)?;
+
+        let (mut program, _) = definitions.remove(&root).expect("the root was generated");
+
+        for key in order.iter().rev().filter(|key| **key != root) {
+            let (definition, _) = definitions
+                .remove(key)
+                .expect("dependencies were generated");
+
+            program = apply(lambda(self.referenced[key].clone(), program), definition);
+        }
+
+        Ok(program)
+    }
+
+    /// A validator taking it's arguments as `Data` and failing unless
+    /// it returns `True`, or unless it returns at all if it doesn't
+    /// return a `Bool`.
+    pub fn generate_validator(&mut self, module: &str, name: &str) -> Result<Program<Name>, Error> {
+        let function = self.generate(module, name)?;
+
+        let definition = self.functions[&(module.to_string(), name.to_string())];
+        let location = definition.location;
+
+        let parameters: Vec<(Name, Rc<Type>)> = definition
+            .arguments
+            .iter()
+            .map(|argument| (self.fresh(argument.name.name()), argument.tipo.clone()))
+            .collect();
+
+        let arguments = parameters
+            .iter()
+            .map(|(parameter, tipo)| data::from_data(tipo, Term::Var(parameter.clone()), location))
+            .collect::<Result<_, _>>()?;
+
+        let mut result = apply_all(function, arguments);
+
+        if definition.return_type.is_bool() {
+            result = if_then_else(result, Term::Constant(Constant::Unit), Term::Error);
+        }
+
+        let term = parameters
+            .into_iter()
+            .rev()
+            .fold(result, |body, (parameter, _)| lambda(parameter, body));
+
+        Ok(Program {
+            version: (1, 0, 0),
+            term,
+        })
+    }
+
+    /// The function `name` of `module` and every function of the
+    /// project it uses, directly or not. The program generated for it
+    /// only depends on their definitions and on the types involved.
+    pub fn dependencies(&self, module: &str, name: &str) -> BTreeSet<(String, String)> {
+        let mut found = BTreeSet::new();
+        let mut queue = vec![(module.to_string(), name.to_string())];
+
+        while let Some(key) = queue.pop() {
+            let Some(function) = self.functions.get(&key) else {
+                continue;
+            };
+
+            if found.insert(key) {
+                let mut referenced = BTreeSet::new();
+
+                references(&function.body, &mut referenced);
+
+                queue.extend(referenced);
+            }
+        }
+
+        found
+    }
+
+    fn fresh(&mut self, text: &str) -> Name {
+        self.next_unique += 1;
+
+        Name {
+            text: text.to_string(),
+            unique: Unique::new(self.next_unique),
+        }
+    }
+
+    /// Order the functions `key` depends on so that each comes after
+    /// it's own dependencies.
+    fn sort(
+        &self,
+        key: &Key,
+        definitions: &BTreeMap<Key, (Term<Name>, BTreeSet<Key>)>,
+        visiting: &mut Vec<Key>,
+        order: &mut Vec<Key>,
+    ) -> Result<(), Error> {
+        if order.contains(key) {
+            return Ok(());
+        }
+
+        if visiting.contains(key) {
+            return Err(Error::MutualRecursion {
+                location: self.functions[key].location,
+                name: key.1.clone(),
+            });
+        }
+
+        visiting.push(key.clone());
+
+        for dependency in &definitions[key].1 {
+            self.sort(dependency, definitions, visiting, order)?;
+        }
+
+        visiting.pop();
+        order.push(key.clone());
+
+        Ok(())
+    }
+
+    /// Generate a function and collect the functions it refers to.
+    fn function(&mut self, key: &Key) -> Result<(Term<Name>, BTreeSet<Key>), Error> {
+        let function = self.functions[key];
+        let this = self.fresh(&function.name);
+
+        self.scope.clear();
+        self.current = Some(Current {
+            key: key.clone(),
+            this: this.clone(),
+            recursive: false,
+            dependencies: BTreeSet::new(),
+        });
+
+        let parameters = if function.arguments.is_empty() {
+            vec![self.fresh("_")]
+        } else {
+            function
+                .arguments
+                .iter()
+                .map(|argument| {
+                    let parameter = self.fresh(argument.name.name());
+
+                    if let ArgName::Named(name) = &argument.name {
+                        self.scope.insert(name.clone(), parameter.clone());
+                    }
+
+                    parameter
+                })
+                .collect()
+        };
+
+        let body = self.expr(&function.body);
+
+        let current = self.current.take().expect("a function is being generated");
+
+        let mut term = parameters
+            .into_iter()
+            .rev()
+            .fold(body?, |body, parameter| lambda(parameter, body));
+
+        if current.recursive {
+            let definition = self.fresh("definition");
+
+            term = apply(
+                lambda(
+                    definition.clone(),
+                    apply(Term::Var(definition.clone()), Term::Var(definition)),
+                ),
+                lambda(this, term),
+            );
+        }
+
+        Ok((term, current.dependencies))
+    }
+
+    fn expr(&mut self, expr: &TypedExpr) -> Result<Term<Name>, Error> {
+        match expr {
+            TypedExpr::Int { value, .. } => Ok(Term::Constant(Constant::Integer(value.clone()))),
+
+            TypedExpr::String { value, .. } => Ok(Term::Constant(Constant::String(value.clone()))),
+
+            TypedExpr::ByteArray { bytes, .. } => {
+                Ok(Term::Constant(Constant::ByteString(bytes.clone())))
+            }
+
+            TypedExpr::Var {
+                location,
+                constructor,
+                name,
+            } => match &constructor.variant {
+                ValueConstructorVariant::LocalVariable { .. } => {
+                    Ok(Term::Var(self.scope[name].clone()))
+                }
+                variant => self.constructor(variant, &constructor.tipo, *location),
+            },
+
+            TypedExpr::ModuleSelect {
+                location,
+                tipo,
+                constructor,
+                ..
+            } => self.constructor(constructor, tipo, *location),
+
+            TypedExpr::Fn {
+                arguments, body, ..
+            } => {
+                let saved = self.scope.clone();
+
+                let parameters = if arguments.is_empty() {
+                    vec![self.fresh("_")]
+                } else {
+                    arguments
+                        .iter()
+                        .map(|argument| {
+                            let parameter = self.fresh(argument.name.name());
+
+                            if let ArgName::Named(name) = &argument.name {
+                                self.scope.insert(name.clone(), parameter.clone());
+                            }
+
+                            parameter
+                        })
+                        .collect()
+                };
+
+                let body = self.expr(body);
+                self.scope = saved;
+
+                Ok(parameters
+                    .into_iter()
+                    .rev()
+                    .fold(body?, |body, parameter| lambda(parameter, body)))
+            }
+
+            TypedExpr::List {
+                elements,
+                tail,
+                tipo,
+                location,
+            } => {
+                let element_type = tipo
+                    .arg_types()
+                    .and_then(|args| args.first().cloned())
+                    .expect("a list has a list type");
+
+                let mut list = match tail {
+                    Some(tail) => self.expr(tail)?,
+                    None => Term::Constant(Constant::ProtoList(UplcType::Data, vec![])),
+                };
+
+                for element in elements.iter().rev() {
+                    let element = data::to_data(&element_type, self.expr(element)?, *location)?;
+
+                    list = builtin_call(DefaultFunction::MkCons, vec![element, list]);
+                }
+
+                Ok(list)
+            }
+
+            TypedExpr::Call { fun, arguments, .. } => {
+                let fun = self.expr(fun)?;
+
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.expr(argument))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(apply_all(fun, arguments))
+            }
+
+            TypedExpr::BinOp {
+                name,
+                left,
+                right,
+                location,
+                ..
+            } => self.bin_op(*name, left, right, *location),
+
+            TypedExpr::UnOp { op, value, .. } => {
+                let value = self.expr(value)?;
+
+                Ok(match op {
+                    UnOp::Not => if_then_else(
+                        value,
+                        Term::Constant(Constant::Bool(false)),
+                        Term::Constant(Constant::Bool(true)),
+                    ),
+                    UnOp::Negate => builtin_call(
+                        DefaultFunction::SubtractInteger,
+                        vec![Term::Constant(Constant::Integer(0.into())), value],
+                    ),
+                })
+            }
+
+            TypedExpr::Sequence { expressions, .. } => self.sequence(expressions),
+
+            TypedExpr::Assignment { .. } => self.sequence(std::slice::from_ref(expr)),
+
+            TypedExpr::When {
+                subject, clauses, ..
+            } => {
+                let subject = self.expr(subject)?;
+                let subject_name = self.fresh("subject");
+
+                let mut term = Term::Error;
+
+                for (index, clause) in clauses.iter().enumerate().rev() {
+                    let saved = self.scope.clone();
+
+                    self.declare_pattern(&clause.pattern);
+                    let then = self.expr(&clause.then);
+
+                    let clause_term = if index + 1 == clauses.len() {
+                        then.and_then(|then| {
+                            self.pattern(
+                                &clause.pattern,
+                                Term::Var(subject_name.clone()),
+                                then,
+                                Term::Error,
+                            )
+                        })
+                    } else {
+                        let fail = self.fresh("fail");
+
+                        then.and_then(|then| {
+                            self.pattern(
+                                &clause.pattern,
+                                Term::Var(subject_name.clone()),
+                                then,
+                                force(Term::Var(fail.clone())),
+                            )
+                        })
+                        .map(|matched| apply(lambda(fail, matched), delay(term)))
+                    };
+
+                    self.scope = saved;
+                    term = clause_term?;
+                }
+
+                Ok(apply(lambda(subject_name, term), subject))
+            }
+
+            TypedExpr::If {
+                branches,
+                final_else,
+                ..
+            } => {
+                let mut term = self.expr(final_else)?;
+
+                for branch in branches.iter().rev() {
+                    let condition = self.expr(&branch.condition)?;
+                    let body = self.expr(&branch.body)?;
+
+                    term = if_then_else(condition, body, term);
+                }
+
+                Ok(term)
+            }
+
+            TypedExpr::RecordAccess {
+                index,
+                record,
+                tipo,
+                location,
+                ..
+            } => {
+                let record = self.expr(record)?;
+
+                data::from_data(
+                    tipo,
+                    data::list_index(data::constr_fields(record), *index),
+                    *location,
+                )
+            }
+
+            TypedExpr::Todo { label, .. } => Ok(fail_with(
+                label.clone().unwrap_or_else(|| "aiken::todo".to_string()),
+            )),
+
+            TypedExpr::ErrorTerm { label, .. } => Ok(match label {
+                Some(label) => fail_with(label.clone()),
+                None => Term::Error,
+            }),
+        }
+    }
+
+    /// A module function, a builtin or a record constructor, used at
+    /// type `tipo`.
+    fn constructor(
+        &mut self,
+        variant: &ValueConstructorVariant,
+        tipo: &Rc<Type>,
+        location: Span,
+    ) -> Result<Term<Name>, Error> {
+        match variant {
+            ValueConstructorVariant::LocalVariable { .. } => {
+                unreachable!("local variables are looked up in scope")
+            }
+
+            ValueConstructorVariant::ModuleFn {
+                name,
+                module,
+                builtin: Some(fun),
+                ..
+            } if module == builtins::BUILTIN => {
+                let generic = self.plutus.values[name].tipo.clone();
+
+                self.coerce(builtin(*fun), &generic, tipo, location)
+            }
+
+            // not generic, no coercion needed
+            ValueConstructorVariant::ModuleFn {
+                builtin: Some(fun), ..
+            } => Ok(builtin(*fun)),
+
+            ValueConstructorVariant::ModuleFn { name, module, .. } if module == builtins::MATH => {
+                Ok(self.math(name))
+            }
+
+            ValueConstructorVariant::ModuleFn { name, module, .. } => {
+                let key = (module.clone(), name.clone());
+
+                let function =
+                    self.functions
+                        .get(&key)
+                        .copied()
+                        .ok_or_else(|| Error::UnknownFunction {
+                            module: module.clone(),
+                            name: name.clone(),
+                        })?;
+
+                let generic = builtins::function(
+                    function
+                        .arguments
+                        .iter()
+                        .map(|argument| argument.tipo.clone())
+                        .collect(),
+                    function.return_type.clone(),
+                );
+
+                let bound = match self.referenced.get(&key) {
+                    Some(bound) => bound.clone(),
+                    None => {
+                        let bound = self.fresh(name);
+                        self.referenced.insert(key.clone(), bound.clone());
+                        bound
+                    }
+                };
+
+                let current = self
+                    .current
+                    .as_mut()
+                    .expect("a function is being generated");
+
+                let term = if current.key == key {
+                    current.recursive = true;
+
+                    apply(
+                        Term::Var(current.this.clone()),
+                        Term::Var(current.this.clone()),
+                    )
+                } else {
+                    current.dependencies.insert(key);
+
+                    Term::Var(bound)
+                };
+
+                self.coerce(term, &generic, tipo, location)
+            }
+
+            ValueConstructorVariant::Record { arity: 0, tag, .. } => Ok(if tipo.is_bool() {
+                Term::Constant(Constant::Bool(*tag == 1))
+            } else if tipo.is_void() {
+                Term::Constant(Constant::Unit)
+            } else {
+                data::constr(*tag)
+            }),
+
+            ValueConstructorVariant::Record { tag, .. } => {
+                let (fields, _) = tipo.fn_types().expect("a record with fields is a function");
+
+                let parameters: Vec<Name> = fields.iter().map(|_| self.fresh("field")).collect();
+
+                let mut list = Term::Constant(Constant::ProtoList(UplcType::Data, vec![]));
+
+                for (parameter, field) in parameters.iter().zip(&fields).rev() {
+                    let field = data::to_data(field, Term::Var(parameter.clone()), location)?;
+
+                    list = builtin_call(DefaultFunction::MkCons, vec![field, list]);
+                }
+
+                let record = builtin_call(
+                    DefaultFunction::ConstrData,
+                    vec![Term::Constant(Constant::Integer((*tag).into())), list],
+                );
+
+                Ok(parameters
+                    .into_iter()
+                    .rev()
+                    .fold(record, |body, parameter| lambda(parameter, body)))
+            }
+        }
+    }
+
+    fn bin_op(
+        &mut self,
+        name: BinOp,
+        left: &TypedExpr,
+        right: &TypedExpr,
+        location: Span,
+    ) -> Result<Term<Name>, Error> {
+        let tipo = left.tipo();
+        let left = self.expr(left)?;
+        let right = self.expr(right)?;
+
+        let integer = |fun, left, right| builtin_call(fun, vec![left, right]);
+
+        Ok(match name {
+            BinOp::And => if_then_else(left, right, Term::Constant(Constant::Bool(false))),
+            BinOp::Or => if_then_else(left, Term::Constant(Constant::Bool(true)), right),
+            BinOp::Eq => self.equals(&tipo, left, right, location)?,
+            BinOp::NotEq => if_then_else(
+                self.equals(&tipo, left, right, location)?,
+                Term::Constant(Constant::Bool(false)),
+                Term::Constant(Constant::Bool(true)),
+            ),
+            BinOp::LtInt => integer(DefaultFunction::LessThanInteger, left, right),
+            BinOp::LtEqInt => integer(DefaultFunction::LessThanEqualsInteger, left, right),
+            BinOp::GtInt => integer(DefaultFunction::LessThanInteger, right, left),
+            BinOp::GtEqInt => integer(DefaultFunction::LessThanEqualsInteger, right, left),
+            BinOp::AddInt => integer(DefaultFunction::AddInteger, left, right),
+            BinOp::SubInt => integer(DefaultFunction::SubtractInteger, left, right),
+            BinOp::MultInt => integer(DefaultFunction::MultiplyInteger, left, right),
+            BinOp::DivInt => integer(DefaultFunction::DivideInteger, left, right),
+            BinOp::ModInt => integer(DefaultFunction::ModInteger, left, right),
+        })
+    }
+
+    /// The expressions of a block, `let`s binding the variables of
+    /// their pattern for the expressions after them.
+    fn sequence(&mut self, expressions: &[TypedExpr]) -> Result<Term<Name>, Error> {
+        let Some((first, rest)) = expressions.split_first() else {
+            return Ok(Term::Constant(Constant::Unit));
+        };
+
+        match first {
+            TypedExpr::Assignment {
+                kind,
+                pattern,
+                value,
+                ..
+            } => {
+                let value = self.expr(value)?;
+
+                // Bound around the whole assignment, as the pattern
+                // may use it more than once.
+                let mut otherwise = None;
+
+                let fail = match kind {
+                    AssignmentKind::Let => Term::Error,
+                    AssignmentKind::Expect => fail_with("aiken::expect".to_string()),
+                    AssignmentKind::ExpectOrFail(message) => fail_with(message.clone()),
+                    AssignmentKind::ExpectElse(default) => {
+                        let name = self.fresh("otherwise");
+                        otherwise = Some((name.clone(), delay(self.expr(default)?)));
+
+                        force(Term::Var(name))
+                    }
+                };
+
+                let saved = self.scope.clone();
+
+                let term = if rest.is_empty() {
+                    let name = self.fresh("value");
+
+                    self.declare_pattern(pattern);
+
+                    self.pattern(
+                        pattern,
+                        Term::Var(name.clone()),
+                        Term::Var(name.clone()),
+                        fail,
+                    )
+                    .map(|matched| apply(lambda(name, matched), value))
+                } else {
+                    self.declare_pattern(pattern);
+
+                    self.sequence(rest)
+                        .and_then(|rest| self.pattern(pattern, value, rest, fail))
+                };
+
+                self.scope = saved;
+
+                match otherwise {
+                    Some((name, default)) => term.map(|term| apply(lambda(name, term), default)),
+                    None => term,
+                }
+            }
+
+            _ if rest.is_empty() => self.expr(first),
+
+            _ => {
+                let first = self.expr(first)?;
+                let rest = self.sequence(rest)?;
+
+                Ok(apply(lambda(self.fresh("_"), rest), first))
+            }
+        }
+    }
+}
+
+/// Collect the functions defined in Aiken an expression refers to.
+fn references(expr: &TypedExpr, found: &mut BTreeSet<Key>) {
+    let mut function = |variant: &ValueConstructorVariant| {
+        if let ValueConstructorVariant::ModuleFn {
+            name,
+            module,
+            builtin: None,
+            ..
+        } = variant
+        {
+            if module != builtins::MATH {
+                found.insert((module.clone(), name.clone()));
+            }
+        }
+    };
+
+    match expr {
+        TypedExpr::Var { constructor, .. } => function(&constructor.variant),
+        TypedExpr::ModuleSelect { constructor, .. } => function(constructor),
+
+        TypedExpr::Fn { body, .. } => references(body, found),
+        TypedExpr::UnOp { value, .. } => references(value, found),
+        TypedExpr::RecordAccess { record, .. } => references(record, found),
+        TypedExpr::BinOp { left, right, .. } => {
+            references(left, found);
+            references(right, found);
+        }
+        TypedExpr::List { elements, tail, .. } => {
+            for element in elements.iter().chain(tail.as_deref()) {
+                references(element, found);
+            }
+        }
+        TypedExpr::Call { fun, arguments, .. } => {
+            for expr in std::iter::once(fun.as_ref()).chain(arguments) {
+                references(expr, found);
+            }
+        }
+        TypedExpr::Sequence { expressions, .. } => {
+            for expr in expressions {
+                references(expr, found);
+            }
+        }
+        TypedExpr::Assignment { kind, value, .. } => {
+            references(value, found);
+
+            if let AssignmentKind::ExpectElse(default) = kind {
+                references(default, found);
+            }
+        }
+        TypedExpr::When {
+            subject, clauses, ..
+        } => {
+            references(subject, found);
+
+            for clause in clauses {
+                references(&clause.then, found);
+            }
+        }
+        TypedExpr::If {
+            branches,
+            final_else,
+            ..
+        } => {
+            for branch in branches {
+                references(&branch.condition, found);
+                references(&branch.body, found);
+            }
+
+            references(final_else, found);
+        }
+
+        TypedExpr::Int { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::ByteArray { .. }
+        | TypedExpr::Todo { .. }
+        | TypedExpr::ErrorTerm { .. } => (),
+    }
+}
+
+fn apply(function: Term<Name>, argument: Term<Name>) -> Term<Name> {
+    Term::Apply {
+        function: function.into(),
+        argument: argument.into(),
+    }
+}
+
+/// Apply a function generated from Aiken, which takes a unit when
+/// it has no arguments.
+fn apply_all(function: Term<Name>, arguments: Vec<Term<Name>>) -> Term<Name> {
+    if arguments.is_empty() {
+        return apply(function, Term::Constant(Constant::Unit));
+    }
+
+    arguments.into_iter().fold(function, apply)
+}
+
+fn lambda(parameter_name: Name, body: Term<Name>) -> Term<Name> {
+    Term::Lambda {
+        parameter_name,
+        body: body.into(),
+    }
+}
+
+fn delay(term: Term<Name>) -> Term<Name> {
+    Term::Delay(term.into())
+}
+
+fn force(term: Term<Name>) -> Term<Name> {
+    Term::Force(term.into())
+}
+
+/// A builtin, forced as many times as it takes type arguments.
+fn builtin(fun: DefaultFunction) -> Term<Name> {
+    (0..fun.force_count()).fold(Term::Builtin(fun), |term, _| force(term))
+}
+
+fn builtin_call(fun: DefaultFunction, arguments: Vec<Term<Name>>) -> Term<Name> {
+    arguments.into_iter().fold(builtin(fun), apply)
+}
+
+/// Only evaluates the branch that is taken.
+fn if_then_else(condition: Term<Name>, then: Term<Name>, otherwise: Term<Name>) -> Term<Name> {
+    force(builtin_call(
+        DefaultFunction::IfThenElse,
+        vec![condition, delay(then), delay(otherwise)],
+    ))
+}
+
+/// Fail after tracing `label`.
+fn fail_with(label: String) -> Term<Name> {
+    force(builtin_call(
+        DefaultFunction::Trace,
+        vec![Term::Constant(Constant::String(label)), delay(Term::Error)],
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use num_bigint::BigInt;
+    use uplc::{
+        ast::{Constant, DeBruijn, NamedDeBruijn, Program, Term},
+        data::PlutusData,
+    };
+
+    use super::{CodeGenerator, Error};
+    use crate::{
+        ast::{ModuleKind, TypedModule},
+        builtins, parser, tipo,
+    };
+
+    fn check(src: &str) -> TypedModule {
+        let module = parser::module(src, "test", ModuleKind::Lib).unwrap();
+
+        let importable_modules = HashMap::from([
+            (builtins::BUILTIN.to_string(), builtins::plutus()),
+            (builtins::MATH.to_string(), builtins::math()),
+        ]);
+
+        tipo::infer_module(module, &importable_modules).unwrap()
+    }
+
+    /// Evaluate the function `name`, which takes no arguments.
+    fn eval(src: &str, name: &str) -> Constant {
+        let module = check(src);
+
+        let term = CodeGenerator::new([&module])
+            .generate("test", name)
+            .unwrap();
+
+        let program = Program {
+            version: (1, 0, 0),
+            term,
+        }
+        .apply_term(&Term::Constant(Constant::Unit));
+
+        let program: Program<NamedDeBruijn> = program.try_into().unwrap();
+
+        match program.eval().unwrap() {
+            Term::Constant(constant) => constant,
+            term => panic!("expected a constant, got {:?}", term),
+        }
+    }
+
+    fn int(n: i64) -> Constant {
+        Constant::Integer(BigInt::from(n))
+    }
+
+    #[test]
+    fn arithmetic_and_conditions() {
+        let src = r#"
+            fn clamp(n, low, high) {
+              if n < low { low } else if n > high { high } else { n }
+            }
+
+            pub fn main() {
+              let x = 7 * 6 - 2
+              clamp(x / 3 + -x % 7, 0, 10)
+            }
+
+            pub fn logic() {
+              !(1 == 2) && (3 >= 4 || "a" != "b")
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(10));
+        assert_eq!(eval(src, "logic"), Constant::Bool(true));
+    }
+
+    #[test]
+    fn generics_and_recursion() {
+        let src = r#"
+            type Option(a) {
+              Some(a)
+              None
+            }
+
+            fn map(xs, f) {
+              when xs is {
+                [] -> []
+                [x, ..rest] -> [f(x), ..map(rest, f)]
+              }
+            }
+
+            fn sum(xs) {
+              when xs is {
+                [] -> 0
+                [x, ..rest] -> x + sum(rest)
+              }
+            }
+
+            fn find(xs, predicate) {
+              when xs is {
+                [] -> None
+                [x, ..rest] -> if predicate(x) { Some(x) } else { find(rest, predicate) }
+              }
+            }
+
+            fn with_default(option, default) {
+              when option is {
+                Some(x) -> x
+                None -> default
+              }
+            }
+
+            pub fn main() {
+              let xs = map([1, 2, 3], fn(n) { n * 10 })
+              let found = find(map(xs, fn(n) { n > 15 }), fn(b) { b })
+              if with_default(found, False) { sum(xs) } else { 0 }
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(60));
+    }
+
+    #[test]
+    fn nested_patterns_and_records() {
+        let src = r#"
+            type Option(a) {
+              Some(a)
+              None
+            }
+
+            type Point {
+              Point { x: Int, y: Int }
+            }
+
+            fn describe(option) {
+              when option is {
+                Some(Some(0)) -> "zero"
+                Some(Some(_)) -> "some"
+                Some(None) -> "none"
+                None -> "nothing"
+              }
+            }
+
+            fn second(xs) {
+              when xs is {
+                [_, x, ..] -> x
+                _ -> -1
+              }
+            }
+
+            pub fn main() {
+              let Point(x, y) = Point(3, 4)
+              let p = Point(2, 1)
+              x * 100 + y * 10 + p.x - p.y + second([5, 6, 7])
+            }
+
+            pub fn strings() {
+              describe(Some(Some(1)))
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(347));
+        assert_eq!(eval(src, "strings"), Constant::String("some".to_string()));
+    }
+
+    #[test]
+    fn builtins() {
+        let src = r#"
+            use aiken/builtin
+
+            pub fn main() {
+              builtin.length_of_byte_string(builtin.sha2_256(#"00ff"))
+            }
+
+            pub fn head() {
+              builtin.head_list([4, 5])
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(32));
+        assert_eq!(eval(src, "head"), int(4));
+    }
+
+    #[test]
+    fn data_patterns() {
+        let src = r#"
+            use aiken/builtin
+
+            fn describe(d: Data) {
+              when d is {
+                Constr(0, [Int(x), ..]) -> x
+                Constr(tag, fields) -> tag * 100 + describe(builtin.list_data(fields))
+                List([]) -> 0
+                List([x, ..rest]) -> describe(x) + describe(builtin.list_data(rest))
+                Int(i) -> i
+                Bytes(b) -> builtin.length_of_byte_string(b)
+                Map(_) -> -1
+              }
+            }
+
+            pub fn main() {
+              let first = builtin.constr_data(0, [builtin.i_data(7), builtin.b_data(#"00")])
+              let second = builtin.constr_data(2, [builtin.i_data(1), builtin.b_data(#"0000")])
+              describe(builtin.list_data([first, second]))
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(7 + 203));
+    }
+
+    #[test]
+    fn expect() {
+        let src = r#"
+            type Option(a) {
+              Some(a)
+              None
+            }
+
+            fn unwrap_or_zero(option) {
+              expect Some(x) = option else 0
+              x + 1
+            }
+
+            pub fn main() {
+              unwrap_or_zero(Some(41)) + unwrap_or_zero(None)
+            }
+
+            pub fn head() {
+              expect [x, ..] = [] or fail "empty list"
+              x
+            }
+
+            pub fn last() {
+              expect [_, x] = [1, 2]
+            }
+            "#;
+
+        assert_eq!(eval(src, "main"), int(42));
+        assert_eq!(
+            eval(src, "last"),
+            Constant::ProtoList(
+                uplc::ast::Type::Data,
+                vec![
+                    Constant::Data(PlutusData::Integer(1.into())),
+                    Constant::Data(PlutusData::Integer(2.into())),
+                ]
+            )
+        );
+
+        let term = CodeGenerator::new([&check(src)])
+            .generate("test", "head")
+            .unwrap();
+
+        let program: Program<NamedDeBruijn> = Program {
+            version: (1, 0, 0),
+            term,
+        }
+        .apply_term(&Term::Constant(Constant::Unit))
+        .try_into()
+        .unwrap();
+
+        let result = program.eval_with_budget(Default::default());
+
+        assert!(result.term.is_err());
+        assert_eq!(result.logs, vec!["empty list".to_string()]);
+    }
+
+    #[test]
+    fn math() {
+        let src = r#"
+            use aiken/math
+
+            pub fn division() {
+              [math.div(-7, 2), math.mod(-7, 2), math.quot(-7, 2), math.rem(-7, 2), -7 / 2, -7 % 2]
+            }
+
+            pub fn abs() {
+              math.abs(-5) * 10 + math.abs(3)
+            }
+
+            pub fn pow() {
+              [math.pow(2, 10), math.pow(-3, 3), math.pow(7, 0)]
+            }
+
+            pub fn negative_exponent() {
+              math.pow(2, -1)
+            }
+            "#;
+
+        let ints = |values: &[i64]| {
+            Constant::ProtoList(
+                uplc::ast::Type::Data,
+                values
+                    .iter()
+                    .map(|value| Constant::Data(PlutusData::Integer((*value).into())))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(eval(src, "division"), ints(&[-4, 1, -3, -1, -4, 1]));
+        assert_eq!(eval(src, "abs"), int(53));
+        assert_eq!(eval(src, "pow"), ints(&[1024, -27, 1]));
+
+        let term = CodeGenerator::new([&check(src)])
+            .generate("test", "negative_exponent")
+            .unwrap();
+
+        let program: Program<NamedDeBruijn> = Program {
+            version: (1, 0, 0),
+            term,
+        }
+        .apply_term(&Term::Constant(Constant::Unit))
+        .try_into()
+        .unwrap();
+
+        let result = program.eval_with_budget(Default::default());
+
+        assert!(result.term.is_err());
+        assert_eq!(result.logs, vec!["aiken/math.pow: negative exponent"]);
+    }
+
+    #[test]
+    fn validator() {
+        let module = check(
+            r#"
+            type Datum {
+              Datum { owner: ByteArray, amount: Int }
+            }
+
+            pub fn spend(datum: Datum, redeemer: Int, _ctx: Data) -> Bool {
+              datum.amount == redeemer && datum.owner == #"ab"
+            }
+            "#,
+        );
+
+        let program = CodeGenerator::new([&module])
+            .generate_validator("test", "spend")
+            .unwrap();
+
+        let run = |amount: i64, redeemer: i64| {
+            let datum = PlutusData::Constr {
+                tag: 0,
+                fields: vec![
+                    PlutusData::ByteString(vec![0xab]),
+                    PlutusData::Integer(amount.into()),
+                ],
+            };
+
+            let program: Program<NamedDeBruijn> = program
+                .apply_data(datum)
+                .apply_data(PlutusData::Integer(redeemer.into()))
+                .apply_data(PlutusData::Constr {
+                    tag: 0,
+                    fields: vec![],
+                })
+                .try_into()
+                .unwrap();
+
+            program.eval()
+        };
+
+        assert_eq!(run(42, 42).unwrap(), Term::Constant(Constant::Unit));
+        assert!(run(42, 41).is_err());
+
+        let _: Program<DeBruijn> = program.try_into().unwrap();
+    }
+
+    #[test]
+    fn dependencies() {
+        let module = check(
+            r#"
+            use aiken/math
+
+            fn double(n) {
+              n * 2
+            }
+
+            fn unused() {
+              1
+            }
+
+            fn quadruple(n) {
+              double(double(n))
+            }
+
+            pub fn main() {
+              when [1] is {
+                [n] -> quadruple(math.abs(n))
+                _ -> 0
+              }
+            }
+            "#,
+        );
+
+        let dependencies = CodeGenerator::new([&module]).dependencies("test", "main");
+
+        let names: Vec<&str> = dependencies.iter().map(|(_, name)| name.as_str()).collect();
+
+        assert_eq!(names, vec!["double", "main", "quadruple"]);
+    }
+
+    #[test]
+    fn mutual_recursion() {
+        let module = check(
+            r#"
+            fn is_even(n) {
+              if n == 0 { True } else { is_odd(n - 1) }
+            }
+
+            fn is_odd(n) {
+              if n == 0 { False } else { is_even(n - 1) }
+            }
+            "#,
+        );
+
+        let error = CodeGenerator::new([&module])
+            .generate("test", "is_even")
+            .unwrap_err();
+
+        assert!(matches!(error, Error::MutualRecursion { .. }));
+    }
+}
@@ -0,0 +1,236 @@
+//! How values of each type are represented at runtime and how to
+//! convert between representations.
+//!
+//! `Int`, `ByteArray`, `String`, `Bool` and `Void` are builtin
+//! constants and functions are lambdas. Everything else is `Data`:
+//! custom types are `Constr` data, lists are builtin lists of data
+//! and generic values are data too. Elements of lists and fields of
+//! custom types are always stored as data, so a generic function
+//! can work on them whatever their type.
+
+use std::rc::Rc;
+
+use uplc::{
+    ast::{Constant, Name, Term},
+    builtins::DefaultFunction,
+    data::PlutusData,
+};
+
+use crate::{
+    ast::Span,
+    tipo::{collapse_links, Type},
+};
+
+use super::{apply, apply_all, builtin_call, error::Error, if_then_else, lambda, CodeGenerator};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Repr {
+    Int,
+    ByteArray,
+    String,
+    Bool,
+    Void,
+    List,
+    Data,
+    Fn(Vec<Rc<Type>>, Rc<Type>),
+}
+
+fn repr(tipo: &Rc<Type>) -> Repr {
+    let tipo = collapse_links(tipo.clone());
+
+    if let Some((args, ret)) = tipo.fn_types() {
+        return Repr::Fn(args, ret);
+    }
+
+    if tipo.is_int() {
+        Repr::Int
+    } else if tipo.is_bytearray() {
+        Repr::ByteArray
+    } else if tipo.is_string() {
+        Repr::String
+    } else if tipo.is_bool() {
+        Repr::Bool
+    } else if tipo.is_void() {
+        Repr::Void
+    } else if tipo.is_list() {
+        Repr::List
+    } else {
+        Repr::Data
+    }
+}
+
+pub fn constr(tag: usize) -> Term<Name> {
+    Term::Constant(Constant::Data(PlutusData::Constr {
+        tag: tag as u64,
+        fields: vec![],
+    }))
+}
+
+pub fn to_data(tipo: &Rc<Type>, term: Term<Name>, location: Span) -> Result<Term<Name>, Error> {
+    Ok(match repr(tipo) {
+        Repr::Int => builtin_call(DefaultFunction::IData, vec![term]),
+        Repr::ByteArray => builtin_call(DefaultFunction::BData, vec![term]),
+        Repr::String => builtin_call(
+            DefaultFunction::BData,
+            vec![builtin_call(DefaultFunction::EncodeUtf8, vec![term])],
+        ),
+        Repr::Bool => if_then_else(term, constr(1), constr(0)),
+        Repr::Void => constr(0),
+        Repr::List => builtin_call(DefaultFunction::ListData, vec![term]),
+        Repr::Data => term,
+        Repr::Fn(..) => return Err(Error::FunctionAsData { location }),
+    })
+}
+
+pub fn from_data(tipo: &Rc<Type>, term: Term<Name>, location: Span) -> Result<Term<Name>, Error> {
+    Ok(match repr(tipo) {
+        Repr::Int => builtin_call(DefaultFunction::UnIData, vec![term]),
+        Repr::ByteArray => builtin_call(DefaultFunction::UnBData, vec![term]),
+        Repr::String => builtin_call(
+            DefaultFunction::DecodeUtf8,
+            vec![builtin_call(DefaultFunction::UnBData, vec![term])],
+        ),
+        Repr::Bool => builtin_call(
+            DefaultFunction::EqualsInteger,
+            vec![
+                constr_tag(term),
+                Term::Constant(Constant::Integer(1.into())),
+            ],
+        ),
+        Repr::Void => Term::Constant(Constant::Unit),
+        Repr::List => builtin_call(DefaultFunction::UnListData, vec![term]),
+        Repr::Data => term,
+        Repr::Fn(..) => return Err(Error::FunctionAsData { location }),
+    })
+}
+
+/// The tag of a `Constr` data.
+pub fn constr_tag(term: Term<Name>) -> Term<Name> {
+    builtin_call(
+        DefaultFunction::FstPair,
+        vec![builtin_call(DefaultFunction::UnConstrData, vec![term])],
+    )
+}
+
+/// The fields of a `Constr` data, as a list of data.
+pub fn constr_fields(term: Term<Name>) -> Term<Name> {
+    builtin_call(
+        DefaultFunction::SndPair,
+        vec![builtin_call(DefaultFunction::UnConstrData, vec![term])],
+    )
+}
+
+/// The element at `index` of a builtin list.
+pub fn list_index(list: Term<Name>, index: usize) -> Term<Name> {
+    let tail = (0..index).fold(list, |list, _| {
+        builtin_call(DefaultFunction::TailList, vec![list])
+    });
+
+    builtin_call(DefaultFunction::HeadList, vec![tail])
+}
+
+impl<'a> CodeGenerator<'a> {
+    /// Convert a value of type `from` to the representation of type
+    /// `to`, where `from` is a generic type `to` is an instance of,
+    /// or the other way around.
+    pub(super) fn coerce(
+        &mut self,
+        term: Term<Name>,
+        from: &Rc<Type>,
+        to: &Rc<Type>,
+        location: Span,
+    ) -> Result<Term<Name>, Error> {
+        match (repr(from), repr(to)) {
+            (Repr::Fn(from_args, from_ret), Repr::Fn(to_args, to_ret)) => {
+                let fun = self.fresh("f");
+
+                let parameters: Vec<Name> = if to_args.is_empty() {
+                    vec![self.fresh("_")]
+                } else {
+                    to_args.iter().map(|_| self.fresh("x")).collect()
+                };
+
+                let arguments = if to_args.is_empty() {
+                    vec![Term::Var(parameters[0].clone())]
+                } else {
+                    parameters
+                        .iter()
+                        .zip(to_args.iter().zip(&from_args))
+                        .map(|(parameter, (to, from))| {
+                            self.coerce(Term::Var(parameter.clone()), to, from, location)
+                        })
+                        .collect::<Result<_, _>>()?
+                };
+
+                let unchanged = arguments
+                    .iter()
+                    .zip(&parameters)
+                    .all(|(argument, parameter)| *argument == Term::Var(parameter.clone()));
+
+                let call = apply_all(Term::Var(fun.clone()), arguments);
+                let result = self.coerce(call.clone(), &from_ret, &to_ret, location)?;
+
+                if unchanged && result == call {
+                    return Ok(term);
+                }
+
+                let body = parameters
+                    .into_iter()
+                    .rev()
+                    .fold(result, |body, parameter| lambda(parameter, body));
+
+                Ok(apply(lambda(fun, body), term))
+            }
+
+            (from_repr, to_repr) if from_repr == to_repr => Ok(term),
+
+            (Repr::Data, _) if from.is_var() => from_data(to, term, location),
+
+            (_, Repr::Data) if to.is_var() => to_data(from, term, location),
+
+            _ => Ok(term),
+        }
+    }
+
+    /// Compare two values of type `tipo`.
+    pub(super) fn equals(
+        &mut self,
+        tipo: &Rc<Type>,
+        left: Term<Name>,
+        right: Term<Name>,
+        location: Span,
+    ) -> Result<Term<Name>, Error> {
+        let bool = |value| Term::Constant(Constant::Bool(value));
+
+        Ok(match repr(tipo) {
+            Repr::Int => builtin_call(DefaultFunction::EqualsInteger, vec![left, right]),
+            Repr::ByteArray => builtin_call(DefaultFunction::EqualsByteString, vec![left, right]),
+            Repr::String => builtin_call(DefaultFunction::EqualsString, vec![left, right]),
+            Repr::Bool => {
+                let name = self.fresh("right");
+                let right_var = Term::Var(name.clone());
+
+                apply(
+                    lambda(
+                        name,
+                        if_then_else(
+                            left,
+                            right_var.clone(),
+                            if_then_else(right_var, bool(false), bool(true)),
+                        ),
+                    ),
+                    right,
+                )
+            }
+            Repr::Void => bool(true),
+            Repr::List | Repr::Data => builtin_call(
+                DefaultFunction::EqualsData,
+                vec![
+                    to_data(tipo, left, location)?,
+                    to_data(tipo, right, location)?,
+                ],
+            ),
+            Repr::Fn(..) => return Err(Error::FunctionEquality { location }),
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::ast::Span;
+
+/// A program the type checker accepts but that can't be turned
+/// into Untyped Plutus Core.
+#[derive(Error, Debug, Diagnostic, Clone, PartialEq)]
+pub enum Error {
+    #[error("Functions can't be stored as Data")]
+    #[diagnostic(
+        code(aiken::codegen::function_as_data),
+        help("Generic values, and the elements and fields of lists and custom types, are stored as Data")
+    )]
+    FunctionAsData {
+        #[label]
+        location: Span,
+    },
+
+    #[error("Functions can't be compared")]
+    #[diagnostic(code(aiken::codegen::function_equality))]
+    FunctionEquality {
+        #[label]
+        location: Span,
+    },
+
+    #[error("`{name}` is mutually recursive with another function, which isn't supported yet")]
+    #[diagnostic(code(aiken::codegen::mutual_recursion))]
+    MutualRecursion {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Unknown function `{module}.{name}`")]
+    #[diagnostic(code(aiken::codegen::unknown_function))]
+    UnknownFunction { module: String, name: String },
+}
@@ -0,0 +1,110 @@
+//! The functions of `aiken/math` without a builtin.
+
+use uplc::{
+    ast::{Constant, Name, Term},
+    builtins::DefaultFunction,
+};
+
+use super::{apply, apply_all, builtin_call, fail_with, if_then_else, lambda, CodeGenerator};
+
+fn int(value: i64) -> Term<Name> {
+    Term::Constant(Constant::Integer(value.into()))
+}
+
+impl<'a> CodeGenerator<'a> {
+    pub(super) fn math(&mut self, name: &str) -> Term<Name> {
+        match name {
+            "abs" => self.abs(),
+            "pow" => self.pow(),
+            _ => unreachable!("the other functions of aiken/math are builtins"),
+        }
+    }
+
+    fn abs(&mut self) -> Term<Name> {
+        let n = self.fresh("n");
+
+        lambda(
+            n.clone(),
+            if_then_else(
+                builtin_call(
+                    DefaultFunction::LessThanInteger,
+                    vec![Term::Var(n.clone()), int(0)],
+                ),
+                builtin_call(
+                    DefaultFunction::SubtractInteger,
+                    vec![int(0), Term::Var(n.clone())],
+                ),
+                Term::Var(n),
+            ),
+        )
+    }
+
+    /// Exponentiation by squaring, recursing through a self
+    /// application like recursive functions do.
+    fn pow(&mut self) -> Term<Name> {
+        let go = self.fresh("go");
+        let this = self.fresh("pow");
+        let base = self.fresh("base");
+        let exponent = self.fresh("exponent");
+        let half = self.fresh("half");
+        let x = self.fresh("x");
+        let n = self.fresh("n");
+
+        let var = |name: &Name| Term::Var(name.clone());
+        let call = |fun, arguments| builtin_call(fun, arguments);
+
+        let recurse = apply_all(
+            apply(var(&this), var(&this)),
+            vec![
+                var(&base),
+                call(DefaultFunction::DivideInteger, vec![var(&exponent), int(2)]),
+            ],
+        );
+
+        let square = call(
+            DefaultFunction::MultiplyInteger,
+            vec![var(&half), var(&half)],
+        );
+
+        let step = if_then_else(
+            call(
+                DefaultFunction::EqualsInteger,
+                vec![
+                    call(DefaultFunction::ModInteger, vec![var(&exponent), int(2)]),
+                    int(0),
+                ],
+            ),
+            square.clone(),
+            call(DefaultFunction::MultiplyInteger, vec![square, var(&base)]),
+        );
+
+        let definition = lambda(
+            this,
+            lambda(
+                base.clone(),
+                lambda(
+                    exponent.clone(),
+                    if_then_else(
+                        call(DefaultFunction::EqualsInteger, vec![var(&exponent), int(0)]),
+                        int(1),
+                        apply(lambda(half, step), recurse),
+                    ),
+                ),
+            ),
+        );
+
+        let body = lambda(
+            x.clone(),
+            lambda(
+                n.clone(),
+                if_then_else(
+                    call(DefaultFunction::LessThanInteger, vec![var(&n), int(0)]),
+                    fail_with("aiken/math.pow: negative exponent".to_string()),
+                    apply_all(apply(var(&go), var(&go)), vec![var(&x), var(&n)]),
+                ),
+            ),
+        );
+
+        apply(lambda(go, body), definition)
+    }
+}
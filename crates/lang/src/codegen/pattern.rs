@@ -0,0 +1,289 @@
+use std::rc::Rc;
+
+use uplc::{
+    ast::{Constant, Name, Term},
+    builtins::DefaultFunction,
+};
+
+use crate::{
+    ast::{Pattern, Span, TypedPattern},
+    builtins,
+    tipo::{DataConstructor, PatternConstructor, Type},
+};
+
+use super::{
+    apply, builtin_call, data, delay, error::Error, force, if_then_else, lambda, CodeGenerator,
+};
+
+impl<'a> CodeGenerator<'a> {
+    /// Bring the variables a pattern binds in scope, so the code
+    /// depending on them can be generated before the pattern itself.
+    pub(super) fn declare_pattern(&mut self, pattern: &TypedPattern) {
+        match pattern {
+            Pattern::Var { name, .. } => {
+                let unique = self.fresh(name);
+                self.scope.insert(name.clone(), unique);
+            }
+            Pattern::Constructor { arguments, .. } => {
+                arguments
+                    .iter()
+                    .for_each(|argument| self.declare_pattern(argument));
+            }
+            Pattern::List { elements, tail, .. } => {
+                elements
+                    .iter()
+                    .chain(tail.as_deref())
+                    .for_each(|element| self.declare_pattern(element));
+            }
+            Pattern::Int { .. } | Pattern::String { .. } | Pattern::Discard { .. } => (),
+        }
+    }
+
+    /// Match `value` against `pattern`, continuing with `success`
+    /// or `fail`. `value` is evaluated at most once.
+    pub(super) fn pattern(
+        &mut self,
+        pattern: &TypedPattern,
+        value: Term<Name>,
+        success: Term<Name>,
+        fail: Term<Name>,
+    ) -> Result<Term<Name>, Error> {
+        match pattern {
+            Pattern::Var { name, .. } => {
+                Ok(apply(lambda(self.scope[name].clone(), success), value))
+            }
+
+            Pattern::Discard { .. } => Ok(success),
+
+            Pattern::Int { value: int, .. } => Ok(if_then_else(
+                builtin_call(
+                    DefaultFunction::EqualsInteger,
+                    vec![value, Term::Constant(Constant::Integer(int.clone()))],
+                ),
+                success,
+                fail,
+            )),
+
+            Pattern::String { value: string, .. } => Ok(if_then_else(
+                builtin_call(
+                    DefaultFunction::EqualsString,
+                    vec![value, Term::Constant(Constant::String(string.clone()))],
+                ),
+                success,
+                fail,
+            )),
+
+            Pattern::Constructor {
+                arguments,
+                constructor:
+                    PatternConstructor::Record {
+                        tag,
+                        constructors_count,
+                        ..
+                    },
+                tipo,
+                location,
+                ..
+            } => {
+                if tipo.is_bool() {
+                    return Ok(match tag {
+                        1 => if_then_else(value, success, fail),
+                        _ => if_then_else(value, fail, success),
+                    });
+                }
+
+                if tipo.is_void() {
+                    return Ok(success);
+                }
+
+                let record = self.fresh("record");
+                let fields = self.fresh("fields");
+
+                let mut body = success;
+
+                for (index, argument) in arguments.iter().enumerate().rev() {
+                    let Some(tipo) = pattern_type(argument) else {
+                        continue;
+                    };
+
+                    let field = data::from_data(
+                        &tipo,
+                        data::list_index(Term::Var(fields.clone()), index),
+                        *location,
+                    )?;
+
+                    body = self.pattern(argument, field, body, fail.clone())?;
+                }
+
+                if arguments
+                    .iter()
+                    .any(|argument| pattern_type(argument).is_some())
+                {
+                    body = apply(
+                        lambda(fields, body),
+                        data::constr_fields(Term::Var(record.clone())),
+                    );
+                }
+
+                if *constructors_count > 1 {
+                    body = if_then_else(
+                        builtin_call(
+                            DefaultFunction::EqualsInteger,
+                            vec![
+                                data::constr_tag(Term::Var(record.clone())),
+                                Term::Constant(Constant::Integer((*tag).into())),
+                            ],
+                        ),
+                        body,
+                        fail,
+                    );
+                }
+
+                Ok(apply(lambda(record, body), value))
+            }
+
+            Pattern::Constructor {
+                arguments,
+                constructor: PatternConstructor::Data(constructor),
+                ..
+            } => {
+                let data = self.fresh("data");
+                let data_var = || Term::Var(data.clone());
+
+                let values = match constructor {
+                    DataConstructor::Constr => vec![
+                        data::constr_tag(data_var()),
+                        data::constr_fields(data_var()),
+                    ],
+                    DataConstructor::Map => vec![data_var()],
+                    DataConstructor::List => {
+                        vec![builtin_call(DefaultFunction::UnListData, vec![data_var()])]
+                    }
+                    DataConstructor::Int => {
+                        vec![builtin_call(DefaultFunction::UnIData, vec![data_var()])]
+                    }
+                    DataConstructor::Bytes => {
+                        vec![builtin_call(DefaultFunction::UnBData, vec![data_var()])]
+                    }
+                };
+
+                let mut body = success;
+
+                for (argument, value) in arguments.iter().zip(values).rev() {
+                    body = self.pattern(argument, value, body, fail.clone())?;
+                }
+
+                let branches = DataConstructor::ALL.iter().map(|branch| {
+                    if branch == constructor {
+                        delay(body.clone())
+                    } else {
+                        delay(fail.clone())
+                    }
+                });
+
+                Ok(apply(
+                    lambda(
+                        data.clone(),
+                        force(builtin_call(
+                            DefaultFunction::ChooseData,
+                            std::iter::once(data_var()).chain(branches).collect(),
+                        )),
+                    ),
+                    value,
+                ))
+            }
+
+            Pattern::List {
+                elements,
+                tail,
+                tipo,
+                location,
+            } => {
+                let element_type = tipo
+                    .arg_types()
+                    .and_then(|args| args.first().cloned())
+                    .expect("a list pattern has a list type");
+
+                let list = self.fresh("list");
+                let body = self.list_pattern(
+                    elements,
+                    tail.as_deref(),
+                    &element_type,
+                    list.clone(),
+                    success,
+                    fail,
+                    *location,
+                )?;
+
+                Ok(apply(lambda(list, body), value))
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn list_pattern(
+        &mut self,
+        elements: &[TypedPattern],
+        tail: Option<&TypedPattern>,
+        element_type: &Rc<Type>,
+        list: Name,
+        success: Term<Name>,
+        fail: Term<Name>,
+        location: Span,
+    ) -> Result<Term<Name>, Error> {
+        let Some((head, elements)) = elements.split_first() else {
+            return match tail {
+                Some(tail) => self.pattern(tail, Term::Var(list), success, fail),
+                None => Ok(choose_list(Term::Var(list), success, fail)),
+            };
+        };
+
+        let rest = self.fresh("rest");
+
+        let rest_pattern = self.list_pattern(
+            elements,
+            tail,
+            element_type,
+            rest.clone(),
+            success,
+            fail.clone(),
+            location,
+        )?;
+
+        let rest_pattern = apply(
+            lambda(rest, rest_pattern),
+            builtin_call(DefaultFunction::TailList, vec![Term::Var(list.clone())]),
+        );
+
+        let head_value = data::from_data(
+            element_type,
+            builtin_call(DefaultFunction::HeadList, vec![Term::Var(list.clone())]),
+            location,
+        )?;
+
+        let non_empty = self.pattern(head, head_value, rest_pattern, fail.clone())?;
+
+        Ok(choose_list(Term::Var(list), fail, non_empty))
+    }
+}
+
+/// `on_empty` when `list` is empty, `on_cons` otherwise.
+fn choose_list(list: Term<Name>, on_empty: Term<Name>, on_cons: Term<Name>) -> Term<Name> {
+    force(builtin_call(
+        DefaultFunction::ChooseList,
+        vec![list, delay(on_empty), delay(on_cons)],
+    ))
+}
+
+/// The type of the values a pattern matches, `None` for patterns
+/// matching anything without binding it.
+fn pattern_type(pattern: &TypedPattern) -> Option<Rc<Type>> {
+    match pattern {
+        Pattern::Discard { .. } => None,
+        Pattern::Int { .. } => Some(builtins::int()),
+        Pattern::String { .. } => Some(builtins::string()),
+        Pattern::Var { tipo, .. }
+        | Pattern::Constructor { tipo, .. }
+        | Pattern::List { tipo, .. } => Some(tipo.clone()),
+    }
+}
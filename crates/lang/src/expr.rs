@@ -0,0 +1,285 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        Annotation, Arg, AssignmentKind, BinOp, IfBranch, Span, TypedArg, TypedClause,
+        TypedPattern, UnOp, UntypedClause, UntypedPattern,
+    },
+    builtins,
+    tipo::{Type, ValueConstructor, ValueConstructorVariant},
+};
+
+/// An expression annotated with the types the type checker inferred.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedExpr {
+    Int {
+        location: Span,
+        tipo: Rc<Type>,
+        value: BigInt,
+    },
+    String {
+        location: Span,
+        tipo: Rc<Type>,
+        value: String,
+    },
+    ByteArray {
+        location: Span,
+        tipo: Rc<Type>,
+        bytes: Vec<u8>,
+    },
+    Var {
+        location: Span,
+        constructor: ValueConstructor,
+        name: String,
+    },
+    Fn {
+        location: Span,
+        tipo: Rc<Type>,
+        arguments: Vec<TypedArg>,
+        return_annotation: Option<Annotation>,
+        body: Box<Self>,
+    },
+    List {
+        location: Span,
+        tipo: Rc<Type>,
+        elements: Vec<Self>,
+        tail: Option<Box<Self>>,
+    },
+    Call {
+        location: Span,
+        tipo: Rc<Type>,
+        fun: Box<Self>,
+        arguments: Vec<Self>,
+    },
+    BinOp {
+        location: Span,
+        tipo: Rc<Type>,
+        name: BinOp,
+        left: Box<Self>,
+        right: Box<Self>,
+    },
+    UnOp {
+        location: Span,
+        tipo: Rc<Type>,
+        op: UnOp,
+        value: Box<Self>,
+    },
+    Sequence {
+        location: Span,
+        expressions: Vec<Self>,
+    },
+    Assignment {
+        location: Span,
+        tipo: Rc<Type>,
+        kind: AssignmentKind<Self>,
+        pattern: TypedPattern,
+        value: Box<Self>,
+    },
+    When {
+        location: Span,
+        tipo: Rc<Type>,
+        subject: Box<Self>,
+        clauses: Vec<TypedClause>,
+    },
+    If {
+        location: Span,
+        tipo: Rc<Type>,
+        branches: Vec<IfBranch<Self>>,
+        final_else: Box<Self>,
+    },
+    /// `record.label`, `index` being the position of the field.
+    RecordAccess {
+        location: Span,
+        tipo: Rc<Type>,
+        label: String,
+        index: usize,
+        record: Box<Self>,
+    },
+    /// `module.label`, `module_alias` being the name the module was
+    /// imported as.
+    ModuleSelect {
+        location: Span,
+        tipo: Rc<Type>,
+        label: String,
+        module_name: String,
+        module_alias: String,
+        constructor: ValueConstructorVariant,
+    },
+    Todo {
+        location: Span,
+        tipo: Rc<Type>,
+        label: Option<String>,
+    },
+    ErrorTerm {
+        location: Span,
+        tipo: Rc<Type>,
+        label: Option<String>,
+    },
+}
+
+impl TypedExpr {
+    pub fn location(&self) -> Span {
+        match self {
+            TypedExpr::Int { location, .. }
+            | TypedExpr::String { location, .. }
+            | TypedExpr::ByteArray { location, .. }
+            | TypedExpr::Var { location, .. }
+            | TypedExpr::Fn { location, .. }
+            | TypedExpr::List { location, .. }
+            | TypedExpr::Call { location, .. }
+            | TypedExpr::BinOp { location, .. }
+            | TypedExpr::UnOp { location, .. }
+            | TypedExpr::Sequence { location, .. }
+            | TypedExpr::Assignment { location, .. }
+            | TypedExpr::When { location, .. }
+            | TypedExpr::If { location, .. }
+            | TypedExpr::RecordAccess { location, .. }
+            | TypedExpr::ModuleSelect { location, .. }
+            | TypedExpr::Todo { location, .. }
+            | TypedExpr::ErrorTerm { location, .. } => *location,
+        }
+    }
+
+    pub fn tipo(&self) -> Rc<Type> {
+        match self {
+            TypedExpr::Var { constructor, .. } => constructor.tipo.clone(),
+            TypedExpr::Sequence { expressions, .. } => expressions
+                .last()
+                .map(TypedExpr::tipo)
+                .unwrap_or_else(builtins::void),
+            TypedExpr::Int { tipo, .. }
+            | TypedExpr::String { tipo, .. }
+            | TypedExpr::ByteArray { tipo, .. }
+            | TypedExpr::Fn { tipo, .. }
+            | TypedExpr::List { tipo, .. }
+            | TypedExpr::Call { tipo, .. }
+            | TypedExpr::BinOp { tipo, .. }
+            | TypedExpr::UnOp { tipo, .. }
+            | TypedExpr::Assignment { tipo, .. }
+            | TypedExpr::When { tipo, .. }
+            | TypedExpr::If { tipo, .. }
+            | TypedExpr::RecordAccess { tipo, .. }
+            | TypedExpr::ModuleSelect { tipo, .. }
+            | TypedExpr::Todo { tipo, .. }
+            | TypedExpr::ErrorTerm { tipo, .. } => tipo.clone(),
+        }
+    }
+}
+
+/// An expression as it comes out of the parser.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UntypedExpr {
+    Int {
+        location: Span,
+        value: BigInt,
+    },
+    String {
+        location: Span,
+        value: String,
+    },
+    ByteArray {
+        location: Span,
+        bytes: Vec<u8>,
+    },
+    Var {
+        location: Span,
+        name: String,
+    },
+    /// `fn(arguments) { body }`
+    Fn {
+        location: Span,
+        arguments: Vec<Arg<()>>,
+        return_annotation: Option<Annotation>,
+        body: Box<Self>,
+    },
+    /// `[a, b, ..tail]`
+    List {
+        location: Span,
+        elements: Vec<Self>,
+        tail: Option<Box<Self>>,
+    },
+    /// `fun(arguments)`, pipes are turned into calls while parsing.
+    Call {
+        location: Span,
+        fun: Box<Self>,
+        arguments: Vec<Self>,
+    },
+    BinOp {
+        location: Span,
+        name: BinOp,
+        left: Box<Self>,
+        right: Box<Self>,
+    },
+    UnOp {
+        location: Span,
+        op: UnOp,
+        value: Box<Self>,
+    },
+    /// The expressions of a block, the last one is it's value.
+    Sequence {
+        location: Span,
+        expressions: Vec<Self>,
+    },
+    /// `let pattern: annotation = value`, or an `expect` whose pattern
+    /// may not match.
+    Assignment {
+        location: Span,
+        kind: AssignmentKind<Self>,
+        pattern: UntypedPattern,
+        annotation: Option<Annotation>,
+        value: Box<Self>,
+    },
+    /// `when subject is { clauses }`
+    When {
+        location: Span,
+        subject: Box<Self>,
+        clauses: Vec<UntypedClause>,
+    },
+    /// `if condition { body } else if condition { body } else { final_else }`
+    If {
+        location: Span,
+        branches: Vec<IfBranch<Self>>,
+        final_else: Box<Self>,
+    },
+    /// `container.label`, either a record field or a module member.
+    FieldAccess {
+        location: Span,
+        container: Box<Self>,
+        label: String,
+    },
+    /// `todo`, a placeholder failing when evaluated.
+    Todo {
+        location: Span,
+        label: Option<String>,
+    },
+    /// `error`, failing on purpose.
+    ErrorTerm {
+        location: Span,
+        label: Option<String>,
+    },
+}
+
+impl UntypedExpr {
+    pub fn location(&self) -> Span {
+        match self {
+            UntypedExpr::Int { location, .. }
+            | UntypedExpr::String { location, .. }
+            | UntypedExpr::ByteArray { location, .. }
+            | UntypedExpr::Var { location, .. }
+            | UntypedExpr::Fn { location, .. }
+            | UntypedExpr::List { location, .. }
+            | UntypedExpr::Call { location, .. }
+            | UntypedExpr::BinOp { location, .. }
+            | UntypedExpr::UnOp { location, .. }
+            | UntypedExpr::Sequence { location, .. }
+            | UntypedExpr::Assignment { location, .. }
+            | UntypedExpr::When { location, .. }
+            | UntypedExpr::If { location, .. }
+            | UntypedExpr::FieldAccess { location, .. }
+            | UntypedExpr::Todo { location, .. }
+            | UntypedExpr::ErrorTerm { location, .. } => *location,
+        }
+    }
+}
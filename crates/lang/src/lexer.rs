@@ -0,0 +1,170 @@
+use chumsky::{prelude::*, Stream};
+
+use crate::{ast::Span, parser::error::ParseError, token::Token};
+
+/// Turn source text into tokens, dropping whitespace and comments.
+pub fn run(src: &str) -> Result<Vec<(Token, Span)>, Vec<ParseError>> {
+    let chars = src
+        .char_indices()
+        .map(|(i, c)| (c, Span::new(i, i + c.len_utf8())));
+
+    lexer().parse(Stream::from_iter(Span::new(src.len(), src.len()), chars))
+}
+
+/// The lexer `run` feeds a module's characters to.
+pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
+    let int = text::int(10).map(Token::Int);
+
+    let escape = just('\\').ignore_then(choice((
+        just('\\'),
+        just('"'),
+        just('n').to('\n'),
+        just('t').to('\t'),
+    )));
+
+    let string = just('"')
+        .ignore_then(filter(|c| *c != '\\' && *c != '"').or(escape).repeated())
+        .then_ignore(just('"'))
+        .collect::<String>()
+        .map(Token::String)
+        .labelled("a string");
+
+    let bytearray = just('#')
+        .ignore_then(just('"'))
+        .ignore_then(filter(|c: &char| c.is_ascii_hexdigit()).repeated())
+        .then_ignore(just('"'))
+        .collect::<String>()
+        .try_map(|hex, span| {
+            decode_hex(&hex).map(Token::ByteArray).ok_or_else(|| {
+                ParseError::invalid(span, "A bytearray needs an even number of hex digits")
+            })
+        })
+        .labelled("a bytearray");
+
+    let op = choice((
+        just("==").to(Token::EqualEqual),
+        just("!=").to(Token::NotEqual),
+        just("<=").to(Token::LessEqual),
+        just(">=").to(Token::GreaterEqual),
+        just("&&").to(Token::AmperAmper),
+        just("||").to(Token::VbarVbar),
+        just("|>").to(Token::Pipe),
+        just("->").to(Token::RArrow),
+        just("..").to(Token::DotDot),
+        just('=').to(Token::Equal),
+        just('<').to(Token::Less),
+        just('>').to(Token::Greater),
+        just('+').to(Token::Plus),
+        just('-').to(Token::Minus),
+        just('*').to(Token::Star),
+        just('/').to(Token::Slash),
+        just('%').to(Token::Percent),
+        just('!').to(Token::Bang),
+        just(':').to(Token::Colon),
+        just(',').to(Token::Comma),
+        just('.').to(Token::Dot),
+    ));
+
+    let grouping = choice((
+        just('(').to(Token::LeftParen),
+        just(')').to(Token::RightParen),
+        just('[').to(Token::LeftSquare),
+        just(']').to(Token::RightSquare),
+        just('{').to(Token::LeftBrace),
+        just('}').to(Token::RightBrace),
+    ));
+
+    let word = text::ident().map(|word: String| match word.as_str() {
+        "as" => Token::As,
+        "else" => Token::Else,
+        "error" => Token::Error,
+        "expect" => Token::Expect,
+        "fn" => Token::Fn,
+        "if" => Token::If,
+        "is" => Token::Is,
+        "let" => Token::Let,
+        "pub" => Token::Pub,
+        "todo" => Token::Todo,
+        "type" => Token::Type,
+        "use" => Token::Use,
+        "when" => Token::When,
+        _ => match word.strip_prefix('_') {
+            Some(name) => Token::DiscardName(name.to_string()),
+            None if word.starts_with(char::is_uppercase) => Token::UpName(word),
+            None => Token::Name(word),
+        },
+    });
+
+    let comment = just("//").then(filter(|c| *c != '\n').repeated()).padded();
+
+    let token = choice((int, string, bytearray, word, op, grouping))
+        .map_with_span(|token, span| (token, span))
+        .padded_by(comment.repeated())
+        .padded();
+
+    token
+        .repeated()
+        .padded()
+        .then_ignore(comment.repeated())
+        .then_ignore(end())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::token::Token;
+
+    use super::run;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        run(src)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn lex_tokens() {
+        assert_eq!(
+            tokens("pub fn foo(_x) -> Int { x |> bar >= #\"00ff\" } // done"),
+            vec![
+                Token::Pub,
+                Token::Fn,
+                Token::Name("foo".to_string()),
+                Token::LeftParen,
+                Token::DiscardName("x".to_string()),
+                Token::RightParen,
+                Token::RArrow,
+                Token::UpName("Int".to_string()),
+                Token::LeftBrace,
+                Token::Name("x".to_string()),
+                Token::Pipe,
+                Token::Name("bar".to_string()),
+                Token::GreaterEqual,
+                Token::ByteArray(vec![0x00, 0xff]),
+                Token::RightBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn odd_bytearray() {
+        let errors = run("#\"abc\"").unwrap_err();
+
+        assert_eq!(
+            errors[0].to_string(),
+            "A bytearray needs an even number of hex digits"
+        );
+    }
+}
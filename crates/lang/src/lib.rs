@@ -0,0 +1,8 @@
+pub mod ast;
+pub mod builtins;
+pub mod codegen;
+pub mod expr;
+pub mod lexer;
+pub mod parser;
+pub mod tipo;
+pub mod token;
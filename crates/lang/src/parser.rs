@@ -0,0 +1,783 @@
+use chumsky::{prelude::*, Stream};
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        Annotation, Arg, ArgName, AssignmentKind, BinOp, Clause, DataType, Definition, Function,
+        IfBranch, Module, ModuleKind, Pattern, RecordConstructor, RecordConstructorArg, Span, UnOp,
+        UnqualifiedImport, UntypedDefinition, UntypedModule, UntypedPattern, Use,
+    },
+    expr::UntypedExpr,
+    lexer,
+    token::Token,
+};
+
+pub mod error;
+
+pub use error::ParseError;
+
+/// Parse the source of a module called `name`, like `aiken/list`.
+pub fn module(src: &str, name: &str, kind: ModuleKind) -> Result<UntypedModule, Vec<ParseError>> {
+    let tokens = lexer::run(src)?;
+
+    let eoi = Span::new(src.len(), src.len());
+
+    let definitions = module_parser().parse(Stream::from_iter(eoi, tokens.into_iter()))?;
+
+    Ok(Module {
+        name: name.to_string(),
+        kind,
+        definitions,
+        type_info: (),
+    })
+}
+
+/// Parse a single expression, handy for tests and tooling.
+pub fn expr(src: &str) -> Result<UntypedExpr, Vec<ParseError>> {
+    let tokens = lexer::run(src)?;
+
+    let eoi = Span::new(src.len(), src.len());
+
+    expr_parser()
+        .then_ignore(end())
+        .parse(Stream::from_iter(eoi, tokens.into_iter()))
+}
+
+fn module_parser() -> impl Parser<Token, Vec<UntypedDefinition>, Error = ParseError> {
+    choice((
+        use_parser().map(Definition::Use),
+        data_type_parser().map(Definition::DataType),
+        fn_parser().map(Definition::Fn),
+    ))
+    .repeated()
+    .then_ignore(end())
+}
+
+fn name() -> impl Parser<Token, String, Error = ParseError> + Clone {
+    select! { Token::Name(name) => name }.labelled("a name")
+}
+
+fn up_name() -> impl Parser<Token, String, Error = ParseError> + Clone {
+    select! { Token::UpName(name) => name }.labelled("a capitalised name")
+}
+
+fn use_parser() -> impl Parser<Token, Use, Error = ParseError> {
+    let unqualified = name()
+        .or(up_name())
+        .then(just(Token::As).ignore_then(name().or(up_name())).or_not())
+        .map_with_span(|(name, as_name), location| UnqualifiedImport {
+            location,
+            name,
+            as_name,
+        });
+
+    just(Token::Use)
+        .ignore_then(name().separated_by(just(Token::Slash)).at_least(1))
+        .then(
+            just(Token::Dot)
+                .ignore_then(
+                    unqualified
+                        .separated_by(just(Token::Comma))
+                        .allow_trailing()
+                        .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+                )
+                .or_not(),
+        )
+        .then(just(Token::As).ignore_then(name()).or_not())
+        .map_with_span(|((module, unqualified), as_name), location| Use {
+            location,
+            module,
+            as_name,
+            unqualified: unqualified.unwrap_or_default(),
+        })
+}
+
+fn data_type_parser() -> impl Parser<Token, DataType, Error = ParseError> {
+    let argument = name()
+        .then_ignore(just(Token::Colon))
+        .or_not()
+        .then(annotation())
+        .map_with_span(|(label, annotation), location| RecordConstructorArg {
+            location,
+            label,
+            annotation,
+        });
+
+    let arguments = |open, close| {
+        argument
+            .clone()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .delimited_by(just(open), just(close))
+    };
+
+    let constructor = up_name()
+        .then(
+            arguments(Token::LeftParen, Token::RightParen)
+                .or(arguments(Token::LeftBrace, Token::RightBrace))
+                .or_not(),
+        )
+        .map_with_span(|(name, arguments), location| RecordConstructor {
+            location,
+            name,
+            arguments: arguments.unwrap_or_default(),
+        });
+
+    just(Token::Pub)
+        .or_not()
+        .then_ignore(just(Token::Type))
+        .then(up_name())
+        .then(
+            name()
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+                .or_not(),
+        )
+        .then(
+            constructor
+                .repeated()
+                .at_least(1)
+                .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+        )
+        .map_with_span(
+            |(((public, name), parameters), constructors), location| DataType {
+                location,
+                public: public.is_some(),
+                name,
+                parameters: parameters.unwrap_or_default(),
+                constructors,
+            },
+        )
+}
+
+fn fn_parser() -> impl Parser<Token, Function<(), UntypedExpr>, Error = ParseError> {
+    just(Token::Pub)
+        .or_not()
+        .then_ignore(just(Token::Fn))
+        .then(name())
+        .then(arguments())
+        .then(just(Token::RArrow).ignore_then(annotation()).or_not())
+        .then(block(expr_parser()))
+        .map_with_span(
+            |((((public, name), arguments), return_annotation), body), location| Function {
+                location,
+                public: public.is_some(),
+                name,
+                arguments,
+                return_annotation,
+                return_type: (),
+                body,
+            },
+        )
+}
+
+fn arguments() -> impl Parser<Token, Vec<Arg<()>>, Error = ParseError> + Clone {
+    let arg_name = select! {
+        Token::Name(name) => ArgName::Named(name),
+        Token::DiscardName(name) => ArgName::Discard(name),
+    }
+    .labelled("an argument");
+
+    arg_name
+        .then(just(Token::Colon).ignore_then(annotation()).or_not())
+        .map_with_span(|(name, annotation), location| Arg {
+            location,
+            name,
+            annotation,
+            tipo: (),
+        })
+        .separated_by(just(Token::Comma))
+        .allow_trailing()
+        .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+}
+
+fn annotation() -> impl Parser<Token, Annotation, Error = ParseError> + Clone {
+    recursive(|annotation| {
+        let arguments = annotation
+            .clone()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .delimited_by(just(Token::LeftParen), just(Token::RightParen));
+
+        let fn_annotation = just(Token::Fn)
+            .ignore_then(arguments.clone())
+            .then_ignore(just(Token::RArrow))
+            .then(annotation)
+            .map_with_span(|(arguments, ret), location| Annotation::Fn {
+                location,
+                arguments,
+                ret: Box::new(ret),
+            });
+
+        let constructor = up_name().then(arguments.clone().or_not()).map_with_span(
+            |(name, arguments), location| Annotation::Constructor {
+                location,
+                module: None,
+                name,
+                arguments: arguments.unwrap_or_default(),
+            },
+        );
+
+        // either a type variable or the module of a qualified type
+        let var_or_qualified = name()
+            .then(
+                just(Token::Dot)
+                    .ignore_then(up_name())
+                    .then(arguments.or_not())
+                    .or_not(),
+            )
+            .map_with_span(|(name, qualified), location| match qualified {
+                Some((type_name, arguments)) => Annotation::Constructor {
+                    location,
+                    module: Some(name),
+                    name: type_name,
+                    arguments: arguments.unwrap_or_default(),
+                },
+                None => Annotation::Var { location, name },
+            });
+
+        choice((fn_annotation, constructor, var_or_qualified))
+    })
+    .labelled("a type")
+}
+
+fn pattern() -> impl Parser<Token, UntypedPattern, Error = ParseError> + Clone {
+    recursive(|pattern| {
+        let int = just(Token::Minus)
+            .or_not()
+            .then(select! { Token::Int(value) => value })
+            .map_with_span(|(minus, value), location| {
+                let value: BigInt = value.parse().expect("the lexer only lets digits through");
+
+                Pattern::Int {
+                    location,
+                    value: if minus.is_some() { -value } else { value },
+                }
+            });
+
+        let string = select! { Token::String(value) => value }
+            .map_with_span(|value, location| Pattern::String { location, value });
+
+        let discard = select! { Token::DiscardName(name) => name }
+            .map_with_span(|name, location| Pattern::Discard { location, name });
+
+        let arguments = pattern
+            .clone()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+            .or_not()
+            .map(Option::unwrap_or_default);
+
+        let constructor = name()
+            .then_ignore(just(Token::Dot))
+            .or_not()
+            .then(up_name())
+            .then(arguments)
+            .map_with_span(
+                |((module, name), arguments), location| Pattern::Constructor {
+                    location,
+                    module,
+                    name,
+                    arguments,
+                    constructor: (),
+                    tipo: (),
+                },
+            );
+
+        let var = name().map_with_span(|name, location| Pattern::Var {
+            location,
+            name,
+            tipo: (),
+        });
+
+        let tail = just(Token::DotDot)
+            .ignore_then(pattern.clone().or_not())
+            .map_with_span(|tail, location| {
+                tail.unwrap_or(Pattern::Discard {
+                    location,
+                    name: String::new(),
+                })
+            });
+
+        let list = pattern
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .then(tail.or_not())
+            .delimited_by(just(Token::LeftSquare), just(Token::RightSquare))
+            .map_with_span(|(elements, tail), location| Pattern::List {
+                location,
+                elements,
+                tail: tail.map(Box::new),
+                tipo: (),
+            });
+
+        choice((int, string, discard, constructor, var, list))
+    })
+    .labelled("a pattern")
+}
+
+/// `{ expressions }`, the value of a block is it's last expression.
+fn block(
+    expr: impl Parser<Token, UntypedExpr, Error = ParseError> + Clone,
+) -> impl Parser<Token, UntypedExpr, Error = ParseError> + Clone {
+    // `or` and `fail` are only keywords after an `expect`
+    let or_fail = just(Token::Name("or".to_string()))
+        .ignore_then(just(Token::Name("fail".to_string())))
+        .ignore_then(select! { Token::String(message) => message })
+        .map(AssignmentKind::ExpectOrFail);
+
+    let otherwise = just(Token::Else)
+        .ignore_then(expr.clone())
+        .map(|default| AssignmentKind::ExpectElse(Box::new(default)))
+        .or(or_fail);
+
+    let assignment = just(Token::Let)
+        .to(AssignmentKind::Let)
+        .or(just(Token::Expect).to(AssignmentKind::Expect))
+        .then(pattern())
+        .then(just(Token::Colon).ignore_then(annotation()).or_not())
+        .then_ignore(just(Token::Equal))
+        .then(expr.clone())
+        .then(otherwise.or_not())
+        .try_map(
+            |((((kind, pattern), annotation), value), otherwise), location| {
+                let kind = match (kind, otherwise) {
+                    (kind, None) => kind,
+                    (AssignmentKind::Expect, Some(otherwise)) => otherwise,
+                    (_, Some(_)) => {
+                        return Err(ParseError::invalid(
+                            location,
+                            "Only an `expect` can have an `else` or `or fail`",
+                        ))
+                    }
+                };
+
+                Ok(UntypedExpr::Assignment {
+                    location,
+                    kind,
+                    pattern,
+                    annotation,
+                    value: Box::new(value),
+                })
+            },
+        );
+
+    assignment
+        .or(expr)
+        .repeated()
+        .at_least(1)
+        .delimited_by(just(Token::LeftBrace), just(Token::RightBrace))
+        .map_with_span(|mut expressions, location| {
+            if expressions.len() == 1 && !matches!(expressions[0], UntypedExpr::Assignment { .. }) {
+                expressions.remove(0)
+            } else {
+                UntypedExpr::Sequence {
+                    location,
+                    expressions,
+                }
+            }
+        })
+}
+
+fn expr_parser() -> impl Parser<Token, UntypedExpr, Error = ParseError> + Clone {
+    recursive(|expr| {
+        let literal = select! {
+            Token::Int(value) => value,
+        }
+        .map_with_span(|value, location| UntypedExpr::Int {
+            location,
+            value: value.parse().expect("the lexer only lets digits through"),
+        })
+        .or(select! { Token::String(value) => value }
+            .map_with_span(|value, location| UntypedExpr::String { location, value }))
+        .or(select! { Token::ByteArray(bytes) => bytes }
+            .map_with_span(|bytes, location| UntypedExpr::ByteArray { location, bytes }));
+
+        let var = name()
+            .or(up_name())
+            .map_with_span(|name, location| UntypedExpr::Var { location, name });
+
+        let list = expr
+            .clone()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .then(just(Token::DotDot).ignore_then(expr.clone()).or_not())
+            .delimited_by(just(Token::LeftSquare), just(Token::RightSquare))
+            .map_with_span(|(elements, tail), location| UntypedExpr::List {
+                location,
+                elements,
+                tail: tail.map(Box::new),
+            });
+
+        let anonymous_fn = just(Token::Fn)
+            .ignore_then(arguments())
+            .then(just(Token::RArrow).ignore_then(annotation()).or_not())
+            .then(block(expr.clone()))
+            .map_with_span(
+                |((arguments, return_annotation), body), location| UntypedExpr::Fn {
+                    location,
+                    arguments,
+                    return_annotation,
+                    body: Box::new(body),
+                },
+            );
+
+        let clause = pattern()
+            .then_ignore(just(Token::RArrow))
+            .then(expr.clone())
+            .map_with_span(|(pattern, then), location| Clause {
+                location,
+                pattern,
+                then,
+            });
+
+        let when = just(Token::When)
+            .ignore_then(expr.clone())
+            .then_ignore(just(Token::Is))
+            .then(
+                clause
+                    .repeated()
+                    .at_least(1)
+                    .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+            )
+            .map_with_span(|(subject, clauses), location| UntypedExpr::When {
+                location,
+                subject: Box::new(subject),
+                clauses,
+            });
+
+        let branch =
+            expr.clone()
+                .then(block(expr.clone()))
+                .map_with_span(|(condition, body), location| IfBranch {
+                    location,
+                    condition,
+                    body,
+                });
+
+        let if_ = just(Token::If)
+            .ignore_then(branch.clone())
+            .then(
+                just(Token::Else)
+                    .ignore_then(just(Token::If))
+                    .ignore_then(branch)
+                    .repeated(),
+            )
+            .then_ignore(just(Token::Else))
+            .then(block(expr.clone()))
+            .map_with_span(|((first, rest), final_else), location| UntypedExpr::If {
+                location,
+                branches: std::iter::once(first).chain(rest).collect(),
+                final_else: Box::new(final_else),
+            });
+
+        let label = select! { Token::String(label) => label }.or_not();
+
+        let todo = just(Token::Todo)
+            .ignore_then(label)
+            .map_with_span(|label, location| UntypedExpr::Todo { location, label });
+
+        let error = just(Token::Error)
+            .ignore_then(label)
+            .map_with_span(|label, location| UntypedExpr::ErrorTerm { location, label });
+
+        let atom = choice((
+            literal,
+            var,
+            list,
+            anonymous_fn,
+            when,
+            if_,
+            todo,
+            error,
+            block(expr.clone()),
+            expr.clone()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen)),
+        ))
+        .labelled("an expression");
+
+        enum Postfix {
+            Call(Vec<UntypedExpr>),
+            FieldAccess(String),
+        }
+
+        let postfix = choice((
+            expr.clone()
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+                .map(Postfix::Call),
+            just(Token::Dot)
+                .ignore_then(name().or(up_name()))
+                .map(Postfix::FieldAccess),
+        ))
+        .map_with_span(|postfix, span: Span| (postfix, span));
+
+        let call = atom
+            .then(postfix.repeated())
+            .foldl(|value, (postfix, span)| {
+                let location = value.location().union(span);
+
+                match postfix {
+                    Postfix::Call(arguments) => UntypedExpr::Call {
+                        location,
+                        fun: Box::new(value),
+                        arguments,
+                    },
+                    Postfix::FieldAccess(label) => UntypedExpr::FieldAccess {
+                        location,
+                        container: Box::new(value),
+                        label,
+                    },
+                }
+            })
+            .boxed();
+
+        let unary = just(Token::Bang)
+            .to(UnOp::Not)
+            .or(just(Token::Minus).to(UnOp::Negate))
+            .map_with_span(|op, span: Span| (op, span))
+            .repeated()
+            .then(call)
+            .foldr(|(op, span), value| UntypedExpr::UnOp {
+                location: span.union(value.location()),
+                op,
+                value: Box::new(value),
+            })
+            .boxed();
+
+        let product = binary(
+            unary,
+            choice((
+                just(Token::Star).to(BinOp::MultInt),
+                just(Token::Slash).to(BinOp::DivInt),
+                just(Token::Percent).to(BinOp::ModInt),
+            )),
+        );
+
+        let sum = binary(
+            product,
+            choice((
+                just(Token::Plus).to(BinOp::AddInt),
+                just(Token::Minus).to(BinOp::SubInt),
+            )),
+        );
+
+        let pipe = sum
+            .clone()
+            .then(just(Token::Pipe).ignore_then(sum).repeated())
+            .foldl(|left, right| {
+                let location = left.location().union(right.location());
+
+                match right {
+                    UntypedExpr::Call { fun, arguments, .. } => UntypedExpr::Call {
+                        location,
+                        fun,
+                        arguments: std::iter::once(left).chain(arguments).collect(),
+                    },
+                    fun => UntypedExpr::Call {
+                        location,
+                        fun: Box::new(fun),
+                        arguments: vec![left],
+                    },
+                }
+            })
+            .boxed();
+
+        let comparison = binary(
+            pipe,
+            choice((
+                just(Token::EqualEqual).to(BinOp::Eq),
+                just(Token::NotEqual).to(BinOp::NotEq),
+                just(Token::LessEqual).to(BinOp::LtEqInt),
+                just(Token::Less).to(BinOp::LtInt),
+                just(Token::GreaterEqual).to(BinOp::GtEqInt),
+                just(Token::Greater).to(BinOp::GtInt),
+            )),
+        );
+
+        let and = binary(comparison, just(Token::AmperAmper).to(BinOp::And));
+
+        binary(and, just(Token::VbarVbar).to(BinOp::Or))
+    })
+}
+
+/// Left associative binary operators of the same precedence.
+fn binary(
+    operand: impl Parser<Token, UntypedExpr, Error = ParseError> + Clone + 'static,
+    operator: impl Parser<Token, BinOp, Error = ParseError> + Clone + 'static,
+) -> BoxedParser<'static, Token, UntypedExpr, ParseError> {
+    operand
+        .clone()
+        .then(operator.then(operand).repeated())
+        .foldl(|left, (name, right)| UntypedExpr::BinOp {
+            location: left.location().union(right.location()),
+            name,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{AssignmentKind, BinOp, Definition, ModuleKind, Pattern},
+        expr::UntypedExpr,
+    };
+
+    #[test]
+    fn parse_module() {
+        let src = r#"
+            use aiken/builtin.{sha2_256} as b
+
+            pub type Option(a) {
+              Some(a)
+              None
+            }
+
+            type Datum {
+              Datum { owner: ByteArray, amount: Int }
+            }
+
+            // the owner has to match
+            pub fn spend(datum: Datum, redeemer: ByteArray, _ctx: Data) -> Bool {
+              let hash = sha2_256(redeemer)
+              when datum is {
+                Datum(owner, _) -> owner == hash && datum.amount > 0
+              }
+            }
+        "#;
+
+        let module = super::module(src, "validators/owner", ModuleKind::Validator).unwrap();
+
+        assert_eq!(module.definitions.len(), 4);
+        assert_eq!(
+            module.dependencies(),
+            vec![(
+                "aiken/builtin".to_string(),
+                module.definitions[0].location()
+            )]
+        );
+
+        match &module.definitions[2] {
+            Definition::DataType(data_type) => {
+                assert_eq!(data_type.name, "Datum");
+                assert_eq!(data_type.constructors[0].arguments.len(), 2);
+            }
+            definition => panic!("expected a type, got {:?}", definition),
+        }
+
+        match &module.definitions[3] {
+            Definition::Fn(function) => {
+                assert!(function.public);
+                assert_eq!(function.arguments.len(), 3);
+                assert!(matches!(
+                    &function.body,
+                    UntypedExpr::Sequence { expressions, .. } if expressions.len() == 2
+                ));
+            }
+            definition => panic!("expected a function, got {:?}", definition),
+        }
+    }
+
+    #[test]
+    fn precedence_and_pipes() {
+        let expr = super::expr("1 + 2 * 3 |> f(4) == x || !y").unwrap();
+
+        let UntypedExpr::BinOp {
+            name: BinOp::Or,
+            left,
+            ..
+        } = expr
+        else {
+            panic!("expected ||");
+        };
+
+        let UntypedExpr::BinOp {
+            name: BinOp::Eq,
+            left,
+            ..
+        } = *left
+        else {
+            panic!("expected ==");
+        };
+
+        let UntypedExpr::Call { arguments, .. } = *left else {
+            panic!("expected the pipe to become a call");
+        };
+
+        assert!(matches!(
+            arguments.as_slice(),
+            [
+                UntypedExpr::BinOp {
+                    name: BinOp::AddInt,
+                    ..
+                },
+                UntypedExpr::Int { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn list_patterns() {
+        let expr = super::expr("when xs is { [] -> 0 [x, ..rest] -> x }").unwrap();
+
+        let UntypedExpr::When { clauses, .. } = expr else {
+            panic!("expected a when expression");
+        };
+
+        assert!(matches!(
+            &clauses[1].pattern,
+            Pattern::List { elements, tail: Some(_), .. } if elements.len() == 1
+        ));
+    }
+
+    #[test]
+    fn expect() {
+        let src = r#"{
+            expect [x] = xs else 0
+            expect Some(y) = x or fail "not some"
+            expect z = y
+            z
+        }"#;
+
+        let UntypedExpr::Sequence { expressions, .. } = super::expr(src).unwrap() else {
+            panic!("expected a block");
+        };
+
+        let kinds: Vec<_> = expressions
+            .iter()
+            .filter_map(|expr| match expr {
+                UntypedExpr::Assignment { kind, .. } => Some(kind.clone()),
+                _ => None,
+            })
+            .collect();
+
+        assert!(matches!(kinds[0], AssignmentKind::ExpectElse(_)));
+        assert_eq!(
+            kinds[1],
+            AssignmentKind::ExpectOrFail("not some".to_string())
+        );
+        assert_eq!(kinds[2], AssignmentKind::Expect);
+
+        let errors = super::expr("{ let x = y else 0 x }").unwrap_err();
+
+        assert_eq!(
+            errors[0].to_string(),
+            "Only an `expect` can have an `else` or `or fail`"
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let src = "fn foo() { let = 1 }";
+
+        let errors = super::module(src, "foo", ModuleKind::Lib).unwrap_err();
+
+        assert_eq!(errors[0].span.start, 15);
+        assert_eq!(errors[0].label, Some("a pattern"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::BTreeSet, fmt::Display, hash::Hash};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::ast::Span;
+
+/// Something the lexer or the parser didn't expect. It renders as a
+/// diagnostic once reported through `miette` with the module's source.
+#[derive(Error, Debug, Diagnostic, Clone, PartialEq, Eq)]
+#[error("{}", .message.clone().unwrap_or_else(|| format!("Unexpected {}", .found.as_deref().unwrap_or("end of input"))))]
+#[diagnostic(code(aiken::parser))]
+pub struct ParseError {
+    #[label("{}", .label.map(String::from).unwrap_or_else(|| expected_message(.expected)))]
+    pub span: Span,
+    /// What would have been accepted instead, sorted.
+    pub expected: BTreeSet<String>,
+    pub found: Option<String>,
+    /// What was being parsed, like "a pattern".
+    pub label: Option<&'static str>,
+    /// Replaces the generic message for errors that aren't about
+    /// unexpected input, like a malformed bytearray.
+    pub message: Option<String>,
+}
+
+impl ParseError {
+    pub fn invalid(span: Span, message: impl Into<String>) -> Self {
+        ParseError {
+            span,
+            expected: BTreeSet::new(),
+            found: None,
+            label: None,
+            message: Some(message.into()),
+        }
+    }
+}
+
+impl<I: Display + Hash + Eq> chumsky::Error<I> for ParseError {
+    type Span = Span;
+    type Label = &'static str;
+
+    fn expected_input_found<Iter: IntoIterator<Item = Option<I>>>(
+        span: Self::Span,
+        expected: Iter,
+        found: Option<I>,
+    ) -> Self {
+        ParseError {
+            span,
+            expected: expected
+                .into_iter()
+                .map(|token| match token {
+                    Some(token) => format!("`{}`", token),
+                    None => String::from("end of input"),
+                })
+                .collect(),
+            found: found.map(|token| format!("`{}`", token)),
+            label: None,
+            message: None,
+        }
+    }
+
+    fn with_label(mut self, label: Self::Label) -> Self {
+        self.label.get_or_insert(label);
+
+        self
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.expected.extend(other.expected);
+
+        self
+    }
+}
+
+fn expected_message(expected: &BTreeSet<String>) -> String {
+    let expected: Vec<&String> = expected.iter().collect();
+
+    match expected.as_slice() {
+        [] => String::from("something else"),
+        [token] => format!("expected {}", token),
+        [init @ .., last] => format!(
+            "expected {} or {}",
+            init.iter()
+                .map(|token| token.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            last
+        ),
+    }
+}
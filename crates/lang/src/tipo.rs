@@ -0,0 +1,304 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use uplc::builtins::DefaultFunction;
+
+use crate::{
+    ast::{ModuleKind, Span},
+    builtins,
+};
+
+mod environment;
+pub mod error;
+mod exhaustive;
+mod expr;
+mod infer;
+mod pattern;
+pub mod pretty;
+
+pub use environment::Environment;
+pub use error::Error;
+pub use infer::infer_module;
+
+/// The type of a value, as inferred by the type checker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// A named type applied to it's parameters, like `Int` or `List(a)`.
+    App {
+        public: bool,
+        module: String,
+        name: String,
+        args: Vec<Rc<Type>>,
+    },
+    /// `fn(args) -> ret`
+    Fn { args: Vec<Rc<Type>>, ret: Rc<Type> },
+    /// A type variable, shared by every type it appears in so that
+    /// unifying it anywhere resolves it everywhere.
+    Var { tipo: Rc<RefCell<TypeVar>> },
+}
+
+impl Type {
+    pub fn is_int(&self) -> bool {
+        self.is_prelude_type("Int")
+    }
+
+    pub fn is_bytearray(&self) -> bool {
+        self.is_prelude_type("ByteArray")
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.is_prelude_type("String")
+    }
+
+    pub fn is_bool(&self) -> bool {
+        self.is_prelude_type("Bool")
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.is_prelude_type("List")
+    }
+
+    pub fn is_data(&self) -> bool {
+        self.is_prelude_type("Data")
+    }
+
+    pub fn is_void(&self) -> bool {
+        self.is_prelude_type("Void")
+    }
+
+    fn is_prelude_type(&self, type_name: &str) -> bool {
+        match self {
+            Type::App { module, name, .. } => module.is_empty() && name == type_name,
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => tipo.is_prelude_type(type_name),
+                _ => false,
+            },
+            Type::Fn { .. } => false,
+        }
+    }
+
+    /// Whether this is a variable that hasn't been resolved to a type.
+    pub fn is_unbound(&self) -> bool {
+        match self {
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => tipo.is_unbound(),
+                TypeVar::Unbound { .. } => true,
+                TypeVar::Generic { .. } => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether this is a variable, either unbound or generic.
+    pub fn is_var(&self) -> bool {
+        match self {
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => tipo.is_var(),
+                _ => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// The type parameters of a named type, like `Int` in `List(Int)`.
+    pub fn arg_types(&self) -> Option<Vec<Rc<Type>>> {
+        match self {
+            Type::App { args, .. } => Some(args.clone()),
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => tipo.arg_types(),
+                _ => None,
+            },
+            Type::Fn { .. } => None,
+        }
+    }
+
+    /// The argument types and return type of a function type.
+    pub fn fn_types(&self) -> Option<(Vec<Rc<Type>>, Rc<Type>)> {
+        match self {
+            Type::Fn { args, ret } => Some((args.clone(), ret.clone())),
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => tipo.fn_types(),
+                _ => None,
+            },
+            Type::App { .. } => None,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", pretty::Printer::new().print(self))
+    }
+}
+
+/// Follow the links of resolved type variables.
+pub fn collapse_links(tipo: Rc<Type>) -> Rc<Type> {
+    if let Type::Var { tipo: var } = tipo.as_ref() {
+        if let TypeVar::Link { tipo } = &*var.borrow() {
+            return collapse_links(tipo.clone());
+        }
+    }
+
+    tipo
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeVar {
+    /// Not known yet, inference may still resolve it.
+    Unbound { id: u64 },
+    /// Resolved to `tipo`.
+    Link { tipo: Rc<Type> },
+    /// A variable of a generalised definition. It is replaced by a
+    /// fresh unbound variable each time the definition is used.
+    Generic { id: u64 },
+}
+
+/// A value in scope: a local variable, a function or a record
+/// constructor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueConstructor {
+    pub public: bool,
+    pub variant: ValueConstructorVariant,
+    pub tipo: Rc<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueConstructorVariant {
+    /// Bound by a function argument, a `let` or a pattern.
+    LocalVariable { location: Span },
+    /// A function defined at the top level of a module, or a
+    /// builtin of `aiken/builtin`.
+    ModuleFn {
+        name: String,
+        module: String,
+        arity: usize,
+        builtin: Option<DefaultFunction>,
+        location: Span,
+    },
+    /// A constructor of a custom type. `tag` is it's position
+    /// amongst the `constructors_count` constructors of the type.
+    Record {
+        name: String,
+        module: String,
+        arity: usize,
+        tag: usize,
+        constructors_count: usize,
+        field_map: Option<HashMap<String, usize>>,
+        location: Span,
+    },
+}
+
+impl ValueConstructorVariant {
+    pub fn location(&self) -> Span {
+        match self {
+            ValueConstructorVariant::LocalVariable { location }
+            | ValueConstructorVariant::ModuleFn { location, .. }
+            | ValueConstructorVariant::Record { location, .. } => *location,
+        }
+    }
+}
+
+/// A type in scope, `parameters` are the generic variables
+/// appearing in `tipo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeConstructor {
+    pub public: bool,
+    pub location: Span,
+    pub module: String,
+    pub parameters: Vec<Rc<Type>>,
+    pub tipo: Rc<Type>,
+}
+
+/// What a constructor pattern matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternConstructor {
+    Record {
+        name: String,
+        tag: usize,
+        arity: usize,
+        constructors_count: usize,
+    },
+    /// One of the shapes a raw `Data` can take.
+    Data(DataConstructor),
+}
+
+/// The constructors `Data` can be matched with, in the order
+/// `ChooseData` takes it's branches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataConstructor {
+    /// `Constr(tag, fields)`
+    Constr,
+    /// `Map(map)`, binding the map itself as pairs have no type.
+    Map,
+    /// `List(elements)`
+    List,
+    /// `Int(int)`
+    Int,
+    /// `Bytes(bytes)`
+    Bytes,
+}
+
+impl DataConstructor {
+    pub const ALL: [DataConstructor; 5] = [
+        DataConstructor::Constr,
+        DataConstructor::Map,
+        DataConstructor::List,
+        DataConstructor::Int,
+        DataConstructor::Bytes,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|constructor| constructor.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataConstructor::Constr => "Constr",
+            DataConstructor::Map => "Map",
+            DataConstructor::List => "List",
+            DataConstructor::Int => "Int",
+            DataConstructor::Bytes => "Bytes",
+        }
+    }
+
+    /// The types of the values the constructor's patterns match.
+    pub fn fields(&self) -> Vec<Rc<Type>> {
+        match self {
+            DataConstructor::Constr => vec![builtins::int(), builtins::list(builtins::data())],
+            DataConstructor::Map => vec![builtins::data()],
+            DataConstructor::List => vec![builtins::list(builtins::data())],
+            DataConstructor::Int => vec![builtins::int()],
+            DataConstructor::Bytes => vec![builtins::byte_array()],
+        }
+    }
+}
+
+/// The types and values a type checked module defines. Modules
+/// importing it only see the public ones, the private ones are kept
+/// for tooling describing the module, like blueprints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeInfo {
+    pub name: String,
+    pub kind: ModuleKind,
+    pub types: HashMap<String, TypeConstructor>,
+    /// The names of the constructors of each type, in tag order.
+    pub types_constructors: HashMap<String, Vec<String>>,
+    pub values: HashMap<String, ValueConstructor>,
+}
+
+impl TypeInfo {
+    /// A type other modules can refer to.
+    pub fn public_type(&self, name: &str) -> Option<&TypeConstructor> {
+        self.types
+            .get(name)
+            .filter(|constructor| constructor.public)
+    }
+
+    /// A value other modules can refer to.
+    pub fn public_value(&self, name: &str) -> Option<&ValueConstructor> {
+        self.values
+            .get(name)
+            .filter(|constructor| constructor.public)
+    }
+}
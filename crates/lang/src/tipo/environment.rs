@@ -0,0 +1,558 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    ast::{Annotation, DataType, ModuleKind, Span, Use},
+    builtins::{self, PRELUDE},
+};
+
+use super::{
+    collapse_links, error::Error, pretty::Printer, Type, TypeConstructor, TypeInfo, TypeVar,
+    ValueConstructor, ValueConstructorVariant,
+};
+
+/// Everything in scope while type checking a module.
+pub struct Environment<'a> {
+    pub current_module: String,
+    pub kind: ModuleKind,
+    pub prelude: TypeInfo,
+    pub importable_modules: &'a HashMap<String, TypeInfo>,
+    /// Modules imported with `use`, by the name they are referred to with.
+    pub imported_modules: HashMap<String, (Span, &'a TypeInfo)>,
+    /// Values in scope: locals, definitions and unqualified imports.
+    pub scope: HashMap<String, ValueConstructor>,
+    /// Types in scope, by the name they are referred to with.
+    pub module_types: HashMap<String, TypeConstructor>,
+    /// The constructors of the types defined in this module.
+    pub module_types_constructors: HashMap<String, Vec<String>>,
+    /// The values defined in this module.
+    pub module_values: HashMap<String, ValueConstructor>,
+    next_id: u64,
+}
+
+/// Why two types couldn't be unified.
+enum UnifyError {
+    CouldNotUnify,
+    RecursiveType,
+}
+
+impl<'a> Environment<'a> {
+    pub fn new(
+        current_module: &str,
+        kind: ModuleKind,
+        importable_modules: &'a HashMap<String, TypeInfo>,
+    ) -> Self {
+        let prelude = builtins::prelude();
+
+        Environment {
+            current_module: current_module.to_string(),
+            kind,
+            scope: prelude.values.clone(),
+            module_types: prelude.types.clone(),
+            prelude,
+            importable_modules,
+            imported_modules: HashMap::new(),
+            module_types_constructors: HashMap::new(),
+            module_values: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+
+        self.next_id
+    }
+
+    /// A fresh type variable.
+    pub fn new_unbound_var(&mut self) -> Rc<Type> {
+        let id = self.next_id();
+
+        builtins::unbound_var(id)
+    }
+
+    pub fn new_generic_var(&mut self) -> Rc<Type> {
+        let id = self.next_id();
+
+        builtins::generic_var(id)
+    }
+
+    /// Look up the interface of a module, the prelude included.
+    pub fn module_info(&self, name: &str) -> Option<&TypeInfo> {
+        if name == PRELUDE {
+            Some(&self.prelude)
+        } else {
+            self.importable_modules.get(name)
+        }
+    }
+
+    /// The constructors of a type, in tag order, or `None` when
+    /// they aren't known.
+    pub fn type_constructors(&self, module: &str, name: &str) -> Option<Vec<ValueConstructor>> {
+        let (names, values) = if module == self.current_module {
+            (
+                self.module_types_constructors.get(name)?,
+                &self.module_values,
+            )
+        } else {
+            let info = self.module_info(module)?;
+
+            (info.types_constructors.get(name)?, &info.values)
+        };
+
+        names.iter().map(|name| values.get(name).cloned()).collect()
+    }
+
+    pub fn insert_variable(
+        &mut self,
+        name: String,
+        variant: ValueConstructorVariant,
+        tipo: Rc<Type>,
+    ) {
+        self.scope.insert(
+            name,
+            ValueConstructor {
+                public: false,
+                variant,
+                tipo,
+            },
+        );
+    }
+
+    /// Add a definition of this module to the scope, making sure it
+    /// isn't defined twice.
+    pub fn insert_module_value(
+        &mut self,
+        name: &str,
+        constructor: ValueConstructor,
+    ) -> Result<(), Error> {
+        if let Some(previous) = self.module_values.get(name) {
+            return Err(Error::DuplicateName {
+                location: constructor.variant.location(),
+                previous: previous.variant.location(),
+                name: name.to_string(),
+            });
+        }
+
+        self.scope.insert(name.to_string(), constructor.clone());
+        self.module_values.insert(name.to_string(), constructor);
+
+        Ok(())
+    }
+
+    pub fn register_import(&mut self, import: &Use) -> Result<(), Error> {
+        let name = import.module.join("/");
+
+        let info = self
+            .importable_modules
+            .get(&name)
+            .ok_or_else(|| Error::UnknownModule {
+                location: import.location,
+                name: name.clone(),
+            })?;
+
+        for unqualified in &import.unqualified {
+            let mut found = false;
+
+            if let Some(constructor) = info.public_type(&unqualified.name) {
+                self.module_types
+                    .insert(unqualified.local_name().to_string(), constructor.clone());
+                found = true;
+            }
+
+            if let Some(constructor) = info.public_value(&unqualified.name) {
+                self.scope
+                    .insert(unqualified.local_name().to_string(), constructor.clone());
+                found = true;
+            }
+
+            if !found {
+                return Err(Error::UnknownModuleValue {
+                    location: unqualified.location,
+                    module: name,
+                    name: unqualified.name.clone(),
+                });
+            }
+        }
+
+        let local_name = import.local_name().to_string();
+
+        if let Some((previous, _)) = self.imported_modules.get(&local_name) {
+            return Err(Error::DuplicateName {
+                location: import.location,
+                previous: *previous,
+                name: local_name,
+            });
+        }
+
+        self.imported_modules
+            .insert(local_name, (import.location, info));
+
+        Ok(())
+    }
+
+    /// Bring a custom type in scope, before any of the types it's
+    /// constructors refer to are known.
+    pub fn register_type(&mut self, data_type: &DataType) -> Result<(), Error> {
+        if let Some(previous) = self
+            .module_types
+            .get(&data_type.name)
+            .filter(|previous| previous.module == self.current_module)
+        {
+            return Err(Error::DuplicateName {
+                location: data_type.location,
+                previous: previous.location,
+                name: data_type.name.clone(),
+            });
+        }
+
+        let parameters: Vec<Rc<Type>> = data_type
+            .parameters
+            .iter()
+            .map(|_| self.new_generic_var())
+            .collect();
+
+        let tipo = Rc::new(Type::App {
+            public: data_type.public,
+            module: self.current_module.clone(),
+            name: data_type.name.clone(),
+            args: parameters.clone(),
+        });
+
+        self.module_types.insert(
+            data_type.name.clone(),
+            TypeConstructor {
+                public: data_type.public,
+                location: data_type.location,
+                module: self.current_module.clone(),
+                parameters,
+                tipo,
+            },
+        );
+
+        self.module_types_constructors.insert(
+            data_type.name.clone(),
+            data_type
+                .constructors
+                .iter()
+                .map(|constructor| constructor.name.clone())
+                .collect(),
+        );
+
+        Ok(())
+    }
+
+    /// Bring the constructors of a custom type in scope.
+    pub fn register_constructors(&mut self, data_type: &DataType) -> Result<(), Error> {
+        let type_constructor = self.module_types[&data_type.name].clone();
+
+        let mut vars: HashMap<String, Rc<Type>> = data_type
+            .parameters
+            .iter()
+            .cloned()
+            .zip(type_constructor.parameters.iter().cloned())
+            .collect();
+
+        for (tag, constructor) in data_type.constructors.iter().enumerate() {
+            let mut fields = Vec::with_capacity(constructor.arguments.len());
+            let mut field_map = HashMap::new();
+
+            for (index, argument) in constructor.arguments.iter().enumerate() {
+                fields.push(self.type_from_annotation(&argument.annotation, &mut vars, false)?);
+
+                if let Some(label) = &argument.label {
+                    field_map.insert(label.clone(), index);
+                }
+            }
+
+            let tipo = if fields.is_empty() {
+                type_constructor.tipo.clone()
+            } else {
+                builtins::function(fields, type_constructor.tipo.clone())
+            };
+
+            self.insert_module_value(
+                &constructor.name,
+                ValueConstructor {
+                    public: data_type.public,
+                    variant: ValueConstructorVariant::Record {
+                        name: constructor.name.clone(),
+                        module: self.current_module.clone(),
+                        arity: constructor.arguments.len(),
+                        tag,
+                        constructors_count: data_type.constructors.len(),
+                        field_map: (!field_map.is_empty()).then_some(field_map),
+                        location: constructor.location,
+                    },
+                    tipo,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Turn an annotation into a type. Type variables are looked up
+    /// in, and added to when `new_vars` is set, `vars`.
+    pub fn type_from_annotation(
+        &mut self,
+        annotation: &Annotation,
+        vars: &mut HashMap<String, Rc<Type>>,
+        new_vars: bool,
+    ) -> Result<Rc<Type>, Error> {
+        match annotation {
+            Annotation::Constructor {
+                location,
+                module,
+                name,
+                arguments,
+            } => {
+                let constructor = match module {
+                    None => self
+                        .module_types
+                        .get(name)
+                        .ok_or_else(|| Error::UnknownType {
+                            location: *location,
+                            name: name.clone(),
+                        })?,
+                    Some(module) => {
+                        let (_, info) = self.imported_modules.get(module).ok_or_else(|| {
+                            Error::UnknownModule {
+                                location: *location,
+                                name: module.clone(),
+                            }
+                        })?;
+
+                        info.public_type(name)
+                            .ok_or_else(|| Error::UnknownModuleType {
+                                location: *location,
+                                module: module.clone(),
+                                name: name.clone(),
+                            })?
+                    }
+                }
+                .clone();
+
+                if constructor.parameters.len() != arguments.len() {
+                    return Err(Error::IncorrectTypeArity {
+                        location: *location,
+                        name: name.clone(),
+                        expected: constructor.parameters.len(),
+                        given: arguments.len(),
+                    });
+                }
+
+                let mut ids = HashMap::new();
+
+                for (parameter, argument) in constructor.parameters.iter().zip(arguments) {
+                    let argument = self.type_from_annotation(argument, vars, new_vars)?;
+
+                    if let Type::Var { tipo } = parameter.as_ref() {
+                        if let TypeVar::Generic { id } = &*tipo.borrow() {
+                            ids.insert(*id, argument);
+                        }
+                    }
+                }
+
+                Ok(self.instantiate(constructor.tipo, &mut ids))
+            }
+
+            Annotation::Fn { arguments, ret, .. } => {
+                let args = arguments
+                    .iter()
+                    .map(|argument| self.type_from_annotation(argument, vars, new_vars))
+                    .collect::<Result<_, _>>()?;
+
+                let ret = self.type_from_annotation(ret, vars, new_vars)?;
+
+                Ok(builtins::function(args, ret))
+            }
+
+            Annotation::Var { location, name } => match vars.get(name) {
+                Some(tipo) => Ok(tipo.clone()),
+                None if new_vars => {
+                    let tipo = self.new_unbound_var();
+                    vars.insert(name.clone(), tipo.clone());
+
+                    Ok(tipo)
+                }
+                None => Err(Error::UnknownTypeVariable {
+                    location: *location,
+                    name: name.clone(),
+                }),
+            },
+        }
+    }
+
+    /// Replace the generic variables of a type with fresh unbound
+    /// ones, the same generic variable getting the same replacement.
+    pub fn instantiate(&mut self, tipo: Rc<Type>, ids: &mut HashMap<u64, Rc<Type>>) -> Rc<Type> {
+        match tipo.as_ref() {
+            Type::App {
+                public,
+                module,
+                name,
+                args,
+            } => Rc::new(Type::App {
+                public: *public,
+                module: module.clone(),
+                name: name.clone(),
+                args: args
+                    .iter()
+                    .map(|arg| self.instantiate(arg.clone(), ids))
+                    .collect(),
+            }),
+
+            Type::Fn { args, ret } => builtins::function(
+                args.iter()
+                    .map(|arg| self.instantiate(arg.clone(), ids))
+                    .collect(),
+                self.instantiate(ret.clone(), ids),
+            ),
+
+            Type::Var { tipo: var } => {
+                let generic = match &*var.borrow() {
+                    TypeVar::Link { tipo } => return self.instantiate(tipo.clone(), ids),
+                    TypeVar::Unbound { .. } => return tipo.clone(),
+                    TypeVar::Generic { id } => *id,
+                };
+
+                ids.entry(generic)
+                    .or_insert_with(|| {
+                        self.next_id += 1;
+
+                        builtins::unbound_var(self.next_id)
+                    })
+                    .clone()
+            }
+        }
+    }
+
+    /// Make `given` and `expected` the same type, resolving the
+    /// variables of either as needed.
+    pub fn unify(
+        &mut self,
+        expected: Rc<Type>,
+        given: Rc<Type>,
+        location: Span,
+    ) -> Result<(), Error> {
+        unify(expected.clone(), given.clone()).map_err(|error| {
+            let mut printer = Printer::new();
+
+            match error {
+                UnifyError::CouldNotUnify => Error::CouldNotUnify {
+                    location,
+                    expected: printer.print(&expected),
+                    given: printer.print(&given),
+                },
+                UnifyError::RecursiveType => Error::RecursiveType {
+                    location,
+                    tipo: printer.print(&given),
+                },
+            }
+        })
+    }
+}
+
+fn unify(t1: Rc<Type>, t2: Rc<Type>) -> Result<(), UnifyError> {
+    let (t1, t2) = (collapse_links(t1), collapse_links(t2));
+
+    if t1 == t2 {
+        return Ok(());
+    }
+
+    if let Type::Var { tipo } = t1.as_ref() {
+        let id = match &*tipo.borrow() {
+            TypeVar::Unbound { id } => Some(*id),
+            _ => None,
+        };
+
+        match id {
+            Some(id) => {
+                occurs_check(&t2, id)?;
+                *tipo.borrow_mut() = TypeVar::Link { tipo: t2 };
+
+                return Ok(());
+            }
+            // A generic variable only unifies with itself, or with a
+            // variable that can be resolved to it.
+            None => match t2.as_ref() {
+                Type::Var { tipo } if !matches!(&*tipo.borrow(), TypeVar::Generic { .. }) => (),
+                _ => return Err(UnifyError::CouldNotUnify),
+            },
+        }
+    }
+
+    if let Type::Var { .. } = t2.as_ref() {
+        return unify(t2, t1);
+    }
+
+    match (t1.as_ref(), t2.as_ref()) {
+        (
+            Type::App {
+                module: m1,
+                name: n1,
+                args: a1,
+                ..
+            },
+            Type::App {
+                module: m2,
+                name: n2,
+                args: a2,
+                ..
+            },
+        ) if m1 == m2 && n1 == n2 && a1.len() == a2.len() => a1
+            .iter()
+            .zip(a2)
+            .try_for_each(|(a1, a2)| unify(a1.clone(), a2.clone())),
+
+        (Type::Fn { args: a1, ret: r1 }, Type::Fn { args: a2, ret: r2 })
+            if a1.len() == a2.len() =>
+        {
+            a1.iter()
+                .zip(a2)
+                .try_for_each(|(a1, a2)| unify(a1.clone(), a2.clone()))?;
+
+            unify(r1.clone(), r2.clone())
+        }
+
+        _ => Err(UnifyError::CouldNotUnify),
+    }
+}
+
+/// Make sure linking variable `id` to `tipo` wouldn't make an
+/// infinite type.
+fn occurs_check(tipo: &Type, id: u64) -> Result<(), UnifyError> {
+    match tipo {
+        Type::Var { tipo } => match &*tipo.borrow() {
+            TypeVar::Link { tipo } => occurs_check(tipo, id),
+            TypeVar::Unbound { id: other } if *other == id => Err(UnifyError::RecursiveType),
+            _ => Ok(()),
+        },
+        Type::App { args, .. } => args.iter().try_for_each(|arg| occurs_check(arg, id)),
+        Type::Fn { args, ret } => {
+            args.iter().try_for_each(|arg| occurs_check(arg, id))?;
+
+            occurs_check(ret, id)
+        }
+    }
+}
+
+/// Turn the unbound variables of a definition's type into generic
+/// ones, so that each use of the definition can pick it's own types.
+pub fn generalise(tipo: &Rc<Type>) {
+    match collapse_links(tipo.clone()).as_ref() {
+        Type::Var { tipo } => {
+            let id = match &*tipo.borrow() {
+                TypeVar::Unbound { id } => *id,
+                _ => return,
+            };
+
+            *tipo.borrow_mut() = TypeVar::Generic { id };
+        }
+        Type::App { args, .. } => args.iter().for_each(generalise),
+        Type::Fn { args, ret } => {
+            args.iter().for_each(generalise);
+            generalise(ret);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::ast::Span;
+
+/// A problem found while type checking a module. Types are kept
+/// printed so errors can be reported from any thread.
+#[derive(Error, Debug, Diagnostic, Clone, PartialEq)]
+pub enum Error {
+    #[error("Type mismatch, expected `{expected}` but found `{given}`")]
+    #[diagnostic(code(aiken::check::could_not_unify))]
+    CouldNotUnify {
+        #[label("expected `{expected}`")]
+        location: Span,
+        expected: String,
+        given: String,
+    },
+
+    #[error("Recursive type, `{tipo}` would have to contain itself")]
+    #[diagnostic(code(aiken::check::recursive_type))]
+    RecursiveType {
+        #[label]
+        location: Span,
+        tipo: String,
+    },
+
+    #[error("`{name}` is defined twice")]
+    #[diagnostic(code(aiken::check::duplicate_name))]
+    DuplicateName {
+        #[label("redefined here")]
+        location: Span,
+        #[label("first defined here")]
+        previous: Span,
+        name: String,
+    },
+
+    #[error("`{name}` is bound twice in the same pattern")]
+    #[diagnostic(code(aiken::check::duplicate_var_in_pattern))]
+    DuplicateVarInPattern {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Expected {expected} arguments but got {given}")]
+    #[diagnostic(code(aiken::check::incorrect_arity))]
+    IncorrectArity {
+        #[label]
+        location: Span,
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("`{name}` takes {expected} type parameters but got {given}")]
+    #[diagnostic(code(aiken::check::incorrect_type_arity))]
+    IncorrectTypeArity {
+        #[label]
+        location: Span,
+        name: String,
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("This isn't a function, it's type is `{tipo}`")]
+    #[diagnostic(code(aiken::check::not_fn))]
+    NotFn {
+        #[label]
+        location: Span,
+        tipo: String,
+    },
+
+    #[error("Unknown variable `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_variable))]
+    UnknownVariable {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Unknown constructor `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_constructor))]
+    UnknownConstructor {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Unknown type `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_type))]
+    UnknownType {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Unknown type variable `{name}`")]
+    #[diagnostic(
+        code(aiken::check::unknown_type_variable),
+        help("Type variables used by constructors have to be parameters of the type")
+    )]
+    UnknownTypeVariable {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Unknown module `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_module))]
+    UnknownModule {
+        #[label]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Module `{module}` has no public value `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_module_value))]
+    UnknownModuleValue {
+        #[label]
+        location: Span,
+        module: String,
+        name: String,
+    },
+
+    #[error("Module `{module}` has no public type `{name}`")]
+    #[diagnostic(code(aiken::check::unknown_module_type))]
+    UnknownModuleType {
+        #[label]
+        location: Span,
+        module: String,
+        name: String,
+    },
+
+    #[error("`{tipo}` has no field `{label}`")]
+    #[diagnostic(
+        code(aiken::check::unknown_record_field),
+        help("Only fields of types with a single constructor can be accessed")
+    )]
+    UnknownRecordField {
+        #[label]
+        location: Span,
+        label: String,
+        tipo: String,
+    },
+
+    #[error("This `when` doesn't match every value, `{missing}` isn't covered")]
+    #[diagnostic(code(aiken::check::not_exhaustive))]
+    NotExhaustive {
+        #[label]
+        location: Span,
+        missing: String,
+    },
+
+    #[error("Division by zero")]
+    #[diagnostic(
+        code(aiken::check::division_by_zero),
+        help("Dividing by zero always fails, use `error` to fail on purpose")
+    )]
+    DivisionByZero {
+        #[label("this is zero")]
+        location: Span,
+    },
+
+    #[error("This pattern doesn't match every value, `{missing}` isn't covered")]
+    #[diagnostic(
+        code(aiken::check::not_exhaustive_let),
+        help("Use a `when` to handle every case")
+    )]
+    NotExhaustiveLet {
+        #[label]
+        location: Span,
+        missing: String,
+    },
+}
+
+impl Error {
+    pub fn location(&self) -> Span {
+        match self {
+            Error::CouldNotUnify { location, .. }
+            | Error::RecursiveType { location, .. }
+            | Error::DuplicateName { location, .. }
+            | Error::DuplicateVarInPattern { location, .. }
+            | Error::IncorrectArity { location, .. }
+            | Error::IncorrectTypeArity { location, .. }
+            | Error::NotFn { location, .. }
+            | Error::UnknownVariable { location, .. }
+            | Error::UnknownConstructor { location, .. }
+            | Error::UnknownType { location, .. }
+            | Error::UnknownTypeVariable { location, .. }
+            | Error::UnknownModule { location, .. }
+            | Error::UnknownModuleValue { location, .. }
+            | Error::UnknownModuleType { location, .. }
+            | Error::UnknownRecordField { location, .. }
+            | Error::NotExhaustive { location, .. }
+            | Error::DivisionByZero { location, .. }
+            | Error::NotExhaustiveLet { location, .. } => *location,
+        }
+    }
+}
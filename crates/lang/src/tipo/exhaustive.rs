@@ -0,0 +1,247 @@
+//! Checks that patterns match every value of a type, following
+//! "Warnings for pattern matching" (Maranget, 2007): a set of
+//! patterns is exhaustive when a wildcard added after them would
+//! never match anything they don't already match.
+
+use std::rc::Rc;
+
+use crate::{
+    ast::{Pattern, TypedPattern},
+    builtins::PRELUDE,
+};
+
+use super::{
+    collapse_links, environment::Environment, DataConstructor, Type, ValueConstructorVariant,
+};
+
+/// A pattern with only what matters to exhaustiveness left: lists
+/// are nested `[]` and `::` constructors, bindings are wildcards.
+#[derive(Debug, Clone, PartialEq)]
+enum Pat {
+    Wildcard,
+    Literal(String),
+    Constructor(String, Vec<Pat>),
+}
+
+const NIL: &str = "[]";
+const CONS: &str = "::";
+
+/// A value of type `tipo` none of `patterns` match, printed as a
+/// pattern, if there is one.
+pub fn missing_pattern(
+    environment: &mut Environment<'_>,
+    patterns: &[&TypedPattern],
+    tipo: Rc<Type>,
+) -> Option<String> {
+    let rows: Vec<Vec<Pat>> = patterns
+        .iter()
+        .map(|pattern| vec![simplify(pattern)])
+        .collect();
+
+    Checker { environment }
+        .useful(&rows, &[tipo])
+        .map(|witness| pretty(&witness[0]))
+}
+
+fn simplify(pattern: &TypedPattern) -> Pat {
+    match pattern {
+        Pattern::Int { value, .. } => Pat::Literal(value.to_string()),
+        Pattern::String { value, .. } => Pat::Literal(format!("{:?}", value)),
+        Pattern::Var { .. } | Pattern::Discard { .. } => Pat::Wildcard,
+        Pattern::Constructor {
+            name, arguments, ..
+        } => Pat::Constructor(name.clone(), arguments.iter().map(simplify).collect()),
+        Pattern::List { elements, tail, .. } => {
+            let tail = match tail {
+                Some(tail) => simplify(tail),
+                None => Pat::Constructor(NIL.to_string(), vec![]),
+            };
+
+            elements.iter().rev().fold(tail, |tail, element| {
+                Pat::Constructor(CONS.to_string(), vec![simplify(element), tail])
+            })
+        }
+    }
+}
+
+struct Checker<'a, 'b> {
+    environment: &'a mut Environment<'b>,
+}
+
+impl<'a, 'b> Checker<'a, 'b> {
+    /// Whether a row of wildcards, one per column, matches values
+    /// none of `rows` match. If so, returns such values.
+    fn useful(&mut self, rows: &[Vec<Pat>], types: &[Rc<Type>]) -> Option<Vec<Pat>> {
+        let Some((tipo, rest)) = types.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+
+        let constructors = self.constructors(tipo.clone());
+
+        let used: Vec<&str> = rows
+            .iter()
+            .filter_map(|row| match &row[0] {
+                Pat::Constructor(name, _) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        match constructors {
+            Some(constructors)
+                if constructors
+                    .iter()
+                    .all(|(name, _)| used.contains(&name.as_str())) =>
+            {
+                for (name, fields) in constructors {
+                    let rows = specialize(rows, &name, fields.len());
+                    let types: Vec<Rc<Type>> = fields.iter().chain(rest).cloned().collect();
+
+                    if let Some(mut witness) = self.useful(&rows, &types) {
+                        let rest = witness.split_off(fields.len());
+
+                        return Some(
+                            std::iter::once(Pat::Constructor(name, witness))
+                                .chain(rest)
+                                .collect(),
+                        );
+                    }
+                }
+
+                None
+            }
+
+            constructors => {
+                let default: Vec<Vec<Pat>> = rows
+                    .iter()
+                    .filter(|row| row[0] == Pat::Wildcard)
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+
+                let witness = self.useful(&default, rest)?;
+
+                let missing = constructors
+                    .and_then(|constructors| {
+                        constructors
+                            .into_iter()
+                            .find(|(name, _)| !used.contains(&name.as_str()))
+                    })
+                    .map(|(name, fields)| Pat::Constructor(name, vec![Pat::Wildcard; fields.len()]))
+                    .unwrap_or(Pat::Wildcard);
+
+                Some(std::iter::once(missing).chain(witness).collect())
+            }
+        }
+    }
+
+    /// The constructors of a type with the types of their fields,
+    /// or `None` if the type doesn't have a finite set of them.
+    fn constructors(&mut self, tipo: Rc<Type>) -> Option<Vec<(String, Vec<Rc<Type>>)>> {
+        let tipo = collapse_links(tipo);
+
+        let Type::App {
+            module, name, args, ..
+        } = tipo.as_ref()
+        else {
+            return None;
+        };
+
+        if module == PRELUDE && name == "List" {
+            return Some(vec![
+                (NIL.to_string(), vec![]),
+                (CONS.to_string(), vec![args[0].clone(), tipo.clone()]),
+            ]);
+        }
+
+        if module == PRELUDE && name == "Data" {
+            return Some(
+                DataConstructor::ALL
+                    .iter()
+                    .map(|constructor| (constructor.name().to_string(), constructor.fields()))
+                    .collect(),
+            );
+        }
+
+        let constructors = self.environment.type_constructors(module, name)?;
+
+        Some(
+            constructors
+                .into_iter()
+                .map(|constructor| {
+                    let name = match &constructor.variant {
+                        ValueConstructorVariant::Record { name, .. } => name.clone(),
+                        _ => unreachable!("a type constructor is a record"),
+                    };
+
+                    let instantiated = self
+                        .environment
+                        .instantiate(constructor.tipo, &mut Default::default());
+
+                    let fields = match instantiated.fn_types() {
+                        Some((fields, ret)) => {
+                            // Can't fail: the constructor builds values of this type.
+                            let _ = self
+                                .environment
+                                .unify(tipo.clone(), ret, Default::default());
+
+                            fields
+                        }
+                        None => vec![],
+                    };
+
+                    (name, fields)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The rows matching a value built with constructor `name`, with
+/// the constructor's fields replacing the first column.
+fn specialize(rows: &[Vec<Pat>], name: &str, arity: usize) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| {
+            let head = match &row[0] {
+                Pat::Constructor(constructor, arguments) if constructor == name => {
+                    arguments.clone()
+                }
+                Pat::Wildcard => vec![Pat::Wildcard; arity],
+                _ => return None,
+            };
+
+            Some(head.into_iter().chain(row[1..].iter().cloned()).collect())
+        })
+        .collect()
+}
+
+fn pretty(pattern: &Pat) -> String {
+    match pattern {
+        Pat::Wildcard => "_".to_string(),
+        Pat::Literal(literal) => literal.clone(),
+        Pat::Constructor(name, _) if name == NIL => "[]".to_string(),
+        Pat::Constructor(name, _) if name == CONS => {
+            let mut elements = vec![];
+            let mut pattern = pattern;
+
+            while let Pat::Constructor(name, arguments) = pattern {
+                if name != CONS {
+                    break;
+                }
+
+                elements.push(pretty(&arguments[0]));
+                pattern = &arguments[1];
+            }
+
+            if *pattern == Pat::Wildcard {
+                elements.push("..".to_string());
+            }
+
+            format!("[{}]", elements.join(", "))
+        }
+        Pat::Constructor(name, arguments) if arguments.is_empty() => name.clone(),
+        Pat::Constructor(name, arguments) => format!(
+            "{}({})",
+            name,
+            arguments.iter().map(pretty).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
@@ -0,0 +1,712 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    ast::{
+        Annotation, Arg, ArgName, AssignmentKind, BinOp, Clause, IfBranch, Span, TypedArg, UnOp,
+        UntypedClause, UntypedPattern,
+    },
+    builtins,
+    expr::{TypedExpr, UntypedExpr},
+};
+
+use super::{
+    collapse_links, environment::Environment, error::Error, exhaustive, pattern::PatternTyper,
+    pretty::Printer, Type, ValueConstructor, ValueConstructorVariant,
+};
+
+/// Infers the types of the expressions of a function body.
+pub struct ExprTyper<'a, 'b> {
+    pub environment: &'a mut Environment<'b>,
+    /// The type variables of the annotations met so far, shared by
+    /// the whole definition.
+    pub vars: HashMap<String, Rc<Type>>,
+}
+
+impl<'a, 'b> ExprTyper<'a, 'b> {
+    pub fn new(environment: &'a mut Environment<'b>, vars: HashMap<String, Rc<Type>>) -> Self {
+        ExprTyper { environment, vars }
+    }
+
+    pub fn infer(&mut self, expr: UntypedExpr) -> Result<TypedExpr, Error> {
+        match expr {
+            UntypedExpr::Int { location, value } => Ok(TypedExpr::Int {
+                location,
+                tipo: builtins::int(),
+                value,
+            }),
+
+            UntypedExpr::String { location, value } => Ok(TypedExpr::String {
+                location,
+                tipo: builtins::string(),
+                value,
+            }),
+
+            UntypedExpr::ByteArray { location, bytes } => Ok(TypedExpr::ByteArray {
+                location,
+                tipo: builtins::byte_array(),
+                bytes,
+            }),
+
+            UntypedExpr::Var { location, name } => self.infer_var(name, location),
+
+            UntypedExpr::Fn {
+                location,
+                arguments,
+                return_annotation,
+                body,
+            } => self.infer_fn(arguments, return_annotation, *body, location),
+
+            UntypedExpr::List {
+                location,
+                elements,
+                tail,
+            } => self.infer_list(elements, tail, location),
+
+            UntypedExpr::Call {
+                location,
+                fun,
+                arguments,
+            } => self.infer_call(*fun, arguments, location),
+
+            UntypedExpr::BinOp {
+                location,
+                name,
+                left,
+                right,
+            } => self.infer_binop(name, *left, *right, location),
+
+            UntypedExpr::UnOp {
+                location,
+                op,
+                value,
+            } => {
+                let tipo = match op {
+                    UnOp::Not => builtins::bool(),
+                    UnOp::Negate => builtins::int(),
+                };
+
+                let value = self.infer(*value)?;
+                self.environment
+                    .unify(tipo.clone(), value.tipo(), value.location())?;
+
+                Ok(TypedExpr::UnOp {
+                    location,
+                    tipo,
+                    op,
+                    value: Box::new(value),
+                })
+            }
+
+            UntypedExpr::Sequence {
+                location,
+                expressions,
+            } => {
+                let scope = self.environment.scope.clone();
+
+                let expressions: Result<Vec<_>, _> = expressions
+                    .into_iter()
+                    .map(|expr| self.infer(expr))
+                    .collect();
+
+                self.environment.scope = scope;
+
+                let expressions = expressions?;
+
+                // The default of an `expect` is the value of the block.
+                if let Some(last) = expressions.last() {
+                    for expr in &expressions {
+                        if let TypedExpr::Assignment {
+                            kind: AssignmentKind::ExpectElse(default),
+                            ..
+                        } = expr
+                        {
+                            self.environment.unify(
+                                last.tipo(),
+                                default.tipo(),
+                                default.location(),
+                            )?;
+                        }
+                    }
+                }
+
+                Ok(TypedExpr::Sequence {
+                    location,
+                    expressions,
+                })
+            }
+
+            UntypedExpr::Assignment {
+                location,
+                kind,
+                pattern,
+                annotation,
+                value,
+            } => self.infer_assignment(kind, pattern, annotation, *value, location),
+
+            UntypedExpr::When {
+                location,
+                subject,
+                clauses,
+            } => self.infer_when(*subject, clauses, location),
+
+            UntypedExpr::If {
+                location,
+                branches,
+                final_else,
+            } => self.infer_if(branches, *final_else, location),
+
+            UntypedExpr::FieldAccess {
+                location,
+                container,
+                label,
+            } => self.infer_field_access(*container, label, location),
+
+            UntypedExpr::Todo { location, label } => Ok(TypedExpr::Todo {
+                location,
+                tipo: self.environment.new_unbound_var(),
+                label,
+            }),
+
+            UntypedExpr::ErrorTerm { location, label } => Ok(TypedExpr::ErrorTerm {
+                location,
+                tipo: self.environment.new_unbound_var(),
+                label,
+            }),
+        }
+    }
+
+    fn infer_var(&mut self, name: String, location: Span) -> Result<TypedExpr, Error> {
+        let ValueConstructor {
+            public,
+            variant,
+            tipo,
+        } = self
+            .environment
+            .scope
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownVariable {
+                location,
+                name: name.clone(),
+            })?;
+
+        let tipo = self.environment.instantiate(tipo, &mut HashMap::new());
+
+        Ok(TypedExpr::Var {
+            location,
+            constructor: ValueConstructor {
+                public,
+                variant,
+                tipo,
+            },
+            name,
+        })
+    }
+
+    /// Type the arguments of a function, using their annotation
+    /// when they have one.
+    pub fn infer_arguments(&mut self, arguments: Vec<Arg<()>>) -> Result<Vec<TypedArg>, Error> {
+        arguments
+            .into_iter()
+            .map(|argument| {
+                let tipo = match &argument.annotation {
+                    Some(annotation) => {
+                        self.environment
+                            .type_from_annotation(annotation, &mut self.vars, true)?
+                    }
+                    None => self.environment.new_unbound_var(),
+                };
+
+                Ok(Arg {
+                    location: argument.location,
+                    name: argument.name,
+                    annotation: argument.annotation,
+                    tipo,
+                })
+            })
+            .collect()
+    }
+
+    /// Type the body of a function whose arguments are typed, making
+    /// sure it returns `return_type` when it is known.
+    pub fn infer_fn_with_known_types(
+        &mut self,
+        arguments: &[TypedArg],
+        body: UntypedExpr,
+        return_type: Option<Rc<Type>>,
+    ) -> Result<TypedExpr, Error> {
+        let scope = self.environment.scope.clone();
+
+        for argument in arguments {
+            if let ArgName::Named(name) = &argument.name {
+                self.environment.insert_variable(
+                    name.clone(),
+                    ValueConstructorVariant::LocalVariable {
+                        location: argument.location,
+                    },
+                    argument.tipo.clone(),
+                );
+            }
+        }
+
+        let body = self.infer(body);
+
+        self.environment.scope = scope;
+
+        let body = body?;
+
+        if let Some(return_type) = return_type {
+            self.environment
+                .unify(return_type, body.tipo(), body.location())?;
+        }
+
+        Ok(body)
+    }
+
+    fn infer_fn(
+        &mut self,
+        arguments: Vec<Arg<()>>,
+        return_annotation: Option<Annotation>,
+        body: UntypedExpr,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let arguments = self.infer_arguments(arguments)?;
+
+        let return_type = return_annotation
+            .as_ref()
+            .map(|annotation| {
+                self.environment
+                    .type_from_annotation(annotation, &mut self.vars, true)
+            })
+            .transpose()?;
+
+        let body = self.infer_fn_with_known_types(&arguments, body, return_type)?;
+
+        let tipo = builtins::function(
+            arguments
+                .iter()
+                .map(|argument| argument.tipo.clone())
+                .collect(),
+            body.tipo(),
+        );
+
+        Ok(TypedExpr::Fn {
+            location,
+            tipo,
+            arguments,
+            return_annotation,
+            body: Box::new(body),
+        })
+    }
+
+    fn infer_list(
+        &mut self,
+        elements: Vec<UntypedExpr>,
+        tail: Option<Box<UntypedExpr>>,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let element_type = self.environment.new_unbound_var();
+        let tipo = builtins::list(element_type.clone());
+
+        let elements = elements
+            .into_iter()
+            .map(|element| {
+                let element = self.infer(element)?;
+
+                self.environment
+                    .unify(element_type.clone(), element.tipo(), element.location())?;
+
+                Ok(element)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let tail = tail
+            .map(|tail| {
+                let tail = self.infer(*tail)?;
+
+                self.environment
+                    .unify(tipo.clone(), tail.tipo(), tail.location())?;
+
+                Ok(Box::new(tail))
+            })
+            .transpose()?;
+
+        Ok(TypedExpr::List {
+            location,
+            tipo,
+            elements,
+            tail,
+        })
+    }
+
+    fn infer_call(
+        &mut self,
+        fun: UntypedExpr,
+        arguments: Vec<UntypedExpr>,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let fun = self.infer(fun)?;
+
+        let (parameters, ret) = match fun.tipo().fn_types() {
+            Some(types) => types,
+            None if fun.tipo().is_unbound() => {
+                let parameters: Vec<Rc<Type>> = arguments
+                    .iter()
+                    .map(|_| self.environment.new_unbound_var())
+                    .collect();
+                let ret = self.environment.new_unbound_var();
+
+                self.environment.unify(
+                    fun.tipo(),
+                    builtins::function(parameters.clone(), ret.clone()),
+                    fun.location(),
+                )?;
+
+                (parameters, ret)
+            }
+            None => {
+                return Err(Error::NotFn {
+                    location: fun.location(),
+                    tipo: Printer::new().print(&fun.tipo()),
+                })
+            }
+        };
+
+        if parameters.len() != arguments.len() {
+            return Err(Error::IncorrectArity {
+                location,
+                expected: parameters.len(),
+                given: arguments.len(),
+            });
+        }
+
+        let arguments = arguments
+            .into_iter()
+            .zip(parameters)
+            .map(|(argument, parameter)| {
+                let argument = self.infer(argument)?;
+
+                self.environment
+                    .unify(parameter, argument.tipo(), argument.location())?;
+
+                Ok(argument)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let builtin = match &fun {
+            TypedExpr::Var { constructor, .. } => match &constructor.variant {
+                ValueConstructorVariant::ModuleFn { builtin, .. } => *builtin,
+                _ => None,
+            },
+            TypedExpr::ModuleSelect {
+                constructor: ValueConstructorVariant::ModuleFn { builtin, .. },
+                ..
+            } => *builtin,
+            _ => None,
+        };
+
+        if let (Some(builtin), [_, divisor]) = (builtin, arguments.as_slice()) {
+            if builtins::is_division(builtin) && is_zero(divisor) {
+                return Err(Error::DivisionByZero {
+                    location: divisor.location(),
+                });
+            }
+        }
+
+        Ok(TypedExpr::Call {
+            location,
+            tipo: ret,
+            fun: Box::new(fun),
+            arguments,
+        })
+    }
+
+    fn infer_binop(
+        &mut self,
+        name: BinOp,
+        left: UntypedExpr,
+        right: UntypedExpr,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let (operand, tipo) = match name {
+            BinOp::And | BinOp::Or => (Some(builtins::bool()), builtins::bool()),
+            BinOp::Eq | BinOp::NotEq => (None, builtins::bool()),
+            BinOp::LtInt | BinOp::LtEqInt | BinOp::GtEqInt | BinOp::GtInt => {
+                (Some(builtins::int()), builtins::bool())
+            }
+            BinOp::AddInt | BinOp::SubInt | BinOp::MultInt | BinOp::DivInt | BinOp::ModInt => {
+                (Some(builtins::int()), builtins::int())
+            }
+        };
+
+        let left = self.infer(left)?;
+
+        // Both sides of an equality have the same, but any, type.
+        let operand = operand.unwrap_or_else(|| left.tipo());
+
+        self.environment
+            .unify(operand.clone(), left.tipo(), left.location())?;
+
+        let right = self.infer(right)?;
+
+        self.environment
+            .unify(operand, right.tipo(), right.location())?;
+
+        if matches!(name, BinOp::DivInt | BinOp::ModInt) && is_zero(&right) {
+            return Err(Error::DivisionByZero {
+                location: right.location(),
+            });
+        }
+
+        Ok(TypedExpr::BinOp {
+            location,
+            tipo,
+            name,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn infer_assignment(
+        &mut self,
+        kind: AssignmentKind<UntypedExpr>,
+        pattern: UntypedPattern,
+        annotation: Option<Annotation>,
+        value: UntypedExpr,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let value = self.infer(value)?;
+        let tipo = value.tipo();
+
+        if let Some(annotation) = &annotation {
+            let annotated =
+                self.environment
+                    .type_from_annotation(annotation, &mut self.vars, true)?;
+
+            self.environment
+                .unify(annotated, tipo.clone(), value.location())?;
+        }
+
+        // typed before the pattern, which doesn't bind anything in it
+        let kind = match kind {
+            AssignmentKind::Let => AssignmentKind::Let,
+            AssignmentKind::Expect => AssignmentKind::Expect,
+            AssignmentKind::ExpectElse(default) => {
+                AssignmentKind::ExpectElse(Box::new(self.infer(*default)?))
+            }
+            AssignmentKind::ExpectOrFail(message) => AssignmentKind::ExpectOrFail(message),
+        };
+
+        let pattern = PatternTyper::new(self.environment).infer(pattern, tipo.clone())?;
+
+        if kind.is_let() {
+            if let Some(missing) =
+                exhaustive::missing_pattern(self.environment, &[&pattern], tipo.clone())
+            {
+                return Err(Error::NotExhaustiveLet {
+                    location: pattern.location(),
+                    missing,
+                });
+            }
+        }
+
+        Ok(TypedExpr::Assignment {
+            location,
+            tipo,
+            kind,
+            pattern,
+            value: Box::new(value),
+        })
+    }
+
+    fn infer_when(
+        &mut self,
+        subject: UntypedExpr,
+        clauses: Vec<UntypedClause>,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let subject = self.infer(subject)?;
+        let subject_type = subject.tipo();
+        let tipo = self.environment.new_unbound_var();
+
+        let mut typed_clauses = Vec::with_capacity(clauses.len());
+
+        for clause in clauses {
+            let scope = self.environment.scope.clone();
+
+            let clause = PatternTyper::new(self.environment)
+                .infer(clause.pattern, subject_type.clone())
+                .and_then(|pattern| {
+                    let then = self.infer(clause.then)?;
+
+                    self.environment
+                        .unify(tipo.clone(), then.tipo(), then.location())?;
+
+                    Ok(Clause {
+                        location: clause.location,
+                        pattern,
+                        then,
+                    })
+                });
+
+            self.environment.scope = scope;
+
+            typed_clauses.push(clause?);
+        }
+
+        let patterns: Vec<_> = typed_clauses.iter().map(|clause| &clause.pattern).collect();
+
+        if let Some(missing) =
+            exhaustive::missing_pattern(self.environment, &patterns, subject_type)
+        {
+            return Err(Error::NotExhaustive { location, missing });
+        }
+
+        Ok(TypedExpr::When {
+            location,
+            tipo,
+            subject: Box::new(subject),
+            clauses: typed_clauses,
+        })
+    }
+
+    fn infer_if(
+        &mut self,
+        branches: Vec<IfBranch<UntypedExpr>>,
+        final_else: UntypedExpr,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        let tipo = self.environment.new_unbound_var();
+
+        let branches = branches
+            .into_iter()
+            .map(|branch| {
+                let condition = self.infer(branch.condition)?;
+
+                self.environment
+                    .unify(builtins::bool(), condition.tipo(), condition.location())?;
+
+                let body = self.infer(branch.body)?;
+
+                self.environment
+                    .unify(tipo.clone(), body.tipo(), body.location())?;
+
+                Ok(IfBranch {
+                    location: branch.location,
+                    condition,
+                    body,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let final_else = self.infer(final_else)?;
+
+        self.environment
+            .unify(tipo.clone(), final_else.tipo(), final_else.location())?;
+
+        Ok(TypedExpr::If {
+            location,
+            tipo,
+            branches,
+            final_else: Box::new(final_else),
+        })
+    }
+
+    fn infer_field_access(
+        &mut self,
+        container: UntypedExpr,
+        label: String,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        // A module name, unless shadowed by a variable.
+        if let UntypedExpr::Var { name, .. } = &container {
+            if !self.environment.scope.contains_key(name) {
+                if let Some((_, info)) = self.environment.imported_modules.get(name) {
+                    let constructor =
+                        info.public_value(&label)
+                            .ok_or_else(|| Error::UnknownModuleValue {
+                                location,
+                                module: name.clone(),
+                                name: label.clone(),
+                            })?;
+
+                    let module_name = info.name.clone();
+                    let variant = constructor.variant.clone();
+                    let tipo = self
+                        .environment
+                        .instantiate(constructor.tipo.clone(), &mut HashMap::new());
+
+                    return Ok(TypedExpr::ModuleSelect {
+                        location,
+                        tipo,
+                        label,
+                        module_name,
+                        module_alias: name.clone(),
+                        constructor: variant,
+                    });
+                }
+            }
+        }
+
+        let record = self.infer(container)?;
+        let record_type = collapse_links(record.tipo());
+
+        let unknown_field = || Error::UnknownRecordField {
+            location,
+            label: label.clone(),
+            tipo: Printer::new().print(&record_type),
+        };
+
+        let Type::App { module, name, .. } = record_type.as_ref() else {
+            return Err(unknown_field());
+        };
+
+        let constructors = self
+            .environment
+            .type_constructors(module, name)
+            .unwrap_or_default();
+
+        let [constructor] = constructors.as_slice() else {
+            return Err(unknown_field());
+        };
+
+        let index = match &constructor.variant {
+            ValueConstructorVariant::Record {
+                field_map: Some(field_map),
+                ..
+            } => field_map.get(&label).copied(),
+            _ => None,
+        }
+        .ok_or_else(unknown_field)?;
+
+        let (fields, ret) = self
+            .environment
+            .instantiate(constructor.tipo.clone(), &mut HashMap::new())
+            .fn_types()
+            .ok_or_else(unknown_field)?;
+
+        self.environment
+            .unify(ret, record.tipo(), record.location())?;
+
+        Ok(TypedExpr::RecordAccess {
+            location,
+            tipo: fields[index].clone(),
+            label,
+            index,
+            record: Box::new(record),
+        })
+    }
+}
+
+/// Whether an expression is a literal zero, possibly negated.
+fn is_zero(expr: &TypedExpr) -> bool {
+    match expr {
+        TypedExpr::Int { value, .. } => value.sign() == num_bigint::Sign::NoSign,
+        TypedExpr::UnOp {
+            op: UnOp::Negate,
+            value,
+            ..
+        } => is_zero(value),
+        _ => false,
+    }
+}
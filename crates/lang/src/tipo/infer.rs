@@ -0,0 +1,682 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use petgraph::{algo::tarjan_scc, graph::DiGraph};
+
+use crate::{
+    ast::{
+        AssignmentKind, Definition, Function, Module, Span, TypedDefinition, TypedFunction,
+        TypedModule, UntypedFunction, UntypedModule,
+    },
+    builtins,
+    expr::UntypedExpr,
+};
+
+use super::{
+    environment::{generalise, Environment},
+    error::Error,
+    expr::ExprTyper,
+    Type, TypeInfo, ValueConstructor, ValueConstructorVariant,
+};
+
+/// Type check a module. `importable_modules` are the interfaces of
+/// the modules it may import, by name.
+pub fn infer_module(
+    module: UntypedModule,
+    importable_modules: &HashMap<String, TypeInfo>,
+) -> Result<TypedModule, Error> {
+    let Module {
+        name,
+        kind,
+        definitions,
+        ..
+    } = module;
+
+    let mut environment = Environment::new(&name, kind, importable_modules);
+
+    for definition in &definitions {
+        if let Definition::Use(import) = definition {
+            environment.register_import(import)?;
+        }
+    }
+
+    for definition in &definitions {
+        if let Definition::DataType(data_type) = definition {
+            environment.register_type(data_type)?;
+        }
+    }
+
+    for definition in &definitions {
+        if let Definition::DataType(data_type) = definition {
+            environment.register_constructors(data_type)?;
+        }
+    }
+
+    let mut functions = HashMap::new();
+    let mut typed_definitions = Vec::with_capacity(definitions.len());
+
+    for (index, definition) in definitions.into_iter().enumerate() {
+        typed_definitions.push(match definition {
+            Definition::Fn(function) => {
+                functions.insert(index, function);
+                None
+            }
+            Definition::DataType(data_type) => Some(Definition::DataType(data_type)),
+            Definition::Use(import) => Some(Definition::Use(import)),
+        });
+    }
+
+    let mut names: HashMap<&str, Span> = HashMap::new();
+
+    for index in 0..typed_definitions.len() {
+        if let Some(function) = functions.get(&index) {
+            if let Some(previous) = names.insert(&function.name, function.location) {
+                return Err(Error::DuplicateName {
+                    location: function.location,
+                    previous,
+                    name: function.name.clone(),
+                });
+            }
+        }
+    }
+
+    for group in dependency_order(&functions) {
+        let typed = infer_functions(&mut environment, &mut functions, &group)?;
+
+        for (index, function) in group.into_iter().zip(typed) {
+            typed_definitions[index] = Some(Definition::Fn(function));
+        }
+    }
+
+    let typed_definitions: Vec<TypedDefinition> = typed_definitions.into_iter().flatten().collect();
+
+    let types = environment
+        .module_types
+        .iter()
+        .filter(|(_, constructor)| constructor.module == name)
+        .map(|(type_name, constructor)| (type_name.clone(), constructor.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let types_constructors = environment
+        .module_types_constructors
+        .iter()
+        .filter(|(type_name, _)| types.contains_key(*type_name))
+        .map(|(type_name, constructors)| (type_name.clone(), constructors.clone()))
+        .collect();
+
+    let values = environment.module_values.clone();
+
+    Ok(Module {
+        type_info: TypeInfo {
+            name: name.clone(),
+            kind,
+            types,
+            types_constructors,
+            values,
+        },
+        name,
+        kind,
+        definitions: typed_definitions,
+    })
+}
+
+/// Infer a group of mutually recursive functions together. Their
+/// types are only generalised once the whole group is known, so
+/// functions defined later can use them at any type.
+fn infer_functions(
+    environment: &mut Environment<'_>,
+    functions: &mut HashMap<usize, UntypedFunction>,
+    group: &[usize],
+) -> Result<Vec<TypedFunction>, Error> {
+    let mut signatures = Vec::with_capacity(group.len());
+
+    for index in group {
+        let function = &functions[index];
+        let mut expr_typer = ExprTyper::new(environment, HashMap::new());
+
+        let arguments = expr_typer.infer_arguments(function.arguments.clone())?;
+
+        let return_type = match &function.return_annotation {
+            Some(annotation) => expr_typer.environment.type_from_annotation(
+                annotation,
+                &mut expr_typer.vars,
+                true,
+            )?,
+            None => expr_typer.environment.new_unbound_var(),
+        };
+
+        let vars = expr_typer.vars;
+
+        let tipo = builtins::function(
+            arguments
+                .iter()
+                .map(|argument| argument.tipo.clone())
+                .collect(),
+            return_type.clone(),
+        );
+
+        environment.insert_module_value(
+            &function.name,
+            ValueConstructor {
+                public: function.public,
+                variant: ValueConstructorVariant::ModuleFn {
+                    name: function.name.clone(),
+                    module: environment.current_module.clone(),
+                    arity: function.arguments.len(),
+                    builtin: None,
+                    location: function.location,
+                },
+                tipo: tipo.clone(),
+            },
+        )?;
+
+        signatures.push((arguments, return_type, vars, tipo));
+    }
+
+    let mut typed = Vec::with_capacity(group.len());
+    let mut types: Vec<Rc<Type>> = Vec::with_capacity(group.len());
+
+    for (index, (arguments, return_type, vars, tipo)) in group.iter().zip(signatures) {
+        let Function {
+            location,
+            public,
+            name,
+            return_annotation,
+            body,
+            ..
+        } = functions
+            .remove(index)
+            .expect("functions are inferred once");
+
+        let body = ExprTyper::new(environment, vars).infer_fn_with_known_types(
+            &arguments,
+            body,
+            Some(return_type.clone()),
+        )?;
+
+        types.push(tipo);
+        typed.push(Function {
+            location,
+            public,
+            name,
+            arguments,
+            return_annotation,
+            return_type,
+            body,
+        });
+    }
+
+    types.iter().for_each(generalise);
+
+    Ok(typed)
+}
+
+/// Group functions referring to each other, ordering the groups so
+/// that every function comes after those it refers to.
+fn dependency_order(functions: &HashMap<usize, UntypedFunction>) -> Vec<Vec<usize>> {
+    let mut graph = DiGraph::<usize, ()>::new();
+    let mut nodes = HashMap::new();
+
+    let mut indices: Vec<&usize> = functions.keys().collect();
+    indices.sort();
+
+    for index in indices {
+        nodes.insert(
+            functions[index].name.as_str(),
+            (*index, graph.add_node(*index)),
+        );
+    }
+
+    for function in functions.values() {
+        let mut references = HashSet::new();
+        collect_references(&function.body, &mut references);
+
+        let (_, from) = nodes[function.name.as_str()];
+
+        for reference in references {
+            if let Some((_, to)) = nodes.get(reference) {
+                graph.add_edge(from, *to, ());
+            }
+        }
+    }
+
+    tarjan_scc(&graph)
+        .into_iter()
+        .map(|group| group.into_iter().map(|node| graph[node]).collect())
+        .collect()
+}
+
+/// Every name an expression refers to. Names shadowed by a local
+/// variable are included too, which may group some functions that
+/// aren't actually recursive but is otherwise harmless.
+fn collect_references<'a>(expr: &'a UntypedExpr, references: &mut HashSet<&'a str>) {
+    match expr {
+        UntypedExpr::Var { name, .. } => {
+            references.insert(name);
+        }
+        UntypedExpr::Int { .. }
+        | UntypedExpr::String { .. }
+        | UntypedExpr::ByteArray { .. }
+        | UntypedExpr::Todo { .. }
+        | UntypedExpr::ErrorTerm { .. } => (),
+        UntypedExpr::Fn { body, .. } => collect_references(body, references),
+        UntypedExpr::List { elements, tail, .. } => {
+            elements
+                .iter()
+                .chain(tail.as_deref())
+                .for_each(|expr| collect_references(expr, references));
+        }
+        UntypedExpr::Call { fun, arguments, .. } => {
+            collect_references(fun, references);
+            arguments
+                .iter()
+                .for_each(|expr| collect_references(expr, references));
+        }
+        UntypedExpr::BinOp { left, right, .. } => {
+            collect_references(left, references);
+            collect_references(right, references);
+        }
+        UntypedExpr::Assignment {
+            value,
+            kind: AssignmentKind::ExpectElse(default),
+            ..
+        } => {
+            collect_references(value, references);
+            collect_references(default, references);
+        }
+        UntypedExpr::UnOp { value, .. }
+        | UntypedExpr::Assignment { value, .. }
+        | UntypedExpr::FieldAccess {
+            container: value, ..
+        } => collect_references(value, references),
+        UntypedExpr::Sequence { expressions, .. } => expressions
+            .iter()
+            .for_each(|expr| collect_references(expr, references)),
+        UntypedExpr::When {
+            subject, clauses, ..
+        } => {
+            collect_references(subject, references);
+            clauses
+                .iter()
+                .for_each(|clause| collect_references(&clause.then, references));
+        }
+        UntypedExpr::If {
+            branches,
+            final_else,
+            ..
+        } => {
+            for branch in branches {
+                collect_references(&branch.condition, references);
+                collect_references(&branch.body, references);
+            }
+            collect_references(final_else, references);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{
+        ast::{Definition, ModuleKind, TypedModule},
+        builtins, parser,
+        tipo::{error::Error, TypeInfo},
+    };
+
+    use super::infer_module;
+
+    fn check_with(
+        name: &str,
+        src: &str,
+        importable_modules: &HashMap<String, TypeInfo>,
+    ) -> Result<TypedModule, Error> {
+        let module = parser::module(src, name, ModuleKind::Lib).unwrap();
+
+        infer_module(module, importable_modules)
+    }
+
+    fn check(src: &str) -> Result<TypedModule, Error> {
+        check_with("test", src, &HashMap::new())
+    }
+
+    fn fn_type(module: &TypedModule, name: &str) -> String {
+        module
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Fn(function) if function.name == name => Some(
+                    builtins::function(
+                        function
+                            .arguments
+                            .iter()
+                            .map(|argument| argument.tipo.clone())
+                            .collect(),
+                        function.return_type.clone(),
+                    )
+                    .to_string(),
+                ),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn generalisation() {
+        let module = check(
+            r#"
+            pub fn twice(f, x) {
+              f(f(x))
+            }
+
+            pub fn id(x) {
+              x
+            }
+
+            pub fn both() {
+              id(1) == twice(fn(n) { n + 1 }, 0) && id(True)
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(fn_type(&module, "twice"), "fn(fn(a) -> a, a) -> a");
+        assert_eq!(fn_type(&module, "id"), "fn(a) -> a");
+        assert_eq!(fn_type(&module, "both"), "fn() -> Bool");
+    }
+
+    #[test]
+    fn recursion_and_custom_types() {
+        let module = check(
+            r#"
+            pub type Option(a) {
+              Some(a)
+              None
+            }
+
+            pub type Pair(a, b) {
+              Pair { fst: a, snd: b }
+            }
+
+            pub fn find(xs, predicate) {
+              when xs is {
+                [] -> None
+                [x, ..rest] -> if predicate(x) { Some(x) } else { find(rest, predicate) }
+              }
+            }
+
+            pub fn swap(pair: Pair(a, b)) -> Pair(b, a) {
+              Pair(pair.snd, pair.fst)
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fn_type(&module, "find"),
+            "fn(List(a), fn(a) -> Bool) -> Option(a)"
+        );
+        assert_eq!(fn_type(&module, "swap"), "fn(Pair(a, b)) -> Pair(b, a)");
+        assert_eq!(
+            module.type_info.types_constructors["Option"],
+            vec!["Some", "None"]
+        );
+    }
+
+    #[test]
+    fn could_not_unify() {
+        let error = check(
+            r#"
+            fn add(n) {
+              n + "one"
+            }
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Type mismatch, expected `Int` but found `String`"
+        );
+        assert!(matches!(error, Error::CouldNotUnify { .. }));
+
+        let error = check("fn f(x) { x(x) }").unwrap_err();
+
+        assert!(matches!(error, Error::RecursiveType { .. }));
+    }
+
+    #[test]
+    fn not_exhaustive() {
+        let error = check(
+            r#"
+            type Option(a) {
+              Some(a)
+              None
+            }
+
+            fn unwrap(option) {
+              when option is {
+                Some(Some(x)) -> x
+                None -> 0
+              }
+            }
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::NotExhaustive {
+                location: error.location(),
+                missing: "Some(None)".to_string(),
+            }
+        );
+
+        let error = check(
+            r#"
+            fn second(xs) {
+              when xs is {
+                [] -> 0
+                [_] -> 1
+                [_, x] -> x
+              }
+            }
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "This `when` doesn't match every value, `[_, _, _, ..]` isn't covered"
+        );
+
+        let error = check("fn head(xs) { let [x, ..] = xs x }").unwrap_err();
+
+        assert!(matches!(error, Error::NotExhaustiveLet { missing, .. } if missing == "[]"));
+    }
+
+    #[test]
+    fn expect() {
+        let src = r#"
+            fn head(xs) {
+              expect [x, ..] = xs else 0
+              x
+            }
+
+            fn tail(xs) {
+              expect [_, ..rest] = xs or fail "no tail"
+              rest
+            }
+            "#;
+
+        let module = check(src).unwrap();
+
+        assert_eq!(fn_type(&module, "head"), "fn(List(Int)) -> Int");
+        assert_eq!(fn_type(&module, "tail"), "fn(List(a)) -> List(a)");
+
+        let error = check(r#"fn f(xs) { expect [x] = xs else "none" x + 1 }"#).unwrap_err();
+
+        assert!(matches!(error, Error::CouldNotUnify { .. }));
+
+        let error = check("fn f(xs) { expect [x] = xs else x x }").unwrap_err();
+
+        assert!(matches!(error, Error::UnknownVariable { name, .. } if name == "x"));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let importable_modules = HashMap::from([
+            (builtins::BUILTIN.to_string(), builtins::plutus()),
+            (builtins::MATH.to_string(), builtins::math()),
+        ]);
+
+        let check = |src| check_with("test", src, &importable_modules);
+
+        for src in [
+            "fn f(x) { x / 0 }",
+            "fn f(x) { x % -0 }",
+            "use aiken/math fn f(x) { math.quot(x, 0) }",
+            "use aiken/builtin.{remainder_integer} fn f(x) { remainder_integer(x, 0) }",
+        ] {
+            let error = check(src).unwrap_err();
+
+            assert!(
+                matches!(error, Error::DivisionByZero { location } if src[location.start..location.end].ends_with('0')),
+                "{src}"
+            );
+        }
+
+        assert!(check("use aiken/math fn f(x) { math.div(0, x) + x / 1 }").is_ok());
+    }
+
+    #[test]
+    fn data_patterns() {
+        let module = check(
+            r#"
+            fn size(d) {
+              when d is {
+                Constr(_, fields) -> 1
+                Map(_) -> 2
+                List(xs) -> 3
+                Int(i) -> i
+                Bytes(_) -> 5
+              }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(fn_type(&module, "size"), "fn(Data) -> Int");
+
+        let error = check(
+            r#"
+            fn tag(d: Data) {
+              when d is {
+                Constr(tag, _) -> tag
+                Int(i) -> i
+              }
+            }
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::NotExhaustive {
+                location: error.location(),
+                missing: "Map(_)".to_string(),
+            }
+        );
+
+        let error = check("fn f(d: Data) { when d is { Int(\"a\") -> 1 _ -> 0 } }").unwrap_err();
+
+        assert!(matches!(error, Error::CouldNotUnify { .. }));
+    }
+
+    #[test]
+    fn imports() {
+        let option = check_with(
+            "aiken/option",
+            r#"
+            pub type Option(a) {
+              Some(a)
+              None
+            }
+
+            pub fn map(option, f) {
+              when option is {
+                Some(a) -> Some(f(a))
+                None -> None
+              }
+            }
+
+            fn hidden() {
+              1
+            }
+
+            type Secret {
+              Secret
+            }
+            "#,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        let importable_modules = HashMap::from([("aiken/option".to_string(), option.type_info)]);
+
+        let module = check_with(
+            "test",
+            r#"
+            use aiken/option.{Option, Some}
+
+            pub fn increment(x: Option(Int)) {
+              option.map(x, fn(n) { n + 1 })
+            }
+
+            pub fn default(x) {
+              when x is {
+                Some(n) -> n
+                option.None -> 0
+              }
+            }
+            "#,
+            &importable_modules,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fn_type(&module, "increment"),
+            "fn(Option(Int)) -> Option(Int)"
+        );
+        assert_eq!(fn_type(&module, "default"), "fn(Option(Int)) -> Int");
+
+        let error = check_with(
+            "test",
+            "use aiken/option fn f() { option.hidden() }",
+            &importable_modules,
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, Error::UnknownModuleValue { name, .. } if name == "hidden"));
+
+        let error = check_with(
+            "test",
+            "use aiken/option.{Secret} fn f() { 1 }",
+            &importable_modules,
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, Error::UnknownModuleValue { name, .. } if name == "Secret"));
+
+        let error = check_with(
+            "test",
+            "use aiken/option fn f(x: option.Secret) { x }",
+            &importable_modules,
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, Error::UnknownModuleType { name, .. } if name == "Secret"));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{collections::HashSet, rc::Rc};
+
+use crate::{
+    ast::{Pattern, Span, TypedPattern, UntypedPattern},
+    builtins,
+};
+
+use super::{
+    environment::Environment, error::Error, DataConstructor, PatternConstructor, Type,
+    ValueConstructor, ValueConstructorVariant,
+};
+
+/// Types the patterns of a `let` or of a `when` clause, bringing
+/// the variables they bind in scope.
+pub struct PatternTyper<'a, 'b> {
+    environment: &'a mut Environment<'b>,
+    bound: HashSet<String>,
+}
+
+impl<'a, 'b> PatternTyper<'a, 'b> {
+    pub fn new(environment: &'a mut Environment<'b>) -> Self {
+        PatternTyper {
+            environment,
+            bound: HashSet::new(),
+        }
+    }
+
+    /// Check that `pattern` can match values of type `tipo`.
+    pub fn infer(
+        &mut self,
+        pattern: UntypedPattern,
+        tipo: Rc<Type>,
+    ) -> Result<TypedPattern, Error> {
+        match pattern {
+            Pattern::Int { location, value } => {
+                self.environment.unify(tipo, builtins::int(), location)?;
+
+                Ok(Pattern::Int { location, value })
+            }
+
+            Pattern::String { location, value } => {
+                self.environment.unify(tipo, builtins::string(), location)?;
+
+                Ok(Pattern::String { location, value })
+            }
+
+            Pattern::Var { location, name, .. } => {
+                if !self.bound.insert(name.clone()) {
+                    return Err(Error::DuplicateVarInPattern { location, name });
+                }
+
+                self.environment.insert_variable(
+                    name.clone(),
+                    ValueConstructorVariant::LocalVariable { location },
+                    tipo.clone(),
+                );
+
+                Ok(Pattern::Var {
+                    location,
+                    name,
+                    tipo,
+                })
+            }
+
+            Pattern::Discard { location, name } => Ok(Pattern::Discard { location, name }),
+
+            Pattern::Constructor {
+                location,
+                module,
+                name,
+                arguments,
+                ..
+            } => {
+                if let Some(constructor) = self.data_constructor(module.as_deref(), &name, &tipo) {
+                    return self.infer_data(location, name, arguments, constructor, tipo);
+                }
+
+                let constructor = self.lookup_constructor(module.as_deref(), &name, location)?;
+
+                let ValueConstructorVariant::Record {
+                    tag,
+                    arity,
+                    constructors_count,
+                    ..
+                } = constructor.variant
+                else {
+                    return Err(Error::UnknownConstructor { location, name });
+                };
+
+                if arguments.len() != arity {
+                    return Err(Error::IncorrectArity {
+                        location,
+                        expected: arity,
+                        given: arguments.len(),
+                    });
+                }
+
+                let instantiated = self
+                    .environment
+                    .instantiate(constructor.tipo, &mut Default::default());
+
+                let (fields, ret) = match instantiated.fn_types() {
+                    Some((fields, ret)) => (fields, ret),
+                    None => (vec![], instantiated),
+                };
+
+                self.environment.unify(tipo.clone(), ret, location)?;
+
+                let arguments = arguments
+                    .into_iter()
+                    .zip(fields)
+                    .map(|(argument, field)| self.infer(argument, field))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Pattern::Constructor {
+                    location,
+                    module,
+                    constructor: PatternConstructor::Record {
+                        name: name.clone(),
+                        tag,
+                        arity,
+                        constructors_count,
+                    },
+                    name,
+                    arguments,
+                    tipo,
+                })
+            }
+
+            Pattern::List {
+                location,
+                elements,
+                tail,
+                ..
+            } => {
+                let element = self.environment.new_unbound_var();
+
+                self.environment
+                    .unify(tipo.clone(), builtins::list(element.clone()), location)?;
+
+                let elements = elements
+                    .into_iter()
+                    .map(|pattern| self.infer(pattern, element.clone()))
+                    .collect::<Result<_, _>>()?;
+
+                let tail = tail
+                    .map(|tail| self.infer(*tail, tipo.clone()).map(Box::new))
+                    .transpose()?;
+
+                Ok(Pattern::List {
+                    location,
+                    elements,
+                    tail,
+                    tipo,
+                })
+            }
+        }
+    }
+
+    /// The `Data` constructor a pattern refers to: when matching a
+    /// `Data`, or when no other constructor has that name.
+    fn data_constructor(
+        &self,
+        module: Option<&str>,
+        name: &str,
+        tipo: &Rc<Type>,
+    ) -> Option<DataConstructor> {
+        if module.is_some() {
+            return None;
+        }
+
+        let constructor = DataConstructor::from_name(name)?;
+
+        (tipo.is_data() || !self.environment.scope.contains_key(name)).then_some(constructor)
+    }
+
+    fn infer_data(
+        &mut self,
+        location: Span,
+        name: String,
+        arguments: Vec<UntypedPattern>,
+        constructor: DataConstructor,
+        tipo: Rc<Type>,
+    ) -> Result<TypedPattern, Error> {
+        self.environment
+            .unify(tipo.clone(), builtins::data(), location)?;
+
+        let fields = constructor.fields();
+
+        if arguments.len() != fields.len() {
+            return Err(Error::IncorrectArity {
+                location,
+                expected: fields.len(),
+                given: arguments.len(),
+            });
+        }
+
+        let arguments = arguments
+            .into_iter()
+            .zip(fields)
+            .map(|(argument, field)| self.infer(argument, field))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Pattern::Constructor {
+            location,
+            module: None,
+            name,
+            arguments,
+            constructor: PatternConstructor::Data(constructor),
+            tipo,
+        })
+    }
+
+    fn lookup_constructor(
+        &self,
+        module: Option<&str>,
+        name: &str,
+        location: Span,
+    ) -> Result<ValueConstructor, Error> {
+        match module {
+            None => {
+                self.environment
+                    .scope
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownConstructor {
+                        location,
+                        name: name.to_string(),
+                    })
+            }
+            Some(module) => {
+                let (_, info) = self
+                    .environment
+                    .imported_modules
+                    .get(module)
+                    .ok_or_else(|| Error::UnknownModule {
+                        location,
+                        name: module.to_string(),
+                    })?;
+
+                info.public_value(name)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownModuleValue {
+                        location,
+                        module: module.to_string(),
+                        name: name.to_string(),
+                    })
+            }
+        }
+    }
+}
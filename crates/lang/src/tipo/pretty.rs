@@ -0,0 +1,56 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{Type, TypeVar};
+
+/// Prints types the way they are written in source, naming type
+/// variables `a`, `b`, ... in the order they are met. Printing
+/// several types with the same printer keeps names consistent
+/// between them.
+#[derive(Debug, Default)]
+pub struct Printer {
+    names: HashMap<u64, String>,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Printer::default()
+    }
+
+    pub fn print(&mut self, tipo: &Type) -> String {
+        match tipo {
+            Type::App { name, args, .. } if args.is_empty() => name.clone(),
+            Type::App { name, args, .. } => format!("{}({})", name, self.print_all(args)),
+            Type::Fn { args, ret } => {
+                format!("fn({}) -> {}", self.print_all(args), self.print(ret))
+            }
+            Type::Var { tipo } => match &*tipo.borrow() {
+                TypeVar::Link { tipo } => self.print(tipo),
+                TypeVar::Unbound { id } | TypeVar::Generic { id } => self.var_name(*id),
+            },
+        }
+    }
+
+    fn print_all(&mut self, types: &[Rc<Type>]) -> String {
+        types
+            .iter()
+            .map(|tipo| self.print(tipo))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn var_name(&mut self, id: u64) -> String {
+        let next = self.names.len();
+
+        self.names
+            .entry(id)
+            .or_insert_with(|| {
+                let letter = (b'a' + (next % 26) as u8) as char;
+
+                match next / 26 {
+                    0 => letter.to_string(),
+                    n => format!("{}{}", letter, n),
+                }
+            })
+            .clone()
+    }
+}
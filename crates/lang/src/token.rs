@@ -0,0 +1,112 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Token {
+    Int(String),
+    String(String),
+    ByteArray(Vec<u8>),
+    Name(String),
+    UpName(String),
+    DiscardName(String),
+
+    // Keywords
+    As,
+    Else,
+    Error,
+    Expect,
+    Fn,
+    If,
+    Is,
+    Let,
+    Pub,
+    Todo,
+    Type,
+    Use,
+    When,
+
+    // Groupings
+    LeftParen,
+    RightParen,
+    LeftSquare,
+    RightSquare,
+    LeftBrace,
+    RightBrace,
+
+    // Int operators
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+
+    // Other punctuation
+    Colon,
+    Comma,
+    Dot,
+    DotDot,
+    Equal,
+    EqualEqual,
+    NotEqual,
+    Bang,
+    AmperAmper,
+    VbarVbar,
+    Pipe,
+    RArrow,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Token::Int(value) | Token::Name(value) | Token::UpName(value) => value,
+            Token::DiscardName(name) => return write!(f, "_{}", name),
+            Token::String(value) => return write!(f, "{:?}", value),
+            Token::ByteArray(_) => "a bytearray",
+            Token::As => "as",
+            Token::Else => "else",
+            Token::Error => "error",
+            Token::Expect => "expect",
+            Token::Fn => "fn",
+            Token::If => "if",
+            Token::Is => "is",
+            Token::Let => "let",
+            Token::Pub => "pub",
+            Token::Todo => "todo",
+            Token::Type => "type",
+            Token::Use => "use",
+            Token::When => "when",
+            Token::LeftParen => "(",
+            Token::RightParen => ")",
+            Token::LeftSquare => "[",
+            Token::RightSquare => "]",
+            Token::LeftBrace => "{",
+            Token::RightBrace => "}",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Star => "*",
+            Token::Slash => "/",
+            Token::Percent => "%",
+            Token::Less => "<",
+            Token::Greater => ">",
+            Token::LessEqual => "<=",
+            Token::GreaterEqual => ">=",
+            Token::Colon => ":",
+            Token::Comma => ",",
+            Token::Dot => ".",
+            Token::DotDot => "..",
+            Token::Equal => "=",
+            Token::EqualEqual => "==",
+            Token::NotEqual => "!=",
+            Token::Bang => "!",
+            Token::AmperAmper => "&&",
+            Token::VbarVbar => "||",
+            Token::Pipe => "|>",
+            Token::RArrow => "->",
+        };
+
+        write!(f, "{}", s)
+    }
+}
@@ -0,0 +1,755 @@
+//! An open file and what the server can tell about it. Positions are
+//! converted between byte offsets and the lines and UTF-16 columns
+//! editors count in.
+
+use std::{collections::HashMap, ops, path::Path, slice};
+
+use aiken_lang::{
+    ast::{AssignmentKind, Definition, ModuleKind},
+    builtins,
+    expr::TypedExpr,
+    parser::ParseError,
+    tipo::{self, ValueConstructorVariant},
+};
+use miette::SourceSpan;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, Hover, HoverContents, MarkupContent, MarkupKind, Position,
+    Range, TextEdit,
+};
+use uplc::{
+    ast::{Name, Term},
+    builtins::DefaultFunction,
+    check::Problem,
+    machine::cost_model::CostModel,
+    parser::{self, Error, Spans},
+};
+
+type Span = ops::Range<usize>;
+
+/// The name Aiken documents are checked under, as the modules they
+/// import from the project aren't known.
+const MODULE: &str = "document";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Symbol {
+    /// A lambda parameter, with the enclosing one of the same name
+    /// it shadows.
+    Binder { span: Span, shadows: Option<Span> },
+    /// A variable, with the parameter it refers to unless it's free.
+    Var { span: Span, binder: Option<Span> },
+    Builtin {
+        span: Span,
+        builtin: DefaultFunction,
+    },
+    /// A value used in an Aiken expression, with where it's defined
+    /// when that's in the same module.
+    Value {
+        span: Span,
+        name: String,
+        tipo: String,
+        definition: Option<Span>,
+        builtin: Option<DefaultFunction>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Uplc,
+    Aiken,
+}
+
+impl Language {
+    /// The language of a file, by it's extension.
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "uplc" => Some(Language::Uplc),
+            "ak" => Some(Language::Aiken),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub language: Language,
+    pub text: String,
+}
+
+impl Document {
+    pub fn new(language: Language, text: String) -> Self {
+        Document { language, text }
+    }
+
+    /// Parse errors, and for Untyped Plutus Core the scoping problems
+    /// `Program::check` finds.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self.language {
+            Language::Uplc => match parser::program(&self.text) {
+                Ok(program) => self.problems(&program.check()),
+                Err(error) => vec![self.parse_error(&error)],
+            },
+            // modules are only parsed, type checking needs the project
+            Language::Aiken => {
+                match aiken_lang::parser::module(&self.text, MODULE, ModuleKind::Lib) {
+                    Ok(_) => vec![],
+                    Err(errors) => errors
+                        .iter()
+                        .map(|error| self.aiken_parse_error(error))
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    /// The signature, semantics and cost of the builtin at
+    /// `position`, or the type of the Aiken value there.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let offset = self.offset(position);
+
+        let (span, value) = self.symbols().into_iter().find_map(|symbol| match symbol {
+            Symbol::Builtin { span, builtin } if contains(&span, offset) => {
+                Some((span, describe(builtin)))
+            }
+            Symbol::Value {
+                span,
+                name,
+                tipo,
+                builtin,
+                ..
+            } if contains(&span, offset) => {
+                let mut value = format!("```aiken\n{name}: {tipo}\n```");
+
+                if let Some(builtin) = builtin {
+                    value = format!("{value}\n{}", builtin.description());
+                }
+
+                Some((span, value))
+            }
+            _ => None,
+        })?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(self.range(&span)),
+        })
+    }
+
+    /// Where the parameter the variable at `position` refers to is,
+    /// or the Aiken variable, function or constructor.
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let offset = self.offset(position);
+
+        self.symbols().into_iter().find_map(|symbol| match symbol {
+            Symbol::Var {
+                span,
+                binder: Some(definition),
+            }
+            | Symbol::Value {
+                span,
+                definition: Some(definition),
+                ..
+            } if contains(&span, offset) => Some(self.range(&definition)),
+            _ => None,
+        })
+    }
+
+    /// Replace the whole document with it's pretty printed program,
+    /// unless it doesn't parse.
+    pub fn format(&self) -> Option<Vec<TextEdit>> {
+        if self.language != Language::Uplc {
+            return None;
+        }
+
+        let program = parser::program(&self.text).ok()?;
+
+        let formatted = format!("{}\n", program.to_pretty());
+
+        if formatted == self.text {
+            return Some(vec![]);
+        }
+
+        Some(vec![TextEdit {
+            range: self.range(&(0..self.text.len())),
+            new_text: formatted,
+        }])
+    }
+
+    /// The names and builtins of the program, in the order they
+    /// appear. There are none while it doesn't parse, or for Aiken
+    /// while it doesn't type check on it's own, e.g. because it
+    /// imports other modules of the project.
+    fn symbols(&self) -> Vec<Symbol> {
+        match self.language {
+            Language::Uplc => match parser::program_with_spans(&self.text) {
+                Ok((program, spans)) => Symbols::collect(&program.term, &spans),
+                Err(_) => vec![],
+            },
+            Language::Aiken => self.values().unwrap_or_default(),
+        }
+    }
+
+    /// The values used in the functions of an Aiken module.
+    fn values(&self) -> Option<Vec<Symbol>> {
+        let module = aiken_lang::parser::module(&self.text, MODULE, ModuleKind::Lib).ok()?;
+
+        let importable_modules = HashMap::from([
+            (builtins::BUILTIN.to_string(), builtins::plutus()),
+            (builtins::MATH.to_string(), builtins::math()),
+        ]);
+
+        let module = tipo::infer_module(module, &importable_modules).ok()?;
+
+        let mut symbols = vec![];
+
+        for definition in &module.definitions {
+            if let Definition::Fn(function) = definition {
+                values(&function.body, &mut symbols);
+            }
+        }
+
+        Some(symbols)
+    }
+
+    /// Point each problem at the variable or parameter it's about.
+    /// Both come in the order they appear in the source.
+    fn problems(&self, problems: &[Problem]) -> Vec<Diagnostic> {
+        let symbols = self.symbols();
+
+        let mut free = symbols.iter().filter_map(|symbol| match symbol {
+            Symbol::Var { span, binder: None } => Some(span),
+            _ => None,
+        });
+
+        let mut shadowing = symbols.iter().filter_map(|symbol| match symbol {
+            Symbol::Binder {
+                span,
+                shadows: Some(_),
+            } => Some(span),
+            _ => None,
+        });
+
+        problems
+            .iter()
+            .map(|problem| {
+                let span = match problem {
+                    Problem::FreeUnique(text, _) => {
+                        free.find(|span| self.text[(*span).clone()] == **text)
+                    }
+                    Problem::Shadowing(text) => {
+                        shadowing.find(|span| self.text[(*span).clone()] == **text)
+                    }
+                    Problem::FreeIndex { .. } => None,
+                };
+
+                Diagnostic {
+                    range: self.range(span.unwrap_or(&(0..0))),
+                    severity: Some(if problem.is_warning() {
+                        DiagnosticSeverity::WARNING
+                    } else {
+                        DiagnosticSeverity::ERROR
+                    }),
+                    source: Some("uplc".to_string()),
+                    message: problem.to_string(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn parse_error(&self, error: &Error) -> Diagnostic {
+        let span = error.span.offset()..error.span.offset() + error.span.len();
+
+        Diagnostic {
+            range: self.range(&span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("uplc".to_string()),
+            message: format!(
+                "Unexpected {}, expected {}",
+                error.found,
+                error.expected.join(" or ")
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn aiken_parse_error(&self, error: &ParseError) -> Diagnostic {
+        let mut message = error.to_string();
+
+        if !error.expected.is_empty() {
+            let expected: Vec<&str> = error.expected.iter().map(String::as_str).collect();
+
+            message = format!("{message}, expected {}", expected.join(" or "));
+        }
+
+        Diagnostic {
+            range: self.range(&(error.span.start..error.span.end)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("aiken".to_string()),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// The byte offset of a position, clamped to the end of it's line.
+    pub fn offset(&self, position: Position) -> usize {
+        let line_start = match position.line {
+            0 => 0,
+            line => match self.text.match_indices('\n').nth(line as usize - 1) {
+                Some((newline, _)) => newline + 1,
+                None => return self.text.len(),
+            },
+        };
+
+        let mut column = 0;
+
+        for (offset, c) in self.text[line_start..].char_indices() {
+            if c == '\n' || column >= position.character as usize {
+                return line_start + offset;
+            }
+
+            column += c.len_utf16();
+        }
+
+        self.text.len()
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let before = &self.text[..offset];
+
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        Position {
+            line: before.matches('\n').count() as u32,
+            character: before[line_start..].encode_utf16().count() as u32,
+        }
+    }
+
+    fn range(&self, span: &Span) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
+
+/// Pairs the parts of a term with the spans the parser recorded for
+/// them. Both are in the order they appear in the source, which is
+/// the order a pre-order walk reaches them.
+struct Symbols<'a> {
+    binders: slice::Iter<'a, SourceSpan>,
+    vars: slice::Iter<'a, SourceSpan>,
+    builtins: slice::Iter<'a, SourceSpan>,
+    /// The parameters in scope, innermost last.
+    scope: Vec<(&'a str, Span)>,
+    symbols: Vec<Symbol>,
+}
+
+impl<'a> Symbols<'a> {
+    fn collect(term: &'a Term<Name>, spans: &'a Spans) -> Vec<Symbol> {
+        let mut symbols = Symbols {
+            binders: spans.binders.iter(),
+            vars: spans.vars.iter(),
+            builtins: spans.builtins.iter(),
+            scope: Vec::new(),
+            symbols: Vec::new(),
+        };
+
+        symbols.term(term);
+
+        symbols.symbols
+    }
+
+    fn term(&mut self, term: &'a Term<Name>) {
+        match term {
+            Term::Var(name) => {
+                if let Some(span) = self.vars.next() {
+                    self.symbols.push(Symbol::Var {
+                        span: range(span),
+                        binder: self.lookup(&name.text),
+                    });
+                }
+            }
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => {
+                let Some(span) = self.binders.next() else {
+                    return;
+                };
+
+                self.symbols.push(Symbol::Binder {
+                    span: range(span),
+                    shadows: self.lookup(&parameter_name.text),
+                });
+
+                self.scope.push((&parameter_name.text, range(span)));
+                self.term(body);
+                self.scope.pop();
+            }
+            Term::Apply { function, argument } => {
+                self.term(function);
+                self.term(argument);
+            }
+            Term::Delay(term) | Term::Force(term) => self.term(term),
+            Term::Builtin(builtin) => {
+                if let Some(span) = self.builtins.next() {
+                    self.symbols.push(Symbol::Builtin {
+                        span: range(span),
+                        builtin: *builtin,
+                    });
+                }
+            }
+            Term::Constant(_) | Term::Error => (),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Span> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(bound, _)| *bound == name)
+            .map(|(_, span)| span.clone())
+    }
+}
+
+/// Collect the variables, functions and constructors an Aiken
+/// expression uses.
+fn values(expr: &TypedExpr, symbols: &mut Vec<Symbol>) {
+    match expr {
+        TypedExpr::Var {
+            location,
+            constructor,
+            name,
+        } => {
+            let (definition, builtin) = match &constructor.variant {
+                ValueConstructorVariant::LocalVariable { location } => (Some(*location), None),
+                ValueConstructorVariant::ModuleFn {
+                    module,
+                    location,
+                    builtin,
+                    ..
+                } => ((module == MODULE).then_some(*location), *builtin),
+                ValueConstructorVariant::Record {
+                    module, location, ..
+                } => ((module == MODULE).then_some(*location), None),
+            };
+
+            symbols.push(Symbol::Value {
+                span: location.start..location.end,
+                name: name.clone(),
+                tipo: constructor.tipo.to_string(),
+                definition: definition.map(|location| location.start..location.end),
+                builtin,
+            });
+        }
+        TypedExpr::ModuleSelect {
+            location,
+            tipo,
+            label,
+            module_alias,
+            constructor,
+            ..
+        } => symbols.push(Symbol::Value {
+            span: location.start..location.end,
+            name: format!("{module_alias}.{label}"),
+            tipo: tipo.to_string(),
+            definition: None,
+            builtin: match constructor {
+                ValueConstructorVariant::ModuleFn { builtin, .. } => *builtin,
+                _ => None,
+            },
+        }),
+
+        TypedExpr::Fn { body, .. } => values(body, symbols),
+        TypedExpr::UnOp { value, .. } => values(value, symbols),
+        TypedExpr::RecordAccess { record, .. } => values(record, symbols),
+        TypedExpr::BinOp { left, right, .. } => {
+            values(left, symbols);
+            values(right, symbols);
+        }
+        TypedExpr::List { elements, tail, .. } => {
+            for element in elements.iter().chain(tail.as_deref()) {
+                values(element, symbols);
+            }
+        }
+        TypedExpr::Call { fun, arguments, .. } => {
+            for expr in std::iter::once(fun.as_ref()).chain(arguments) {
+                values(expr, symbols);
+            }
+        }
+        TypedExpr::Sequence { expressions, .. } => {
+            for expr in expressions {
+                values(expr, symbols);
+            }
+        }
+        TypedExpr::Assignment { kind, value, .. } => {
+            values(value, symbols);
+
+            if let AssignmentKind::ExpectElse(default) = kind {
+                values(default, symbols);
+            }
+        }
+        TypedExpr::When {
+            subject, clauses, ..
+        } => {
+            values(subject, symbols);
+
+            for clause in clauses {
+                values(&clause.then, symbols);
+            }
+        }
+        TypedExpr::If {
+            branches,
+            final_else,
+            ..
+        } => {
+            for branch in branches {
+                values(&branch.condition, symbols);
+                values(&branch.body, symbols);
+            }
+
+            values(final_else, symbols);
+        }
+
+        TypedExpr::Int { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::ByteArray { .. }
+        | TypedExpr::Todo { .. }
+        | TypedExpr::ErrorTerm { .. } => (),
+    }
+}
+
+fn range(span: &SourceSpan) -> Span {
+    span.offset()..span.offset() + span.len()
+}
+
+/// Include the end, so hovering just past a name still finds it.
+fn contains(span: &Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+fn describe(builtin: DefaultFunction) -> String {
+    let signature = builtin.signature();
+
+    let cost = CostModel::default().builtin_costs.costing(builtin).class();
+
+    format!(
+        "```uplc\n(builtin {builtin})\n```\n{}\n\n\
+        **Type** `{signature}`\n\n\
+        **Arity** {}, forced {} times, since Plutus {:?}\n\n\
+        **Cost** cpu {}, memory {}",
+        builtin.description(),
+        signature.arity(),
+        signature.force_count,
+        signature.since,
+        cost.cpu,
+        cost.mem,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use tower_lsp::lsp_types::{DiagnosticSeverity, HoverContents, Position, Range};
+
+    use uplc::builtins::DefaultFunction;
+
+    use super::{Document, Language, Symbol};
+
+    fn uplc(text: &str) -> Document {
+        Document::new(Language::Uplc, text.to_string())
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+        Range {
+            start: Position::new(start.0, start.1),
+            end: Position::new(end.0, end.1),
+        }
+    }
+
+    #[test]
+    fn positions() {
+        let document = uplc("(con string \"é😀\")\nx");
+
+        // the emoji is two UTF-16 code units and four bytes
+        assert_eq!(document.offset(Position::new(0, 16)), 19);
+        assert_eq!(document.position(19), Position::new(0, 16));
+        assert_eq!(document.offset(Position::new(0, 99)), 21);
+        assert_eq!(document.offset(Position::new(1, 0)), 22);
+        assert_eq!(document.offset(Position::new(5, 0)), 23);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let document = uplc("(program 1.0.0\n  (lam x [x (lam x y)]))");
+
+        let diagnostics = document.diagnostics();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].range, range((1, 17), (1, 18)));
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[1].range, range((1, 19), (1, 20)));
+        assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::ERROR));
+
+        let diagnostics = uplc("(program 1.0.0 (lam x x)").diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, range((0, 24), (0, 24)));
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Unexpected end of input"));
+
+        let aiken = |text: &str| Document::new(Language::Aiken, text.to_string());
+
+        assert!(aiken("pub fn id(a) {\n  a\n}\n").diagnostics().is_empty());
+        assert_eq!(aiken("pub fn id(a) {\n  a\n").diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn hover_and_definition() {
+        let document = uplc("(program 1.0.0 (lam x [(builtin addInteger) x x]))");
+
+        let hover = document.hover(Position::new(0, 35)).unwrap();
+
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown")
+        };
+
+        assert!(markup.value.contains("Add two integers."));
+        assert!(markup.value.contains("`integer -> integer -> integer`"));
+        assert!(markup.value.contains("Arity** 2"));
+        assert!(markup
+            .value
+            .contains("cpu linear in the larger size, memory linear in the larger size"));
+        assert_eq!(hover.range, Some(range((0, 32), (0, 42))));
+
+        assert_eq!(document.hover(Position::new(0, 20)), None);
+
+        assert_eq!(
+            document.definition(Position::new(0, 46)),
+            Some(range((0, 20), (0, 21)))
+        );
+        assert_eq!(document.definition(Position::new(0, 40)), None);
+    }
+
+    #[test]
+    fn aiken_hover_and_definition() {
+        let src = r#"use aiken/builtin
+
+type Datum {
+  Datum { amount: Int }
+}
+
+fn double(n: Int) -> Int {
+  n * 2
+}
+
+pub fn main() {
+  let datum = Datum(21)
+  builtin.add_integer(double(datum.amount), 0)
+}
+"#;
+
+        let document = Document::new(Language::Aiken, src.to_string());
+
+        let at = |text: &str, nth: usize| {
+            let offset = src.match_indices(text).nth(nth).unwrap().0;
+
+            document.position(offset)
+        };
+
+        let hover = |position| match document.hover(position).unwrap().contents {
+            HoverContents::Markup(markup) => markup.value,
+            _ => panic!("expected markdown"),
+        };
+
+        assert!(hover(at("double(datum", 0)).contains("double: fn(Int) -> Int"));
+        assert!(hover(at("datum.amount", 0)).contains("datum: Datum"));
+        assert!(hover(at("builtin.add_integer", 0)).contains("Add two integers."));
+
+        // the local variable, the function and the constructor
+        assert_eq!(
+            document.definition(at("n * 2", 0)).unwrap().start,
+            at("n: Int", 0)
+        );
+        assert_eq!(
+            document.definition(at("double(datum", 0)).unwrap().start,
+            at("fn double", 0)
+        );
+        assert_eq!(
+            document.definition(at("Datum(21)", 0)).unwrap().start,
+            at("Datum { amount", 0)
+        );
+        assert_eq!(document.definition(at("builtin.add_integer", 0)), None);
+
+        // other modules of the project aren't known
+        let importing = Document::new(
+            Language::Aiken,
+            "use project/other\npub fn main() { other.f() }".to_string(),
+        );
+
+        assert_eq!(importing.hover(Position::new(1, 17)), None);
+    }
+
+    #[test]
+    fn symbols() {
+        let src = r#"(program 1.0.0
+  (lam x [(lam x x) (builtin addInteger) (con (list string) ["x y"]) y x]))"#;
+
+        let at = |text: &str, nth: usize| {
+            let start = src.match_indices(text).nth(nth).unwrap().0;
+
+            start..start + text.len()
+        };
+
+        assert_eq!(
+            uplc(src).symbols(),
+            vec![
+                Symbol::Binder {
+                    span: at("x", 0),
+                    shadows: None
+                },
+                Symbol::Binder {
+                    span: at("x", 1),
+                    shadows: Some(at("x", 0))
+                },
+                Symbol::Var {
+                    span: at("x", 2),
+                    binder: Some(at("x", 1))
+                },
+                Symbol::Builtin {
+                    span: at("addInteger", 0),
+                    builtin: DefaultFunction::AddInteger
+                },
+                Symbol::Var {
+                    span: at("y", 1),
+                    binder: None
+                },
+                Symbol::Var {
+                    span: at("x", 4),
+                    binder: Some(at("x", 0))
+                },
+            ]
+        );
+
+        assert_eq!(uplc("(program 1.0.0 (lam f [f (builtin ").symbols(), vec![]);
+    }
+
+    #[test]
+    fn format() {
+        let edits = uplc("(program 1.0.0 [(lam x x)\n(con integer 1)])")
+            .format()
+            .unwrap();
+
+        assert_eq!(
+            edits[0].new_text,
+            "(program 1.0.0 [ (lam x x) (con integer 1) ])\n"
+        );
+        assert_eq!(edits[0].range, range((0, 0), (1, 17)));
+
+        assert_eq!(uplc(&edits[0].new_text).format(), Some(vec![]));
+        assert_eq!(uplc("(program").format(), None);
+    }
+}
@@ -0,0 +1,32 @@
+//! A language server for Untyped Plutus Core and Aiken, speaking the
+//! Language Server Protocol over stdin and stdout.
+//!
+//! `.uplc` files get diagnostics from the parser and scope checker,
+//! hovers on builtins, go to definition for variables and formatting.
+//! `.ak` files get parse errors, and hovers with the type of values
+//! and go to definition for variables, functions and constructors
+//! of the same module. Modules importing others of their project
+//! can't be type checked on their own, they only get parse errors.
+
+use tower_lsp::{LspService, Server};
+
+mod document;
+mod server;
+
+/// Serve the editor on the other end of stdin and stdout until it
+/// disconnects.
+pub fn start() -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let (service, socket) = LspService::new(server::Server::new);
+
+        Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+            .serve(service)
+            .await;
+    });
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+        DocumentFormattingParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams,
+        HoverProviderCapability, InitializeParams, InitializeResult, Location, OneOf,
+        ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit,
+        Url,
+    },
+    Client, LanguageServer,
+};
+
+use crate::document::{Document, Language};
+
+pub struct Server {
+    client: Client,
+    /// The open documents in a language the server knows.
+    documents: Mutex<HashMap<Url, Document>>,
+}
+
+impl Server {
+    pub fn new(client: Client) -> Self {
+        Server {
+            client,
+            documents: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn update(&self, uri: Url, text: String) {
+        let Some(language) = uri.to_file_path().ok().and_then(|path| Language::of(&path)) else {
+            return;
+        };
+
+        let document = Document::new(language, text);
+
+        let diagnostics = document.diagnostics();
+
+        self.documents.lock().unwrap().insert(uri.clone(), document);
+
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    fn with_document<T>(&self, uri: &Url, f: impl FnOnce(&Document) -> Option<T>) -> Option<T> {
+        self.documents.lock().unwrap().get(uri).and_then(f)
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Server {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "aiken".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.update(params.text_document.uri, params.text_document.text)
+            .await;
+    }
+
+    async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
+        // with full syncing the last change is the whole document
+        if let Some(change) = params.content_changes.pop() {
+            self.update(params.text_document.uri, change.text).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+
+        self.documents.lock().unwrap().remove(&uri);
+
+        self.client.publish_diagnostics(uri, vec![], None).await;
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let params = params.text_document_position_params;
+
+        Ok(self.with_document(&params.text_document.uri, |document| {
+            document.hover(params.position)
+        }))
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let params = params.text_document_position_params;
+        let uri = params.text_document.uri;
+
+        Ok(self
+            .with_document(&uri, |document| document.definition(params.position))
+            .map(|range| GotoDefinitionResponse::Scalar(Location { uri, range })))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        Ok(self.with_document(&params.text_document.uri, Document::format))
+    }
+}
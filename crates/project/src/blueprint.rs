@@ -0,0 +1,530 @@
+//! Plutus blueprints (CIP-57), describing a project's validators
+//! for off-chain code: their compiled code, hash and the schemas
+//! of their arguments.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    rc::Rc,
+};
+
+use aiken_lang::{
+    ast::{TypedArg, TypedFunction},
+    builtins,
+    tipo::{Type, TypeInfo},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, error::Error, module::CheckedModule, Artifact, Options};
+
+pub mod schema;
+
+use schema::{Definitions, Schema};
+
+/// The file blueprints are written to, at the root of the project.
+pub const FILE_NAME: &str = "plutus.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Blueprint {
+    pub preamble: Preamble,
+    pub validators: Vec<Validator>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub definitions: BTreeMap<String, Schema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preamble {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub version: String,
+    pub plutus_version: String,
+    pub compiler: Compiler,
+    pub options: Options,
+    /// See `Project::source_hash`.
+    pub source_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compiler {
+    pub name: String,
+    pub version: String,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Compiler {
+            name: "aiken".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A blueprint read back from a built project, with what
+/// `Project::verify` and the blueprint commands need.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Recorded {
+    pub preamble: Preamble,
+    pub validators: Vec<RecordedValidator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedValidator {
+    pub title: String,
+    pub compiled_code: String,
+    pub hash: String,
+}
+
+impl Recorded {
+    /// Read the blueprint at the root of a project.
+    pub fn read(root: &Path) -> Result<Recorded, Error> {
+        let path = root.join(FILE_NAME);
+
+        let json = fs::read_to_string(&path).map_err(|error| Error::FileIo {
+            path: path.clone(),
+            error,
+        })?;
+
+        serde_json::from_str(&json).map_err(|error| Error::Blueprint { path, error })
+    }
+
+    /// The validator called `name`, as `module.function`, or only by
+    /// it's module or function when that's enough to tell it apart. A
+    /// project with a single validator doesn't need to name it.
+    pub fn select(&self, name: Option<&str>) -> Result<&RecordedValidator, Error> {
+        let titles = |validators: Vec<&RecordedValidator>| {
+            validators
+                .iter()
+                .map(|validator| validator.title.clone())
+                .collect()
+        };
+
+        let Some(name) = name else {
+            return match self.validators.as_slice() {
+                [validator] => Ok(validator),
+                validators => Err(Error::AmbiguousValidator {
+                    name: None,
+                    candidates: titles(validators.iter().collect()),
+                }),
+            };
+        };
+
+        if let Some(validator) = self.validators.iter().find(|v| v.title == name) {
+            return Ok(validator);
+        }
+
+        let matching: Vec<_> = self
+            .validators
+            .iter()
+            .filter(|validator| {
+                let (module, function) = validator
+                    .title
+                    .rsplit_once('.')
+                    .unwrap_or(("", &validator.title));
+
+                module == name || function == name
+            })
+            .collect();
+
+        match matching.as_slice() {
+            [validator] => Ok(validator),
+            [] => Err(Error::UnknownValidator {
+                name: name.to_string(),
+                suggestions: self.suggestions(name),
+            }),
+            _ => Err(Error::AmbiguousValidator {
+                name: Some(name.to_string()),
+                candidates: titles(matching),
+            }),
+        }
+    }
+
+    /// The titles of up to three validators that look like `name`,
+    /// closest first.
+    fn suggestions(&self, name: &str) -> Vec<String> {
+        let mut scored: Vec<(f64, &str)> = self
+            .validators
+            .iter()
+            .map(|validator| {
+                let title = validator.title.as_str();
+                let function = title
+                    .rsplit_once('.')
+                    .map_or(title, |(_, function)| function);
+
+                let score = strsim::normalized_levenshtein(name, title)
+                    .max(strsim::normalized_levenshtein(name, function));
+
+                (score, title)
+            })
+            .filter(|(score, _)| *score >= 0.5)
+            .collect();
+
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        scored
+            .into_iter()
+            .take(3)
+            .map(|(_, title)| title.to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validator {
+    /// `module.function`
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datum: Option<Argument>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redeemer: Option<Argument>,
+    /// Arguments the validator must be applied to before use.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Argument>,
+    /// Hex encoded, the CBOR bytes of the script wrapped in CBOR
+    /// again as off-chain libraries expect.
+    pub compiled_code: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Argument {
+    pub title: String,
+    pub schema: Schema,
+}
+
+/// A validator with the definitions it's schemas refer to, which
+/// is what the cache keeps for each validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub validator: Validator,
+    pub definitions: BTreeMap<String, Schema>,
+}
+
+impl Entry {
+    /// `types` are the type infos of the project's modules and of
+    /// the prelude, see `type_infos`.
+    pub fn new(
+        artifact: &Artifact,
+        function: &TypedFunction,
+        types: &HashMap<String, &TypeInfo>,
+    ) -> Result<Entry, Error> {
+        let mut definitions = Definitions::new(types);
+
+        let validator = Validator::new(artifact, function, &mut definitions)?;
+
+        Ok(Entry {
+            validator,
+            definitions: definitions.schemas,
+        })
+    }
+}
+
+/// The type infos schemas are looked up in, by module.
+pub fn type_infos<'a>(
+    modules: &'a [CheckedModule],
+    prelude: &'a TypeInfo,
+) -> HashMap<String, &'a TypeInfo> {
+    let mut infos: HashMap<String, &TypeInfo> = modules
+        .iter()
+        .map(|module| (module.ast.name.clone(), &module.ast.type_info))
+        .collect();
+
+    infos.insert(builtins::PRELUDE.to_string(), prelude);
+
+    infos
+}
+
+impl Blueprint {
+    pub fn new(
+        config: &Config,
+        options: Options,
+        source_hash: String,
+        entries: Vec<Entry>,
+    ) -> Blueprint {
+        let mut definitions = BTreeMap::new();
+
+        let validators = entries
+            .into_iter()
+            .map(|entry| {
+                definitions.extend(entry.definitions);
+
+                entry.validator
+            })
+            .collect();
+
+        Blueprint {
+            preamble: Preamble {
+                title: config.name.clone(),
+                description: config.description.clone(),
+                version: config.version.clone(),
+                plutus_version: format!("{:?}", config.plutus_version).to_lowercase(),
+                compiler: Compiler::default(),
+                options,
+                source_hash,
+            },
+            validators,
+            definitions,
+        }
+    }
+}
+
+impl Validator {
+    /// Spending validators take a datum, a redeemer and the script
+    /// context, others only a redeemer and the context. The
+    /// arguments before those are parameters.
+    fn new(
+        artifact: &Artifact,
+        function: &TypedFunction,
+        definitions: &mut Definitions,
+    ) -> Result<Validator, Error> {
+        let title = format!("{}.{}", artifact.module, artifact.name);
+
+        let mut argument = |argument: &TypedArg| {
+            Ok(Argument {
+                title: argument.name.name().trim_start_matches('_').to_string(),
+                schema: schema(definitions, &title, &argument.tipo)?,
+            })
+        };
+
+        let mut arguments: Vec<_> = function.arguments.iter().collect();
+
+        // the script context, described by the ledger
+        arguments.pop();
+
+        let redeemer = arguments.pop().map(&mut argument).transpose()?;
+
+        let datum = if function.name == "spend" {
+            arguments.pop().map(&mut argument).transpose()?
+        } else {
+            None
+        };
+
+        let parameters = arguments
+            .into_iter()
+            .map(argument)
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Validator {
+            title,
+            datum,
+            redeemer,
+            parameters,
+            compiled_code: hex::encode(cbor_bytes(&artifact.cbor)),
+            hash: hex::encode(artifact.hash),
+        })
+    }
+}
+
+fn schema(
+    definitions: &mut Definitions,
+    validator: &str,
+    tipo: &Rc<Type>,
+) -> Result<Schema, Error> {
+    definitions
+        .schema(tipo)
+        .map_err(|_| Error::UnsupportedType {
+            validator: validator.to_string(),
+            tipo: tipo.to_string(),
+        })
+}
+
+/// `bytes` as a CBOR bytestring.
+fn cbor_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = minicbor::Encoder::new(Vec::new());
+
+    encoder.bytes(bytes).expect("writing to a vec can't fail");
+
+    encoder.into_writer()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+
+    use aiken_lang::{
+        ast::{Definition, ModuleKind},
+        builtins, parser, tipo,
+    };
+    use serde_json::json;
+
+    use super::{
+        schema::{Definitions, Schema},
+        Recorded,
+    };
+    use crate::error::Error;
+
+    #[test]
+    fn schemas() {
+        let module = parser::module(
+            r#"
+            pub type Option(a) {
+              Some(a)
+              None
+            }
+
+            type Tree {
+              Leaf(Int)
+              Node(Tree, Tree)
+            }
+
+            type Datum {
+              Datum { owner: ByteArray, amounts: List(Option(Int)), tree: Tree }
+            }
+
+            pub fn spend(datum: Datum, flag: Bool, _ctx: Data) -> Bool {
+              flag
+            }
+            "#,
+            "test/validator",
+            ModuleKind::Validator,
+        )
+        .unwrap();
+
+        let module = tipo::infer_module(module, &HashMap::new()).unwrap();
+
+        let prelude = builtins::prelude();
+        let infos = HashMap::from([
+            (module.name.clone(), &module.type_info),
+            (builtins::PRELUDE.to_string(), &prelude),
+        ]);
+
+        let Some(Definition::Fn(spend)) = module.definitions.last() else {
+            panic!("expected the validator last");
+        };
+
+        let mut definitions = Definitions::new(&infos);
+
+        let datum = definitions.schema(&spend.arguments[0].tipo).unwrap();
+        let flag = definitions.schema(&spend.arguments[1].tipo).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&datum).unwrap(),
+            json!({ "$ref": "#/definitions/test~1validator~1Datum" })
+        );
+        assert_eq!(
+            serde_json::to_value(&flag).unwrap(),
+            json!({ "$ref": "#/definitions/Bool" })
+        );
+
+        assert_eq!(
+            serde_json::to_value(&definitions.schemas).unwrap(),
+            json!({
+                "Bool": {
+                    "title": "Bool",
+                    "anyOf": [
+                        { "title": "False", "dataType": "constructor", "index": 0, "fields": [] },
+                        { "title": "True", "dataType": "constructor", "index": 1, "fields": [] },
+                    ],
+                },
+                "test/validator/Datum": {
+                    "title": "Datum",
+                    "anyOf": [{
+                        "title": "Datum",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [
+                            { "title": "owner", "dataType": "bytes" },
+                            {
+                                "title": "amounts",
+                                "dataType": "list",
+                                "items": { "$ref": "#/definitions/test~1validator~1Option$Int" },
+                            },
+                            { "title": "tree", "$ref": "#/definitions/test~1validator~1Tree" },
+                        ],
+                    }],
+                },
+                "test/validator/Option$Int": {
+                    "title": "Option",
+                    "anyOf": [
+                        {
+                            "title": "Some",
+                            "dataType": "constructor",
+                            "index": 0,
+                            "fields": [{ "dataType": "integer" }],
+                        },
+                        { "title": "None", "dataType": "constructor", "index": 1, "fields": [] },
+                    ],
+                },
+                "test/validator/Tree": {
+                    "title": "Tree",
+                    "anyOf": [
+                        {
+                            "title": "Leaf",
+                            "dataType": "constructor",
+                            "index": 0,
+                            "fields": [{ "dataType": "integer" }],
+                        },
+                        {
+                            "title": "Node",
+                            "dataType": "constructor",
+                            "index": 1,
+                            "fields": [
+                                { "$ref": "#/definitions/test~1validator~1Tree" },
+                                { "$ref": "#/definitions/test~1validator~1Tree" },
+                            ],
+                        },
+                    ],
+                },
+            })
+        );
+
+        // as cached blueprint entries are read back
+        let read: BTreeMap<String, Schema> =
+            serde_json::from_value(serde_json::to_value(&definitions.schemas).unwrap()).unwrap();
+
+        assert_eq!(read, definitions.schemas);
+    }
+
+    #[test]
+    fn select() {
+        let blueprint: Recorded = serde_json::from_value(json!({
+            "preamble": {
+                "title": "test",
+                "version": "0.0.0",
+                "plutusVersion": "v2",
+                "compiler": { "name": "aiken", "version": "0.0.1" },
+                "options": { "optimize": false },
+                "sourceHash": "",
+            },
+            "validators": [
+                { "title": "vesting.spend", "compiledCode": "", "hash": "01" },
+                { "title": "vesting.mint", "compiledCode": "", "hash": "02" },
+                { "title": "swap.spend", "compiledCode": "", "hash": "03" },
+            ],
+        }))
+        .unwrap();
+
+        let hash = |name| {
+            blueprint
+                .select(name)
+                .map(|validator| validator.hash.as_str())
+        };
+
+        assert_eq!(hash(Some("swap.spend")).unwrap(), "03");
+        assert_eq!(hash(Some("swap")).unwrap(), "03");
+        assert_eq!(hash(Some("mint")).unwrap(), "02");
+
+        assert!(matches!(
+            hash(None),
+            Err(Error::AmbiguousValidator { name: None, .. })
+        ));
+
+        match hash(Some("spend")) {
+            Err(Error::AmbiguousValidator { candidates, .. }) => {
+                assert_eq!(candidates, vec!["vesting.spend", "swap.spend"])
+            }
+            result => panic!("expected ambiguity, got {result:?}"),
+        }
+
+        match hash(Some("vesting.spnd")) {
+            Err(Error::UnknownValidator { suggestions, .. }) => {
+                assert_eq!(suggestions, vec!["vesting.spend", "vesting.mint"])
+            }
+            result => panic!("expected suggestions, got {result:?}"),
+        }
+    }
+}
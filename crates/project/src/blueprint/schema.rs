@@ -0,0 +1,375 @@
+//! JSON schemas describing how values of Aiken types are encoded as
+//! Plutus data.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use aiken_lang::tipo::{collapse_links, Type, TypeInfo, TypeVar, ValueConstructorVariant};
+use serde::{de::Error as _, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Any data, for `Data` and generic values.
+    Data,
+    Integer,
+    Bytes,
+    List(Box<Schema>),
+    /// A custom type, encoded as one of it's constructors.
+    Constructors {
+        title: String,
+        constructors: Vec<Constructor>,
+    },
+    /// A schema of the blueprint's definitions.
+    Reference(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Constructor {
+    pub title: String,
+    pub index: usize,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub title: Option<String>,
+    pub schema: Schema,
+}
+
+/// Builds schemas, collecting the schemas of custom types as
+/// definitions so that recursive types can refer to themselves.
+pub struct Definitions<'a> {
+    modules: &'a HashMap<String, &'a TypeInfo>,
+    pub schemas: BTreeMap<String, Schema>,
+}
+
+/// A type containing a function, which can't be data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType;
+
+impl<'a> Definitions<'a> {
+    pub fn new(modules: &'a HashMap<String, &'a TypeInfo>) -> Self {
+        Definitions {
+            modules,
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    pub fn schema(&mut self, tipo: &Rc<Type>) -> Result<Schema, FunctionType> {
+        let tipo = collapse_links(tipo.clone());
+
+        match tipo.as_ref() {
+            Type::Fn { .. } => Err(FunctionType),
+
+            Type::Var { .. } => Ok(Schema::Data),
+
+            Type::App { .. } if tipo.is_int() => Ok(Schema::Integer),
+
+            Type::App { .. } if tipo.is_bytearray() || tipo.is_string() => Ok(Schema::Bytes),
+
+            Type::App { .. } if tipo.is_data() => Ok(Schema::Data),
+
+            Type::App { args, .. } if tipo.is_list() => {
+                Ok(Schema::List(self.schema(&args[0])?.into()))
+            }
+
+            Type::App {
+                module, name, args, ..
+            } => {
+                let key = key(&tipo);
+
+                if !self.schemas.contains_key(&key) {
+                    // a placeholder, in case the type refers to itself
+                    self.schemas.insert(key.clone(), Schema::Data);
+
+                    let schema = self.constructors(module, name, args)?;
+
+                    self.schemas.insert(key.clone(), schema);
+                }
+
+                Ok(Schema::Reference(key))
+            }
+        }
+    }
+
+    fn constructors(
+        &mut self,
+        module: &str,
+        name: &str,
+        args: &[Rc<Type>],
+    ) -> Result<Schema, FunctionType> {
+        let Some(info) = self.modules.get(module) else {
+            return Ok(Schema::Data);
+        };
+
+        let Some(names) = info.types_constructors.get(name) else {
+            return Ok(Schema::Data);
+        };
+
+        let parameters: HashMap<u64, Rc<Type>> = info.types[name]
+            .parameters
+            .iter()
+            .zip(args)
+            .filter_map(|(parameter, arg)| generic_id(parameter).map(|id| (id, arg.clone())))
+            .collect();
+
+        let mut constructors = vec![];
+
+        for (index, constructor_name) in names.iter().enumerate() {
+            let constructor = &info.values[constructor_name];
+
+            let labels: HashMap<usize, &String> = match &constructor.variant {
+                ValueConstructorVariant::Record {
+                    field_map: Some(field_map),
+                    ..
+                } => field_map
+                    .iter()
+                    .map(|(label, index)| (*index, label))
+                    .collect(),
+                _ => HashMap::new(),
+            };
+
+            let field_types = constructor
+                .tipo
+                .fn_types()
+                .map(|(fields, _)| fields)
+                .unwrap_or_default();
+
+            let fields = field_types
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    Ok(Field {
+                        title: labels.get(&index).map(|label| label.to_string()),
+                        schema: self.schema(&substitute(field, &parameters))?,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+
+            constructors.push(Constructor {
+                title: constructor_name.clone(),
+                index,
+                fields,
+            });
+        }
+
+        Ok(Schema::Constructors {
+            title: name.to_string(),
+            constructors,
+        })
+    }
+}
+
+/// The name of a type amongst the definitions, like
+/// `aiken/option/Option$Int`.
+fn key(tipo: &Rc<Type>) -> String {
+    let tipo = collapse_links(tipo.clone());
+
+    match tipo.as_ref() {
+        Type::App {
+            module, name, args, ..
+        } => {
+            let name = if module.is_empty() {
+                name.clone()
+            } else {
+                format!("{module}/{name}")
+            };
+
+            if args.is_empty() {
+                name
+            } else {
+                let args: Vec<String> = args.iter().map(key).collect();
+
+                format!("{name}${}", args.join("_"))
+            }
+        }
+        Type::Var { .. } => "Data".to_string(),
+        Type::Fn { .. } => "Fn".to_string(),
+    }
+}
+
+fn generic_id(tipo: &Rc<Type>) -> Option<u64> {
+    match tipo.as_ref() {
+        Type::Var { tipo } => match &*tipo.borrow() {
+            TypeVar::Generic { id } => Some(*id),
+            TypeVar::Link { tipo } => generic_id(tipo),
+            TypeVar::Unbound { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replace the generic variables of a type with their instances.
+fn substitute(tipo: &Rc<Type>, instances: &HashMap<u64, Rc<Type>>) -> Rc<Type> {
+    if let Some(instance) = generic_id(tipo).and_then(|id| instances.get(&id)) {
+        return instance.clone();
+    }
+
+    match collapse_links(tipo.clone()).as_ref() {
+        Type::App {
+            public,
+            module,
+            name,
+            args,
+        } => Type::App {
+            public: *public,
+            module: module.clone(),
+            name: name.clone(),
+            args: args.iter().map(|arg| substitute(arg, instances)).collect(),
+        }
+        .into(),
+        Type::Fn { args, ret } => Type::Fn {
+            args: args.iter().map(|arg| substitute(arg, instances)).collect(),
+            ret: substitute(ret, instances),
+        }
+        .into(),
+        Type::Var { .. } => tipo.clone(),
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        self.serialize_entries::<S>(&mut map, false)?;
+        map.end()
+    }
+}
+
+impl Schema {
+    /// Write the entries of the schema, leaving out it's title when
+    /// it already has one.
+    fn serialize_entries<S: Serializer>(
+        &self,
+        map: &mut S::SerializeMap,
+        titled: bool,
+    ) -> Result<(), S::Error> {
+        match self {
+            Schema::Data => {
+                if !titled {
+                    map.serialize_entry("title", "Data")?;
+                }
+                map.serialize_entry("description", "Any Plutus data.")?;
+            }
+            Schema::Integer => map.serialize_entry("dataType", "integer")?,
+            Schema::Bytes => map.serialize_entry("dataType", "bytes")?,
+            Schema::List(items) => {
+                map.serialize_entry("dataType", "list")?;
+                map.serialize_entry("items", items)?;
+            }
+            Schema::Constructors {
+                title,
+                constructors,
+            } => {
+                if !titled {
+                    map.serialize_entry("title", title)?;
+                }
+                map.serialize_entry("anyOf", constructors)?;
+            }
+            Schema::Reference(key) => {
+                // a JSON pointer, escaping `~` and `/`
+                let pointer = key.replace('~', "~0").replace('/', "~1");
+                map.serialize_entry("$ref", &format!("#/definitions/{pointer}"))?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for Constructor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("title", &self.title)?;
+        map.serialize_entry("dataType", "constructor")?;
+        map.serialize_entry("index", &self.index)?;
+        map.serialize_entry("fields", &self.fields)?;
+        map.end()
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        if let Some(title) = &self.title {
+            map.serialize_entry("title", title)?;
+        }
+
+        self.schema
+            .serialize_entries::<S>(&mut map, self.title.is_some())?;
+        map.end()
+    }
+}
+
+/// Schemas are read back from cached blueprint entries, which were
+/// written by the `Serialize` implementations above.
+impl<'de> Deserialize<'de> for Schema {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Map::deserialize(deserializer)?;
+
+        Schema::from_entries(entries).map_err(D::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut entries = Map::deserialize(deserializer)?;
+
+        // the field's title, a schema in a field has none
+        let title = match entries.remove("title") {
+            Some(Value::String(title)) => Some(title),
+            None => None,
+            Some(_) => return Err(D::Error::custom("a field's title must be a string")),
+        };
+
+        Ok(Field {
+            schema: Schema::from_entries(entries).map_err(D::Error::custom)?,
+            title,
+        })
+    }
+}
+
+impl Schema {
+    fn from_entries(mut entries: Map<String, Value>) -> Result<Schema, serde_json::Error> {
+        let invalid = || serde_json::Error::custom("not a schema written by aiken");
+
+        if let Some(pointer) = entries.remove("$ref") {
+            let pointer = String::deserialize(pointer)?;
+
+            let key = pointer
+                .strip_prefix("#/definitions/")
+                .ok_or_else(invalid)?
+                .replace("~1", "/")
+                .replace("~0", "~");
+
+            return Ok(Schema::Reference(key));
+        }
+
+        if let Some(constructors) = entries.remove("anyOf") {
+            let title = match entries.remove("title") {
+                Some(title) => String::deserialize(title)?,
+                None => String::new(),
+            };
+
+            return Ok(Schema::Constructors {
+                title,
+                constructors: Vec::deserialize(constructors)?,
+            });
+        }
+
+        match entries.remove("dataType").as_ref().and_then(Value::as_str) {
+            Some("integer") => Ok(Schema::Integer),
+            Some("bytes") => Ok(Schema::Bytes),
+            Some("list") => {
+                let items = entries.remove("items").ok_or_else(invalid)?;
+
+                Ok(Schema::List(Box::new(Schema::deserialize(items)?)))
+            }
+            None if entries.contains_key("description") => Ok(Schema::Data),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Build results kept under `.aiken/cache/`, so unchanged projects
+//! don't have to be checked and compiled again. Everything is
+//! addressed by a hash of what went into it, so changing a source,
+//! the manifest, an option or the compiler gives new addresses
+//! instead of stale results:
+//!
+//! - `builds/<key>.json`, the blueprint of a build, keyed by
+//!   `Cache::build_key`
+//! - `validators/<key>.json`, the blueprint entry of a single
+//!   validator, keyed by `Cache::validator_key`, so that changing one
+//!   validator of a project doesn't compile the others again
+//! - `scripts/<hash>.flat`, the compiled validators, by their hash
+//! - `applied/<key>.flat`, validators applied to parameters, keyed
+//!   by `Cache::applied_key`
+//!
+//! Entries that can't be read back are ignored and written again.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use uplc::{
+    ast::{DeBruijn, PlutusVersion, Program},
+    data::PlutusData,
+};
+
+use crate::{
+    blueprint::{self, Entry},
+    error::Error,
+    Artifact, Options,
+};
+
+/// Where the cache is, relative to the root of the project.
+pub const DIRECTORY: &str = ".aiken/cache";
+
+const KINDS: [&str; 4] = ["builds", "validators", "scripts", "applied"];
+
+pub struct Cache {
+    directory: PathBuf,
+    version: PlutusVersion,
+}
+
+/// How many entries of a kind there are and how many bytes they take.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub kind: &'static str,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+impl Cache {
+    pub fn new(root: &Path, version: PlutusVersion) -> Cache {
+        Cache {
+            directory: root.join(DIRECTORY),
+            version,
+        }
+    }
+
+    /// The address of a build of sources hashing to `source_hash`,
+    /// compiled with `options` by this compiler.
+    pub fn build_key(source_hash: &str, options: Options) -> String {
+        let options = serde_json::to_string(&options).expect("options are valid JSON");
+
+        key([
+            env!("CARGO_PKG_VERSION").as_bytes(),
+            options.as_bytes(),
+            source_hash.as_bytes(),
+        ])
+    }
+
+    /// The address of a validator compiled with `options` by this
+    /// compiler from `sources`, see `Project::validator_sources`.
+    pub fn validator_key<'a>(
+        &self,
+        options: Options,
+        sources: impl IntoIterator<Item = &'a str>,
+    ) -> String {
+        let options = serde_json::to_string(&options).expect("options are valid JSON");
+        let version = format!("{:?}", self.version);
+
+        let sources: Vec<&[u8]> = sources.into_iter().map(str::as_bytes).collect();
+
+        key([
+            env!("CARGO_PKG_VERSION").as_bytes(),
+            options.as_bytes(),
+            version.as_bytes(),
+        ]
+        .into_iter()
+        .chain(sources))
+    }
+
+    /// The address of the validator hashing to `hash` applied to
+    /// `arguments`, in order.
+    pub fn applied_key(hash: &[u8], arguments: &[PlutusData]) -> String {
+        let arguments: Vec<Vec<u8>> = arguments.iter().map(PlutusData::to_cbor).collect();
+
+        key([hash]
+            .into_iter()
+            .chain(arguments.iter().map(Vec::as_slice)))
+    }
+
+    /// The blueprint and validators of a cached build, unless some
+    /// of them are missing.
+    pub fn build(&self, key: &str) -> Option<(String, Vec<Artifact>)> {
+        let json = fs::read_to_string(self.path("builds", key, "json")).ok()?;
+
+        let recorded: blueprint::Recorded = serde_json::from_str(&json).ok()?;
+
+        let artifacts = recorded
+            .validators
+            .iter()
+            .map(|validator| {
+                let (module, name) = validator.title.rsplit_once('.')?;
+
+                let artifact =
+                    self.script(&self.path("scripts", &validator.hash, "flat"), module, name)?;
+
+                (hex::encode(artifact.hash) == validator.hash).then_some(artifact)
+            })
+            .collect::<Option<_>>()?;
+
+        Some((json, artifacts))
+    }
+
+    pub fn store_build(&self, key: &str, json: &str, artifacts: &[Artifact]) -> Result<(), Error> {
+        for artifact in artifacts {
+            write(
+                &self.path("scripts", &hex::encode(artifact.hash), "flat"),
+                &artifact.flat,
+            )?;
+        }
+
+        // last, so the build is only found once it's scripts are there
+        write(&self.path("builds", key, "json"), json.as_bytes())
+    }
+
+    /// The blueprint entry and script of a validator compiled
+    /// earlier, unless it's script is missing.
+    pub fn validator(&self, key: &str, module: &str, name: &str) -> Option<(Entry, Artifact)> {
+        let json = fs::read_to_string(self.path("validators", key, "json")).ok()?;
+
+        let entry: Entry = serde_json::from_str(&json).ok()?;
+
+        let artifact = self.script(
+            &self.path("scripts", &entry.validator.hash, "flat"),
+            module,
+            name,
+        )?;
+
+        (hex::encode(artifact.hash) == entry.validator.hash).then_some((entry, artifact))
+    }
+
+    pub fn store_validator(
+        &self,
+        key: &str,
+        entry: &Entry,
+        artifact: &Artifact,
+    ) -> Result<(), Error> {
+        write(
+            &self.path("scripts", &hex::encode(artifact.hash), "flat"),
+            &artifact.flat,
+        )?;
+
+        let json = serde_json::to_string(entry).expect("blueprint entries are valid JSON");
+
+        write(&self.path("validators", key, "json"), json.as_bytes())
+    }
+
+    /// A validator applied to parameters earlier, named after the one
+    /// it was applied from.
+    pub fn applied(&self, key: &str, module: &str, name: &str) -> Option<Artifact> {
+        self.script(&self.path("applied", key, "flat"), module, name)
+    }
+
+    pub fn store_applied(&self, key: &str, artifact: &Artifact) -> Result<(), Error> {
+        write(&self.path("applied", key, "flat"), &artifact.flat)
+    }
+
+    /// What each kind of entry takes up.
+    pub fn usage(&self) -> Result<Vec<Usage>, Error> {
+        KINDS
+            .iter()
+            .map(|kind| {
+                let directory = self.directory.join(kind);
+
+                let mut usage = Usage {
+                    kind,
+                    ..Usage::default()
+                };
+
+                let entries = match fs::read_dir(&directory) {
+                    Ok(entries) => entries,
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(usage),
+                    Err(error) => {
+                        return Err(Error::FileIo {
+                            path: directory,
+                            error,
+                        })
+                    }
+                };
+
+                for entry in entries {
+                    let metadata = entry.and_then(|entry| entry.metadata()).map_err(|error| {
+                        Error::FileIo {
+                            path: directory.clone(),
+                            error,
+                        }
+                    })?;
+
+                    usage.entries += 1;
+                    usage.bytes += metadata.len();
+                }
+
+                Ok(usage)
+            })
+            .collect()
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) -> Result<(), Error> {
+        match fs::remove_dir_all(&self.directory) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(Error::FileIo {
+                path: self.directory.clone(),
+                error,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn script(&self, path: &Path, module: &str, name: &str) -> Option<Artifact> {
+        let flat = fs::read(path).ok()?;
+
+        let program = Program::<DeBruijn>::from_flat_with_version(&flat, self.version).ok()?;
+
+        Some(Artifact {
+            module: module.to_string(),
+            name: name.to_string(),
+            cbor: program.to_cbor().ok()?,
+            hash: program.to_hash(self.version).ok()?,
+            program,
+            flat,
+        })
+    }
+
+    fn path(&self, kind: &str, key: &str, extension: &str) -> PathBuf {
+        self.directory.join(kind).join(format!("{key}.{extension}"))
+    }
+}
+
+/// The hex encoded blake2b-256 of `inputs`, each preceded by it's
+/// length so they can't run into each other.
+fn key<'a>(inputs: impl IntoIterator<Item = &'a [u8]>) -> String {
+    let mut hasher = Blake2b::<U32>::new();
+
+    for input in inputs {
+        hasher.update((input.len() as u64).to_be_bytes());
+        hasher.update(input);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let io_error = |error| Error::FileIo {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    fs::create_dir_all(path.parent().expect("entries are in a directory")).map_err(io_error)?;
+
+    fs::write(path, contents).map_err(io_error)
+}
@@ -0,0 +1,47 @@
+use std::{fs, path::Path};
+
+use serde::{de, Deserialize, Deserializer};
+use uplc::ast::PlutusVersion;
+
+use crate::error::Error;
+
+/// The `aiken.toml` manifest at the root of a project.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// The Plutus version validators are compiled for.
+    #[serde(
+        default = "default_plutus_version",
+        deserialize_with = "plutus_version"
+    )]
+    pub plutus_version: PlutusVersion,
+}
+
+impl Config {
+    pub fn load(root: &Path) -> Result<Config, Error> {
+        let path = root.join("aiken.toml");
+
+        let raw = fs::read_to_string(&path).map_err(|error| Error::FileIo {
+            path: path.clone(),
+            error,
+        })?;
+
+        toml::from_str(&raw).map_err(|error| Error::Manifest { path, error })
+    }
+}
+
+fn default_plutus_version() -> PlutusVersion {
+    PlutusVersion::V2
+}
+
+fn plutus_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PlutusVersion, D::Error> {
+    let version = String::deserialize(deserializer)?;
+
+    version
+        .parse()
+        .map_err(|_| de::Error::custom(format!("unknown Plutus version `{version}`")))
+}
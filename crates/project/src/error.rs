@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+
+use aiken_lang::{codegen, parser::ParseError, tipo};
+use miette::Diagnostic;
+use thiserror::Error;
+
+#[derive(Error, Debug, Diagnostic)]
+pub enum Error {
+    #[error("{}: {error}", .path.display())]
+    #[diagnostic(code(aiken::project::io))]
+    FileIo {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    #[error("Invalid manifest {}: {error}", .path.display())]
+    #[diagnostic(code(aiken::project::manifest))]
+    Manifest {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+
+    #[error("Module `{name}` is defined by both {} and {}", .first.display(), .second.display())]
+    #[diagnostic(code(aiken::project::duplicate_module))]
+    DuplicateModule {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+
+    #[error("Import cycle, `{}` imports itself through {}", .modules[0], .modules.join(" -> "))]
+    #[diagnostic(
+        code(aiken::project::import_cycle),
+        help("Move what the modules share into a module they can both import")
+    )]
+    ImportCycle { modules: Vec<String> },
+
+    #[error("Couldn't parse {}", .path.display())]
+    #[diagnostic(code(aiken::project::parse))]
+    Parse {
+        path: PathBuf,
+        #[source_code]
+        src: String,
+        #[related]
+        errors: Vec<ParseError>,
+    },
+
+    #[error("Couldn't type check {}", .path.display())]
+    #[diagnostic(code(aiken::project::type_check))]
+    Type {
+        path: PathBuf,
+        #[source_code]
+        src: String,
+        #[related]
+        errors: Vec<tipo::Error>,
+    },
+
+    #[error("Couldn't compile validator `{name}` of {}", .path.display())]
+    #[diagnostic(code(aiken::project::codegen))]
+    Codegen {
+        path: PathBuf,
+        name: String,
+        #[source_code]
+        src: String,
+        #[related]
+        errors: Vec<codegen::Error>,
+    },
+
+    #[error("Validator `{validator}` takes a `{tipo}`, which can't be described as data")]
+    #[diagnostic(
+        code(aiken::project::unsupported_type),
+        help("Blueprints describe validator arguments as data, which can't contain functions")
+    )]
+    UnsupportedType { validator: String, tipo: String },
+
+    #[error("Invalid blueprint {}: {error}", .path.display())]
+    #[diagnostic(code(aiken::project::blueprint))]
+    Blueprint {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+
+    #[error("No validator `{name}` in the blueprint")]
+    #[diagnostic(
+        code(aiken::project::unknown_validator),
+        help("{}", did_you_mean(.suggestions))
+    )]
+    UnknownValidator {
+        name: String,
+        suggestions: Vec<String>,
+    },
+
+    #[error("{} validators match{}", .candidates.len(), .name.as_ref().map(|name| format!(" `{name}`")).unwrap_or_default())]
+    #[diagnostic(
+        code(aiken::project::ambiguous_validator),
+        help("Pick one of {}", .candidates.join(", "))
+    )]
+    AmbiguousValidator {
+        name: Option<String>,
+        candidates: Vec<String>,
+    },
+
+    #[error("The blueprint can't be reproduced: {}", .mismatches.join(", "))]
+    #[diagnostic(
+        code(aiken::project::unreproducible),
+        help("It was written by aiken {recorded}, this is aiken {current}")
+    )]
+    Unreproducible {
+        mismatches: Vec<String>,
+        recorded: String,
+        current: String,
+    },
+
+    #[error("Validator `{title}` of the blueprint isn't a valid script: {reason}")]
+    #[diagnostic(
+        code(aiken::project::invalid_script),
+        help("Build the project again to write a new blueprint")
+    )]
+    InvalidScript { title: String, reason: String },
+
+    #[error("Couldn't encode validator `{name}` of module `{module}`: {error}")]
+    #[diagnostic(code(aiken::project::flat))]
+    Flat {
+        module: String,
+        name: String,
+        error: flat::en::Error,
+    },
+}
+
+impl Error {
+    /// The file the error is about, if it is about one.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::FileIo { path, .. }
+            | Error::Manifest { path, .. }
+            | Error::Parse { path, .. }
+            | Error::Type { path, .. }
+            | Error::Codegen { path, .. }
+            | Error::Blueprint { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The source the spans of the error are in.
+    pub fn src(&self) -> Option<&str> {
+        match self {
+            Error::Parse { src, .. } | Error::Type { src, .. } | Error::Codegen { src, .. } => {
+                Some(src)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::from("Validators are named `module.function`"),
+        suggestions => format!("Did you mean {}?", suggestions.join(" or ")),
+    }
+}
@@ -0,0 +1,1069 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use aiken_lang::{
+    ast::{Definition, ModuleKind},
+    builtins,
+    codegen::CodeGenerator,
+    parser,
+    tipo::{self, TypeInfo},
+};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use serde::{Deserialize, Serialize};
+use uplc::{
+    ast::{DeBruijn, Program},
+    data::PlutusData,
+    optimize::Pass,
+};
+
+pub mod blueprint;
+pub mod cache;
+pub mod config;
+pub mod error;
+pub mod module;
+
+use blueprint::{Blueprint, Entry};
+use cache::Cache;
+use config::Config;
+use error::Error;
+use module::{CheckedModule, ParsedModule, Source};
+
+/// A compiled validator, as written to the `artifacts/` directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub module: String,
+    pub name: String,
+    pub program: Program<DeBruijn>,
+    pub flat: Vec<u8>,
+    pub cbor: Vec<u8>,
+    pub hash: [u8; 28],
+}
+
+/// How validators are compiled. They are recorded in the blueprint's
+/// preamble, for `Project::verify` to compile them the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Options {
+    /// Simplify the generated programs with every optimizer pass.
+    pub optimize: bool,
+}
+
+/// A directory with an `aiken.toml` manifest, libraries under
+/// `lib/` and validators under `validators/`.
+pub struct Project {
+    root: PathBuf,
+    pub config: Config,
+}
+
+impl Project {
+    pub fn new(root: PathBuf) -> Result<Project, Error> {
+        let config = Config::load(&root)?;
+
+        Ok(Project { root, config })
+    }
+
+    /// The blueprint written by the last build.
+    pub fn blueprint(&self) -> Result<blueprint::Recorded, Error> {
+        blueprint::Recorded::read(&self.root)
+    }
+
+    /// Compile every public function of the validator modules,
+    /// write them to `artifacts/` and describe them in a blueprint.
+    /// Sources built with the same options before are taken from the
+    /// cache instead of being compiled again.
+    pub fn build(&self, options: Options) -> Result<Vec<Artifact>, Error> {
+        let sources = self.read_sources()?;
+
+        let source_hash = self.source_hash(&sources)?;
+
+        let cache = self.cache();
+        let key = Cache::build_key(&source_hash, options);
+
+        let (json, artifacts) = match cache.build(&key) {
+            Some(cached) => cached,
+            None => {
+                let (blueprint, artifacts) =
+                    self.compile_blueprint(sources, source_hash, options, Some(&cache))?;
+
+                let json =
+                    serde_json::to_string_pretty(&blueprint).expect("blueprints are valid JSON");
+
+                cache.store_build(&key, &json, &artifacts)?;
+
+                (json, artifacts)
+            }
+        };
+
+        self.write_artifacts(&artifacts)?;
+
+        let path = self.root.join(blueprint::FILE_NAME);
+
+        fs::write(&path, json).map_err(|error| Error::FileIo { path, error })?;
+
+        Ok(artifacts)
+    }
+
+    /// The validator `name` of the last build's blueprint, as
+    /// `Recorded::select` finds it, applied to `arguments` in order.
+    pub fn apply(&self, name: Option<&str>, arguments: &[PlutusData]) -> Result<Artifact, Error> {
+        let recorded = self.blueprint()?;
+
+        let validator = recorded.select(name)?;
+
+        let invalid = |reason: String| Error::InvalidScript {
+            title: validator.title.clone(),
+            reason,
+        };
+
+        let hash = hex::decode(&validator.hash).map_err(|e| invalid(e.to_string()))?;
+
+        let (module, name) = validator
+            .title
+            .rsplit_once('.')
+            .unwrap_or(("", &validator.title));
+
+        let cache = self.cache();
+        let key = Cache::applied_key(&hash, arguments);
+
+        if let Some(applied) = cache.applied(&key, module, name) {
+            return Ok(applied);
+        }
+
+        let cbor = hex::decode(&validator.compiled_code).map_err(|e| invalid(e.to_string()))?;
+
+        let program = Program::<DeBruijn>::from_cbor(&cbor).map_err(|e| invalid(e.to_string()))?;
+
+        let program = arguments.iter().fold(program, |program, argument| {
+            program.apply_data(argument.clone())
+        });
+
+        let version = self.config.plutus_version;
+
+        let flat_error = |error| Error::Flat {
+            module: module.to_string(),
+            name: name.to_string(),
+            error,
+        };
+
+        let applied = Artifact {
+            module: module.to_string(),
+            name: name.to_string(),
+            flat: program.to_flat_with_version(version).map_err(flat_error)?,
+            cbor: program.to_cbor().map_err(flat_error)?,
+            hash: program.to_hash(version).map_err(flat_error)?,
+            program,
+        };
+
+        cache.store_applied(&key, &applied)?;
+
+        Ok(applied)
+    }
+
+    /// The build results kept under `.aiken/cache/`.
+    pub fn cache(&self) -> Cache {
+        Cache::new(&self.root, self.config.plutus_version)
+    }
+
+    /// Compile the validators again with the options recorded in the
+    /// blueprint, without writing anything, and make sure they come out
+    /// byte for byte the same as the blueprint says.
+    pub fn verify(&self) -> Result<Vec<Artifact>, Error> {
+        let recorded = blueprint::Recorded::read(&self.root)?;
+
+        let sources = self.read_sources()?;
+
+        let source_hash = self.source_hash(&sources)?;
+
+        let (blueprint, artifacts) =
+            self.compile_blueprint(sources, source_hash, recorded.preamble.options, None)?;
+
+        let mut mismatches = vec![];
+
+        if recorded.preamble.source_hash != blueprint.preamble.source_hash {
+            mismatches.push("the sources changed since the blueprint was written".to_string());
+        }
+
+        for validator in &blueprint.validators {
+            match recorded
+                .validators
+                .iter()
+                .find(|recorded| recorded.title == validator.title)
+            {
+                None => mismatches.push(format!("`{}` isn't in the blueprint", validator.title)),
+                Some(recorded) if recorded.compiled_code != validator.compiled_code => {
+                    mismatches.push(format!("`{}` compiles to different code", validator.title))
+                }
+                Some(_) => (),
+            }
+        }
+
+        for recorded in &recorded.validators {
+            if !blueprint
+                .validators
+                .iter()
+                .any(|validator| validator.title == recorded.title)
+            {
+                mismatches.push(format!("`{}` no longer exists", recorded.title));
+            }
+        }
+
+        if !mismatches.is_empty() {
+            return Err(Error::Unreproducible {
+                mismatches,
+                recorded: recorded.preamble.compiler.version,
+                current: blueprint.preamble.compiler.version,
+            });
+        }
+
+        Ok(artifacts)
+    }
+
+    /// Check and compile the project, describing the result in a
+    /// blueprint. Validators found in `cache` aren't compiled again.
+    fn compile_blueprint(
+        &self,
+        sources: Vec<Source>,
+        source_hash: String,
+        options: Options,
+        cache: Option<&Cache>,
+    ) -> Result<(Blueprint, Vec<Artifact>), Error> {
+        let checked = self.check_sources(sources)?;
+
+        let (artifacts, entries) = self.compile(&checked, options, cache)?;
+
+        let blueprint = Blueprint::new(&self.config, options, source_hash, entries);
+
+        Ok((blueprint, artifacts))
+    }
+
+    /// Parse and type check every module, in dependency order.
+    pub fn check(&self) -> Result<Vec<CheckedModule>, Error> {
+        self.check_sources(self.read_sources()?)
+    }
+
+    /// Like `check`, but going on past modules that don't parse or type
+    /// check, handing each error to `report` as soon as it is found.
+    /// Modules importing one that failed aren't checked, their errors
+    /// would only repeat it's. The modules that checked are returned.
+    pub fn check_reporting(&self, report: &mut impl FnMut(Error)) -> Vec<CheckedModule> {
+        match self.read_sources() {
+            Ok(sources) => self.check_sources_reporting(sources, report),
+            Err(error) => {
+                report(error);
+
+                vec![]
+            }
+        }
+    }
+
+    fn check_sources(&self, sources: Vec<Source>) -> Result<Vec<CheckedModule>, Error> {
+        let mut first = None;
+
+        let checked = self.check_sources_reporting(sources, &mut |error| {
+            first.get_or_insert(error);
+        });
+
+        match first {
+            Some(error) => Err(error),
+            None => Ok(checked),
+        }
+    }
+
+    fn check_sources_reporting(
+        &self,
+        sources: Vec<Source>,
+        report: &mut impl FnMut(Error),
+    ) -> Vec<CheckedModule> {
+        let mut failed = HashSet::new();
+        let mut parsed = vec![];
+
+        for source in sources {
+            match parser::module(&source.code, &source.name, source.kind) {
+                Ok(ast) => parsed.push(ParsedModule {
+                    path: source.path,
+                    code: source.code,
+                    ast,
+                }),
+                Err(errors) => {
+                    failed.insert(source.name);
+
+                    report(Error::Parse {
+                        path: source.path,
+                        src: source.code,
+                        errors,
+                    });
+                }
+            }
+        }
+
+        let sorted = match module::sort_dependencies(parsed) {
+            Ok(sorted) => sorted,
+            Err(error) => {
+                report(error);
+
+                return vec![];
+            }
+        };
+
+        let mut importable_modules: HashMap<String, TypeInfo> = HashMap::from([
+            (builtins::BUILTIN.to_string(), builtins::plutus()),
+            (builtins::MATH.to_string(), builtins::math()),
+        ]);
+
+        let mut checked = vec![];
+
+        for module in sorted {
+            let name = module.ast.name.clone();
+
+            if module
+                .ast
+                .dependencies()
+                .iter()
+                .any(|(dependency, _)| failed.contains(dependency))
+            {
+                failed.insert(name);
+
+                continue;
+            }
+
+            match tipo::infer_module(module.ast, &importable_modules) {
+                Ok(ast) => {
+                    importable_modules.insert(ast.name.clone(), ast.type_info.clone());
+
+                    checked.push(CheckedModule {
+                        path: module.path,
+                        code: module.code,
+                        ast,
+                    });
+                }
+                Err(error) => {
+                    failed.insert(name);
+
+                    report(Error::Type {
+                        path: module.path,
+                        src: module.code,
+                        errors: vec![error],
+                    });
+                }
+            }
+        }
+
+        checked
+    }
+
+    fn read_sources(&self) -> Result<Vec<Source>, Error> {
+        let mut sources = module::read_sources(&self.root.join("lib"), ModuleKind::Lib)?;
+        sources.extend(module::read_sources(
+            &self.root.join("validators"),
+            ModuleKind::Validator,
+        )?);
+
+        let mut seen: HashMap<&str, &Path> = HashMap::new();
+
+        for source in &sources {
+            if let Some(first) = seen.insert(&source.name, &source.path) {
+                return Err(Error::DuplicateModule {
+                    name: source.name.clone(),
+                    first: first.to_path_buf(),
+                    second: source.path.clone(),
+                });
+            }
+        }
+
+        Ok(sources)
+    }
+
+    /// The blake2b-256 of the manifest and of every module, each
+    /// preceded by it's name and length, hex encoded. Anything that
+    /// changes the compiled code changes it, short of the compiler.
+    fn source_hash(&self, sources: &[Source]) -> Result<String, Error> {
+        let path = self.root.join("aiken.toml");
+
+        let manifest = fs::read_to_string(&path).map_err(|error| Error::FileIo { path, error })?;
+
+        let inputs = [("aiken.toml", &manifest)].into_iter().chain(
+            sources
+                .iter()
+                .map(|source| (source.name.as_str(), &source.code)),
+        );
+
+        let mut hasher = Blake2b::<U32>::new();
+
+        for (name, contents) in inputs {
+            hasher.update(name.as_bytes());
+            hasher.update([0]);
+            hasher.update((contents.len() as u64).to_be_bytes());
+            hasher.update(contents.as_bytes());
+        }
+
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    fn compile(
+        &self,
+        checked: &[CheckedModule],
+        options: Options,
+        cache: Option<&Cache>,
+    ) -> Result<(Vec<Artifact>, Vec<Entry>), Error> {
+        let mut generator = CodeGenerator::new(checked.iter().map(|module| &module.ast));
+        let version = self.config.plutus_version;
+
+        let prelude = builtins::prelude();
+        let types = blueprint::type_infos(checked, &prelude);
+
+        let modules: HashMap<&str, &CheckedModule> = checked
+            .iter()
+            .map(|module| (module.ast.name.as_str(), module))
+            .collect();
+
+        let mut artifacts = vec![];
+        let mut entries = vec![];
+
+        for module in checked {
+            if module.ast.kind != ModuleKind::Validator {
+                continue;
+            }
+
+            let validators =
+                module
+                    .ast
+                    .definitions
+                    .iter()
+                    .filter_map(|definition| match definition {
+                        Definition::Fn(function) if function.public => Some(function),
+                        _ => None,
+                    });
+
+            for function in validators {
+                let name = &function.name;
+
+                let key = cache.map(|cache| {
+                    let sources = validator_sources(&generator, &modules, &module.ast.name, name);
+
+                    (cache, cache.validator_key(options, sources))
+                });
+
+                let cached = key
+                    .as_ref()
+                    .and_then(|(cache, key)| cache.validator(key, &module.ast.name, name));
+
+                if let Some((entry, artifact)) = cached {
+                    artifacts.push(artifact);
+                    entries.push(entry);
+
+                    continue;
+                }
+
+                let program = generator
+                    .generate_validator(&module.ast.name, name)
+                    .map_err(|error| Error::Codegen {
+                        path: module.path.clone(),
+                        name: name.clone(),
+                        src: module.code.clone(),
+                        errors: vec![error],
+                    })?;
+
+                let program = if options.optimize {
+                    program.optimize(&Pass::ALL, version)
+                } else {
+                    program
+                };
+
+                let program: Program<DeBruijn> = program
+                    .try_into()
+                    .expect("generated programs have no free variables");
+
+                let flat_error = |error| Error::Flat {
+                    module: module.ast.name.clone(),
+                    name: name.clone(),
+                    error,
+                };
+
+                let flat = program.to_flat_with_version(version).map_err(flat_error)?;
+                let cbor = program.to_cbor().map_err(flat_error)?;
+                let hash = program.to_hash(version).map_err(flat_error)?;
+
+                let artifact = Artifact {
+                    module: module.ast.name.clone(),
+                    name: name.clone(),
+                    program,
+                    flat,
+                    cbor,
+                    hash,
+                };
+
+                let entry = Entry::new(&artifact, function, &types)?;
+
+                if let Some((cache, key)) = &key {
+                    cache.store_validator(key, &entry, &artifact)?;
+                }
+
+                artifacts.push(artifact);
+                entries.push(entry);
+            }
+        }
+
+        Ok((artifacts, entries))
+    }
+
+    /// Write each validator to `artifacts/<module>/<name>/`, as flat
+    /// bytes, as the hex encoded CBOR found on chain and with it's
+    /// hash.
+    fn write_artifacts(&self, artifacts: &[Artifact]) -> Result<(), Error> {
+        for artifact in artifacts {
+            let directory = self
+                .root
+                .join("artifacts")
+                .join(&artifact.module)
+                .join(&artifact.name);
+
+            let io_error = |path: &Path| {
+                let path = path.to_path_buf();
+                move |error| Error::FileIo { path, error }
+            };
+
+            fs::create_dir_all(&directory).map_err(io_error(&directory))?;
+
+            let files = [
+                ("script.flat", artifact.flat.clone()),
+                ("script.cbor", hex::encode(&artifact.cbor).into_bytes()),
+                ("hash.txt", hex::encode(artifact.hash).into_bytes()),
+            ];
+
+            for (file, contents) in files {
+                let path = directory.join(file);
+
+                fs::write(&path, contents).map_err(io_error(&path))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What the validator `name` of `module` is compiled from, in a
+/// stable order: the source of every function it uses and the types
+/// and imports of the modules it depends on. Changing anything else
+/// in the project leaves it's cache entry valid.
+fn validator_sources<'a>(
+    generator: &CodeGenerator,
+    modules: &HashMap<&str, &'a CheckedModule>,
+    module: &'a str,
+    name: &'a str,
+) -> Vec<&'a str> {
+    let mut sources = vec![module, name];
+
+    for (module, name) in generator.dependencies(module, name) {
+        let Some(checked) = modules.get(module.as_str()) else {
+            continue;
+        };
+
+        for definition in &checked.ast.definitions {
+            match definition {
+                Definition::Fn(function) if function.name == name => sources.extend([
+                    checked.ast.name.as_str(),
+                    function.name.as_str(),
+                    &checked.code[function.location.start..function.location.end],
+                ]),
+                _ => (),
+            }
+        }
+    }
+
+    // every module imported, directly or not
+    let mut imported = BTreeSet::from([module.to_string()]);
+    let mut queue = vec![module.to_string()];
+
+    while let Some(name) = queue.pop() {
+        if let Some(checked) = modules.get(name.as_str()) {
+            for (dependency, _) in checked.ast.dependencies() {
+                if imported.insert(dependency.clone()) {
+                    queue.push(dependency);
+                }
+            }
+        }
+    }
+
+    for name in &imported {
+        let Some(checked) = modules.get(name.as_str()) else {
+            continue;
+        };
+
+        sources.push(checked.ast.name.as_str());
+
+        for definition in &checked.ast.definitions {
+            if !matches!(definition, Definition::Fn(_)) {
+                let location = definition.location();
+
+                sources.push(&checked.code[location.start..location.end]);
+            }
+        }
+    }
+
+    sources
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use uplc::{
+        ast::{Constant, DeBruijn, NamedDeBruijn, Program, Term},
+        data::PlutusData,
+    };
+
+    use super::{
+        cache::{self, Cache},
+        error::Error,
+        Options, Project,
+    };
+
+    /// A fresh project directory containing `files`.
+    fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("aiken-project-{}-{}", name, std::process::id()));
+
+        let _ = fs::remove_dir_all(&root);
+
+        for (path, contents) in files {
+            let path = root.join(path);
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        root
+    }
+
+    const MANIFEST: &str = r#"
+        name = "test"
+        version = "0.0.0"
+        plutus_version = "v2"
+    "#;
+
+    #[test]
+    fn build() {
+        let root = project(
+            "build",
+            &[
+                ("aiken.toml", MANIFEST),
+                (
+                    "lib/test/math.ak",
+                    "use test/option pub fn double(n) { option.unwrap(option.Some(n), 0) * 2 }",
+                ),
+                (
+                    "lib/test/option.ak",
+                    r#"
+                    pub type Option(a) {
+                      Some(a)
+                      None
+                    }
+
+                    pub fn unwrap(option, default) {
+                      when option is {
+                        Some(a) -> a
+                        None -> default
+                      }
+                    }
+                    "#,
+                ),
+                (
+                    "validators/always.ak",
+                    r#"
+                    use test/math
+
+                    pub fn spend(datum: Int, redeemer: Int, _ctx: Data) -> Bool {
+                      math.double(datum) == redeemer
+                    }
+                    "#,
+                ),
+            ],
+        );
+
+        let artifacts = Project::new(root.clone())
+            .unwrap()
+            .build(Options::default())
+            .unwrap();
+
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].module, "always");
+        assert_eq!(artifacts[0].name, "spend");
+
+        let directory = root.join("artifacts/always/spend");
+
+        assert_eq!(
+            fs::read(directory.join("script.flat")).unwrap(),
+            artifacts[0].flat
+        );
+        assert_eq!(
+            fs::read_to_string(directory.join("hash.txt")).unwrap(),
+            hex::encode(artifacts[0].hash)
+        );
+
+        let blueprint: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("plutus.json")).unwrap()).unwrap();
+
+        assert_eq!(blueprint["preamble"]["plutusVersion"], "v2");
+        assert_eq!(blueprint["preamble"]["compiler"]["name"], "aiken");
+        assert_eq!(blueprint["preamble"]["options"]["optimize"], false);
+        assert_eq!(
+            blueprint["preamble"]["sourceHash"].as_str().unwrap().len(),
+            64
+        );
+        assert_eq!(blueprint["validators"][0]["title"], "always.spend");
+        assert_eq!(blueprint["validators"][0]["datum"]["title"], "datum");
+        assert_eq!(
+            blueprint["validators"][0]["redeemer"]["schema"]["dataType"],
+            "integer"
+        );
+        assert_eq!(
+            blueprint["validators"][0]["hash"],
+            hex::encode(artifacts[0].hash)
+        );
+        assert!(blueprint["validators"][0]["compiledCode"]
+            .as_str()
+            .unwrap()
+            .ends_with(&hex::encode(&artifacts[0].cbor)));
+
+        let run = |datum: i64, redeemer: i64| {
+            let program: Program<NamedDeBruijn> = artifacts[0]
+                .program
+                .apply_data(PlutusData::Integer(datum.into()))
+                .apply_data(PlutusData::Integer(redeemer.into()))
+                .apply_data(PlutusData::Constr {
+                    tag: 0,
+                    fields: vec![],
+                })
+                .into();
+
+            program.eval()
+        };
+
+        assert_eq!(run(21, 42).unwrap(), Term::Constant(Constant::Unit));
+        assert!(run(21, 41).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn verify() {
+        let validator =
+            "pub fn spend(datum: Int, redeemer: Int, _ctx: Data) -> Bool { datum == redeemer }";
+
+        let root = project(
+            "verify",
+            &[("aiken.toml", MANIFEST), ("validators/equal.ak", validator)],
+        );
+
+        let project = Project::new(root.clone()).unwrap();
+
+        assert!(matches!(project.verify(), Err(Error::FileIo { .. })));
+
+        let options = Options { optimize: true };
+
+        let built = project.build(options).unwrap();
+
+        // compiled again with the recorded options
+        assert_eq!(project.verify().unwrap(), built);
+
+        let blueprint = root.join("plutus.json");
+        let json = fs::read_to_string(&blueprint).unwrap();
+
+        fs::write(&blueprint, json.replace(&hex::encode(&built[0].cbor), "00")).unwrap();
+
+        match project.verify() {
+            Err(Error::Unreproducible { mismatches, .. }) => {
+                assert_eq!(mismatches, vec!["`equal.spend` compiles to different code"])
+            }
+            result => panic!("expected a mismatch, got {result:?}"),
+        }
+
+        fs::write(&blueprint, json).unwrap();
+        fs::write(root.join("validators/equal.ak"), format!("{validator}\n")).unwrap();
+
+        match project.verify() {
+            Err(Error::Unreproducible { mismatches, .. }) => assert_eq!(
+                mismatches,
+                vec!["the sources changed since the blueprint was written"]
+            ),
+            result => panic!("expected a mismatch, got {result:?}"),
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn cache() {
+        let validator =
+            "pub fn spend(datum: Int, redeemer: Int, _ctx: Data) -> Bool { datum == redeemer }";
+
+        let root = project(
+            "cache",
+            &[("aiken.toml", MANIFEST), ("validators/equal.ak", validator)],
+        );
+
+        let project = Project::new(root.clone()).unwrap();
+        let cache = project.cache();
+
+        let entries = |cache: &Cache| -> Vec<usize> {
+            cache
+                .usage()
+                .unwrap()
+                .iter()
+                .map(|usage| usage.entries)
+                .collect()
+        };
+
+        assert_eq!(entries(&cache), vec![0, 0, 0, 0]);
+
+        let built = project.build(Options::default()).unwrap();
+
+        assert_eq!(entries(&cache), vec![1, 1, 1, 0]);
+
+        // a second build takes the blueprint from the cache
+        let builds = root.join(cache::DIRECTORY).join("builds");
+        let entry = fs::read_dir(&builds)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let json = fs::read_to_string(&entry).unwrap();
+
+        fs::write(
+            &entry,
+            json.replace("\"title\": \"test\"", "\"title\": \"cached\""),
+        )
+        .unwrap();
+
+        assert_eq!(project.build(Options::default()).unwrap(), built);
+        assert!(fs::read_to_string(root.join("plutus.json"))
+            .unwrap()
+            .contains("\"cached\""));
+
+        // other options or sources miss it
+        project.build(Options { optimize: true }).unwrap();
+
+        assert_eq!(entries(&cache)[..2], [2, 2]);
+
+        // the validator itself didn't change
+        fs::write(root.join("validators/equal.ak"), format!("{validator}\n")).unwrap();
+
+        assert_eq!(project.build(Options::default()).unwrap(), built);
+        assert_eq!(entries(&cache)[..2], [3, 2]);
+        assert!(fs::read_to_string(root.join("plutus.json"))
+            .unwrap()
+            .contains("\"title\": \"test\""));
+
+        let arguments = [PlutusData::Integer(42.into())];
+
+        let applied = project.apply(Some("spend"), &arguments).unwrap();
+
+        assert_eq!(entries(&cache)[3], 1);
+        assert_eq!(project.apply(None, &arguments).unwrap(), applied);
+        assert_eq!(
+            applied.program,
+            Program::<DeBruijn>::from_flat(&built[0].flat)
+                .unwrap()
+                .apply_data(arguments[0].clone())
+        );
+
+        // a blueprint whose hash isn't hex is refused, instead of
+        // being cached under the key of an empty hash
+        let blueprint = fs::read_to_string(root.join("plutus.json")).unwrap();
+
+        fs::write(
+            root.join("plutus.json"),
+            blueprint.replace(&hex::encode(built[0].hash), "not hex"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            project.apply(None, &arguments),
+            Err(Error::InvalidScript { .. })
+        ));
+
+        fs::write(root.join("plutus.json"), blueprint).unwrap();
+
+        cache.clear().unwrap();
+
+        assert_eq!(entries(&cache), vec![0, 0, 0, 0]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn validator_cache() {
+        let root = project(
+            "validator_cache",
+            &[
+                ("aiken.toml", MANIFEST),
+                (
+                    "lib/test/types.ak",
+                    "pub type Datum { Datum { owner: ByteArray } }",
+                ),
+                (
+                    "lib/test/helpers.ak",
+                    "pub fn check(n: Int) { n > 0 } pub fn other() { 1 }",
+                ),
+                (
+                    "validators/positive.ak",
+                    "use test/helpers pub fn spend(_d: Data, n: Int, _ctx: Data) { helpers.check(n) }",
+                ),
+                (
+                    "validators/owner.ak",
+                    "use test/types.{Datum} pub fn spend(d: Datum, _r: Data, _ctx: Data) { d.owner == #\"ab\" }",
+                ),
+            ],
+        );
+
+        let project = Project::new(root.clone()).unwrap();
+
+        let validators = || {
+            fs::read_dir(root.join(cache::DIRECTORY).join("validators"))
+                .unwrap()
+                .count()
+        };
+
+        project.build(Options::default()).unwrap();
+
+        assert_eq!(validators(), 2);
+
+        // a function no validator uses
+        fs::write(
+            root.join("lib/test/helpers.ak"),
+            "pub fn check(n: Int) { n > 0 } pub fn other() { 2 }",
+        )
+        .unwrap();
+
+        project.build(Options::default()).unwrap();
+
+        assert_eq!(validators(), 2);
+
+        // a function only one of them uses
+        fs::write(
+            root.join("lib/test/helpers.ak"),
+            "pub fn check(n: Int) { n >= 0 } pub fn other() { 2 }",
+        )
+        .unwrap();
+
+        project.build(Options::default()).unwrap();
+
+        assert_eq!(validators(), 3);
+
+        // a type the other one takes
+        fs::write(
+            root.join("lib/test/types.ak"),
+            "pub type Datum { Datum { amount: Int, owner: ByteArray } }",
+        )
+        .unwrap();
+
+        let artifacts = project.build(Options::default()).unwrap();
+
+        assert_eq!(validators(), 4);
+
+        // what was put together from the cache is what compiling
+        // everything again gives
+        assert_eq!(project.verify().unwrap(), artifacts);
+
+        let blueprint: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("plutus.json")).unwrap()).unwrap();
+
+        assert!(blueprint["definitions"]["test/types/Datum"].is_object());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn import_cycle() {
+        let root = project(
+            "import_cycle",
+            &[
+                ("aiken.toml", MANIFEST),
+                ("lib/a.ak", "use b pub fn a() { 1 }"),
+                ("lib/b.ak", "use c pub fn b() { 1 }"),
+                ("lib/c.ak", "use a pub fn c() { 1 }"),
+            ],
+        );
+
+        let error = Project::new(root.clone())
+            .unwrap()
+            .build(Options::default())
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Import cycle, `a` imports itself through a -> b -> c -> a"
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let root = project(
+            "errors",
+            &[
+                ("aiken.toml", MANIFEST),
+                ("validators/bad.ak", "pub fn spend() { 1 + True }"),
+            ],
+        );
+
+        let error = Project::new(root.clone())
+            .unwrap()
+            .build(Options::default())
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Type { .. }));
+
+        fs::write(root.join("aiken.toml"), "name = 1").unwrap();
+
+        assert!(matches!(
+            Project::new(root.clone()),
+            Err(Error::Manifest { .. })
+        ));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn check_reporting() {
+        let root = project(
+            "check_reporting",
+            &[
+                ("aiken.toml", MANIFEST),
+                ("lib/broken.ak", "pub fn broken( { 1 }"),
+                ("lib/fine.ak", "pub fn fine() { 1 }"),
+                ("lib/uses_broken.ak", "use broken pub fn f() { 1 + True }"),
+                (
+                    "validators/bad.ak",
+                    "use fine pub fn spend() { fine.fine() + True }",
+                ),
+            ],
+        );
+
+        let project = Project::new(root.clone()).unwrap();
+
+        let mut errors = vec![];
+
+        let checked = project.check_reporting(&mut |error| errors.push(error));
+
+        let names: Vec<_> = checked
+            .iter()
+            .map(|module| module.ast.name.as_str())
+            .collect();
+
+        assert_eq!(names, vec!["fine"]);
+
+        // nothing about the module importing the broken one
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], Error::Parse { path, .. } if path.ends_with("broken.ak")));
+        assert!(matches!(&errors[1], Error::Type { path, .. } if path.ends_with("bad.ak")));
+
+        assert!(matches!(project.check(), Err(Error::Parse { .. })));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use aiken_lang::ast::{ModuleKind, TypedModule, UntypedModule};
+use petgraph::{algo, graph::NodeIndex, Direction, Graph};
+use walkdir::WalkDir;
+
+use crate::error::Error;
+
+/// The extension of Aiken source files.
+pub const EXTENSION: &str = "ak";
+
+/// A source file of the project, before it is parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub path: PathBuf,
+    /// The module path, like `aiken/list` for `lib/aiken/list.ak`.
+    pub name: String,
+    pub code: String,
+    pub kind: ModuleKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedModule {
+    pub path: PathBuf,
+    pub code: String,
+    pub ast: UntypedModule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedModule {
+    pub path: PathBuf,
+    pub code: String,
+    pub ast: TypedModule,
+}
+
+/// Read every `.ak` file under `directory`, naming modules after
+/// their path relative to it.
+pub fn read_sources(directory: &Path, kind: ModuleKind) -> Result<Vec<Source>, Error> {
+    if !directory.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut sources = vec![];
+
+    for entry in WalkDir::new(directory).sort_by_file_name() {
+        let entry = entry.map_err(|error| Error::FileIo {
+            path: error.path().unwrap_or(directory).to_path_buf(),
+            error: error.into(),
+        })?;
+
+        let path = entry.path();
+
+        if !entry.file_type().is_file()
+            || path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION)
+        {
+            continue;
+        }
+
+        let name = path
+            .strip_prefix(directory)
+            .expect("walked paths are under the directory")
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let code = fs::read_to_string(path).map_err(|error| Error::FileIo {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        sources.push(Source {
+            path: path.to_path_buf(),
+            name,
+            code,
+            kind,
+        });
+    }
+
+    Ok(sources)
+}
+
+/// Sort modules so that each comes after the modules it imports.
+/// Imports of modules that aren't part of the project are left for
+/// the type checker to report.
+pub fn sort_dependencies(modules: Vec<ParsedModule>) -> Result<Vec<ParsedModule>, Error> {
+    let mut graph = Graph::<usize, ()>::new();
+
+    let indices: HashMap<&str, NodeIndex> = modules
+        .iter()
+        .enumerate()
+        .map(|(index, module)| (module.ast.name.as_str(), graph.add_node(index)))
+        .collect();
+
+    for module in &modules {
+        let importer = indices[module.ast.name.as_str()];
+
+        for (dependency, _) in module.ast.dependencies() {
+            if let Some(imported) = indices.get(dependency.as_str()) {
+                graph.add_edge(*imported, importer, ());
+            }
+        }
+    }
+
+    let order = algo::toposort(&graph, None).map_err(|cycle| Error::ImportCycle {
+        modules: import_cycle(&graph, &modules, cycle.node_id()),
+    })?;
+
+    let mut modules: Vec<Option<ParsedModule>> = modules.into_iter().map(Some).collect();
+
+    Ok(order
+        .into_iter()
+        .map(|node| {
+            modules[graph[node]]
+                .take()
+                .expect("each module is sorted once")
+        })
+        .collect())
+}
+
+/// The names of the modules in a cycle going through `start`,
+/// starting and ending with it.
+fn import_cycle(
+    graph: &Graph<usize, ()>,
+    modules: &[ParsedModule],
+    start: NodeIndex,
+) -> Vec<String> {
+    let name = |node: NodeIndex| modules[graph[node]].ast.name.clone();
+
+    let mut path = vec![start];
+    let mut visited = vec![start];
+
+    fn search(
+        graph: &Graph<usize, ()>,
+        start: NodeIndex,
+        path: &mut Vec<NodeIndex>,
+        visited: &mut Vec<NodeIndex>,
+    ) -> bool {
+        let current = *path.last().expect("the path starts with `start`");
+
+        for next in graph.neighbors_directed(current, Direction::Outgoing) {
+            if next == start {
+                return true;
+            }
+
+            if !visited.contains(&next) {
+                visited.push(next);
+                path.push(next);
+
+                if search(graph, start, path, visited) {
+                    return true;
+                }
+
+                path.pop();
+            }
+        }
+
+        false
+    }
+
+    search(graph, start, &mut path, &mut visited);
+
+    // edges point from imported to importer, list importers first
+    path.reverse();
+
+    let mut names: Vec<String> = path.into_iter().map(name).collect();
+
+    // start from the same module whichever one toposort stopped at
+    let first = (0..names.len())
+        .min_by_key(|index| &names[*index])
+        .expect("a cycle has modules");
+    names.rotate_left(first);
+    names.push(names[0].clone());
+
+    names
+}
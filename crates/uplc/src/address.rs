@@ -0,0 +1,39 @@
+use bech32::{ToBase32, Variant};
+use strum_macros::EnumString;
+
+/// The networks addresses can be built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// The network id stored in the low bits of an address header.
+    pub fn id(&self) -> u8 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Testnet => 0,
+        }
+    }
+
+    /// The human readable part of bech32 addresses on this network.
+    pub fn hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "addr",
+            Network::Testnet => "addr_test",
+        }
+    }
+}
+
+/// The bech32 enterprise address of a script, one without a
+/// staking part, locking outputs to the script with `script_hash`.
+pub fn script_address(script_hash: &[u8], network: Network) -> String {
+    let mut bytes = vec![0b0111_0000 | network.id()];
+
+    bytes.extend(script_hash);
+
+    bech32::encode(network.hrp(), bytes.to_base32(), Variant::Bech32)
+        .expect("address human readable parts are valid")
+}
@@ -0,0 +1,296 @@
+//! Summaries of what a program is made of, e.g. which builtins a
+//! script uses and how much of it's flat size is constants.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display},
+};
+
+use flat::en::{self, Encoder};
+
+use crate::{
+    ast::{visit::Visitor, Constant, Program, Term},
+    flat::Binder,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProgramStats {
+    pub version: (usize, usize, usize),
+    /// Length in bytes of the flat encoded program.
+    pub flat_size: usize,
+    pub terms: TermCounts,
+    /// How many times each builtin is referenced, by name.
+    pub builtins: BTreeMap<String, usize>,
+    /// The constants by their type, e.g. `integer` or `list data`.
+    pub constants: BTreeMap<String, ConstantStats>,
+    pub depth: Depth,
+}
+
+/// How many terms of each kind there are.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TermCounts {
+    pub var: usize,
+    pub delay: usize,
+    pub lambda: usize,
+    pub apply: usize,
+    pub constant: usize,
+    pub force: usize,
+    pub error: usize,
+    pub builtin: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConstantStats {
+    pub count: usize,
+    /// Bits the constants take flat encoded, their types included but
+    /// not the tags of the terms holding them. Byte strings are
+    /// measured as if their padding started on a byte boundary, so
+    /// this is within a byte per constant of their share of the program.
+    pub flat_bits: usize,
+}
+
+/// The deepest nesting of terms in the program.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Depth {
+    /// Of terms of any kind.
+    pub term: usize,
+    /// Of lambdas, i.e. the most variables in scope at once.
+    pub lambda: usize,
+    /// Of applications, `[f a b]` being two.
+    pub apply: usize,
+}
+
+impl ProgramStats {
+    /// Bits taken by all the constants flat encoded.
+    pub fn constant_bits(&self) -> usize {
+        self.constants.values().map(|stats| stats.flat_bits).sum()
+    }
+}
+
+impl<'b, T> Program<T>
+where
+    T: Binder<'b> + Debug,
+{
+    pub fn stats(&self) -> Result<ProgramStats, en::Error> {
+        let mut collector = Collector::default();
+
+        self.term.traverse(&mut collector);
+
+        let mut constants: BTreeMap<String, ConstantStats> = BTreeMap::new();
+
+        for constant in collector.constants {
+            let mut e = Encoder::new();
+
+            e.encode(constant)?;
+
+            let stats = constants.entry(constant.ty().to_string()).or_default();
+
+            stats.count += 1;
+            stats.flat_bits += e.bits_written();
+        }
+
+        Ok(ProgramStats {
+            version: self.version,
+            flat_size: self.to_flat()?.len(),
+            terms: collector.terms,
+            builtins: collector.builtins,
+            constants,
+            depth: collector.max,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Collector<'a> {
+    terms: TermCounts,
+    builtins: BTreeMap<String, usize>,
+    constants: Vec<&'a Constant>,
+    /// The nesting of the term being visited.
+    current: Depth,
+    max: Depth,
+}
+
+impl<'a, T> Visitor<'a, T> for Collector<'a> {
+    fn enter(&mut self, term: &'a Term<T>) -> bool {
+        self.current.term += 1;
+
+        match term {
+            Term::Var(_) => self.terms.var += 1,
+            Term::Delay(_) => self.terms.delay += 1,
+            Term::Lambda { .. } => {
+                self.terms.lambda += 1;
+                self.current.lambda += 1;
+            }
+            Term::Apply { .. } => {
+                self.terms.apply += 1;
+                self.current.apply += 1;
+            }
+            Term::Constant(constant) => {
+                self.terms.constant += 1;
+                self.constants.push(constant);
+            }
+            Term::Force(_) => self.terms.force += 1,
+            Term::Error => self.terms.error += 1,
+            Term::Builtin(builtin) => {
+                self.terms.builtin += 1;
+                *self.builtins.entry(builtin.to_string()).or_default() += 1;
+            }
+        }
+
+        self.max.term = self.max.term.max(self.current.term);
+        self.max.lambda = self.max.lambda.max(self.current.lambda);
+        self.max.apply = self.max.apply.max(self.current.apply);
+
+        true
+    }
+
+    fn leave(&mut self, term: &'a Term<T>) {
+        self.current.term -= 1;
+
+        match term {
+            Term::Lambda { .. } => self.current.lambda -= 1,
+            Term::Apply { .. } => self.current.apply -= 1,
+            _ => (),
+        }
+    }
+}
+
+impl Display for ProgramStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor, patch) = self.version;
+
+        let bytes = |bits: usize| bits.div_ceil(8);
+
+        writeln!(f, "version     {major}.{minor}.{patch}")?;
+        writeln!(f, "flat size   {} bytes", self.flat_size)?;
+        writeln!(
+            f,
+            "constants   {} bytes ({}%)",
+            bytes(self.constant_bits()),
+            (self.constant_bits() * 100)
+                .checked_div(self.flat_size * 8)
+                .unwrap_or(0)
+        )?;
+
+        let TermCounts {
+            var,
+            delay,
+            lambda,
+            apply,
+            constant,
+            force,
+            error,
+            builtin,
+        } = &self.terms;
+
+        writeln!(f, "\nterms")?;
+
+        for (kind, count) in [
+            ("var", var),
+            ("delay", delay),
+            ("lambda", lambda),
+            ("apply", apply),
+            ("constant", constant),
+            ("force", force),
+            ("error", error),
+            ("builtin", builtin),
+        ] {
+            writeln!(f, "  {kind:<30} {count}")?;
+        }
+
+        if !self.builtins.is_empty() {
+            writeln!(f, "\nbuiltins")?;
+
+            for (name, count) in &self.builtins {
+                writeln!(f, "  {name:<30} {count}")?;
+            }
+        }
+
+        if !self.constants.is_empty() {
+            writeln!(f, "\nconstants")?;
+
+            for (ty, stats) in &self.constants {
+                writeln!(
+                    f,
+                    "  {ty:<30} {:<6} {} bytes",
+                    stats.count,
+                    bytes(stats.flat_bits)
+                )?;
+            }
+        }
+
+        writeln!(f, "\ndepth")?;
+        writeln!(f, "  {:<30} {}", "term", self.depth.term)?;
+        writeln!(f, "  {:<30} {}", "lambda", self.depth.lambda)?;
+        write!(f, "  {:<30} {}", "apply", self.depth.apply)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        ast::{DeBruijn, Program},
+        parser,
+    };
+
+    use super::{ConstantStats, Depth, TermCounts};
+
+    #[test]
+    fn stats() {
+        let program: Program<DeBruijn> = parser::program(
+            "(program 1.0.0
+              (lam x
+                (lam y
+                  [(builtin addInteger)
+                    [(builtin addInteger) x (con integer 1)]
+                    (force (delay [(builtin unIData) (con data (I 2))]))])))",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let stats = program.stats().unwrap();
+
+        assert_eq!(stats.flat_size, program.to_flat().unwrap().len());
+        assert_eq!(
+            stats.terms,
+            TermCounts {
+                var: 1,
+                delay: 1,
+                lambda: 2,
+                apply: 5,
+                constant: 2,
+                force: 1,
+                error: 0,
+                builtin: 3,
+            }
+        );
+        assert_eq!(
+            stats.builtins,
+            BTreeMap::from([("addInteger".to_string(), 2), ("unIData".to_string(), 1)])
+        );
+        // a type tag list of 6 bits and the integer in a byte
+        assert_eq!(
+            stats.constants["integer"],
+            ConstantStats {
+                count: 1,
+                flat_bits: 14
+            }
+        );
+        assert_eq!(stats.constants["data"].count, 1);
+        assert_eq!(
+            stats.depth,
+            Depth {
+                term: 7,
+                lambda: 2,
+                apply: 4
+            }
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use std::{collections::HashMap, rc::Rc};
+
+use miette::SourceSpan;
+use thiserror::Error;
+
+use crate::{
+    ast::{visit::Visitor, DeBruijn, Name, Program, Term, Unique},
+    debruijn,
+    parser::{Comment, Spans},
+};
+
+/// Metadata that is thrown away when a `Program<Name>` is
+/// converted to it's on chain `DeBruijn` form. It is meant to be
+/// persisted alongside the flat bytes (see `to_flat`) so that a
+/// decoded program can get it's original names back.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Annotations {
+    /// Original lambda parameter names in the order the lambdas
+    /// are reached by a pre-order walk of the program's term.
+    pub binders: Vec<Name>,
+    /// Where each of the `binders` was in the source, empty unless the
+    /// annotations were collected with `from_source`.
+    pub spans: Vec<SourceSpan>,
+    /// The comments of the source, which no term can hold.
+    pub comments: Vec<Comment>,
+    /// Where each of the `comments` goes back, empty unless the
+    /// annotations were collected with `from_source`.
+    pub placements: Vec<Placement>,
+}
+
+/// Where a comment was relative to the terms of the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Before the program.
+    Leading,
+    /// Before the term reached `n`th by a pre-order walk of the
+    /// program's term, counting from 0.
+    Before(usize),
+    /// After the last term.
+    Trailing,
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Annotations contain {expected} binders but the program has {found} lambdas")]
+    BinderCountMismatch { expected: usize, found: usize },
+    #[error(transparent)]
+    DeBruijn(#[from] debruijn::Error),
+}
+
+impl Annotations {
+    /// Collect the annotations of a parsed program.
+    pub fn from_program(program: &Program<Name>) -> Self {
+        let mut binders = Vec::new();
+
+        collect_binders(&program.term, &mut binders);
+
+        Annotations {
+            binders,
+            ..Annotations::default()
+        }
+    }
+
+    /// Collect the annotations of a program parsed with
+    /// `parser::program_with_spans`, keeping where it's binders were
+    /// and it's comments.
+    pub fn from_source(program: &Program<Name>, spans: Spans) -> Self {
+        let placements = spans
+            .comments
+            .iter()
+            .map(|comment| placement(comment, &spans))
+            .collect();
+
+        Annotations {
+            spans: spans.binders,
+            comments: spans.comments,
+            placements,
+            ..Annotations::from_program(program)
+        }
+    }
+
+    /// The original lambda parameter names, usable as hints for
+    /// `Program::<DeBruijn>::to_named_debruijn_with_hints` when the
+    /// program has been transformed and can no longer be restored exactly.
+    pub fn name_hints(&self) -> Vec<String> {
+        self.binders.iter().map(|name| name.text.clone()).collect()
+    }
+
+    /// Convert a `DeBruijn` program back to a `Program<Name>`
+    /// using the names recorded in these annotations.
+    pub fn restore(&self, program: Program<DeBruijn>) -> Result<Program<Name>, Error> {
+        let mut program: Program<Name> = program.try_into()?;
+
+        let mut fresh_binders = Vec::new();
+
+        collect_binders(&program.term, &mut fresh_binders);
+
+        if fresh_binders.len() != self.binders.len() {
+            return Err(Error::BinderCountMismatch {
+                expected: self.binders.len(),
+                found: fresh_binders.len(),
+            });
+        }
+
+        let renames: HashMap<Unique, &Name> = fresh_binders
+            .into_iter()
+            .map(|name| name.unique)
+            .zip(self.binders.iter())
+            .collect();
+
+        rename(&mut program.term, &renames);
+
+        Ok(program)
+    }
+
+    /// Print a program restored with these annotations, with the
+    /// comments put back where they were. Comments without a
+    /// placement go before the program.
+    pub fn to_pretty(&self, program: &Program<Name>) -> String {
+        program.to_pretty_with_comments(self.comments.iter().enumerate().map(|(i, comment)| {
+            let placement = self.placements.get(i).copied();
+
+            (
+                placement.unwrap_or(Placement::Leading),
+                comment.text.as_str(),
+            )
+        }))
+    }
+}
+
+/// Where `comment` was, it goes before the first term after it.
+fn placement(comment: &Comment, spans: &Spans) -> Placement {
+    let end = comment.span.offset() + comment.span.len();
+
+    if spans.program.is_some_and(|program| end <= program.offset()) {
+        return Placement::Leading;
+    }
+
+    spans
+        .terms
+        .iter()
+        .position(|term| term.offset() >= end)
+        .map_or(Placement::Trailing, Placement::Before)
+}
+
+/// The lambda parameters of a term, in pre-order.
+struct Binders<'a>(&'a mut Vec<Name>);
+
+impl<'a> Visitor<'a, Name> for Binders<'_> {
+    fn enter(&mut self, term: &'a Term<Name>) -> bool {
+        if let Term::Lambda { parameter_name, .. } = term {
+            self.0.push(parameter_name.clone());
+        }
+
+        true
+    }
+}
+
+fn collect_binders(term: &Term<Name>, binders: &mut Vec<Name>) {
+    term.traverse(&mut Binders(binders));
+}
+
+fn rename(term: &mut Term<Name>, renames: &HashMap<Unique, &Name>) {
+    match term {
+        Term::Var(name) => {
+            if let Some(original) = renames.get(&name.unique) {
+                *name = (*original).clone();
+            }
+        }
+        Term::Delay(term) => rename(Rc::make_mut(term), renames),
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => {
+            if let Some(original) = renames.get(&parameter_name.unique) {
+                *parameter_name = (*original).clone();
+            }
+
+            rename(Rc::make_mut(body), renames);
+        }
+        Term::Apply { function, argument } => {
+            rename(Rc::make_mut(function), renames);
+            rename(Rc::make_mut(argument), renames);
+        }
+        Term::Constant(_) => (),
+        Term::Force(term) => rename(Rc::make_mut(term), renames),
+        Term::Error => (),
+        Term::Builtin(_) => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{DeBruijn, NamedDeBruijn, PlutusVersion, Program, Term},
+        optimize::Pass,
+        parser,
+    };
+
+    use super::{Annotations, Placement};
+
+    #[test]
+    fn restore_names() {
+        let code = r#"
+        (program 1.0.0
+            (lam x (lam y [ x (lam x [ x y ]) ]))
+        )
+        "#;
+
+        let parsed_program = parser::program(code).unwrap();
+
+        let annotations = Annotations::from_program(&parsed_program);
+
+        let debruijn_program: Program<DeBruijn> = parsed_program.clone().try_into().unwrap();
+
+        let restored_program = annotations.restore(debruijn_program).unwrap();
+
+        assert_eq!(restored_program, parsed_program);
+    }
+
+    #[test]
+    fn name_hints() {
+        let code = r#"
+        (program 1.0.0
+            (lam x (lam y [ x (lam z [ z y ]) ]))
+        )
+        "#;
+
+        let parsed_program = parser::program(code).unwrap();
+
+        let hints = Annotations::from_program(&parsed_program).name_hints();
+
+        let debruijn_program: Program<DeBruijn> = parsed_program.clone().try_into().unwrap();
+
+        let named_program = debruijn_program
+            .clone()
+            .to_named_debruijn_with_hints(hints.clone());
+
+        let expected_program: Program<NamedDeBruijn> = parsed_program.try_into().unwrap();
+
+        assert_eq!(named_program, expected_program);
+
+        let program = debruijn_program
+            .to_name_with_hints(hints[..1].to_vec())
+            .unwrap();
+
+        let code = r#"
+        (program 1.0.0
+            (lam x (lam i_1 [ x (lam i_2 [ i_2 i_1 ]) ]))
+        )
+        "#;
+
+        assert_eq!(program, parser::program(code).unwrap());
+    }
+
+    #[test]
+    fn optimized_round_trip() {
+        let code = r#"
+        (program 1.0.0
+            (lam x [ (lam x x) (lam y x) ])
+        )
+        "#;
+
+        let parsed_program = parser::program(code).unwrap();
+
+        let hints = Annotations::from_program(&parsed_program).name_hints();
+
+        // the second hint is for the inlined `(lam x x)`, but names
+        // `(lam y x)` now
+        let optimized = parsed_program.optimize(&Pass::ALL, PlutusVersion::V2);
+
+        let debruijn_program: Program<DeBruijn> = optimized.try_into().unwrap();
+
+        let program = debruijn_program
+            .clone()
+            .to_name_with_hints(hints.clone())
+            .unwrap();
+
+        let pretty = program.to_pretty();
+
+        assert_eq!(
+            pretty,
+            parser::program("(program 1.0.0 (lam x (lam x_1 x)))")
+                .unwrap()
+                .to_pretty()
+        );
+
+        let reparsed: Program<DeBruijn> = parser::program(&pretty).unwrap().try_into().unwrap();
+
+        assert_eq!(reparsed, debruijn_program);
+
+        let named_program = debruijn_program.to_named_debruijn_with_hints(hints);
+
+        let Term::Lambda { body, .. } = named_program.term else {
+            panic!("expected a lambda")
+        };
+
+        let Term::Lambda { parameter_name, .. } = body.as_ref() else {
+            panic!("expected a lambda")
+        };
+
+        assert_eq!(parameter_name.text, "x_1");
+    }
+
+    #[test]
+    fn from_source() {
+        let code = "-- identity\n(program 1.0.0 (lam x {- the parameter -} x))";
+
+        let (program, spans) = parser::program_with_spans(code).unwrap();
+
+        let annotations = Annotations::from_source(&program, spans);
+
+        assert_eq!(annotations.binders.len(), 1);
+        assert_eq!(annotations.spans, vec![(32, 1).into()]);
+        assert_eq!(
+            annotations
+                .comments
+                .iter()
+                .map(|comment| (comment.span, comment.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ((0, 11).into(), "-- identity"),
+                ((34, 19).into(), "{- the parameter -}")
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_round_trip() {
+        let code = r#"
+        -- apply x to y
+        (program 1.0.0
+            [
+                (lam x {- x is a function -} (lam y [ x -- the function
+                    y ]))
+                {- the identity -} (lam z z)
+                (con integer 1)
+            ]
+        )
+        -- the end
+        "#;
+
+        let (parsed_program, spans) = parser::program_with_spans(code).unwrap();
+
+        let annotations = Annotations::from_source(&parsed_program, spans);
+
+        assert_eq!(
+            annotations.placements,
+            vec![
+                Placement::Leading,
+                Placement::Before(3),
+                Placement::Before(6),
+                Placement::Before(7),
+                Placement::Trailing
+            ]
+        );
+
+        // as `uplc flat -a` and `uplc unflat -a` do
+        let program: Program<DeBruijn> = parsed_program.clone().try_into().unwrap();
+
+        let bytes = program.to_flat().unwrap();
+        let annotation_bytes = annotations.to_flat().unwrap();
+
+        let annotations = Annotations::from_flat(&annotation_bytes).unwrap();
+        let restored = annotations
+            .restore(Program::<DeBruijn>::from_flat(&bytes).unwrap())
+            .unwrap();
+
+        let printed = annotations.to_pretty(&restored);
+
+        assert_eq!(
+            printed,
+            r#"-- apply x to y
+(program 1.0.0
+  [
+    (lam x
+      {- x is a function -}
+      (lam y
+        [
+          x
+          -- the function
+          y
+        ]
+      )
+    )
+    {- the identity -}
+    (lam z z)
+    (con integer 1)
+  ]
+)
+-- the end"#
+        );
+
+        let (reparsed, spans) = parser::program_with_spans(&printed).unwrap();
+
+        let reannotated = Annotations::from_source(&reparsed, spans);
+
+        // the same comments in the same places, at other offsets
+        assert_eq!(reparsed, restored);
+        assert_eq!(reannotated.placements, annotations.placements);
+        assert_eq!(
+            reannotated
+                .comments
+                .iter()
+                .map(|c| &c.text)
+                .collect::<Vec<_>>(),
+            annotations
+                .comments
+                .iter()
+                .map(|c| &c.text)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn flat_round_trip() {
+        let code = r#"
+        -- apply x to y
+        (program 1.0.0
+            (lam x (lam y [ x y ]))
+        )
+        "#;
+
+        let (parsed_program, spans) = parser::program_with_spans(code).unwrap();
+
+        let annotations = Annotations::from_source(&parsed_program, spans);
+
+        let bytes = annotations.to_flat().unwrap();
+
+        assert_eq!(Annotations::from_flat(&bytes).unwrap(), annotations);
+    }
+}
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use proptest::{collection::vec, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    ast::{Constant, DeBruijn, Name, Program, Term, Type},
+    builtins::DefaultFunction,
+    data::PlutusData,
+    parser::Interner,
+};
+
+/// How deeply generated terms nest.
+const MAX_DEPTH: u32 = 4;
+
+/// The names lambdas are given, few enough that shadowing happens.
+const NAMES: [&str; 4] = ["a", "b", "x", "y"];
+
+/// Well scoped programs with uniques assigned like the parser does.
+pub fn program() -> impl Strategy<Value = Program<Name>> {
+    term(MAX_DEPTH, Vec::new()).prop_map(|term| {
+        let mut program = Program {
+            version: (1, 0, 0),
+            term,
+        };
+
+        Interner::new().program(&mut program);
+
+        program
+    })
+}
+
+/// Well scoped programs in the form they are encoded to flat.
+pub fn debruijn_program() -> impl Strategy<Value = Program<DeBruijn>> {
+    program().prop_map(|program| {
+        program
+            .try_into()
+            .expect("generated programs are well scoped")
+    })
+}
+
+/// A term only referring to variables in `scope`.
+pub fn term(depth: u32, scope: Vec<&'static str>) -> BoxedStrategy<Term<Name>> {
+    let mut leaves = vec![
+        constant().prop_map(Term::Constant).boxed(),
+        builtin().prop_map(Term::Builtin).boxed(),
+        Just(Term::Error).boxed(),
+    ];
+
+    if !scope.is_empty() {
+        leaves.push(
+            proptest::sample::select(scope.clone())
+                .prop_map(|text| Term::Var(name(text)))
+                .boxed(),
+        );
+    }
+
+    let leaf = proptest::strategy::Union::new(leaves).boxed();
+
+    if depth == 0 {
+        return leaf;
+    }
+
+    let outer_scope = scope.clone();
+
+    let lambda = proptest::sample::select(NAMES.to_vec()).prop_flat_map(move |text| {
+        let mut scope = outer_scope.clone();
+
+        scope.push(text);
+
+        term(depth - 1, scope).prop_map(move |body| Term::Lambda {
+            parameter_name: name(text),
+            body: Rc::new(body),
+        })
+    });
+
+    let sub_term = || term(depth - 1, scope.clone());
+
+    prop_oneof![
+        leaf,
+        lambda,
+        sub_term().prop_map(|term| Term::Delay(Rc::new(term))),
+        sub_term().prop_map(|term| Term::Force(Rc::new(term))),
+        (sub_term(), sub_term()).prop_map(|(function, argument)| Term::Apply {
+            function: Rc::new(function),
+            argument: Rc::new(argument),
+        }),
+    ]
+    .boxed()
+}
+
+/// Constants that survive being printed and parsed back, so no
+/// chars.
+pub fn constant() -> impl Strategy<Value = Constant> {
+    let integer = || any::<i128>().prop_map(|i| Constant::Integer(i.into()));
+    let bytestring = || vec(any::<u8>(), 0..40).prop_map(Constant::ByteString);
+
+    prop_oneof![
+        integer(),
+        bytestring(),
+        "(?s).{0,16}".prop_map(Constant::String),
+        Just(Constant::Unit),
+        any::<bool>().prop_map(Constant::Bool),
+        data().prop_map(Constant::Data),
+        vec(integer(), 0..4).prop_map(|items| Constant::ProtoList(Type::Integer, items)),
+        (integer(), bytestring()).prop_map(|(left, right)| Constant::ProtoPair(
+            Type::Integer,
+            Type::ByteString,
+            Box::new(left),
+            Box::new(right)
+        )),
+    ]
+}
+
+pub fn data() -> impl Strategy<Value = PlutusData> {
+    let leaf = prop_oneof![
+        any::<i128>().prop_map(|i| PlutusData::Integer(i.into())),
+        vec(any::<u8>(), 0..80).prop_map(PlutusData::ByteString),
+    ];
+
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            (0..200u64, vec(inner.clone(), 0..4))
+                .prop_map(|(tag, fields)| PlutusData::Constr { tag, fields }),
+            vec((inner.clone(), inner.clone()), 0..4).prop_map(PlutusData::Map),
+            vec(inner, 0..4).prop_map(PlutusData::List),
+        ]
+    })
+}
+
+pub fn builtin() -> impl Strategy<Value = DefaultFunction> {
+    proptest::sample::select(DefaultFunction::iter().collect::<Vec<_>>())
+}
+
+fn name(text: &str) -> Name {
+    Name {
+        text: text.to_string(),
+        unique: 0.into(),
+    }
+}
@@ -1,58 +1,133 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
+
+use num_bigint::BigInt;
+use strum_macros::EnumString;
 
 use crate::{
-    builtins::DefaultFunction,
+    builtins::{DefaultFunction, UnavailableBuiltin},
+    data::PlutusData,
     debruijn::{self, Converter},
+    machine::{
+        self,
+        cost_model::{CostModels, ExBudget},
+        EvalResult, Machine,
+    },
 };
 
+pub mod visit;
+
 /// This represents a program in Untyped Plutus Core.
 /// A program contains a version tuple and a term.
 /// It is generic because Term requires a generic type.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program<T> {
     pub version: (usize, usize, usize),
     pub term: Term<T>,
 }
 
+impl<T> Program<T>
+where
+    T: Clone,
+{
+    /// Apply the program's body to `term`, this is how parameterized
+    /// scripts get their parameters before being hashed and deployed.
+    pub fn apply_term(&self, term: &Term<T>) -> Program<T> {
+        Program {
+            version: self.version,
+            term: Term::Apply {
+                function: Rc::new(self.term.clone()),
+                argument: Rc::new(term.clone()),
+            },
+        }
+    }
+
+    /// Apply the program's body to a `Data` constant.
+    pub fn apply_data(&self, data: PlutusData) -> Program<T> {
+        self.apply_term(&Term::Constant(data.into()))
+    }
+}
+
+impl<T> Program<T> {
+    /// Make sure the program only uses builtins that exist in `version`.
+    pub fn check_version(&self, version: PlutusVersion) -> Result<(), UnavailableBuiltin> {
+        self.term.check_version(version)
+    }
+}
+
 /// This represents a term in Untyped Plutus Core.
 /// We need a generic type for the different forms that a program may be in.
 /// Specifically, `Var` and `parameter_name` in `Lambda` can be a `Name`,
 /// `NamedDebruijn`, or `DeBruijn`. When encoded to flat for on chain usage
 /// we must encode using the `DeBruijn` form.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum Term<T> {
     // tag: 0
     Var(T),
     // tag: 1
-    Delay(Box<Term<T>>),
+    Delay(Rc<Term<T>>),
     // tag: 2
     Lambda {
         parameter_name: T,
-        body: Box<Term<T>>,
+        body: Rc<Term<T>>,
     },
     // tag: 3
     Apply {
-        function: Box<Term<T>>,
-        argument: Box<Term<T>>,
+        function: Rc<Term<T>>,
+        argument: Rc<Term<T>>,
     },
     // tag: 4
     Constant(Constant),
     // tag: 5
-    Force(Box<Term<T>>),
+    Force(Rc<Term<T>>),
     // tag: 6
     Error,
     // tag: 7
     Builtin(DefaultFunction),
 }
 
+impl<T> Term<T> {
+    /// Make sure the term only uses builtins that exist in `version`.
+    pub fn check_version(&self, version: PlutusVersion) -> Result<(), UnavailableBuiltin> {
+        let mut stack = vec![self];
+
+        while let Some(term) = stack.pop() {
+            match term {
+                Term::Builtin(fun) if !fun.is_available_in(version) => {
+                    return Err(UnavailableBuiltin(*fun, version));
+                }
+                Term::Delay(term) | Term::Force(term) => stack.push(term),
+                Term::Lambda { body, .. } => stack.push(body),
+                Term::Apply { function, argument } => {
+                    stack.push(argument);
+                    stack.push(function);
+                }
+                Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// A container for the various constants that are available
 /// in Untyped Plutus Core. Used in the `Constant` variant of `Term`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum Constant {
     // tag: 0
-    Integer(isize),
+    Integer(#[cfg_attr(feature = "serde", serde(with = "crate::json::big_int"))] BigInt),
     // tag: 1
-    ByteString(Vec<u8>),
+    ByteString(#[cfg_attr(feature = "serde", serde(with = "crate::json::bytes"))] Vec<u8>),
     // tag: 2
     String(String),
     // tag: 3
@@ -61,12 +136,70 @@ pub enum Constant {
     Unit,
     // tag: 5
     Bool(bool),
+    // tag: 6
+    ProtoList(Type, Vec<Constant>),
+    // tag: 7
+    ProtoPair(Type, Type, Box<Constant>, Box<Constant>),
+    // tag: 8
+    Data(PlutusData),
+}
+
+impl Constant {
+    /// The type of this constant. There is no char type in the
+    /// spec, so char constants are typed as strings.
+    pub fn ty(&self) -> Type {
+        match self {
+            Constant::Integer(_) => Type::Integer,
+            Constant::ByteString(_) => Type::ByteString,
+            Constant::String(_) | Constant::Char(_) => Type::String,
+            Constant::Unit => Type::Unit,
+            Constant::Bool(_) => Type::Bool,
+            Constant::ProtoList(ty, _) => Type::List(Box::new(ty.clone())),
+            Constant::ProtoPair(left, right, _, _) => {
+                Type::Pair(Box::new(left.clone()), Box::new(right.clone()))
+            }
+            Constant::Data(_) => Type::Data,
+        }
+    }
+}
+
+/// The type of a constant, or of a builtin's argument or result. List
+/// and pair constants carry the types of their elements so that empty
+/// lists can be typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum Type {
+    Bool,
+    Integer,
+    String,
+    ByteString,
+    Unit,
+    List(Box<Type>),
+    Pair(Box<Type>, Box<Type>),
+    Data,
+    /// A type variable bound by a builtin's foralls, see
+    /// `DefaultFunction::signature`. Constants never have one.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Var(#[cfg_attr(feature = "serde", serde(skip))] &'static str),
+}
+
+/// The Plutus language versions a program can be deployed as.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Copy, Hash, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum PlutusVersion {
+    V1,
+    V2,
 }
 
 /// A Name containing it's parsed textual representation
 /// and a unique id from string interning. The Name's text is
 /// interned during parsing.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Name {
     pub text: String,
     pub unique: Unique,
@@ -74,6 +207,7 @@ pub struct Name {
 
 /// A unique id used for string interning.
 #[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Unique(isize);
 
 impl Unique {
@@ -111,6 +245,7 @@ impl Display for Unique {
 /// `Name` is replaced by `NamedDebruijn` when converting
 /// program to it's debruijn form.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedDeBruijn {
     pub text: String,
     pub index: DeBruijn,
@@ -120,6 +255,7 @@ pub struct NamedDeBruijn {
 /// It allows for injecting fake textual names while also using Debruijn for decoding
 /// without having to loop through twice.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FakeNamedDeBruijn(NamedDeBruijn);
 
 impl From<DeBruijn> for FakeNamedDeBruijn {
@@ -148,6 +284,7 @@ impl From<NamedDeBruijn> for FakeNamedDeBruijn {
 
 /// Represents a debruijn index.
 #[derive(Debug, Clone, PartialEq, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeBruijn(usize);
 
 impl DeBruijn {
@@ -169,6 +306,12 @@ impl From<DeBruijn> for usize {
     }
 }
 
+impl Display for DeBruijn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl From<NamedDeBruijn> for DeBruijn {
     fn from(n: NamedDeBruijn) -> Self {
         n.index
@@ -319,6 +462,79 @@ impl TryFrom<Term<DeBruijn>> for Term<Name> {
     }
 }
 
+impl Program<NamedDeBruijn> {
+    /// Evaluate the program's term with the CEK machine,
+    /// returning it's normal form.
+    pub fn eval(&self) -> Result<Term<NamedDeBruijn>, machine::Error> {
+        self.eval_with_budget(ExBudget::default()).term
+    }
+
+    /// Evaluate the program's term without exceeding `initial_budget`,
+    /// also returning the execution units that were consumed and the
+    /// messages it traced. Every builtin is available, like in Plutus V2.
+    pub fn eval_with_budget(&self, initial_budget: ExBudget) -> EvalResult {
+        self.eval_with_version(PlutusVersion::V2, initial_budget)
+    }
+
+    /// Like `eval_with_budget` but failing on builtins that
+    /// don't exist in `version`, and priced with the default cost
+    /// model of `version`.
+    pub fn eval_with_version(
+        &self,
+        version: PlutusVersion,
+        initial_budget: ExBudget,
+    ) -> EvalResult {
+        self.eval_with_cost_models(version, &CostModels::default(), initial_budget)
+    }
+
+    /// Like `eval_with_version`, priced with the cost model
+    /// `cost_models` has for `version`.
+    pub fn eval_with_cost_models(
+        &self,
+        version: PlutusVersion,
+        cost_models: &CostModels,
+        initial_budget: ExBudget,
+    ) -> EvalResult {
+        let cost_model = cost_models.get(version).clone();
+
+        let mut machine = Machine::new(version, cost_model, initial_budget, 200);
+
+        let term = machine.run(self.term.clone());
+
+        EvalResult {
+            term,
+            budget: initial_budget - machine.ex_budget,
+            logs: machine.logs,
+        }
+    }
+}
+
+impl Program<DeBruijn> {
+    /// Convert to `NamedDeBruijn` form, naming the lambda parameters
+    /// after `hints` instead of the injected placeholder name.
+    /// Hints are consumed in the order the lambdas are reached by a
+    /// pre-order walk, which is the order `Annotations::name_hints` returns.
+    pub fn to_named_debruijn_with_hints(self, hints: Vec<String>) -> Program<NamedDeBruijn> {
+        let mut converter = Converter::with_hints(hints);
+
+        Program::<NamedDeBruijn> {
+            version: self.version,
+            term: converter.debruijn_to_named_debruijn(self.term),
+        }
+    }
+
+    /// Convert to `Name` form, naming the lambda parameters after `hints`.
+    /// See `to_named_debruijn_with_hints`.
+    pub fn to_name_with_hints(self, hints: Vec<String>) -> Result<Program<Name>, debruijn::Error> {
+        let mut converter = Converter::with_hints(hints);
+
+        Ok(Program::<Name> {
+            version: self.version,
+            term: converter.debruijn_to_name(self.term)?,
+        })
+    }
+}
+
 impl From<Program<DeBruijn>> for Program<NamedDeBruijn> {
     fn from(value: Program<DeBruijn>) -> Self {
         Program::<NamedDeBruijn> {
@@ -0,0 +1,247 @@
+//! Traversals of terms, so that analyses and transformations don't
+//! each need their own recursive match over every kind of term.
+//! They use a stack of their own rather than recursion, deep terms
+//! can't overflow the thread's stack.
+
+use std::rc::Rc;
+
+use crate::builtins::DefaultFunction;
+
+use super::{Constant, Term};
+
+/// Called on each term of a `Term::traverse`, before and after it's
+/// subterms.
+pub trait Visitor<'a, T> {
+    /// Return `false` to skip the subterms of `term`.
+    fn enter(&mut self, _term: &'a Term<T>) -> bool {
+        true
+    }
+
+    /// Called once the subterms of `term` have been visited, or
+    /// skipped.
+    fn leave(&mut self, _term: &'a Term<T>) {}
+}
+
+/// A term whose subterms have been replaced by the results of folding
+/// them, see `Term::fold`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Folded<'a, T, R> {
+    Var(&'a T),
+    Delay(R),
+    Lambda { parameter_name: &'a T, body: R },
+    Apply { function: R, argument: R },
+    Constant(&'a Constant),
+    Force(R),
+    Error,
+    Builtin(DefaultFunction),
+}
+
+impl<T> Term<T> {
+    /// The immediate subterms, from left to right.
+    pub fn subterms(&self) -> impl DoubleEndedIterator<Item = &Term<T>> {
+        let (first, second) = match self {
+            Term::Delay(term) | Term::Force(term) => (Some(term), None),
+            Term::Lambda { body, .. } => (Some(body), None),
+            Term::Apply { function, argument } => (Some(function), Some(argument)),
+            Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => (None, None),
+        };
+
+        first.into_iter().chain(second).map(AsRef::as_ref)
+    }
+
+    /// Visit every term depth first, from left to right.
+    pub fn traverse<'a>(&'a self, visitor: &mut impl Visitor<'a, T>) {
+        enum Step<'a, T> {
+            Enter(&'a Term<T>),
+            Leave(&'a Term<T>),
+        }
+
+        let mut stack = vec![Step::Enter(self)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(term) => {
+                    stack.push(Step::Leave(term));
+
+                    if visitor.enter(term) {
+                        stack.extend(term.subterms().rev().map(Step::Enter));
+                    }
+                }
+                Step::Leave(term) => visitor.leave(term),
+            }
+        }
+    }
+
+    /// Fold the term bottom up, `f` gets each term with the results
+    /// for it's subterms in their place.
+    pub fn fold<'a, R>(&'a self, mut f: impl FnMut(Folded<'a, T, R>) -> R) -> R {
+        let mut stack = vec![(self, false)];
+        let mut results = Vec::new();
+
+        while let Some((term, folded_subterms)) = stack.pop() {
+            if !folded_subterms {
+                stack.push((term, true));
+                stack.extend(term.subterms().rev().map(|subterm| (subterm, false)));
+
+                continue;
+            }
+
+            let mut result = || results.pop().expect("subterms are folded first");
+
+            let folded = match term {
+                Term::Var(name) => Folded::Var(name),
+                Term::Delay(_) => Folded::Delay(result()),
+                Term::Lambda { parameter_name, .. } => Folded::Lambda {
+                    parameter_name,
+                    body: result(),
+                },
+                Term::Apply { .. } => {
+                    let argument = result();
+
+                    Folded::Apply {
+                        function: result(),
+                        argument,
+                    }
+                }
+                Term::Constant(constant) => Folded::Constant(constant),
+                Term::Force(_) => Folded::Force(result()),
+                Term::Error => Folded::Error,
+                Term::Builtin(builtin) => Folded::Builtin(*builtin),
+            };
+
+            results.push(f(folded));
+        }
+
+        results.pop().expect("the term itself is folded last")
+    }
+
+    /// The same term with `f` applied to every name, bottom up.
+    pub fn map_names<U>(&self, mut f: impl FnMut(&T) -> U) -> Term<U> {
+        self.fold(|term| match term {
+            Folded::Var(name) => Term::Var(f(name)),
+            Folded::Delay(term) => Term::Delay(Rc::new(term)),
+            Folded::Lambda {
+                parameter_name,
+                body,
+            } => Term::Lambda {
+                parameter_name: f(parameter_name),
+                body: Rc::new(body),
+            },
+            Folded::Apply { function, argument } => Term::Apply {
+                function: Rc::new(function),
+                argument: Rc::new(argument),
+            },
+            Folded::Constant(constant) => Term::Constant(constant.clone()),
+            Folded::Force(term) => Term::Force(Rc::new(term)),
+            Folded::Error => Term::Error,
+            Folded::Builtin(builtin) => Term::Builtin(builtin),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::{
+        ast::{Name, Term},
+        parser,
+    };
+
+    use super::{Folded, Visitor};
+
+    fn term(code: &str) -> Term<Name> {
+        parser::program(&format!("(program 1.0.0 {code})"))
+            .unwrap()
+            .term
+    }
+
+    #[test]
+    fn traverse() {
+        struct Trace(Vec<String>);
+
+        impl<'a> Visitor<'a, Name> for Trace {
+            fn enter(&mut self, term: &'a Term<Name>) -> bool {
+                self.0.push(format!("enter {}", term.to_pretty()));
+
+                // skip the insides of delays
+                !matches!(term, Term::Delay(_))
+            }
+
+            fn leave(&mut self, term: &'a Term<Name>) {
+                self.0.push(format!("leave {}", term.to_pretty()));
+            }
+        }
+
+        let mut trace = Trace(vec![]);
+
+        term("(lam x [x (delay x)])").traverse(&mut trace);
+
+        assert_eq!(
+            trace.0,
+            vec![
+                "enter (lam x [ x (delay x) ])",
+                "enter [ x (delay x) ]",
+                "enter x",
+                "leave x",
+                "enter (delay x)",
+                "leave (delay x)",
+                "leave [ x (delay x) ]",
+                "leave (lam x [ x (delay x) ])",
+            ]
+        );
+    }
+
+    #[test]
+    fn fold() {
+        let term = term("[(lam x (force x)) (delay (con integer 1)) (builtin addInteger)]");
+
+        let size = term.fold(|term| match term {
+            Folded::Delay(size) | Folded::Lambda { body: size, .. } | Folded::Force(size) => {
+                size + 1
+            }
+            Folded::Apply { function, argument } => function + argument + 1,
+            Folded::Var(_) | Folded::Constant(_) | Folded::Error | Folded::Builtin(_) => 1,
+        });
+
+        assert_eq!(size, 8);
+
+        // deeper than recursion would allow
+        let mut deep = Term::<Name>::Error;
+
+        for _ in 0..1_000_000 {
+            deep = Term::Delay(Rc::new(deep));
+        }
+
+        let depth = deep.fold(|term| match term {
+            Folded::Delay(depth) => depth + 1,
+            _ => 0,
+        });
+
+        assert_eq!(depth, 1_000_000);
+
+        // dropping it would recurse as deep
+        std::mem::forget(deep);
+    }
+
+    #[test]
+    fn map_names() {
+        let term = term("(lam x (lam y [x y]))");
+
+        let texts = term.map_names(|name| name.text.to_uppercase());
+
+        assert_eq!(
+            texts,
+            Term::Lambda {
+                parameter_name: "X".to_string(),
+                body: Rc::new(Term::Lambda {
+                    parameter_name: "Y".to_string(),
+                    body: Rc::new(Term::Apply {
+                        function: Rc::new(Term::Var("X".to_string())),
+                        argument: Rc::new(Term::Var("Y".to_string())),
+                    }),
+                }),
+            }
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Programs decoded from flat bytes that borrow their bytestrings from
+//! those bytes instead of copying them, see
+//! `Program::<DeBruijn>::from_flat_borrowed`. They are meant for
+//! looking through many scripts, convert them into `ast::Program` to
+//! evaluate or print them.
+
+use std::{borrow::Cow, rc::Rc};
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{self, DeBruijn, Type},
+    builtins::DefaultFunction,
+    data::PlutusData,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program<'a> {
+    pub version: (usize, usize, usize),
+    pub term: Term<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term<'a> {
+    Var(DeBruijn),
+    Delay(Box<Term<'a>>),
+    Lambda {
+        parameter_name: DeBruijn,
+        body: Box<Term<'a>>,
+    },
+    Apply {
+        function: Box<Term<'a>>,
+        argument: Box<Term<'a>>,
+    },
+    Constant(Constant<'a>),
+    Force(Box<Term<'a>>),
+    Error,
+    Builtin(DefaultFunction),
+}
+
+/// A constant as written in flat, so without chars. Bytestrings of at
+/// most 255 bytes are borrowed, longer ones are written in several
+/// chunks and had to be joined.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant<'a> {
+    Integer(BigInt),
+    ByteString(Cow<'a, [u8]>),
+    String(String),
+    Unit,
+    Bool(bool),
+    ProtoList(Type, Vec<Constant<'a>>),
+    ProtoPair(Type, Type, Box<Constant<'a>>, Box<Constant<'a>>),
+    Data(PlutusData),
+}
+
+impl From<Program<'_>> for ast::Program<DeBruijn> {
+    fn from(program: Program<'_>) -> Self {
+        ast::Program {
+            version: program.version,
+            term: program.term.into(),
+        }
+    }
+}
+
+/// Terms are converted recursively, which is fine as decoding them
+/// kept them within `Limits::MAX_DEPTH`.
+impl From<Term<'_>> for ast::Term<DeBruijn> {
+    fn from(term: Term<'_>) -> Self {
+        match term {
+            Term::Var(name) => ast::Term::Var(name),
+            Term::Delay(term) => ast::Term::Delay(Rc::new((*term).into())),
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => ast::Term::Lambda {
+                parameter_name,
+                body: Rc::new((*body).into()),
+            },
+            Term::Apply { function, argument } => ast::Term::Apply {
+                function: Rc::new((*function).into()),
+                argument: Rc::new((*argument).into()),
+            },
+            Term::Constant(constant) => ast::Term::Constant(constant.into()),
+            Term::Force(term) => ast::Term::Force(Rc::new((*term).into())),
+            Term::Error => ast::Term::Error,
+            Term::Builtin(builtin) => ast::Term::Builtin(builtin),
+        }
+    }
+}
+
+impl From<Constant<'_>> for ast::Constant {
+    fn from(constant: Constant<'_>) -> Self {
+        match constant {
+            Constant::Integer(i) => ast::Constant::Integer(i),
+            Constant::ByteString(bytes) => ast::Constant::ByteString(bytes.into_owned()),
+            Constant::String(s) => ast::Constant::String(s),
+            Constant::Unit => ast::Constant::Unit,
+            Constant::Bool(b) => ast::Constant::Bool(b),
+            Constant::ProtoList(ty, items) => {
+                ast::Constant::ProtoList(ty, items.into_iter().map(Into::into).collect())
+            }
+            Constant::ProtoPair(left_ty, right_ty, left, right) => ast::Constant::ProtoPair(
+                left_ty,
+                right_ty,
+                Box::new((*left).into()),
+                Box::new((*right).into()),
+            ),
+            Constant::Data(data) => ast::Constant::Data(data),
+        }
+    }
+}
@@ -1,10 +1,15 @@
+use std::fmt;
+
 use flat::de;
-use strum_macros::EnumString;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use crate::ast::{PlutusVersion, Type};
 
 /// All the possible builtin functions in Untyped Plutus Core.
 #[repr(u8)]
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, EnumString, PartialEq, Copy)]
+#[derive(Debug, Clone, Display, EnumString, EnumIter, PartialEq, Copy)]
 #[strum(serialize_all = "camelCase")]
 pub enum DefaultFunction {
     // Integer functions
@@ -195,3 +200,379 @@ impl TryFrom<u8> for DefaultFunction {
         }
     }
 }
+
+impl Type {
+    /// The type variables in this type, in the order they appear.
+    fn variables(&self, variables: &mut Vec<&'static str>) {
+        match self {
+            Type::List(elem) => elem.variables(variables),
+            Type::Pair(l, r) => {
+                l.variables(variables);
+                r.variables(variables);
+            }
+            Type::Var(name) if !variables.contains(name) => variables.push(name),
+            _ => (),
+        }
+    }
+}
+
+/// Everything there is to know about a builtin function's shape:
+/// how many times it needs to be forced, the types of the arguments
+/// it takes, the type of it's result and the first Plutus version
+/// it is available in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub force_count: usize,
+    pub arguments: Vec<Type>,
+    pub result: Type,
+    pub since: PlutusVersion,
+}
+
+impl Signature {
+    fn new(arguments: Vec<Type>, result: Type) -> Self {
+        Signature {
+            force_count: 0,
+            arguments,
+            result,
+            since: PlutusVersion::V1,
+        }
+    }
+
+    fn forall(mut self, force_count: usize) -> Self {
+        self.force_count = force_count;
+
+        self
+    }
+
+    fn since(mut self, version: PlutusVersion) -> Self {
+        self.since = version;
+
+        self
+    }
+
+    /// The number of arguments the builtin needs to be saturated.
+    pub fn arity(&self) -> usize {
+        self.arguments.len()
+    }
+}
+
+/// Written like `forall a. bool -> a -> a -> a`.
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut variables = Vec::new();
+
+        for ty in self.arguments.iter().chain([&self.result]) {
+            ty.variables(&mut variables);
+        }
+
+        if !variables.is_empty() {
+            write!(f, "forall {}. ", variables.join(" "))?;
+        }
+
+        for argument in &self.arguments {
+            write!(f, "{} -> ", argument)?;
+        }
+
+        write!(f, "{}", self.result)
+    }
+}
+
+impl DefaultFunction {
+    /// Look up the signature of this builtin.
+    pub fn signature(&self) -> Signature {
+        use DefaultFunction::*;
+        use Type::*;
+
+        fn list(t: Type) -> Type {
+            List(Box::new(t))
+        }
+
+        fn pair(fst: Type, snd: Type) -> Type {
+            Pair(Box::new(fst), Box::new(snd))
+        }
+
+        match self {
+            AddInteger | SubtractInteger | MultiplyInteger | DivideInteger | QuotientInteger
+            | RemainderInteger | ModInteger => Signature::new(vec![Integer, Integer], Integer),
+            EqualsInteger | LessThanInteger | LessThanEqualsInteger => {
+                Signature::new(vec![Integer, Integer], Bool)
+            }
+            AppendByteString => Signature::new(vec![ByteString, ByteString], ByteString),
+            ConsByteString => Signature::new(vec![Integer, ByteString], ByteString),
+            SliceByteString => Signature::new(vec![Integer, Integer, ByteString], ByteString),
+            LengthOfByteString => Signature::new(vec![ByteString], Integer),
+            IndexByteString => Signature::new(vec![ByteString, Integer], Integer),
+            EqualsByteString | LessThanByteString | LessThanEqualsByteString => {
+                Signature::new(vec![ByteString, ByteString], Bool)
+            }
+            Sha2_256 | Sha3_256 | Blake2b_256 => Signature::new(vec![ByteString], ByteString),
+            VerifySignature => Signature::new(vec![ByteString, ByteString, ByteString], Bool),
+            VerifyEcdsaSecp256k1Signature | VerifySchnorrSecp256k1Signature => {
+                Signature::new(vec![ByteString, ByteString, ByteString], Bool)
+                    .since(PlutusVersion::V2)
+            }
+            AppendString => Signature::new(vec![String, String], String),
+            EqualsString => Signature::new(vec![String, String], Bool),
+            EncodeUtf8 => Signature::new(vec![String], ByteString),
+            DecodeUtf8 => Signature::new(vec![ByteString], String),
+            IfThenElse => Signature::new(vec![Bool, Var("a"), Var("a")], Var("a")).forall(1),
+            ChooseUnit => Signature::new(vec![Unit, Var("a")], Var("a")).forall(1),
+            Trace => Signature::new(vec![String, Var("a")], Var("a")).forall(1),
+            FstPair => Signature::new(vec![pair(Var("a"), Var("b"))], Var("a")).forall(2),
+            SndPair => Signature::new(vec![pair(Var("a"), Var("b"))], Var("b")).forall(2),
+            ChooseList => {
+                Signature::new(vec![list(Var("a")), Var("b"), Var("b")], Var("b")).forall(2)
+            }
+            MkCons => Signature::new(vec![Var("a"), list(Var("a"))], list(Var("a"))).forall(1),
+            HeadList => Signature::new(vec![list(Var("a"))], Var("a")).forall(1),
+            TailList => Signature::new(vec![list(Var("a"))], list(Var("a"))).forall(1),
+            NullList => Signature::new(vec![list(Var("a"))], Bool).forall(1),
+            ChooseData => Signature::new(
+                vec![Data, Var("a"), Var("a"), Var("a"), Var("a"), Var("a")],
+                Var("a"),
+            )
+            .forall(1),
+            ConstrData => Signature::new(vec![Integer, list(Data)], Data),
+            MapData => Signature::new(vec![list(pair(Data, Data))], Data),
+            ListData => Signature::new(vec![list(Data)], Data),
+            IData => Signature::new(vec![Integer], Data),
+            BData => Signature::new(vec![ByteString], Data),
+            UnConstrData => Signature::new(vec![Data], pair(Integer, list(Data))),
+            UnMapData => Signature::new(vec![Data], list(pair(Data, Data))),
+            UnListData => Signature::new(vec![Data], list(Data)),
+            UnIData => Signature::new(vec![Data], Integer),
+            UnBData => Signature::new(vec![Data], ByteString),
+            EqualsData => Signature::new(vec![Data, Data], Bool),
+            SerialiseData => Signature::new(vec![Data], ByteString).since(PlutusVersion::V2),
+            MkPairData => Signature::new(vec![Data, Data], pair(Data, Data)),
+            MkNilData => Signature::new(vec![Unit], list(Data)),
+            MkNilPairData => Signature::new(vec![Unit], list(pair(Data, Data))),
+        }
+    }
+
+    /// The number of arguments this builtin needs to be saturated.
+    /// Kept apart from `signature`, which allocates, as the machine
+    /// asks for it on every builtin application.
+    pub const fn arity(&self) -> usize {
+        use DefaultFunction::*;
+
+        match self {
+            Sha2_256 | Sha3_256 | Blake2b_256 | LengthOfByteString | EncodeUtf8 | DecodeUtf8
+            | FstPair | SndPair | HeadList | TailList | NullList | ListData | MapData | IData
+            | BData | UnConstrData | UnMapData | UnListData | UnIData | UnBData | SerialiseData
+            | MkNilData | MkNilPairData => 1,
+            AddInteger
+            | SubtractInteger
+            | MultiplyInteger
+            | DivideInteger
+            | QuotientInteger
+            | RemainderInteger
+            | ModInteger
+            | EqualsInteger
+            | LessThanInteger
+            | LessThanEqualsInteger
+            | AppendByteString
+            | ConsByteString
+            | IndexByteString
+            | EqualsByteString
+            | LessThanByteString
+            | LessThanEqualsByteString
+            | AppendString
+            | EqualsString
+            | ChooseUnit
+            | Trace
+            | MkCons
+            | ConstrData
+            | EqualsData
+            | MkPairData => 2,
+            SliceByteString
+            | VerifySignature
+            | VerifyEcdsaSecp256k1Signature
+            | VerifySchnorrSecp256k1Signature
+            | IfThenElse
+            | ChooseList => 3,
+            ChooseData => 6,
+        }
+    }
+
+    /// The number of `Force`s needed before this builtin can be applied.
+    pub const fn force_count(&self) -> usize {
+        use DefaultFunction::*;
+
+        match self {
+            IfThenElse | ChooseUnit | Trace | MkCons | HeadList | TailList | NullList
+            | ChooseData => 1,
+            FstPair | SndPair | ChooseList => 2,
+            _ => 0,
+        }
+    }
+
+    /// The first Plutus version in which this builtin is available.
+    pub const fn since(&self) -> PlutusVersion {
+        use DefaultFunction::*;
+
+        match self {
+            VerifyEcdsaSecp256k1Signature | VerifySchnorrSecp256k1Signature | SerialiseData => {
+                PlutusVersion::V2
+            }
+            _ => PlutusVersion::V1,
+        }
+    }
+
+    /// Whether scripts written for `version` may use this builtin.
+    pub fn is_available_in(&self, version: PlutusVersion) -> bool {
+        self.since() <= version
+    }
+
+    /// What the builtin does, in a sentence.
+    pub fn description(&self) -> &'static str {
+        use DefaultFunction::*;
+
+        match self {
+            AddInteger => "Add two integers.",
+            SubtractInteger => "Subtract the second integer from the first.",
+            MultiplyInteger => "Multiply two integers.",
+            DivideInteger => {
+                "Divide the first integer by the second, rounding towards negative infinity. Fails on zero."
+            }
+            QuotientInteger => {
+                "Divide the first integer by the second, rounding towards zero. Fails on zero."
+            }
+            RemainderInteger => {
+                "The remainder of `quotientInteger`, with the sign of the first integer. Fails on zero."
+            }
+            ModInteger => {
+                "The remainder of `divideInteger`, with the sign of the second integer. Fails on zero."
+            }
+            EqualsInteger => "Whether two integers are equal.",
+            LessThanInteger => "Whether the first integer is less than the second.",
+            LessThanEqualsInteger => {
+                "Whether the first integer is less than or equal to the second."
+            }
+            AppendByteString => "Concatenate two bytestrings.",
+            ConsByteString => {
+                "Prepend a byte, the integer modulo 256, to a bytestring."
+            }
+            SliceByteString => {
+                "Take as many bytes as the second integer from a bytestring, starting at the index given by the first."
+            }
+            LengthOfByteString => "The number of bytes in a bytestring.",
+            IndexByteString => "The byte at an index of a bytestring. Fails out of bounds.",
+            EqualsByteString => "Whether two bytestrings are equal.",
+            LessThanByteString => {
+                "Whether the first bytestring comes before the second, lexicographically."
+            }
+            LessThanEqualsByteString => {
+                "Whether the first bytestring comes before the second, lexicographically, or equals it."
+            }
+            Sha2_256 => "The SHA2-256 hash of a bytestring.",
+            Sha3_256 => "The SHA3-256 hash of a bytestring.",
+            Blake2b_256 => "The BLAKE2b-256 hash of a bytestring.",
+            VerifySignature => {
+                "Verify an Ed25519 signature, given the public key, the message and the signature."
+            }
+            VerifyEcdsaSecp256k1Signature => {
+                "Verify an ECDSA signature on the SECP256k1 curve, given the public key, the message hash and the signature."
+            }
+            VerifySchnorrSecp256k1Signature => {
+                "Verify a Schnorr signature on the SECP256k1 curve, given the public key, the message and the signature."
+            }
+            AppendString => "Concatenate two strings.",
+            EqualsString => "Whether two strings are equal.",
+            EncodeUtf8 => "The UTF-8 encoding of a string.",
+            DecodeUtf8 => "Decode a string from UTF-8. Fails on invalid UTF-8.",
+            IfThenElse => "The second argument if the condition is true, the third otherwise.",
+            ChooseUnit => "The second argument, once the unit is evaluated.",
+            Trace => "Log the string and return the second argument.",
+            FstPair => "The first element of a pair.",
+            SndPair => "The second element of a pair.",
+            ChooseList => "The second argument if the list is empty, the third otherwise.",
+            MkCons => "Prepend an element to a list.",
+            HeadList => "The first element of a list. Fails on an empty list.",
+            TailList => "A list without it's first element. Fails on an empty list.",
+            NullList => "Whether a list is empty.",
+            ChooseData => {
+                "Choose between the arguments by the kind of data: constr, map, list, integer or bytestring."
+            }
+            ConstrData => "Data for a constructor, given it's index and fields.",
+            MapData => "Data for a map, given it's key value pairs.",
+            ListData => "Data for a list.",
+            IData => "Data for an integer.",
+            BData => "Data for a bytestring.",
+            UnConstrData => "The index and fields of constructor data. Fails on other data.",
+            UnMapData => "The key value pairs of map data. Fails on other data.",
+            UnListData => "The elements of list data. Fails on other data.",
+            UnIData => "The integer of integer data. Fails on other data.",
+            UnBData => "The bytestring of bytestring data. Fails on other data.",
+            EqualsData => "Whether two pieces of data are equal.",
+            SerialiseData => "The CBOR encoding of data.",
+            MkPairData => "A pair of two pieces of data.",
+            MkNilData => "An empty list of data.",
+            MkNilPairData => "An empty list of pairs of data.",
+        }
+    }
+}
+
+/// A program uses a builtin that doesn't exist in the
+/// Plutus version it is meant for.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("Builtin '{0:?}' is not available in Plutus {1:?}")]
+pub struct UnavailableBuiltin(pub DefaultFunction, pub PlutusVersion);
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+
+    use crate::ast::PlutusVersion;
+
+    use super::DefaultFunction;
+
+    #[test]
+    fn tags_round_trip() {
+        for builtin in DefaultFunction::iter() {
+            assert_eq!(DefaultFunction::try_from(builtin as u8).unwrap(), builtin);
+        }
+    }
+
+    #[test]
+    fn signatures() {
+        assert_eq!(DefaultFunction::AddInteger.arity(), 2);
+        assert_eq!(DefaultFunction::AddInteger.force_count(), 0);
+
+        assert_eq!(DefaultFunction::IfThenElse.arity(), 3);
+        assert_eq!(DefaultFunction::IfThenElse.force_count(), 1);
+
+        assert_eq!(DefaultFunction::ChooseData.arity(), 6);
+        assert_eq!(DefaultFunction::ChooseList.force_count(), 2);
+
+        assert_eq!(
+            DefaultFunction::AddInteger.signature().to_string(),
+            "integer -> integer -> integer"
+        );
+        assert_eq!(
+            DefaultFunction::ChooseList.signature().to_string(),
+            "forall a b. (list a) -> b -> b -> b"
+        );
+
+        let v2_only: Vec<DefaultFunction> = DefaultFunction::iter()
+            .filter(|builtin| builtin.since() == PlutusVersion::V2)
+            .collect();
+
+        assert_eq!(
+            v2_only,
+            vec![
+                DefaultFunction::VerifyEcdsaSecp256k1Signature,
+                DefaultFunction::VerifySchnorrSecp256k1Signature,
+                DefaultFunction::SerialiseData,
+            ]
+        );
+
+        for builtin in DefaultFunction::iter() {
+            let signature = builtin.signature();
+
+            assert_eq!(builtin.arity(), signature.arity(), "{builtin}");
+            assert_eq!(builtin.force_count(), signature.force_count, "{builtin}");
+            assert_eq!(builtin.since(), signature.since, "{builtin}");
+        }
+    }
+}
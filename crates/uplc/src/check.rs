@@ -0,0 +1,173 @@
+use thiserror::Error;
+
+use crate::ast::{DeBruijn, FakeNamedDeBruijn, Name, NamedDeBruijn, Program, Term, Unique};
+
+/// A scoping problem found by `Program::check`.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum Problem {
+    #[error("Free variable `{0}` with unique {1}")]
+    FreeUnique(String, Unique),
+    #[error("Index {index} is out of range, {depth} variables are in scope")]
+    FreeIndex { index: DeBruijn, depth: usize },
+    #[error("Parameter `{0}` shadows a variable with the same name")]
+    Shadowing(String),
+}
+
+impl Problem {
+    /// Shadowing is allowed, it's only reported because it is easy
+    /// to get wrong when writing programs by hand.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Problem::Shadowing(_))
+    }
+}
+
+impl Program<Name> {
+    /// Find every variable that isn't bound by a lambda and every
+    /// parameter shadowing another one, in the order they appear.
+    pub fn check(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+
+        check_names(&self.term, &mut Vec::new(), &mut problems);
+
+        problems
+    }
+}
+
+fn check_names(term: &Term<Name>, scope: &mut Vec<Unique>, problems: &mut Vec<Problem>) {
+    match term {
+        Term::Var(name) => {
+            if !scope.contains(&name.unique) {
+                problems.push(Problem::FreeUnique(name.text.clone(), name.unique));
+            }
+        }
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => {
+            if scope.contains(&parameter_name.unique) {
+                problems.push(Problem::Shadowing(parameter_name.text.clone()));
+            }
+
+            scope.push(parameter_name.unique);
+
+            check_names(body, scope, problems);
+
+            scope.pop();
+        }
+        Term::Apply { function, argument } => {
+            check_names(function, scope, problems);
+            check_names(argument, scope, problems);
+        }
+        Term::Delay(term) | Term::Force(term) => check_names(term, scope, problems),
+        Term::Constant(_) | Term::Error | Term::Builtin(_) => (),
+    }
+}
+
+/// Binders referring to variables by their debruijn index.
+pub trait Indexed {
+    fn index(&self) -> DeBruijn;
+}
+
+impl Indexed for DeBruijn {
+    fn index(&self) -> DeBruijn {
+        *self
+    }
+}
+
+impl Indexed for NamedDeBruijn {
+    fn index(&self) -> DeBruijn {
+        self.index
+    }
+}
+
+impl Indexed for FakeNamedDeBruijn {
+    fn index(&self) -> DeBruijn {
+        self.clone().into()
+    }
+}
+
+impl<T> Program<T>
+where
+    T: Indexed,
+{
+    /// Find every variable whose index doesn't point to an enclosing
+    /// lambda, indices start at 1 for the closest one.
+    pub fn check(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+
+        check_indices(&self.term, 0, &mut problems);
+
+        problems
+    }
+}
+
+fn check_indices<T: Indexed>(term: &Term<T>, depth: usize, problems: &mut Vec<Problem>) {
+    match term {
+        Term::Var(name) => {
+            let index = name.index();
+
+            if usize::from(index) == 0 || usize::from(index) > depth {
+                problems.push(Problem::FreeIndex { index, depth });
+            }
+        }
+        Term::Lambda { body, .. } => check_indices(body, depth + 1, problems),
+        Term::Apply { function, argument } => {
+            check_indices(function, depth, problems);
+            check_indices(argument, depth, problems);
+        }
+        Term::Delay(term) | Term::Force(term) => check_indices(term, depth, problems),
+        Term::Constant(_) | Term::Error | Term::Builtin(_) => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::{
+        ast::{DeBruijn, Program, Term},
+        parser,
+    };
+
+    use super::Problem;
+
+    #[test]
+    fn report_every_problem() {
+        let program = parser::program("(program 1.0.0 (lam x [ (lam x [ x y ]) z ]))").unwrap();
+
+        let problems = program.check();
+
+        assert!(matches!(
+            problems.as_slice(),
+            [
+                Problem::Shadowing(x),
+                Problem::FreeUnique(y, _),
+                Problem::FreeUnique(z, _),
+            ] if x == "x" && y == "y" && z == "z"
+        ));
+
+        assert!(problems[0].is_warning() && !problems[1].is_warning());
+    }
+
+    #[test]
+    fn out_of_range_indices() {
+        let program: Program<DeBruijn> = Program {
+            version: (1, 0, 0),
+            term: Term::Lambda {
+                parameter_name: DeBruijn::new(0),
+                body: Rc::new(Term::Apply {
+                    function: Rc::new(Term::Var(DeBruijn::new(1))),
+                    argument: Rc::new(Term::Var(DeBruijn::new(2))),
+                }),
+            },
+        };
+
+        assert_eq!(
+            program.check(),
+            vec![Problem::FreeIndex {
+                index: DeBruijn::new(2),
+                depth: 1
+            }]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! A runner for test vectors laid out like the Plutus conformance
+//! suite: a program `name.uplc` next to `name.uplc.expected`, holding
+//! the program it evaluates to, `evaluation failure` or `parse error`,
+//! and optionally `name.uplc.budget.expected`, holding the execution
+//! units evaluating it takes, e.g. `({cpu: 23100 | mem: 200})`.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    ast::{Name, NamedDeBruijn, Program},
+    machine::cost_model::ExBudget,
+    parser,
+};
+
+const PARSE_ERROR: &str = "parse error";
+const EVALUATION_FAILURE: &str = "evaluation failure";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Invalid expected output in {path}: {source}")]
+    Expected {
+        path: PathBuf,
+        source: Box<parser::Error>,
+    },
+    #[error("Invalid expected budget in {path}, `{content}`")]
+    Budget { path: PathBuf, content: String },
+}
+
+/// How a test vector went.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    /// The program didn't evaluate to what was expected, both are
+    /// in the textual syntax or one of the failure markers.
+    Output {
+        expected: String,
+        actual: String,
+    },
+    /// The program evaluated as expected, but took a different budget.
+    Budget {
+        expected: ExBudget,
+        actual: ExBudget,
+    },
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Passed => write!(f, "passed"),
+            Outcome::Output { expected, actual } => {
+                write!(f, "expected\n  {expected}\nbut got\n  {actual}")
+            }
+            Outcome::Budget { expected, actual } => write!(
+                f,
+                "expected mem: {} & cpu: {}\nbut used mem: {} & cpu: {}",
+                expected.mem, expected.cpu, actual.mem, actual.cpu
+            ),
+        }
+    }
+}
+
+/// The test vectors under `dir`, every `.uplc` file with an expected
+/// output next to it, in order.
+pub fn discover(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut vectors = Vec::new();
+    let mut directories = vec![dir.to_path_buf()];
+
+    while let Some(dir) = directories.pop() {
+        let entries = fs::read_dir(&dir).map_err(|source| Error::Io {
+            path: dir.clone(),
+            source,
+        })?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(|source| Error::Io {
+                    path: dir.clone(),
+                    source,
+                })?
+                .path();
+
+            if path.is_dir() {
+                directories.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|extension| extension == "uplc")
+                && with_suffix(&path, ".expected").is_file()
+            {
+                vectors.push(path);
+            }
+        }
+    }
+
+    vectors.sort();
+
+    Ok(vectors)
+}
+
+/// Parse, convert to debruijn indices and evaluate the program at
+/// `path`, comparing the result with the expected one up to the names
+/// of lambda parameters, and the budget if one is expected.
+pub fn run(path: &Path) -> Result<Outcome, Error> {
+    let source = read(path)?;
+
+    let expected_path = with_suffix(path, ".expected");
+    let expected = read(&expected_path)?.trim().to_string();
+
+    let (program, budget) = match parser::program(&source) {
+        Err(_) => return Ok(compare(expected, PARSE_ERROR)),
+        Ok(program) => match Program::<NamedDeBruijn>::try_from(program) {
+            // as in Plutus, free variables fail at evaluation
+            Err(_) => return Ok(compare(expected, EVALUATION_FAILURE)),
+            Ok(program) => {
+                let result = program.eval_with_budget(ExBudget::default());
+
+                let term = match result.term {
+                    Ok(term) => term,
+                    Err(_) => return Ok(compare(expected, EVALUATION_FAILURE)),
+                };
+
+                let program = Program::<Name>::try_from(Program {
+                    version: program.version,
+                    term,
+                })
+                .expect("evaluated terms are closed");
+
+                (program, result.budget)
+            }
+        },
+    };
+
+    let actual = program.to_pretty();
+
+    if expected == PARSE_ERROR || expected == EVALUATION_FAILURE {
+        return Ok(Outcome::Output { expected, actual });
+    }
+
+    let expected_program = parser::program(&expected).map_err(|source| Error::Expected {
+        path: expected_path,
+        source: source.into(),
+    })?;
+
+    if expected_program.version != program.version || !expected_program.term.alpha_eq(&program.term)
+    {
+        return Ok(Outcome::Output { expected, actual });
+    }
+
+    let budget_path = with_suffix(path, ".budget.expected");
+
+    if !budget_path.is_file() {
+        return Ok(Outcome::Passed);
+    }
+
+    let content = read(&budget_path)?;
+
+    let expected_budget = parse_budget(&content).ok_or(Error::Budget {
+        path: budget_path,
+        content,
+    })?;
+
+    if expected_budget == budget {
+        Ok(Outcome::Passed)
+    } else {
+        Ok(Outcome::Budget {
+            expected: expected_budget,
+            actual: budget,
+        })
+    }
+}
+
+fn compare(expected: String, actual: &str) -> Outcome {
+    if expected == actual {
+        Outcome::Passed
+    } else {
+        Outcome::Output {
+            expected,
+            actual: actual.to_string(),
+        }
+    }
+}
+
+/// Read a budget written as `({cpu: 23100 | mem: 200})`.
+fn parse_budget(content: &str) -> Option<ExBudget> {
+    let field = |name: &str| -> Option<i64> {
+        let (_, rest) = content.split_once(name)?;
+
+        let digits: String = rest
+            .trim_start_matches([':', ' '])
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+
+        digits.parse().ok()
+    };
+
+    Some(ExBudget {
+        cpu: field("cpu")?,
+        mem: field("mem")?,
+    })
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+
+    path.push(suffix);
+
+    path.into()
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::machine::cost_model::ExBudget;
+
+    use super::{discover, parse_budget, run, Outcome};
+
+    /// Runs the vectors in `test_data/conformance`, or those under
+    /// `UPLC_CONFORMANCE_DIR`, e.g. the `test-cases/uplc` directory of
+    /// a checkout of the Plutus suite. The suite isn't vendored, the
+    /// vectors here are written by hand in it's layout and syntax.
+    #[test]
+    fn vectors() {
+        let dir = std::env::var("UPLC_CONFORMANCE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/conformance")
+            });
+
+        let vectors = discover(&dir).unwrap();
+
+        assert!(!vectors.is_empty());
+
+        let failures: Vec<String> = vectors
+            .iter()
+            .filter_map(|path| match run(path) {
+                Ok(Outcome::Passed) => None,
+                Ok(outcome) => Some(format!("{}\n{outcome}", path.display())),
+                Err(error) => Some(format!("{}\n{error}", path.display())),
+            })
+            .collect();
+
+        assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+    }
+
+    #[test]
+    fn budget() {
+        assert_eq!(
+            parse_budget("({cpu: 23100\n| mem: 200})"),
+            Some(ExBudget {
+                cpu: 23100,
+                mem: 200
+            })
+        );
+        assert_eq!(parse_budget("evaluation failure"), None);
+    }
+}
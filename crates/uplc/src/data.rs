@@ -0,0 +1,340 @@
+use minicbor::{
+    data::{Tag, Type},
+    decode, encode, Decode, Decoder, Encode, Encoder,
+};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::ToPrimitive;
+use thiserror::Error;
+
+use crate::ast::Constant;
+
+/// The `Data` type shared between on chain scripts and the ledger.
+/// It's wire format is CBOR, which is also how it is embedded in
+/// flat encoded programs.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
+pub enum PlutusData {
+    Constr { tag: u64, fields: Vec<PlutusData> },
+    Map(Vec<(PlutusData, PlutusData)>),
+    List(Vec<PlutusData>),
+    Integer(#[cfg_attr(feature = "serde", serde(with = "crate::json::big_int"))] BigInt),
+    ByteString(#[cfg_attr(feature = "serde", serde(with = "crate::json::bytes"))] Vec<u8>),
+}
+
+impl PlutusData {
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, decode::Error> {
+        minicbor::decode(bytes)
+    }
+
+    pub fn to_cbor(&self) -> Vec<u8> {
+        minicbor::to_vec(self).expect("writing to a vec can't fail")
+    }
+}
+
+impl From<PlutusData> for Constant {
+    fn from(data: PlutusData) -> Self {
+        Constant::Data(data)
+    }
+}
+
+/// Only `Data` constants can be turned back into `PlutusData`.
+#[derive(Error, Debug, PartialEq)]
+#[error("Expected a data constant but got {0:?}")]
+pub struct NotData(pub Constant);
+
+impl TryFrom<Constant> for PlutusData {
+    type Error = NotData;
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        match constant {
+            Constant::Data(data) => Ok(data),
+            rest => Err(NotData(rest)),
+        }
+    }
+}
+
+/// Bytestrings longer than this are split into chunks.
+const BYTES_CHUNK_SIZE: usize = 64;
+
+impl<C> Encode<C> for PlutusData {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            PlutusData::Constr { tag, fields } => {
+                match tag {
+                    0..=6 => {
+                        e.tag(Tag::Unassigned(121 + tag))?;
+                    }
+                    7..=127 => {
+                        e.tag(Tag::Unassigned(1280 + tag - 7))?;
+                    }
+                    _ => {
+                        e.tag(Tag::Unassigned(102))?;
+                        e.array(2)?;
+                        e.u64(*tag)?;
+                    }
+                }
+
+                encode_list(fields, e, ctx)?;
+            }
+            PlutusData::Map(entries) => {
+                e.map(entries.len() as u64)?;
+
+                for (key, value) in entries {
+                    key.encode(e, ctx)?;
+                    value.encode(e, ctx)?;
+                }
+            }
+            PlutusData::List(items) => encode_list(items, e, ctx)?,
+            PlutusData::Integer(i) => {
+                match i
+                    .to_i128()
+                    .and_then(|i| minicbor::data::Int::try_from(i).ok())
+                {
+                    Some(i) => {
+                        e.int(i)?;
+                    }
+                    None => {
+                        // negative bignums store `-1 - n`
+                        let (tag, magnitude) = match i.sign() {
+                            Sign::Minus => (Tag::NegBignum, (-i - 1u8).magnitude().clone()),
+                            _ => (Tag::PosBignum, i.magnitude().clone()),
+                        };
+
+                        e.tag(tag)?;
+
+                        encode_bytes(&magnitude.to_bytes_be(), e)?;
+                    }
+                }
+            }
+            PlutusData::ByteString(bytes) => encode_bytes(bytes, e)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Non empty lists are written with an indefinite length like the
+/// ledger does, so that re-encoding decoded data is byte for byte identical.
+fn encode_list<C, W: encode::Write>(
+    items: &[PlutusData],
+    e: &mut Encoder<W>,
+    ctx: &mut C,
+) -> Result<(), encode::Error<W::Error>> {
+    if items.is_empty() {
+        e.array(0)?;
+    } else {
+        e.begin_array()?;
+
+        for item in items {
+            item.encode(e, ctx)?;
+        }
+
+        e.end()?;
+    }
+
+    Ok(())
+}
+
+fn encode_bytes<W: encode::Write>(
+    bytes: &[u8],
+    e: &mut Encoder<W>,
+) -> Result<(), encode::Error<W::Error>> {
+    if bytes.len() <= BYTES_CHUNK_SIZE {
+        e.bytes(bytes)?;
+    } else {
+        e.begin_bytes()?;
+
+        for chunk in bytes.chunks(BYTES_CHUNK_SIZE) {
+            e.bytes(chunk)?;
+        }
+
+        e.end()?;
+    }
+
+    Ok(())
+}
+
+/// How deeply data may nest, decoding fails past it instead of
+/// overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+impl<'b, C> Decode<'b, C> for PlutusData {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut C) -> Result<Self, decode::Error> {
+        decode_data(d, MAX_DEPTH)
+    }
+}
+
+/// Decode data nested at most `depth` deep.
+fn decode_data(d: &mut Decoder, depth: usize) -> Result<PlutusData, decode::Error> {
+    let mut depth = depth
+        .checked_sub(1)
+        .ok_or_else(|| decode::Error::message("Data is nested too deeply"))?;
+
+    match d.datatype()? {
+        Type::Tag => match d.tag()? {
+            Tag::PosBignum => {
+                let magnitude = BigUint::from_bytes_be(&decode_bytes(d)?);
+
+                Ok(PlutusData::Integer(BigInt::from(magnitude)))
+            }
+            Tag::NegBignum => {
+                let magnitude = BigUint::from_bytes_be(&decode_bytes(d)?);
+
+                Ok(PlutusData::Integer(-BigInt::from(magnitude) - 1u8))
+            }
+            Tag::Unassigned(x @ 121..=127) => Ok(PlutusData::Constr {
+                tag: x - 121,
+                fields: decode_list(d, &mut depth)?,
+            }),
+            Tag::Unassigned(x @ 1280..=1400) => Ok(PlutusData::Constr {
+                tag: x - 1280 + 7,
+                fields: decode_list(d, &mut depth)?,
+            }),
+            Tag::Unassigned(102) => {
+                if d.array()? != Some(2) {
+                    return Err(decode::Error::message(
+                        "Expected a constructor index and it's fields",
+                    ));
+                }
+
+                Ok(PlutusData::Constr {
+                    tag: d.u64()?,
+                    fields: decode_list(d, &mut depth)?,
+                })
+            }
+            _ => Err(decode::Error::message("Unknown tag for plutus data")),
+        },
+        Type::Map | Type::MapIndef => {
+            let entries = d
+                .map_iter_with::<_, Nested, Nested>(&mut depth)?
+                .map(|entry| entry.map(|(Nested(key), Nested(value))| (key, value)))
+                .collect::<Result<_, _>>()?;
+
+            Ok(PlutusData::Map(entries))
+        }
+        Type::Array | Type::ArrayIndef => Ok(PlutusData::List(decode_list(d, &mut depth)?)),
+        Type::Bytes | Type::BytesIndef => Ok(PlutusData::ByteString(decode_bytes(d)?)),
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::Int => {
+            let i: i128 = d.int()?.into();
+
+            Ok(PlutusData::Integer(i.into()))
+        }
+        ty => Err(decode::Error::type_mismatch(ty)),
+    }
+}
+
+/// Data decoded with the depth it may still nest as context.
+struct Nested(PlutusData);
+
+impl<'b> Decode<'b, usize> for Nested {
+    fn decode(d: &mut Decoder<'b>, depth: &mut usize) -> Result<Self, decode::Error> {
+        decode_data(d, *depth).map(Nested)
+    }
+}
+
+fn decode_list(d: &mut Decoder, depth: &mut usize) -> Result<Vec<PlutusData>, decode::Error> {
+    d.array_iter_with::<_, Nested>(depth)?
+        .map(|item| item.map(|Nested(data)| data))
+        .collect()
+}
+
+fn decode_bytes(d: &mut Decoder) -> Result<Vec<u8>, decode::Error> {
+    let mut bytes = Vec::new();
+
+    for chunk in d.bytes_iter()? {
+        bytes.extend(chunk?);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::Constant;
+
+    use super::{NotData, PlutusData};
+
+    #[test]
+    fn cbor_round_trip() {
+        // 121([_ 1, h'ab', [], {2: -18446744073709551617}, 1281([])])
+        let bytes = hex::decode("d8799f0141ab80a102c349010000000000000000d9050180ff").unwrap();
+
+        let data = PlutusData::from_cbor(&bytes).unwrap();
+
+        assert_eq!(
+            data,
+            PlutusData::Constr {
+                tag: 0,
+                fields: vec![
+                    PlutusData::Integer(1.into()),
+                    PlutusData::ByteString(vec![0xab]),
+                    PlutusData::List(vec![]),
+                    PlutusData::Map(vec![(
+                        PlutusData::Integer(2.into()),
+                        PlutusData::Integer("-18446744073709551617".parse().unwrap()),
+                    )]),
+                    PlutusData::Constr {
+                        tag: 8,
+                        fields: vec![]
+                    },
+                ],
+            }
+        );
+
+        assert_eq!(data.to_cbor(), bytes);
+    }
+
+    #[test]
+    fn long_bytestring() {
+        let data = PlutusData::ByteString(vec![7; 100]);
+
+        let bytes = data.to_cbor();
+
+        assert_eq!(bytes[0], 0x5f);
+        assert_eq!(PlutusData::from_cbor(&bytes).unwrap(), data);
+    }
+
+    #[test]
+    fn nesting() {
+        // lists of one element around a map entry, nested `depth` deep
+        let nested = |depth: usize| {
+            let mut bytes = vec![0x81; depth - 2];
+            bytes.extend([0xa1, 0x00, 0x00]);
+            bytes
+        };
+
+        assert!(PlutusData::from_cbor(&nested(super::MAX_DEPTH)).is_ok());
+        assert!(PlutusData::from_cbor(&nested(super::MAX_DEPTH + 1)).is_err());
+        assert!(PlutusData::from_cbor(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn constant_conversions() {
+        let data = PlutusData::List(vec![PlutusData::Integer(1.into())]);
+
+        let constant = Constant::from(data.clone());
+
+        assert_eq!(constant, Constant::Data(data.clone()));
+        assert_eq!(PlutusData::try_from(constant), Ok(data));
+        assert_eq!(
+            PlutusData::try_from(Constant::Unit),
+            Err(NotData(Constant::Unit))
+        );
+    }
+}
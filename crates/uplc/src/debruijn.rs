@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
 
 use thiserror::Error;
 
@@ -11,11 +14,16 @@ struct Level(usize);
 pub enum Error {
     #[error("Free Unique `{0}`")]
     FreeUnique(Unique),
+    #[error("Free Index `{0}`")]
+    FreeIndex(DeBruijn),
 }
 
 pub struct Converter {
     current_level: Level,
     levels: Vec<HashMap<Unique, Level>>,
+    current_unique: Unique,
+    hints: VecDeque<String>,
+    binder_texts: Vec<String>,
 }
 
 impl Converter {
@@ -23,6 +31,22 @@ impl Converter {
         Converter {
             current_level: Level(0),
             levels: vec![HashMap::new()],
+            current_unique: Unique::new(0),
+            hints: VecDeque::new(),
+            binder_texts: Vec::new(),
+        }
+    }
+
+    /// Create a converter that names the lambda parameters it
+    /// reconstructs after `hints`, consumed in the order the lambdas
+    /// are reached by a pre-order walk. Once the hints run out the
+    /// usual placeholder names are used. A hint naming a parameter in
+    /// scope that the lambda's body uses gets a `_1`, `_2`, ... suffix,
+    /// so the body still refers to that parameter once printed.
+    pub fn with_hints(hints: Vec<String>) -> Self {
+        Converter {
+            hints: hints.into(),
+            ..Converter::new()
         }
     }
 
@@ -35,7 +59,9 @@ impl Converter {
                 text,
                 index: self.get_index(unique)?,
             }),
-            Term::Delay(term) => Term::Delay(Box::new(self.name_to_named_debruijn(*term)?)),
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.name_to_named_debruijn(Rc::unwrap_or_clone(term))?,
+            )),
             Term::Lambda {
                 parameter_name,
                 body,
@@ -51,21 +77,25 @@ impl Converter {
 
                 self.start_scope();
 
-                let body = self.name_to_named_debruijn(*body)?;
+                let body = self.name_to_named_debruijn(Rc::unwrap_or_clone(body))?;
 
                 self.end_scope();
 
+                self.remove_unique(parameter_name.unique);
+
                 Term::Lambda {
                     parameter_name: name,
-                    body: Box::new(body),
+                    body: Rc::new(body),
                 }
             }
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.name_to_named_debruijn(*function)?),
-                argument: Box::new(self.name_to_named_debruijn(*argument)?),
+                function: Rc::new(self.name_to_named_debruijn(Rc::unwrap_or_clone(function))?),
+                argument: Rc::new(self.name_to_named_debruijn(Rc::unwrap_or_clone(argument))?),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => Term::Force(Box::new(self.name_to_named_debruijn(*term)?)),
+            Term::Force(term) => Term::Force(Rc::new(
+                self.name_to_named_debruijn(Rc::unwrap_or_clone(term))?,
+            )),
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         };
@@ -76,7 +106,9 @@ impl Converter {
     pub fn name_to_debruijn(&mut self, term: Term<Name>) -> Result<Term<DeBruijn>, Error> {
         let converted_term = match term {
             Term::Var(Name { unique, .. }) => Term::Var(self.get_index(unique)?),
-            Term::Delay(term) => Term::Delay(Box::new(self.name_to_debruijn(*term)?)),
+            Term::Delay(term) => {
+                Term::Delay(Rc::new(self.name_to_debruijn(Rc::unwrap_or_clone(term))?))
+            }
             Term::Lambda {
                 parameter_name,
                 body,
@@ -87,21 +119,25 @@ impl Converter {
 
                 self.start_scope();
 
-                let body = self.name_to_debruijn(*body)?;
+                let body = self.name_to_debruijn(Rc::unwrap_or_clone(body))?;
 
                 self.end_scope();
 
+                self.remove_unique(parameter_name.unique);
+
                 Term::Lambda {
                     parameter_name: name,
-                    body: Box::new(body),
+                    body: Rc::new(body),
                 }
             }
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.name_to_debruijn(*function)?),
-                argument: Box::new(self.name_to_debruijn(*argument)?),
+                function: Rc::new(self.name_to_debruijn(Rc::unwrap_or_clone(function))?),
+                argument: Rc::new(self.name_to_debruijn(Rc::unwrap_or_clone(argument))?),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => Term::Force(Box::new(self.name_to_debruijn(*term)?)),
+            Term::Force(term) => {
+                Term::Force(Rc::new(self.name_to_debruijn(Rc::unwrap_or_clone(term))?))
+            }
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         };
@@ -111,54 +147,177 @@ impl Converter {
 
     pub fn named_debruijn_to_name(
         &mut self,
-        _term: Term<NamedDeBruijn>,
+        term: Term<NamedDeBruijn>,
     ) -> Result<Term<Name>, Error> {
-        todo!()
+        let converted_term = match term {
+            Term::Var(NamedDeBruijn { text, index }) => Term::Var(Name {
+                text,
+                unique: self.get_unique(index)?,
+            }),
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.named_debruijn_to_name(Rc::unwrap_or_clone(term))?,
+            )),
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => {
+                let unique = self.fresh_unique();
+
+                self.declare_unique(unique);
+
+                let name = Name {
+                    text: parameter_name.text,
+                    unique,
+                };
+
+                self.start_scope();
+
+                let body = self.named_debruijn_to_name(Rc::unwrap_or_clone(body))?;
+
+                self.end_scope();
+
+                self.remove_unique(unique);
+
+                Term::Lambda {
+                    parameter_name: name,
+                    body: Rc::new(body),
+                }
+            }
+            Term::Apply { function, argument } => Term::Apply {
+                function: Rc::new(self.named_debruijn_to_name(Rc::unwrap_or_clone(function))?),
+                argument: Rc::new(self.named_debruijn_to_name(Rc::unwrap_or_clone(argument))?),
+            },
+            Term::Constant(constant) => Term::Constant(constant),
+            Term::Force(term) => Term::Force(Rc::new(
+                self.named_debruijn_to_name(Rc::unwrap_or_clone(term))?,
+            )),
+            Term::Error => Term::Error,
+            Term::Builtin(builtin) => Term::Builtin(builtin),
+        };
+
+        Ok(converted_term)
     }
 
     pub fn named_debruijn_to_debruijn(&mut self, term: Term<NamedDeBruijn>) -> Term<DeBruijn> {
         match term {
             Term::Var(name) => Term::Var(name.into()),
-            Term::Delay(term) => Term::Delay(Box::new(self.named_debruijn_to_debruijn(*term))),
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.named_debruijn_to_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Lambda {
                 parameter_name,
                 body,
             } => Term::Lambda {
                 parameter_name: parameter_name.into(),
-                body: Box::new(self.named_debruijn_to_debruijn(*body)),
+                body: Rc::new(self.named_debruijn_to_debruijn(Rc::unwrap_or_clone(body))),
             },
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.named_debruijn_to_debruijn(*function)),
-                argument: Box::new(self.named_debruijn_to_debruijn(*argument)),
+                function: Rc::new(self.named_debruijn_to_debruijn(Rc::unwrap_or_clone(function))),
+                argument: Rc::new(self.named_debruijn_to_debruijn(Rc::unwrap_or_clone(argument))),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => Term::Force(Box::new(self.named_debruijn_to_debruijn(*term))),
+            Term::Force(term) => Term::Force(Rc::new(
+                self.named_debruijn_to_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         }
     }
 
-    pub fn debruijn_to_name(&mut self, _term: Term<DeBruijn>) -> Result<Term<Name>, Error> {
-        todo!()
+    pub fn debruijn_to_name(&mut self, term: Term<DeBruijn>) -> Result<Term<Name>, Error> {
+        let converted_term = match term {
+            Term::Var(index) => {
+                let unique = self.get_unique(index)?;
+
+                Term::Var(Name {
+                    text: self.binder_text(index, || format!("i_{}", unique)),
+                    unique,
+                })
+            }
+            Term::Delay(term) => {
+                Term::Delay(Rc::new(self.debruijn_to_name(Rc::unwrap_or_clone(term))?))
+            }
+            Term::Lambda { body, .. } => {
+                let unique = self.fresh_unique();
+
+                self.declare_unique(unique);
+
+                let name = Name {
+                    text: self
+                        .next_hint(&body)
+                        .unwrap_or_else(|| format!("i_{}", unique)),
+                    unique,
+                };
+
+                self.start_scope();
+
+                self.binder_texts.push(name.text.clone());
+
+                let body = self.debruijn_to_name(Rc::unwrap_or_clone(body))?;
+
+                self.binder_texts.pop();
+
+                self.end_scope();
+
+                self.remove_unique(unique);
+
+                Term::Lambda {
+                    parameter_name: name,
+                    body: Rc::new(body),
+                }
+            }
+            Term::Apply { function, argument } => Term::Apply {
+                function: Rc::new(self.debruijn_to_name(Rc::unwrap_or_clone(function))?),
+                argument: Rc::new(self.debruijn_to_name(Rc::unwrap_or_clone(argument))?),
+            },
+            Term::Constant(constant) => Term::Constant(constant),
+            Term::Force(term) => {
+                Term::Force(Rc::new(self.debruijn_to_name(Rc::unwrap_or_clone(term))?))
+            }
+            Term::Error => Term::Error,
+            Term::Builtin(builtin) => Term::Builtin(builtin),
+        };
+
+        Ok(converted_term)
     }
 
     pub fn debruijn_to_named_debruijn(&mut self, term: Term<DeBruijn>) -> Term<NamedDeBruijn> {
         match term {
-            Term::Var(name) => Term::Var(name.into()),
-            Term::Delay(term) => Term::Delay(Box::new(self.debruijn_to_named_debruijn(*term))),
+            Term::Var(index) => Term::Var(NamedDeBruijn {
+                text: self.binder_text(index, || String::from("i")),
+                index,
+            }),
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.debruijn_to_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Lambda {
                 parameter_name,
                 body,
-            } => Term::Lambda {
-                parameter_name: parameter_name.into(),
-                body: Box::new(self.debruijn_to_named_debruijn(*body)),
-            },
+            } => {
+                let name = NamedDeBruijn {
+                    text: self.next_hint(&body).unwrap_or_else(|| String::from("i")),
+                    index: parameter_name,
+                };
+
+                self.binder_texts.push(name.text.clone());
+
+                let body = self.debruijn_to_named_debruijn(Rc::unwrap_or_clone(body));
+
+                self.binder_texts.pop();
+
+                Term::Lambda {
+                    parameter_name: name,
+                    body: Rc::new(body),
+                }
+            }
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.debruijn_to_named_debruijn(*function)),
-                argument: Box::new(self.debruijn_to_named_debruijn(*argument)),
+                function: Rc::new(self.debruijn_to_named_debruijn(Rc::unwrap_or_clone(function))),
+                argument: Rc::new(self.debruijn_to_named_debruijn(Rc::unwrap_or_clone(argument))),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => Term::Force(Box::new(self.debruijn_to_named_debruijn(*term))),
+            Term::Force(term) => Term::Force(Rc::new(
+                self.debruijn_to_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         }
@@ -170,24 +329,30 @@ impl Converter {
     ) -> Term<NamedDeBruijn> {
         match term {
             Term::Var(name) => Term::Var(name.into()),
-            Term::Delay(term) => {
-                Term::Delay(Box::new(self.fake_named_debruijn_to_named_debruijn(*term)))
-            }
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.fake_named_debruijn_to_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Lambda {
                 parameter_name,
                 body,
             } => Term::Lambda {
                 parameter_name: parameter_name.into(),
-                body: Box::new(self.fake_named_debruijn_to_named_debruijn(*body)),
+                body: Rc::new(
+                    self.fake_named_debruijn_to_named_debruijn(Rc::unwrap_or_clone(body)),
+                ),
             },
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.fake_named_debruijn_to_named_debruijn(*function)),
-                argument: Box::new(self.fake_named_debruijn_to_named_debruijn(*argument)),
+                function: Rc::new(
+                    self.fake_named_debruijn_to_named_debruijn(Rc::unwrap_or_clone(function)),
+                ),
+                argument: Rc::new(
+                    self.fake_named_debruijn_to_named_debruijn(Rc::unwrap_or_clone(argument)),
+                ),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => {
-                Term::Force(Box::new(self.fake_named_debruijn_to_named_debruijn(*term)))
-            }
+            Term::Force(term) => Term::Force(Rc::new(
+                self.fake_named_debruijn_to_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         }
@@ -199,24 +364,30 @@ impl Converter {
     ) -> Term<FakeNamedDeBruijn> {
         match term {
             Term::Var(name) => Term::Var(name.into()),
-            Term::Delay(term) => {
-                Term::Delay(Box::new(self.named_debruijn_to_fake_named_debruijn(*term)))
-            }
+            Term::Delay(term) => Term::Delay(Rc::new(
+                self.named_debruijn_to_fake_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Lambda {
                 parameter_name,
                 body,
             } => Term::Lambda {
                 parameter_name: parameter_name.into(),
-                body: Box::new(self.named_debruijn_to_fake_named_debruijn(*body)),
+                body: Rc::new(
+                    self.named_debruijn_to_fake_named_debruijn(Rc::unwrap_or_clone(body)),
+                ),
             },
             Term::Apply { function, argument } => Term::Apply {
-                function: Box::new(self.named_debruijn_to_fake_named_debruijn(*function)),
-                argument: Box::new(self.named_debruijn_to_fake_named_debruijn(*argument)),
+                function: Rc::new(
+                    self.named_debruijn_to_fake_named_debruijn(Rc::unwrap_or_clone(function)),
+                ),
+                argument: Rc::new(
+                    self.named_debruijn_to_fake_named_debruijn(Rc::unwrap_or_clone(argument)),
+                ),
             },
             Term::Constant(constant) => Term::Constant(constant),
-            Term::Force(term) => {
-                Term::Force(Box::new(self.named_debruijn_to_fake_named_debruijn(*term)))
-            }
+            Term::Force(term) => Term::Force(Rc::new(
+                self.named_debruijn_to_fake_named_debruijn(Rc::unwrap_or_clone(term)),
+            )),
             Term::Error => Term::Error,
             Term::Builtin(builtin) => Term::Builtin(builtin),
         }
@@ -234,6 +405,71 @@ impl Converter {
         Err(Error::FreeUnique(unique))
     }
 
+    fn get_unique(&mut self, index: DeBruijn) -> Result<Unique, Error> {
+        let index_value: usize = index.into();
+
+        if index_value == 0 || index_value > self.current_level.0 {
+            return Err(Error::FreeIndex(index));
+        }
+
+        let target_level = self.current_level.0 - index_value;
+
+        for (unique, found_level) in self.levels[target_level].iter() {
+            if found_level.0 == target_level {
+                return Ok(*unique);
+            }
+        }
+
+        Err(Error::FreeIndex(index))
+    }
+
+    /// The name for a lambda with `body`, nested in the lambdas named
+    /// by `binder_texts`.
+    fn next_hint(&mut self, body: &Term<DeBruijn>) -> Option<String> {
+        let hint = self.hints.pop_front()?;
+
+        // inside the body, 1 is the lambda's own parameter
+        let captures = |text: &str| {
+            self.binder_texts
+                .iter()
+                .rev()
+                .enumerate()
+                .any(|(offset, bound)| bound == text && references(body, offset + 2))
+        };
+
+        if !captures(&hint) {
+            return Some(hint);
+        }
+
+        (1..)
+            .map(|suffix| format!("{hint}_{suffix}"))
+            .find(|text| !captures(text))
+    }
+
+    fn binder_text(&self, index: DeBruijn, placeholder: impl FnOnce() -> String) -> String {
+        let index: usize = index.into();
+
+        index
+            .checked_sub(1)
+            .and_then(|offset| self.binder_texts.iter().rev().nth(offset))
+            .cloned()
+            .unwrap_or_else(placeholder)
+    }
+
+    fn fresh_unique(&mut self) -> Unique {
+        let unique = self.current_unique;
+
+        self.current_unique.increment();
+
+        unique
+    }
+
+    fn remove_unique(&mut self, unique: Unique) {
+        let scope = &mut self.levels[self.current_level.0];
+
+        scope.remove(&unique);
+    }
+
     fn declare_unique(&mut self, unique: Unique) {
         let scope = &mut self.levels[self.current_level.0];
 
@@ -252,3 +488,17 @@ impl Converter {
         self.levels.pop();
     }
 }
+
+/// Whether `term` uses the variable with `index`, counted from the
+/// top of `term`.
+fn references(term: &Term<DeBruijn>, index: usize) -> bool {
+    match term {
+        Term::Var(var) => usize::from(*var) == index,
+        Term::Delay(term) | Term::Force(term) => references(term, index),
+        Term::Lambda { body, .. } => references(body, index + 1),
+        Term::Apply { function, argument } => {
+            references(function, index) || references(argument, index)
+        }
+        Term::Constant(_) | Term::Error | Term::Builtin(_) => false,
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::Display;
+
+use crate::ast::{Name, Program, Term};
+
+/// A step from a term into one of it's subterms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step<'a> {
+    Delay,
+    /// Into the body of a lambda, with the parameter it has on the left.
+    Lambda(&'a Name),
+    Function,
+    Argument,
+    Force,
+}
+
+/// Where two programs diverge, the subterms at `path` differ
+/// while everything around them is alpha equivalent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference<'a> {
+    pub path: Vec<Step<'a>>,
+    pub left: &'a Term<Name>,
+    pub right: &'a Term<Name>,
+}
+
+impl Difference<'_> {
+    /// The terms around the difference, with a `•` in it's place.
+    pub fn context(&self) -> String {
+        self.path
+            .iter()
+            .rev()
+            .fold("•".to_string(), |inner, step| match step {
+                Step::Delay => format!("(delay {inner})"),
+                Step::Lambda(name) => format!("(lam {} {inner})", name.text),
+                Step::Function => format!("[{inner} _]"),
+                Step::Argument => format!("[_ {inner}]"),
+                Step::Force => format!("(force {inner})"),
+            })
+    }
+}
+
+impl Display for Difference<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "in {}", self.context())?;
+
+        for line in self.left.to_pretty().lines() {
+            writeln!(f, "- {line}")?;
+        }
+
+        for line in self.right.to_pretty().lines() {
+            writeln!(f, "+ {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Term<Name> {
+    /// Equality up to the names of lambda parameters. Bound variables
+    /// are compared by their debruijn index and free ones by their text,
+    /// uniques differ between separately parsed programs.
+    pub fn alpha_eq(&self, other: &Term<Name>) -> bool {
+        let mut differences = Vec::new();
+
+        compare(self, other, &mut Scopes::default(), &mut differences, true);
+
+        differences.is_empty()
+    }
+}
+
+impl Program<Name> {
+    /// The outermost subterms where the two programs' terms aren't
+    /// alpha equivalent, from left to right. Versions aren't compared.
+    pub fn diff<'a>(&'a self, other: &'a Program<Name>) -> Vec<Difference<'a>> {
+        let mut differences = Vec::new();
+
+        compare(
+            &self.term,
+            &other.term,
+            &mut Scopes::default(),
+            &mut differences,
+            false,
+        );
+
+        differences
+    }
+}
+
+/// The lambdas around the terms being compared.
+#[derive(Default)]
+struct Scopes<'a> {
+    path: Vec<Step<'a>>,
+    left: Vec<&'a Name>,
+    right: Vec<&'a Name>,
+}
+
+fn index(scope: &[&Name], name: &Name) -> Option<usize> {
+    scope.iter().rev().position(|bound| *bound == name)
+}
+
+fn compare<'a>(
+    left: &'a Term<Name>,
+    right: &'a Term<Name>,
+    scopes: &mut Scopes<'a>,
+    differences: &mut Vec<Difference<'a>>,
+    first_only: bool,
+) {
+    if first_only && !differences.is_empty() {
+        return;
+    }
+
+    let mut nested = |step, left, right, scopes: &mut Scopes<'a>| {
+        scopes.path.push(step);
+        compare(left, right, scopes, differences, first_only);
+        scopes.path.pop();
+    };
+
+    let same = match (left, right) {
+        (Term::Var(l), Term::Var(r)) => match (index(&scopes.left, l), index(&scopes.right, r)) {
+            (None, None) => l.text == r.text,
+            (l, r) => l == r,
+        },
+        (Term::Delay(l), Term::Delay(r)) => {
+            nested(Step::Delay, l, r, scopes);
+
+            true
+        }
+        (
+            Term::Lambda {
+                parameter_name: l_name,
+                body: l_body,
+            },
+            Term::Lambda {
+                parameter_name: r_name,
+                body: r_body,
+            },
+        ) => {
+            scopes.left.push(l_name);
+            scopes.right.push(r_name);
+
+            nested(Step::Lambda(l_name), l_body, r_body, scopes);
+
+            scopes.left.pop();
+            scopes.right.pop();
+
+            true
+        }
+        (
+            Term::Apply {
+                function: l_function,
+                argument: l_argument,
+            },
+            Term::Apply {
+                function: r_function,
+                argument: r_argument,
+            },
+        ) => {
+            nested(Step::Function, l_function, r_function, scopes);
+            nested(Step::Argument, l_argument, r_argument, scopes);
+
+            true
+        }
+        (Term::Force(l), Term::Force(r)) => {
+            nested(Step::Force, l, r, scopes);
+
+            true
+        }
+        (Term::Constant(l), Term::Constant(r)) => l == r,
+        (Term::Error, Term::Error) => true,
+        (Term::Builtin(l), Term::Builtin(r)) => l == r,
+        _ => false,
+    };
+
+    if !same {
+        differences.push(Difference {
+            path: scopes.path.clone(),
+            left,
+            right,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{Name, Program, Term},
+        parser,
+    };
+
+    fn term(code: &str) -> Term<Name> {
+        parser::program(&format!("(program 1.0.0 {code})"))
+            .unwrap()
+            .term
+    }
+
+    #[test]
+    fn alpha_eq() {
+        assert!(term("(lam x (lam y [x y]))").alpha_eq(&term("(lam a (lam b [a b]))")));
+        assert!(term("(lam x (lam x x))").alpha_eq(&term("(lam x (lam y y))")));
+        assert!(!term("(lam x (lam y [x y]))").alpha_eq(&term("(lam a (lam b [b a]))")));
+        assert!(!term("(lam x (lam x x))").alpha_eq(&term("(lam x (lam y x))")));
+
+        // free variables are compared by name
+        assert!(term("[f (lam x x)]").alpha_eq(&term("[f (lam y y)]")));
+        assert!(!term("[f (lam x x)]").alpha_eq(&term("[g (lam y y)]")));
+        assert!(!term("(lam f f)").alpha_eq(&term("(lam x f)")));
+
+        // unlike equality, which compares uniques
+        let mut interner = parser::Interner::new();
+
+        parser::program_with_interner("(program 1.0.0 y)", &mut interner).unwrap();
+
+        let shifted = parser::program_with_interner("(program 1.0.0 (lam x x))", &mut interner)
+            .unwrap()
+            .term;
+
+        assert_ne!(term("(lam x x)"), shifted);
+        assert!(term("(lam x x)").alpha_eq(&shifted));
+    }
+
+    #[test]
+    fn diff() {
+        let program = |code: &str| -> Program<Name> {
+            parser::program(&format!("(program 1.0.0 {code})")).unwrap()
+        };
+
+        let left = program("(lam x [(force (con integer 1)) (lam y x) (builtin addInteger)])");
+        let right = program("(lam z [(force (con integer 2)) (lam w w) (builtin addInteger)])");
+
+        let differences = left.diff(&right);
+
+        assert_eq!(
+            differences
+                .iter()
+                .map(|difference| difference.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "in (lam x [[(force •) _] _])\n- (con integer 1)\n+ (con integer 2)\n",
+                "in (lam x [[_ (lam y •)] _])\n- x\n+ w\n",
+            ]
+        );
+
+        assert!(left.diff(&left).is_empty());
+        assert_eq!(left.diff(&program("(error)")).len(), 1);
+    }
+}
@@ -1,23 +1,38 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, rc::Rc};
 
+use blake2::{digest::consts::U28, Blake2b, Digest};
 use flat::{
     de::{self, Decode, Decoder},
     en::{self, Encode, Encoder},
     Flat,
 };
+use miette::SourceSpan;
+use num_bigint::BigInt;
 
 use crate::{
-    ast::{Constant, DeBruijn, FakeNamedDeBruijn, Name, NamedDeBruijn, Program, Term, Unique},
+    annotations::{Annotations, Placement},
+    ast::{
+        Constant, DeBruijn, FakeNamedDeBruijn, Name, NamedDeBruijn, PlutusVersion, Program, Term,
+        Type, Unique,
+    },
+    borrowed,
     builtins::DefaultFunction,
+    data::PlutusData,
+    limits::Limits,
+    parser::Comment,
+    typed,
 };
 
 const BUILTIN_TAG_WIDTH: u32 = 7;
 const CONST_TAG_WIDTH: u32 = 4;
 const TERM_TAG_WIDTH: u32 = 4;
+const TYPE_TAG_WIDTH: usize = 3;
+const KIND_TAG_WIDTH: usize = 1;
 
 pub trait Binder<'b>: Encode + Decode<'b> {
     fn binder_encode(&self, e: &mut Encoder) -> Result<(), en::Error>;
     fn binder_decode(d: &mut Decoder) -> Result<Self, de::Error>;
+    fn text(&self) -> String;
 }
 
 impl<'b, T> Flat<'b> for Program<T> where T: Binder<'b> + Debug {}
@@ -40,11 +55,278 @@ where
         Ok(hex)
     }
 
+    /// Decode a program within the default `Limits`.
     pub fn from_flat(bytes: &'b [u8]) -> Result<Self, de::Error> {
+        Self::from_flat_with_limits(bytes, Limits::default())
+    }
+
+    /// Decode a program, failing instead of overflowing the stack on
+    /// terms nested deeper than `limits` allow.
+    pub fn from_flat_with_limits(bytes: &'b [u8], limits: Limits) -> Result<Self, de::Error> {
+        if bytes.len() > limits.max_size {
+            return Err(de::Error::Message(format!(
+                "Expected at most {} bytes but got {}",
+                limits.max_size,
+                bytes.len()
+            )));
+        }
+
+        flat::decode_with_max_depth(bytes, limits.max_depth)
+    }
+
+    /// Encode a program meant for `version`, refusing
+    /// to use builtins it doesn't have.
+    pub fn to_flat_with_version(&self, version: PlutusVersion) -> Result<Vec<u8>, en::Error> {
+        self.check_version(version)
+            .map_err(|err| en::Error::Message(err.to_string()))?;
+
+        self.to_flat()
+    }
+
+    /// Decode a program meant for `version`, refusing
+    /// to use builtins it doesn't have.
+    pub fn from_flat_with_version(
+        bytes: &'b [u8],
+        version: PlutusVersion,
+    ) -> Result<Self, de::Error> {
+        let program = Self::from_flat(bytes)?;
+
+        program
+            .check_version(version)
+            .map_err(|err| de::Error::Message(err.to_string()))?;
+
+        Ok(program)
+    }
+
+    /// Encode the program the way scripts are stored on chain,
+    /// as flat bytes wrapped in a CBOR bytestring.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, en::Error> {
+        let flat_bytes = self.flat()?;
+
+        let mut e = minicbor::Encoder::new(Vec::new());
+
+        e.bytes(&flat_bytes).expect("writing to a vec can't fail");
+
+        Ok(e.into_writer())
+    }
+
+    /// The hash a script is identified by on chain, the blake2b-224
+    /// of it's CBOR encoding prefixed by the language tag of `version`.
+    pub fn to_hash(&self, version: PlutusVersion) -> Result<[u8; 28], en::Error> {
+        let tag = match version {
+            PlutusVersion::V1 => 1,
+            PlutusVersion::V2 => 2,
+        };
+
+        let hash = Blake2b::<U28>::new()
+            .chain_update([tag])
+            .chain_update(self.to_cbor()?)
+            .finalize();
+
+        Ok(hash.into())
+    }
+
+    /// Decode a script as found on chain. Scripts taken from a
+    /// `cardano-cli` text envelope are wrapped in CBOR twice, both
+    /// forms are accepted.
+    pub fn from_cbor(bytes: &'b [u8]) -> Result<Self, de::Error> {
+        let mut flat_bytes = unwrap_cbor_bytes(bytes).ok_or_else(|| {
+            de::Error::Message("Expected flat bytes wrapped in a CBOR bytestring".to_string())
+        })?;
+
+        // flat programs start with their major version, which
+        // is never mistaken for a CBOR bytestring header
+        if let Some(inner) = unwrap_cbor_bytes(flat_bytes) {
+            flat_bytes = inner;
+        }
+
+        Self::from_flat(flat_bytes)
+    }
+
+    /// Decode the scripts of a CBOR sequence, e.g. a dump of the scripts
+    /// found on chain, one at a time as the iterator is advanced. Each
+    /// script is wrapped like `from_cbor` accepts and is read in place,
+    /// only the decoded terms are allocated. A script failing to decode
+    /// doesn't stop the ones after it, truncated CBOR ends the sequence.
+    pub fn from_cbor_seq(bytes: &'b [u8]) -> impl Iterator<Item = Result<Self, de::Error>> + 'b
+    where
+        T: 'b,
+    {
+        let mut d = minicbor::Decoder::new(bytes);
+        let mut truncated = false;
+
+        std::iter::from_fn(move || {
+            let start = d.position();
+
+            if truncated || start == bytes.len() {
+                return None;
+            }
+
+            Some(match d.skip() {
+                Ok(()) => Self::from_cbor(&bytes[start..d.position()]),
+                Err(err) => {
+                    truncated = true;
+
+                    Err(de::Error::Message(err.to_string()))
+                }
+            })
+        })
+    }
+
+    /// Like `from_cbor` but for a script meant for `version`.
+    pub fn from_cbor_with_version(
+        bytes: &'b [u8],
+        version: PlutusVersion,
+    ) -> Result<Self, de::Error> {
+        let program = Self::from_cbor(bytes)?;
+
+        program
+            .check_version(version)
+            .map_err(|err| de::Error::Message(err.to_string()))?;
+
+        Ok(program)
+    }
+}
+
+impl Program<DeBruijn> {
+    /// Decode a program within the default `Limits`, borrowing it's
+    /// bytestrings from `bytes` where they are written in one piece,
+    /// see `borrowed::Constant`.
+    pub fn from_flat_borrowed(bytes: &[u8]) -> Result<borrowed::Program<'_>, de::Error> {
+        let mut d = Decoder::with_max_depth(bytes, Limits::MAX_DEPTH);
+
+        let version = (
+            usize::decode(&mut d)?,
+            usize::decode(&mut d)?,
+            usize::decode(&mut d)?,
+        );
+        let term = decode_borrowed_term(&mut d)?;
+
+        d.filler()?;
+
+        Ok(borrowed::Program { version, term })
+    }
+}
+
+/// The contents of `bytes` if it is exactly one definite length CBOR bytestring.
+fn unwrap_cbor_bytes(bytes: &[u8]) -> Option<&[u8]> {
+    let mut d = minicbor::Decoder::new(bytes);
+
+    d.bytes().ok().filter(|_| d.position() == bytes.len())
+}
+
+impl<'b> Flat<'b> for Annotations {}
+
+impl Annotations {
+    pub fn to_flat(&self) -> Result<Vec<u8>, en::Error> {
+        self.flat()
+    }
+
+    pub fn from_flat(bytes: &[u8]) -> Result<Self, de::Error> {
         Self::unflat(bytes)
     }
 }
 
+impl Encode for Annotations {
+    fn encode(&self, e: &mut Encoder) -> Result<(), en::Error> {
+        for binder in &self.binders {
+            e.bool(true);
+            binder.encode(e)?;
+        }
+
+        e.bool(false);
+
+        for span in &self.spans {
+            e.bool(true);
+            encode_span(span, e)?;
+        }
+
+        e.bool(false);
+
+        for comment in &self.comments {
+            e.bool(true);
+            encode_span(&comment.span, e)?;
+            comment.text.encode(e)?;
+        }
+
+        e.bool(false);
+
+        for placement in &self.placements {
+            e.bool(true);
+
+            match placement {
+                Placement::Leading => 0_usize.encode(e)?,
+                Placement::Before(index) => {
+                    1_usize.encode(e)?;
+                    index.encode(e)?;
+                }
+                Placement::Trailing => 2_usize.encode(e)?,
+            }
+        }
+
+        e.bool(false);
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b> for Annotations {
+    fn decode(d: &mut Decoder) -> Result<Self, de::Error> {
+        let mut binders = Vec::new();
+
+        while d.bool()? {
+            binders.push(Name::decode(d)?);
+        }
+
+        let mut spans = Vec::new();
+
+        while d.bool()? {
+            spans.push(decode_span(d)?);
+        }
+
+        let mut comments = Vec::new();
+
+        while d.bool()? {
+            comments.push(Comment {
+                span: decode_span(d)?,
+                text: String::decode(d)?,
+            });
+        }
+
+        let mut placements = Vec::new();
+
+        while d.bool()? {
+            placements.push(match usize::decode(d)? {
+                0 => Placement::Leading,
+                1 => Placement::Before(usize::decode(d)?),
+                2 => Placement::Trailing,
+                x => {
+                    return Err(de::Error::Message(format!(
+                        "Unknown comment placement tag: {}",
+                        x
+                    )))
+                }
+            });
+        }
+
+        Ok(Annotations {
+            binders,
+            spans,
+            comments,
+            placements,
+        })
+    }
+}
+
+fn encode_span(span: &SourceSpan, e: &mut Encoder) -> Result<(), en::Error> {
+    span.offset().encode(e)?;
+    span.len().encode(e)
+}
+
+fn decode_span(d: &mut Decoder) -> Result<SourceSpan, de::Error> {
+    Ok((usize::decode(d)?, usize::decode(d)?).into())
+}
+
 impl<'b, T> Encode for Program<T>
 where
     T: Binder<'b> + Debug,
@@ -129,46 +411,77 @@ impl<'b, T> Decode<'b> for Term<T>
 where
     T: Binder<'b>,
 {
+    /// Terms are decoded with a stack of the terms waiting for their
+    /// subterms rather than recursively, since the bytes can come from
+    /// anywhere.
     fn decode(d: &mut Decoder) -> Result<Self, de::Error> {
-        match decode_term_tag(d)? {
-            0 => Ok(Term::Var(T::decode(d)?)),
-            1 => Ok(Term::Delay(Box::new(Term::decode(d)?))),
-            2 => Ok(Term::Lambda {
-                parameter_name: T::binder_decode(d)?,
-                body: Box::new(Term::decode(d)?),
-            }),
-            3 => Ok(Term::Apply {
-                function: Box::new(Term::decode(d)?),
-                argument: Box::new(Term::decode(d)?),
-            }),
-            // Need size limit for Constant
-            4 => Ok(Term::Constant(Constant::decode(d)?)),
-            5 => Ok(Term::Force(Box::new(Term::decode(d)?))),
-            6 => Ok(Term::Error),
-            7 => Ok(Term::Builtin(DefaultFunction::decode(d)?)),
-            x => Err(de::Error::Message(format!(
-                "Unknown term constructor tag: {}",
-                x
-            ))),
+        let mut frames = Vec::new();
+
+        loop {
+            // descend to a term without subterms
+            let mut term = loop {
+                d.enter()?;
+
+                match decode_term_tag(d)? {
+                    0 => break Term::Var(T::decode(d)?),
+                    1 => frames.push(Frame::Delay),
+                    2 => frames.push(Frame::Lambda(T::binder_decode(d)?)),
+                    3 => frames.push(Frame::Function),
+                    4 => break Term::Constant(Constant::decode(d)?),
+                    5 => frames.push(Frame::Force),
+                    6 => break Term::Error,
+                    7 => break Term::Builtin(DefaultFunction::decode(d)?),
+                    x => {
+                        return Err(de::Error::Message(format!(
+                            "Unknown term constructor tag: {}",
+                            x
+                        )))
+                    }
+                }
+            };
+
+            // complete the terms waiting for it, until one is still
+            // missing it's argument
+            loop {
+                d.leave();
+
+                term = match frames.pop() {
+                    None => return Ok(term),
+                    Some(Frame::Delay) => Term::Delay(Rc::new(term)),
+                    Some(Frame::Lambda(parameter_name)) => Term::Lambda {
+                        parameter_name,
+                        body: Rc::new(term),
+                    },
+                    Some(Frame::Function) => {
+                        frames.push(Frame::Argument(term));
+
+                        break;
+                    }
+                    Some(Frame::Argument(function)) => Term::Apply {
+                        function: Rc::new(function),
+                        argument: Rc::new(term),
+                    },
+                    Some(Frame::Force) => Term::Force(Rc::new(term)),
+                };
+            }
         }
     }
 }
 
+/// A term being decoded, waiting for it's next subterm.
+enum Frame<N, T> {
+    Delay,
+    Lambda(N),
+    /// An application waiting for it's function.
+    Function,
+    /// An application waiting for it's argument.
+    Argument(T),
+    Force,
+}
+
 impl Encode for &Constant {
     fn encode(&self, e: &mut Encoder) -> Result<(), en::Error> {
         match self {
-            Constant::Integer(i) => {
-                encode_constant(0, e)?;
-                i.encode(e)?;
-            }
-            Constant::ByteString(bytes) => {
-                encode_constant(1, e)?;
-                bytes.encode(e)?;
-            }
-            Constant::String(s) => {
-                encode_constant(2, e)?;
-                s.encode(e)?;
-            }
             // there is no char constant tag
             Constant::Char(c) => {
                 let mut b = [0; 4];
@@ -177,10 +490,10 @@ impl Encode for &Constant {
 
                 s.as_bytes().encode(e)?;
             }
-            Constant::Unit => encode_constant(3, e)?,
-            Constant::Bool(b) => {
-                encode_constant(4, e)?;
-                b.encode(e)?;
+            constant => {
+                e.encode_list_with(encode_type(&constant.ty())?, encode_constant_tag)?;
+
+                encode_constant_value(constant, e)?;
             }
         }
 
@@ -188,20 +501,441 @@ impl Encode for &Constant {
     }
 }
 
+fn encode_constant_value(constant: &Constant, e: &mut Encoder) -> Result<(), en::Error> {
+    match constant {
+        Constant::Integer(i) => i.encode(e)?,
+        Constant::ByteString(bytes) => bytes.encode(e)?,
+        Constant::String(s) => s.encode(e)?,
+        Constant::Char(c) => c.encode(e)?,
+        Constant::Unit => (),
+        Constant::Bool(b) => b.encode(e)?,
+        Constant::ProtoList(_, items) => {
+            for item in items {
+                e.bool(true);
+                encode_constant_value(item, e)?;
+            }
+
+            e.bool(false);
+        }
+        Constant::ProtoPair(_, _, left, right) => {
+            encode_constant_value(left, e)?;
+            encode_constant_value(right, e)?;
+        }
+        // data is embedded as it's cbor serialisation
+        Constant::Data(data) => data.to_cbor().encode(e)?,
+    }
+
+    Ok(())
+}
+
 impl<'b> Decode<'b> for Constant {
     fn decode(d: &mut Decoder) -> Result<Self, de::Error> {
-        match decode_constant(d)? {
-            0 => Ok(Constant::Integer(isize::decode(d)?)),
-            1 => Ok(Constant::ByteString(Vec::<u8>::decode(d)?)),
-            2 => Ok(Constant::String(String::decode(d)?)),
-            3 => Ok(Constant::Unit),
-            4 => Ok(Constant::Bool(bool::decode(d)?)),
+        let ty = decode_constant_type(d)?;
+
+        decode_constant_value(&ty, d)
+    }
+}
+
+fn decode_constant_type(d: &mut Decoder) -> Result<Type, de::Error> {
+    let tags = d.decode_list_with(decode_constant_tag)?;
+
+    let mut tags = tags.into_iter();
+
+    let ty = decode_type(&mut tags, d)?;
+
+    if tags.next().is_some() {
+        return Err(de::Error::Message(
+            "Improper encoding on constant tag, left over type tags".to_string(),
+        ));
+    }
+
+    Ok(ty)
+}
+
+fn decode_constant_value(ty: &Type, d: &mut Decoder) -> Result<Constant, de::Error> {
+    match ty {
+        Type::Integer => Ok(Constant::Integer(BigInt::decode(d)?)),
+        Type::ByteString => Ok(Constant::ByteString(Vec::<u8>::decode(d)?)),
+        Type::String => Ok(Constant::String(String::decode(d)?)),
+        Type::Unit => Ok(Constant::Unit),
+        Type::Bool => Ok(Constant::Bool(bool::decode(d)?)),
+        Type::List(elem) => {
+            let mut items = Vec::new();
+
+            while d.bool()? {
+                items.push(decode_constant_value(elem, d)?);
+            }
+
+            Ok(Constant::ProtoList(elem.as_ref().clone(), items))
+        }
+        Type::Pair(left, right) => {
+            let left_value = decode_constant_value(left, d)?;
+            let right_value = decode_constant_value(right, d)?;
+
+            Ok(Constant::ProtoPair(
+                left.as_ref().clone(),
+                right.as_ref().clone(),
+                Box::new(left_value),
+                Box::new(right_value),
+            ))
+        }
+        Type::Data => {
+            let bytes = Vec::<u8>::decode(d)?;
+
+            let data = PlutusData::from_cbor(&bytes)
+                .map_err(|err| de::Error::Message(format!("Invalid data constant: {}", err)))?;
+
+            Ok(Constant::Data(data))
+        }
+        Type::Var(name) => Err(de::Error::Message(format!(
+            "Constants can't have the type variable `{name}`"
+        ))),
+    }
+}
+
+/// Like `Term::decode`, for a term borrowing from the decoded bytes.
+fn decode_borrowed_term<'a>(d: &mut Decoder<'a>) -> Result<borrowed::Term<'a>, de::Error> {
+    let mut frames = Vec::new();
+
+    loop {
+        let mut term = loop {
+            d.enter()?;
+
+            match decode_term_tag(d)? {
+                0 => break borrowed::Term::Var(DeBruijn::decode(d)?),
+                1 => frames.push(Frame::Delay),
+                2 => frames.push(Frame::Lambda(DeBruijn::binder_decode(d)?)),
+                3 => frames.push(Frame::Function),
+                4 => {
+                    let ty = decode_constant_type(d)?;
+
+                    break borrowed::Term::Constant(decode_borrowed_constant_value(&ty, d)?);
+                }
+                5 => frames.push(Frame::Force),
+                6 => break borrowed::Term::Error,
+                7 => break borrowed::Term::Builtin(DefaultFunction::decode(d)?),
+                x => {
+                    return Err(de::Error::Message(format!(
+                        "Unknown term constructor tag: {}",
+                        x
+                    )))
+                }
+            }
+        };
+
+        loop {
+            d.leave();
+
+            term = match frames.pop() {
+                None => return Ok(term),
+                Some(Frame::Delay) => borrowed::Term::Delay(Box::new(term)),
+                Some(Frame::Lambda(parameter_name)) => borrowed::Term::Lambda {
+                    parameter_name,
+                    body: Box::new(term),
+                },
+                Some(Frame::Function) => {
+                    frames.push(Frame::Argument(term));
+
+                    break;
+                }
+                Some(Frame::Argument(function)) => borrowed::Term::Apply {
+                    function: Box::new(function),
+                    argument: Box::new(term),
+                },
+                Some(Frame::Force) => borrowed::Term::Force(Box::new(term)),
+            };
+        }
+    }
+}
+
+fn decode_borrowed_constant_value<'a>(
+    ty: &Type,
+    d: &mut Decoder<'a>,
+) -> Result<borrowed::Constant<'a>, de::Error> {
+    match ty {
+        Type::ByteString => Ok(borrowed::Constant::ByteString(d.bytes_borrowed()?)),
+        Type::List(elem) => {
+            let mut items = Vec::new();
+
+            while d.bool()? {
+                items.push(decode_borrowed_constant_value(elem, d)?);
+            }
+
+            Ok(borrowed::Constant::ProtoList(elem.as_ref().clone(), items))
+        }
+        Type::Pair(left, right) => {
+            let left_value = decode_borrowed_constant_value(left, d)?;
+            let right_value = decode_borrowed_constant_value(right, d)?;
+
+            Ok(borrowed::Constant::ProtoPair(
+                left.as_ref().clone(),
+                right.as_ref().clone(),
+                Box::new(left_value),
+                Box::new(right_value),
+            ))
+        }
+        Type::Data => {
+            let bytes = d.bytes_borrowed()?;
+
+            let data = PlutusData::from_cbor(&bytes)
+                .map_err(|err| de::Error::Message(format!("Invalid data constant: {}", err)))?;
+
+            Ok(borrowed::Constant::Data(data))
+        }
+        // nothing else holds bytes
+        ty => Ok(match decode_constant_value(ty, d)? {
+            Constant::Integer(i) => borrowed::Constant::Integer(i),
+            Constant::String(s) => borrowed::Constant::String(s),
+            Constant::Unit => borrowed::Constant::Unit,
+            Constant::Bool(b) => borrowed::Constant::Bool(b),
+            _ => unreachable!("decoded a {ty:?} constant"),
+        }),
+    }
+}
+
+/// The type tags of a constant, type applications are written in
+/// prefix form using tag 7.
+fn encode_type(ty: &Type) -> Result<Vec<u8>, en::Error> {
+    Ok(match ty {
+        Type::Integer => vec![0],
+        Type::ByteString => vec![1],
+        Type::String => vec![2],
+        Type::Unit => vec![3],
+        Type::Bool => vec![4],
+        Type::List(elem) => [vec![7, 5], encode_type(elem)?].concat(),
+        Type::Pair(left, right) => {
+            [vec![7, 7, 6], encode_type(left)?, encode_type(right)?].concat()
+        }
+        Type::Data => vec![8],
+        Type::Var(name) => {
+            return Err(en::Error::Message(format!(
+                "Constants can't have the type variable `{name}`"
+            )))
+        }
+    })
+}
+
+fn decode_type(tags: &mut impl Iterator<Item = u8>, d: &mut Decoder) -> Result<Type, de::Error> {
+    d.enter()?;
+
+    let ty = match tags.next() {
+        Some(0) => Ok(Type::Integer),
+        Some(1) => Ok(Type::ByteString),
+        Some(2) => Ok(Type::String),
+        Some(3) => Ok(Type::Unit),
+        Some(4) => Ok(Type::Bool),
+        Some(7) => match tags.next() {
+            Some(5) => Ok(Type::List(Box::new(decode_type(tags, d)?))),
+            Some(7) => match tags.next() {
+                Some(6) => {
+                    let left = decode_type(tags, d)?;
+                    let right = decode_type(tags, d)?;
+
+                    Ok(Type::Pair(Box::new(left), Box::new(right)))
+                }
+                x => Err(de::Error::Message(format!(
+                    "Unknown constant type application tag: {:?}",
+                    x
+                ))),
+            },
             x => Err(de::Error::Message(format!(
-                "Unknown constant constructor tag: {}",
+                "Unknown constant type application tag: {:?}",
                 x
             ))),
+        },
+        Some(8) => Ok(Type::Data),
+        x => Err(de::Error::Message(format!(
+            "Unknown constant constructor tag: {:?}",
+            x
+        ))),
+    };
+
+    d.leave();
+
+    ty
+}
+
+impl typed::Program {
+    /// Decode a Typed Plutus Core program whose names have uniques,
+    /// as plutus-tx tooling writes them, within the default `Limits`.
+    /// Names are suffixed with their unique so they stay distinct.
+    pub fn from_flat(bytes: &[u8]) -> Result<Self, de::Error> {
+        let limits = Limits::default();
+
+        if bytes.len() > limits.max_size {
+            return Err(de::Error::Message(format!(
+                "Expected at most {} bytes but got {}",
+                limits.max_size,
+                bytes.len()
+            )));
+        }
+
+        flat::decode_with_max_depth(bytes, limits.max_depth)
+    }
+}
+
+impl<'b> Decode<'b> for typed::Program {
+    fn decode(d: &mut Decoder) -> Result<Self, de::Error> {
+        let version = (usize::decode(d)?, usize::decode(d)?, usize::decode(d)?);
+        let term = decode_typed_term(d)?;
+
+        Ok(typed::Program { version, term })
+    }
+}
+
+fn decode_typed_term(d: &mut Decoder) -> Result<typed::Term, de::Error> {
+    d.enter()?;
+
+    let term = match decode_term_tag(d)? {
+        0 => typed::Term::Var(decode_typed_name(d)?),
+        1 => typed::Term::TyAbs {
+            parameter_name: decode_typed_name(d)?,
+            kind: decode_kind(d)?,
+            body: Box::new(decode_typed_term(d)?),
+        },
+        2 => typed::Term::Lambda {
+            parameter_name: decode_typed_name(d)?,
+            parameter_type: decode_typed_type(d)?,
+            body: Box::new(decode_typed_term(d)?),
+        },
+        3 => typed::Term::Apply {
+            function: Box::new(decode_typed_term(d)?),
+            argument: Box::new(decode_typed_term(d)?),
+        },
+        4 => typed::Term::Constant(Constant::decode(d)?),
+        5 => typed::Term::TyInst {
+            term: Box::new(decode_typed_term(d)?),
+            ty: decode_typed_type(d)?,
+        },
+        6 => typed::Term::Unwrap(Box::new(decode_typed_term(d)?)),
+        7 => typed::Term::IWrap {
+            pattern: decode_typed_type(d)?,
+            argument: decode_typed_type(d)?,
+            term: Box::new(decode_typed_term(d)?),
+        },
+        8 => typed::Term::Error(decode_typed_type(d)?),
+        9 => typed::Term::Builtin(DefaultFunction::decode(d)?),
+        x => {
+            return Err(de::Error::Message(format!(
+                "Unknown typed term constructor tag: {}",
+                x
+            )))
+        }
+    };
+
+    d.leave();
+
+    Ok(term)
+}
+
+fn decode_typed_type(d: &mut Decoder) -> Result<typed::Type, de::Error> {
+    d.enter()?;
+
+    let ty = match d.bits8(TYPE_TAG_WIDTH)? {
+        0 => typed::Type::Var(decode_typed_name(d)?),
+        1 => typed::Type::Fun(
+            Box::new(decode_typed_type(d)?),
+            Box::new(decode_typed_type(d)?),
+        ),
+        2 => typed::Type::IFix(
+            Box::new(decode_typed_type(d)?),
+            Box::new(decode_typed_type(d)?),
+        ),
+        3 => typed::Type::Forall {
+            parameter_name: decode_typed_name(d)?,
+            kind: decode_kind(d)?,
+            body: Box::new(decode_typed_type(d)?),
+        },
+        4 => {
+            let tags = d.decode_list_with(decode_constant_tag)?;
+            let mut tags = tags.into_iter();
+
+            let ty = decode_builtin_type(&mut tags)?;
+
+            if tags.next().is_some() {
+                return Err(de::Error::Message(
+                    "Improper encoding on builtin type, left over type tags".to_string(),
+                ));
+            }
+
+            typed::Type::Builtin(ty)
         }
+        5 => typed::Type::Lambda {
+            parameter_name: decode_typed_name(d)?,
+            kind: decode_kind(d)?,
+            body: Box::new(decode_typed_type(d)?),
+        },
+        6 => typed::Type::Apply(
+            Box::new(decode_typed_type(d)?),
+            Box::new(decode_typed_type(d)?),
+        ),
+        x => {
+            return Err(de::Error::Message(format!(
+                "Unknown type constructor tag: {}",
+                x
+            )))
+        }
+    };
+
+    d.leave();
+
+    Ok(ty)
+}
+
+/// The textual form of a builtin type. Unlike constants, a type may
+/// be `list` or `pair` applied to fewer arguments than they take.
+fn decode_builtin_type(tags: &mut impl Iterator<Item = u8>) -> Result<String, de::Error> {
+    let mut arguments = Vec::new();
+    let mut applications = 0;
+
+    let head = loop {
+        match tags.next() {
+            Some(7) => applications += 1,
+            Some(0) => break "integer",
+            Some(1) => break "bytestring",
+            Some(2) => break "string",
+            Some(3) => break "unit",
+            Some(4) => break "bool",
+            Some(5) => break "list",
+            Some(6) => break "pair",
+            Some(8) => break "data",
+            x => {
+                return Err(de::Error::Message(format!(
+                    "Unknown builtin type tag: {:?}",
+                    x
+                )))
+            }
+        }
+    };
+
+    for _ in 0..applications {
+        arguments.push(decode_builtin_type(tags)?);
     }
+
+    if arguments.is_empty() {
+        Ok(head.to_string())
+    } else {
+        Ok(format!("({} {})", head, arguments.join(" ")))
+    }
+}
+
+fn decode_kind(d: &mut Decoder) -> Result<typed::Kind, de::Error> {
+    d.enter()?;
+
+    let kind = match d.bits8(KIND_TAG_WIDTH)? {
+        0 => typed::Kind::Type,
+        _ => typed::Kind::Arrow(Box::new(decode_kind(d)?), Box::new(decode_kind(d)?)),
+    };
+
+    d.leave();
+
+    Ok(kind)
+}
+
+fn decode_typed_name(d: &mut Decoder) -> Result<String, de::Error> {
+    let Name { text, unique } = Name::decode(d)?;
+
+    Ok(format!("{}_{}", text, unique))
 }
 
 impl Encode for Unique {
@@ -246,6 +980,10 @@ impl<'b> Binder<'b> for Name {
     fn binder_decode(d: &mut Decoder) -> Result<Self, de::Error> {
         Name::decode(d)
     }
+
+    fn text(&self) -> String {
+        self.text.clone()
+    }
 }
 
 impl Encode for NamedDeBruijn {
@@ -266,11 +1004,11 @@ impl<'b> Decode<'b> for NamedDeBruijn {
     }
 }
 
+/// Parameters are written without their index, which is always 0,
+/// like the Plutus tooling writes them.
 impl<'b> Binder<'b> for NamedDeBruijn {
     fn binder_encode(&self, e: &mut Encoder) -> Result<(), en::Error> {
-        self.text.encode(e)?;
-
-        Ok(())
+        self.text.encode(e)
     }
 
     fn binder_decode(d: &mut Decoder) -> Result<Self, de::Error> {
@@ -279,6 +1017,10 @@ impl<'b> Binder<'b> for NamedDeBruijn {
             index: DeBruijn::new(0),
         })
     }
+
+    fn text(&self) -> String {
+        format!("{}_{}", self.text, self.index)
+    }
 }
 
 impl Encode for DeBruijn {
@@ -303,6 +1045,10 @@ impl<'b> Binder<'b> for DeBruijn {
     fn binder_decode(_d: &mut Decoder) -> Result<Self, de::Error> {
         Ok(DeBruijn::new(0))
     }
+
+    fn text(&self) -> String {
+        format!("i_{}", self)
+    }
 }
 
 impl Encode for FakeNamedDeBruijn {
@@ -333,6 +1079,10 @@ impl<'b> Binder<'b> for FakeNamedDeBruijn {
 
         Ok(index.into())
     }
+
+    fn text(&self) -> String {
+        NamedDeBruijn::from(self.clone()).text()
+    }
 }
 
 impl Encode for DefaultFunction {
@@ -370,24 +1120,6 @@ fn safe_encode_bits(num_bits: u32, byte: u8, e: &mut Encoder) -> Result<(), en::
     }
 }
 
-pub fn encode_constant(tag: u8, e: &mut Encoder) -> Result<(), en::Error> {
-    e.encode_list_with([tag].to_vec(), encode_constant_tag)?;
-
-    Ok(())
-}
-
-pub fn decode_constant(d: &mut Decoder) -> Result<u8, de::Error> {
-    let u8_list = d.decode_list_with(decode_constant_tag)?;
-    if u8_list.len() > 1 {
-        Err(de::Error::Message(
-            "Improper encoding on constant tag. Should be list of one item encoded in 4 bits"
-                .to_string(),
-        ))
-    } else {
-        Ok(u8_list[0])
-    }
-}
-
 pub fn encode_constant_tag(tag: u8, e: &mut Encoder) -> Result<(), en::Error> {
     safe_encode_bits(CONST_TAG_WIDTH, tag, e)
 }
@@ -400,15 +1132,103 @@ pub fn decode_constant_tag(d: &mut Decoder) -> Result<u8, de::Error> {
 mod test {
     use flat::Flat;
 
-    use crate::ast::Name;
+    use crate::{
+        address::{self, Network},
+        ast::{DeBruijn, Name, NamedDeBruijn, PlutusVersion},
+        parser,
+    };
 
     use super::{Constant, Program, Term};
 
+    #[test]
+    fn flat_decode_typed_program() {
+        use flat::en::{Encode, Encoder};
+
+        use crate::{ast::Type, typed};
+
+        let mut e = Encoder::new();
+
+        let name = |e: &mut Encoder, text: &str, unique: isize| {
+            Name {
+                text: text.to_string(),
+                unique: unique.into(),
+            }
+            .encode(e)
+            .unwrap();
+        };
+
+        let builtin_type = |e: &mut Encoder, tags: &[u8]| {
+            e.bits(3, 4);
+
+            for tag in tags {
+                e.bool(true).bits(4, *tag);
+            }
+
+            e.bool(false);
+        };
+
+        for part in [1_usize, 0, 0] {
+            part.encode(&mut e).unwrap();
+        }
+
+        // [ { (abs a (type) (lam x a x)) (con (list integer)) }
+        //   (iwrap (lam f (fun (type) (type)) f) (con list) (con (list integer) [1])) ]
+        e.bits(4, 3);
+        e.bits(4, 5);
+        e.bits(4, 1);
+        name(&mut e, "a", 0);
+        e.bits(1, 0);
+        e.bits(4, 2);
+        name(&mut e, "x", 1);
+        e.bits(3, 0);
+        name(&mut e, "a", 0);
+        e.bits(4, 0);
+        name(&mut e, "x", 1);
+        builtin_type(&mut e, &[7, 5, 0]);
+        e.bits(4, 7);
+        e.bits(3, 5);
+        name(&mut e, "f", 2);
+        e.bits(1, 1).bits(1, 0).bits(1, 0);
+        e.bits(3, 0);
+        name(&mut e, "f", 2);
+        builtin_type(&mut e, &[5]);
+        e.bits(4, 4);
+        (&Constant::ProtoList(Type::Integer, vec![Constant::Integer(1.into())]))
+            .encode(&mut e)
+            .unwrap();
+        e.encode(flat::filler::Filler::FillerEnd).unwrap();
+
+        let program = typed::Program::from_flat(&e.buffer).unwrap();
+
+        let typed::Term::Apply { function, argument } = &program.term else {
+            panic!("expected an application, got {:?}", program.term);
+        };
+
+        assert!(matches!(
+            function.as_ref(),
+            typed::Term::TyInst { ty: typed::Type::Builtin(ty), .. } if ty == "(list integer)"
+        ));
+        assert!(matches!(
+            argument.as_ref(),
+            typed::Term::IWrap { argument: typed::Type::Builtin(ty), .. } if ty == "list"
+        ));
+
+        assert_eq!(
+            program.erase(),
+            parser::program(
+                "(program 1.0.0 [ (force (delay (lam x_1 x_1))) (con (list integer) [1]) ])"
+            )
+            .unwrap()
+        );
+
+        assert!(typed::Program::from_flat(&[0x01, 0x00, 0x00, 0xf0]).is_err());
+    }
+
     #[test]
     fn flat_encode_integer() {
         let program = Program::<Name> {
             version: (11, 22, 33),
-            term: Term::Constant(Constant::Integer(11)),
+            term: Term::Constant(Constant::Integer(11.into())),
         };
 
         let bytes = program.to_flat().unwrap();
@@ -427,11 +1247,188 @@ mod test {
 
         let expected_program = Program {
             version: (11, 22, 33),
-            term: Term::Constant(Constant::Integer(11)),
+            term: Term::Constant(Constant::Integer(11.into())),
         };
 
         let actual_program: Program<Name> = Program::unflat(&flat_encoded).unwrap();
 
         assert_eq!(actual_program, expected_program)
     }
+
+    #[test]
+    fn flat_round_trip_list_pair_and_data() {
+        let code = r#"
+        (program 1.0.0
+            [
+                (con (list (list bool)) [[True, False], []])
+                (con (pair bytestring data) (#00ff, (Constr 0 [I 1, I 2])))
+            ]
+        )
+        "#;
+
+        let program: Program<DeBruijn> = parser::program(code).unwrap().try_into().unwrap();
+
+        let bytes = program.to_flat().unwrap();
+
+        assert_eq!(Program::<DeBruijn>::from_flat(&bytes).unwrap(), program);
+    }
+
+    #[test]
+    fn flat_round_trip_with_names() {
+        let program = parser::program("(program 1.0.0 (lam x (lam y [x (delay y)])))").unwrap();
+
+        let bytes = program.to_flat().unwrap();
+
+        assert_eq!(Program::<Name>::from_flat(&bytes).unwrap(), program);
+
+        let program: Program<NamedDeBruijn> = program.try_into().unwrap();
+
+        let bytes = program.to_flat().unwrap();
+
+        assert_eq!(
+            Program::<NamedDeBruijn>::from_flat(&bytes).unwrap(),
+            program
+        );
+
+        // `(lam x x)`, the parameter is only the text "x" while the
+        // variable is followed by it's index
+        let bytes = hex::decode("01000021017800010178000101").unwrap();
+
+        assert_eq!(
+            Program::<NamedDeBruijn>::from_flat(&bytes).unwrap(),
+            Program {
+                version: (1, 0, 0),
+                term: Term::Lambda {
+                    parameter_name: NamedDeBruijn {
+                        text: "x".to_string(),
+                        index: 0.into(),
+                    },
+                    body: Term::Var(NamedDeBruijn {
+                        text: "x".to_string(),
+                        index: 1.into(),
+                    })
+                    .into(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn cbor_round_trip() {
+        let program: Program<DeBruijn> = parser::program("(program 1.0.0 (con integer 11))")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let cbor = program.to_cbor().unwrap();
+
+        assert_eq!(cbor, hex::decode("46010000480581").unwrap());
+
+        assert_eq!(Program::<DeBruijn>::from_cbor(&cbor).unwrap(), program);
+
+        // double wrapped like in a cardano-cli text envelope
+        let envelope = hex::decode("4746010000480581").unwrap();
+
+        assert_eq!(Program::<DeBruijn>::from_cbor(&envelope).unwrap(), program);
+    }
+
+    #[test]
+    fn cbor_sequence() {
+        let program = |code: &str| -> Program<DeBruijn> {
+            parser::program(code).unwrap().try_into().unwrap()
+        };
+
+        let first = program("(program 1.0.0 (con integer 11))");
+        let second = program("(program 1.0.0 (lam x x))");
+
+        let mut bytes = first.to_cbor().unwrap();
+        // a bytestring that isn't a program
+        bytes.extend(hex::decode("4100").unwrap());
+        // double wrapped
+        bytes.extend(
+            minicbor::to_vec(minicbor::bytes::ByteVec::from(second.to_cbor().unwrap())).unwrap(),
+        );
+        // truncated
+        bytes.extend(hex::decode("4601").unwrap());
+
+        let scripts: Vec<_> = Program::<DeBruijn>::from_cbor_seq(&bytes).collect();
+
+        assert_eq!(scripts.len(), 4);
+        assert_eq!(scripts[0].as_ref().unwrap(), &first);
+        assert!(scripts[1].is_err());
+        assert_eq!(scripts[2].as_ref().unwrap(), &second);
+        assert!(scripts[3].is_err());
+
+        assert_eq!(Program::<DeBruijn>::from_cbor_seq(&[]).count(), 0);
+    }
+
+    #[test]
+    fn flat_decode_borrowed() {
+        use std::borrow::Cow;
+
+        use crate::borrowed;
+
+        let code = format!(
+            "(program 1.0.0 [(lam x [(builtin appendByteString) x (con bytestring #{})]) (con bytestring #{})])",
+            "ab".repeat(32),
+            "cd".repeat(300),
+        );
+
+        let program: Program<DeBruijn> = parser::program(&code).unwrap().try_into().unwrap();
+
+        let bytes = program.to_flat().unwrap();
+
+        let decoded = Program::<DeBruijn>::from_flat_borrowed(&bytes).unwrap();
+
+        let borrowed::Term::Apply { function, argument } = &decoded.term else {
+            panic!("expected an application, got {:?}", decoded.term)
+        };
+
+        let borrowed::Term::Lambda { body, .. } = function.as_ref() else {
+            panic!("expected a lambda, got {function:?}")
+        };
+
+        let borrowed::Term::Apply {
+            argument: short, ..
+        } = body.as_ref()
+        else {
+            panic!("expected an application, got {body:?}")
+        };
+
+        // written in one chunk
+        assert!(matches!(
+            short.as_ref(),
+            borrowed::Term::Constant(borrowed::Constant::ByteString(Cow::Borrowed(b))) if b == &[0xab; 32]
+        ));
+
+        // written in two chunks
+        assert!(matches!(
+            argument.as_ref(),
+            borrowed::Term::Constant(borrowed::Constant::ByteString(Cow::Owned(b))) if b == &[0xcd; 300]
+        ));
+
+        assert_eq!(Program::<DeBruijn>::from(decoded), program);
+
+        assert!(Program::<DeBruijn>::from_flat_borrowed(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn script_hash() {
+        // the always succeeding script used throughout the ledger's tests
+        let cbor = hex::decode("4d01000033222220051200120011").unwrap();
+
+        let program = Program::<DeBruijn>::from_cbor(&cbor).unwrap();
+
+        let hash = program.to_hash(PlutusVersion::V1).unwrap();
+
+        assert_eq!(
+            hex::encode(hash),
+            "67f33146617a5e61936081db3b2117cbf59bd2123748f58ac9678656"
+        );
+
+        assert_eq!(
+            address::script_address(&hash, Network::Testnet),
+            "addr_test1wpnlxv2xv9a9ucvnvzqakwepzl9ltx7jzgm53av2e9ncv4sysemm8"
+        );
+    }
 }
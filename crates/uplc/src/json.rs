@@ -0,0 +1,151 @@
+//! Helpers for the JSON representation of programs, enabled by the
+//! `serde` feature.
+//!
+//! Terms, constants, constant types and data are tagged with their
+//! variant in snake case, with the variant's contents under `value`:
+//!
+//! ```json
+//! {
+//!   "version": [1, 0, 0],
+//!   "term": {
+//!     "type": "apply",
+//!     "value": {
+//!       "function": { "type": "builtin", "value": "iData" },
+//!       "argument": {
+//!         "type": "constant",
+//!         "value": { "type": "integer", "value": "42" }
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Integers are written as decimal strings since they are unbounded,
+//! bytestrings as hex strings and builtins by their textual name.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::builtins::DefaultFunction;
+
+impl Serialize for DefaultFunction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DefaultFunction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        name.parse()
+            .map_err(|_| de::Error::custom(format!("unknown builtin `{name}`")))
+    }
+}
+
+pub mod big_int {
+    use num_bigint::BigInt;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(int: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(int)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let digits = String::deserialize(deserializer)?;
+
+        digits
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid integer `{digits}`")))
+    }
+}
+
+pub mod bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+
+        hex::decode(&hex).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    use crate::{
+        arbitrary,
+        ast::{DeBruijn, Name, Program},
+        parser,
+    };
+
+    #[test]
+    fn representation() {
+        let program = parser::program(
+            r#"(program 1.0.0
+                 [ (lam x [(builtin sha2_256) x]) (con bytestring #ff) ]
+               )"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&program).unwrap(),
+            json!({
+                "version": [1, 0, 0],
+                "term": {
+                    "type": "apply",
+                    "value": {
+                        "function": {
+                            "type": "lambda",
+                            "value": {
+                                "parameter_name": { "text": "x", "unique": 0 },
+                                "body": {
+                                    "type": "apply",
+                                    "value": {
+                                        "function": { "type": "builtin", "value": "sha2_256" },
+                                        "argument": {
+                                            "type": "var",
+                                            "value": { "text": "x", "unique": 0 },
+                                        },
+                                    },
+                                },
+                            },
+                        },
+                        "argument": {
+                            "type": "constant",
+                            "value": { "type": "byte_string", "value": "ff" },
+                        },
+                    },
+                },
+            })
+        );
+
+        let program: Program<DeBruijn> = program.try_into().unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&program).unwrap()["term"]["value"]["function"]["value"]
+                ["parameter_name"],
+            json!(0)
+        );
+
+        assert!(serde_json::from_value::<Program<DeBruijn>>(json!({
+            "version": [1, 0, 0],
+            "term": { "type": "builtin", "value": "notABuiltin" },
+        }))
+        .is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(program in arbitrary::program()) {
+            let json = serde_json::to_string(&program).unwrap();
+
+            prop_assert_eq!(serde_json::from_str::<Program<Name>>(&json).unwrap(), program);
+        }
+    }
+}
@@ -1,8 +1,26 @@
+pub mod address;
+pub mod analysis;
+pub mod annotations;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod arbitrary;
 pub mod ast;
+pub mod borrowed;
 pub mod builtins;
+pub mod check;
+pub mod conformance;
+pub mod data;
 mod debruijn;
+pub mod diff;
 mod flat;
+#[cfg(feature = "serde")]
+mod json;
+pub mod limits;
+pub mod machine;
+pub mod optimize;
 pub mod parser;
+mod pretty;
+pub mod tx;
+pub mod typed;
 
 #[cfg(test)]
 mod test;
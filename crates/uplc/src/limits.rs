@@ -0,0 +1,28 @@
+/// Bounds on the programs the parser and the flat decoder accept.
+/// Terms are processed recursively, so programs from untrusted
+/// sources must be limited to keep them from overflowing the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// How deeply terms, constants and constant types may nest. The
+    /// arguments of an application count towards it's depth, since
+    /// `[f a b c]` is `[[[f a] b] c]`.
+    pub max_depth: usize,
+    /// The length in bytes of the source text or flat encoding.
+    pub max_size: usize,
+}
+
+impl Limits {
+    /// The default depth, shallow enough for programs to be parsed,
+    /// converted and evaluated within the main thread's 8MiB stack,
+    /// even in debug builds.
+    pub const MAX_DEPTH: usize = 1000;
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_depth: Limits::MAX_DEPTH,
+            max_size: usize::MAX,
+        }
+    }
+}
@@ -0,0 +1,764 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::{
+    ast::{Constant, NamedDeBruijn, PlutusVersion, Term},
+    builtins::DefaultFunction,
+};
+
+pub mod cost_model;
+mod error;
+pub mod runtime;
+
+use cost_model::{CostModel, ExBudget, StepKind};
+pub use error::Error;
+use runtime::BuiltinRuntime;
+
+/// The environment a term is evaluated in, the values of the
+/// variables in scope with the innermost first. Variables are looked
+/// up by their debruijn index. Binding a value shares the values
+/// already bound, so closures capture their environment for free.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Env(Option<Rc<Binding>>);
+
+#[derive(Debug, PartialEq)]
+struct Binding {
+    value: Value,
+    rest: Env,
+}
+
+impl Env {
+    /// `self` with `value` bound by the innermost lambda.
+    pub fn push(&self, value: Value) -> Env {
+        Env(Some(Rc::new(Binding {
+            value,
+            rest: self.clone(),
+        })))
+    }
+
+    /// The value of the variable with debruijn `index`, 1 being the
+    /// innermost.
+    pub fn lookup(&self, index: usize) -> Option<&Value> {
+        self.iter().nth(index.checked_sub(1)?)
+    }
+
+    /// The values bound, innermost first.
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        std::iter::successors(self.0.as_deref(), |binding| binding.rest.0.as_deref())
+            .map(|binding| &binding.value)
+    }
+}
+
+/// The result of evaluating a term.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Con(Constant),
+    Delay(Rc<Term<NamedDeBruijn>>, Env),
+    Lambda {
+        parameter_name: NamedDeBruijn,
+        body: Rc<Term<NamedDeBruijn>>,
+        env: Env,
+    },
+    Builtin {
+        fun: DefaultFunction,
+        runtime: BuiltinRuntime,
+    },
+}
+
+/// Everything there is to know about an evaluation, the resulting
+/// term, the execution units it consumed and what it traced.
+#[derive(Debug)]
+pub struct EvalResult {
+    pub term: Result<Term<NamedDeBruijn>, Error>,
+    pub budget: ExBudget,
+    pub logs: Vec<String>,
+}
+
+/// The continuation of the machine. Frames are pushed while
+/// descending into a term and popped once a value is returned.
+#[derive(Debug, Clone)]
+pub enum Frame {
+    Force,
+    ApplyArg(Env, Rc<Term<NamedDeBruijn>>),
+    ApplyFun(Value),
+}
+
+/// Where the machine is in between two transitions.
+#[derive(Debug, Clone)]
+pub enum MachineState {
+    /// About to compute a term in an environment.
+    Compute(Env, Rc<Term<NamedDeBruijn>>),
+    /// Returning a value to the frame on top of the stack.
+    Return(Value),
+    /// The stack is empty and the term is fully evaluated.
+    Done(Term<NamedDeBruijn>),
+}
+
+/// A CEK machine for evaluating Untyped Plutus Core terms.
+/// Instead of recursing, the continuation is kept as an explicit
+/// stack of frames so deeply nested terms don't overflow the stack.
+///
+/// Every step is charged against `ex_budget`. Like the reference
+/// implementation, steps are only paid for once `slippage` of them
+/// have accumulated, so running out of budget is noticed a little late.
+pub struct Machine {
+    version: PlutusVersion,
+    costs: CostModel,
+    pub ex_budget: ExBudget,
+    /// Messages emitted by the `trace` builtin, in order.
+    pub logs: Vec<String>,
+    /// Builtins to stop at once they have all their arguments,
+    /// see `Machine::breakpoint`.
+    pub breakpoints: Vec<DefaultFunction>,
+    breakpoint: Option<Term<NamedDeBruijn>>,
+    slippage: u32,
+    unbudgeted_steps: [u32; 8],
+    frames: Vec<Frame>,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self::new(
+            PlutusVersion::V2,
+            CostModel::default(),
+            ExBudget::default(),
+            200,
+        )
+    }
+}
+
+impl Machine {
+    /// A machine for running scripts written for `version`,
+    /// using a builtin it doesn't have is an error.
+    pub fn new(
+        version: PlutusVersion,
+        costs: CostModel,
+        initial_budget: ExBudget,
+        slippage: u32,
+    ) -> Machine {
+        Machine {
+            version,
+            costs,
+            ex_budget: initial_budget,
+            logs: Vec::new(),
+            breakpoints: Vec::new(),
+            breakpoint: None,
+            slippage,
+            unbudgeted_steps: [0; 8],
+            frames: Vec::new(),
+        }
+    }
+
+    /// Evaluate a closed term to it's normal form.
+    pub fn run(&mut self, term: Term<NamedDeBruijn>) -> Result<Term<NamedDeBruijn>, Error> {
+        let mut state = self.start(term)?;
+
+        loop {
+            state = match state {
+                MachineState::Done(term) => return Ok(term),
+                state => self.step(state)?,
+            };
+        }
+    }
+
+    /// Pay for starting up, returning the state evaluating `term` begins in.
+    pub fn start(&mut self, term: Term<NamedDeBruijn>) -> Result<MachineState, Error> {
+        let startup_budget = self.costs.machine_costs.startup;
+
+        self.spend_budget(startup_budget)?;
+
+        Ok(MachineState::Compute(Env::default(), Rc::new(term)))
+    }
+
+    /// Make a single transition, for running the machine one step at a time.
+    pub fn step(&mut self, state: MachineState) -> Result<MachineState, Error> {
+        self.breakpoint = None;
+
+        match state {
+            MachineState::Compute(env, term) => self.compute(env, term),
+            MachineState::Return(value) => match self.frames.pop() {
+                Some(frame) => self.return_compute(frame, value),
+                None => {
+                    self.spend_unbudgeted_steps()?;
+
+                    Ok(MachineState::Done(value.to_term()))
+                }
+            },
+            MachineState::Done(term) => Ok(MachineState::Done(term)),
+        }
+    }
+
+    /// The call to one of `breakpoints` the last step made, with the
+    /// arguments it was called with.
+    pub fn breakpoint(&self) -> Option<&Term<NamedDeBruijn>> {
+        self.breakpoint.as_ref()
+    }
+
+    /// The continuation, the innermost frame comes last.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    fn compute(&mut self, env: Env, term: Rc<Term<NamedDeBruijn>>) -> Result<MachineState, Error> {
+        if let Some(kind) = step_kind(&term) {
+            self.step_and_maybe_spend(kind)?;
+        }
+
+        match term.as_ref() {
+            Term::Var(name) => {
+                let value = lookup_var(&env, name)
+                    .ok_or_else(|| Error::OpenTermEvaluated(Term::Var(name.clone()).to_pretty()))?;
+
+                Ok(MachineState::Return(value.clone()))
+            }
+            Term::Delay(body) => Ok(MachineState::Return(Value::Delay(body.clone(), env))),
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => Ok(MachineState::Return(Value::Lambda {
+                parameter_name: parameter_name.clone(),
+                body: body.clone(),
+                env,
+            })),
+            Term::Apply { function, argument } => {
+                self.frames
+                    .push(Frame::ApplyArg(env.clone(), argument.clone()));
+
+                Ok(MachineState::Compute(env, function.clone()))
+            }
+            Term::Constant(constant) => Ok(MachineState::Return(Value::Con(constant.clone()))),
+            Term::Force(body) => {
+                self.frames.push(Frame::Force);
+
+                Ok(MachineState::Compute(env, body.clone()))
+            }
+            Term::Error => Err(Error::EvaluationFailure),
+            Term::Builtin(fun) if !fun.is_available_in(self.version) => {
+                Err(Error::UnavailableBuiltin(*fun, self.version))
+            }
+            Term::Builtin(fun) => Ok(MachineState::Return(Value::Builtin {
+                fun: *fun,
+                runtime: BuiltinRuntime::new(*fun),
+            })),
+        }
+    }
+
+    fn return_compute(&mut self, frame: Frame, value: Value) -> Result<MachineState, Error> {
+        match frame {
+            Frame::Force => self.force_evaluate(value),
+            Frame::ApplyArg(env, argument) => {
+                self.frames.push(Frame::ApplyFun(value));
+
+                Ok(MachineState::Compute(env, argument))
+            }
+            Frame::ApplyFun(function) => self.apply_evaluate(function, value),
+        }
+    }
+
+    fn force_evaluate(&mut self, value: Value) -> Result<MachineState, Error> {
+        match value {
+            Value::Delay(body, env) => Ok(MachineState::Compute(env, body)),
+            Value::Builtin { fun, mut runtime } => {
+                if runtime.needs_force() {
+                    runtime.consume_force();
+
+                    self.eval_builtin(fun, runtime)
+                } else {
+                    Err(Error::BuiltinTermArgumentExpected(
+                        runtime.to_term().to_pretty(),
+                    ))
+                }
+            }
+            rest => Err(Error::NonPolymorphicInstantiation(
+                rest.to_term().to_pretty(),
+            )),
+        }
+    }
+
+    fn apply_evaluate(&mut self, function: Value, argument: Value) -> Result<MachineState, Error> {
+        match function {
+            Value::Lambda { body, env, .. } => Ok(MachineState::Compute(env.push(argument), body)),
+            Value::Builtin { fun, mut runtime } => {
+                if runtime.is_arrow() {
+                    runtime.push(argument);
+
+                    self.eval_builtin(fun, runtime)
+                } else {
+                    Err(Error::UnexpectedBuiltinTermArgument(
+                        runtime.to_term().to_pretty(),
+                    ))
+                }
+            }
+            rest => Err(Error::NonFunctionalApplication(rest.to_term().to_pretty())),
+        }
+    }
+
+    fn eval_builtin(
+        &mut self,
+        fun: DefaultFunction,
+        runtime: BuiltinRuntime,
+    ) -> Result<MachineState, Error> {
+        if runtime.is_ready() {
+            if self.breakpoints.contains(&fun) {
+                self.breakpoint = Some(runtime.to_term());
+            }
+
+            let cost = runtime.to_ex_budget(&self.costs.builtin_costs);
+
+            self.spend_budget(cost)?;
+
+            Ok(MachineState::Return(runtime.call(&mut self.logs)?))
+        } else {
+            Ok(MachineState::Return(Value::Builtin { fun, runtime }))
+        }
+    }
+
+    fn step_and_maybe_spend(&mut self, kind: StepKind) -> Result<(), Error> {
+        self.unbudgeted_steps[kind as usize] += 1;
+        self.unbudgeted_steps[7] += 1;
+
+        if self.unbudgeted_steps[7] >= self.slippage {
+            self.spend_unbudgeted_steps()?;
+        }
+
+        Ok(())
+    }
+
+    fn spend_unbudgeted_steps(&mut self) -> Result<(), Error> {
+        for kind in STEP_KINDS {
+            let mut cost = self.costs.machine_costs.get(kind);
+
+            cost.occurrences(self.unbudgeted_steps[kind as usize] as i64);
+
+            self.spend_budget(cost)?;
+
+            self.unbudgeted_steps[kind as usize] = 0;
+        }
+
+        self.unbudgeted_steps[7] = 0;
+
+        Ok(())
+    }
+
+    fn spend_budget(&mut self, spend_budget: ExBudget) -> Result<(), Error> {
+        self.ex_budget = self.ex_budget - spend_budget;
+
+        if self.ex_budget.mem < 0 || self.ex_budget.cpu < 0 {
+            Err(Error::OutOfExError(self.ex_budget))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+const STEP_KINDS: [StepKind; 7] = [
+    StepKind::Constant,
+    StepKind::Var,
+    StepKind::Lambda,
+    StepKind::Apply,
+    StepKind::Delay,
+    StepKind::Force,
+    StepKind::Builtin,
+];
+
+/// The kind of step computing `term` is charged as. Errors are free.
+fn step_kind(term: &Term<NamedDeBruijn>) -> Option<StepKind> {
+    match term {
+        Term::Var(_) => Some(StepKind::Var),
+        Term::Delay(_) => Some(StepKind::Delay),
+        Term::Lambda { .. } => Some(StepKind::Lambda),
+        Term::Apply { .. } => Some(StepKind::Apply),
+        Term::Constant(_) => Some(StepKind::Constant),
+        Term::Force(_) => Some(StepKind::Force),
+        Term::Error => None,
+        Term::Builtin(_) => Some(StepKind::Builtin),
+    }
+}
+
+impl Display for MachineState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MachineState::Compute(env, term) => {
+                write!(f, "compute {}", with_env(0, env, term).to_pretty())
+            }
+            MachineState::Return(value) => write!(f, "return {}", value.to_term().to_pretty()),
+            MachineState::Done(term) => write!(f, "done {}", term.to_pretty()),
+        }
+    }
+}
+
+/// Frames are rendered as the term they are waiting
+/// on a value for, with a `_` for the hole.
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frame::Force => write!(f, "(force _)"),
+            Frame::ApplyArg(env, argument) => {
+                write!(f, "[ _ {} ]", with_env(0, env, argument).to_pretty())
+            }
+            Frame::ApplyFun(function) => write!(f, "[ {} _ ]", function.to_term().to_pretty()),
+        }
+    }
+}
+
+impl Value {
+    /// Discharge a value back into a term, substituting the
+    /// variables bound in it's environment.
+    pub fn to_term(&self) -> Term<NamedDeBruijn> {
+        match self {
+            Value::Con(constant) => Term::Constant(constant.clone()),
+            Value::Delay(body, env) => Term::Delay(Rc::new(with_env(0, env, body))),
+            Value::Lambda {
+                parameter_name,
+                body,
+                env,
+            } => Term::Lambda {
+                parameter_name: parameter_name.clone(),
+                body: Rc::new(with_env(1, env, body)),
+            },
+            Value::Builtin { runtime, .. } => runtime.to_term(),
+        }
+    }
+
+    /// The size of a value as seen by the cost model. Only
+    /// constants have a size, everything else counts as one unit.
+    pub fn to_ex_mem(&self) -> i64 {
+        match self {
+            Value::Con(constant) => constant.to_ex_mem(),
+            _ => 1,
+        }
+    }
+}
+
+fn lookup_var<'a>(env: &'a Env, name: &NamedDeBruijn) -> Option<&'a Value> {
+    env.lookup(name.index.into())
+}
+
+/// Substitute the values of `env` into `term`. Variables bound
+/// by one of the `lam_cnt` lambdas surrounding `term` are left alone.
+fn with_env(lam_cnt: usize, env: &Env, term: &Term<NamedDeBruijn>) -> Term<NamedDeBruijn> {
+    match term {
+        Term::Var(name) => {
+            let index: usize = name.index.into();
+
+            if index <= lam_cnt {
+                return Term::Var(name.clone());
+            }
+
+            let free_name = NamedDeBruijn {
+                text: name.text.clone(),
+                index: (index - lam_cnt).into(),
+            };
+
+            match lookup_var(env, &free_name) {
+                Some(value) => value.to_term(),
+                None => Term::Var(name.clone()),
+            }
+        }
+        Term::Delay(body) => Term::Delay(Rc::new(with_env(lam_cnt, env, body))),
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => Term::Lambda {
+            parameter_name: parameter_name.clone(),
+            body: Rc::new(with_env(lam_cnt + 1, env, body)),
+        },
+        Term::Apply { function, argument } => Term::Apply {
+            function: Rc::new(with_env(lam_cnt, env, function)),
+            argument: Rc::new(with_env(lam_cnt, env, argument)),
+        },
+        Term::Constant(constant) => Term::Constant(constant.clone()),
+        Term::Force(body) => Term::Force(Rc::new(with_env(lam_cnt, env, body))),
+        Term::Error => Term::Error,
+        Term::Builtin(builtin) => Term::Builtin(*builtin),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{Constant, NamedDeBruijn, Program, Term},
+        builtins::DefaultFunction,
+        parser,
+    };
+
+    use super::{cost_model::ExBudget, Env, Error, Machine, MachineState, Value};
+
+    fn eval(code: &str) -> Result<Term<NamedDeBruijn>, Error> {
+        let program: Program<NamedDeBruijn> = parser::program(code).unwrap().try_into().unwrap();
+
+        program.eval()
+    }
+
+    #[test]
+    fn apply_lambda() {
+        let term = eval(
+            r#"
+            (program 1.0.0
+                [ (lam x (lam y x)) (con integer 1) (con integer 2) ]
+            )
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(term, Term::Constant(Constant::Integer(1.into())));
+    }
+
+    #[test]
+    fn force_delay_and_builtins() {
+        let term = eval(
+            r#"
+            (program 1.0.0
+                (force
+                    [
+                        (force (builtin ifThenElse))
+                        [ (builtin lessThanInteger) (con integer 1) (con integer 2) ]
+                        (delay [ (builtin divideInteger) (con integer -7) (con integer 2) ])
+                        (delay (error))
+                    ]
+                )
+            )
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(term, Term::Constant(Constant::Integer((-4).into())));
+    }
+
+    #[test]
+    fn partial_application() {
+        let term = eval(
+            r#"
+            (program 1.0.0
+                [ (lam x [ (builtin addInteger) x ]) (con integer 1) ]
+            )
+            "#,
+        )
+        .unwrap();
+
+        let expected: Program<NamedDeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                [ (builtin addInteger) (con integer 1) ]
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        assert_eq!(term, expected.term);
+    }
+
+    #[test]
+    fn discharge_lambda() {
+        let term = eval(
+            r#"
+            (program 1.0.0
+                [ (lam x (lam y [ y x ])) (con integer 1) ]
+            )
+            "#,
+        )
+        .unwrap();
+
+        let expected: Program<NamedDeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                (lam y [ y (con integer 1) ])
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        assert_eq!(term, expected.term);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            eval("(program 1.0.0 (error))"),
+            Err(Error::EvaluationFailure)
+        ));
+
+        assert!(matches!(
+            eval("(program 1.0.0 [ (con integer 1) (con integer 2) ])"),
+            Err(Error::NonFunctionalApplication(_))
+        ));
+
+        assert!(matches!(
+            eval("(program 1.0.0 [ (builtin ifThenElse) (con bool True) ])"),
+            Err(Error::UnexpectedBuiltinTermArgument(_))
+        ));
+
+        assert!(matches!(
+            eval("(program 1.0.0 [ (builtin modInteger) (con integer 1) (con integer 0) ])"),
+            Err(Error::DivideByZero(_))
+        ));
+    }
+
+    #[test]
+    fn budget() {
+        let program: Program<NamedDeBruijn> = parser::program(
+            "(program 1.0.0 [ (builtin addInteger) (con integer 1) (con integer 2) ])",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let result = program.eval_with_budget(ExBudget::default());
+
+        assert_eq!(
+            result.term.unwrap(),
+            Term::Constant(Constant::Integer(3.into()))
+        );
+        assert_eq!(result.budget, ExBudget::new(602, 321577));
+
+        let result = program.eval_with_budget(ExBudget::new(600, 321577));
+
+        assert!(matches!(result.term, Err(Error::OutOfExError(_))));
+    }
+
+    #[test]
+    fn environment() {
+        let integer = |i: i64| Value::Con(Constant::Integer(i.into()));
+
+        let outer = Env::default().push(integer(1));
+        let inner = outer.push(integer(2));
+
+        assert_eq!(inner.lookup(1), Some(&integer(2)));
+        assert_eq!(inner.lookup(2), Some(&integer(1)));
+        assert_eq!(inner.lookup(0), None);
+        assert_eq!(inner.lookup(3), None);
+        assert_eq!(outer.lookup(1), Some(&integer(1)));
+
+        // every iteration closes over the environment of the last one
+        let term = eval(
+            r#"
+            (program 1.0.0
+                [
+                    (lam f [ (lam x [ f (lam v [ x x v ]) ]) (lam x [ f (lam v [ x x v ]) ]) ])
+                    (lam self (lam n
+                        (force [
+                            (force (builtin ifThenElse))
+                            [ (builtin equalsInteger) n (con integer 0) ]
+                            (delay (con integer 0))
+                            (delay [
+                                (builtin addInteger)
+                                n
+                                [ self [ (builtin subtractInteger) n (con integer 1) ] ]
+                            ])
+                        ])
+                    ))
+                    (con integer 1000)
+                ]
+            )
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(term, Term::Constant(Constant::Integer(500500.into())));
+    }
+
+    #[test]
+    fn step_through() {
+        let program: Program<NamedDeBruijn> =
+            parser::program("(program 1.0.0 [ (lam x x) (force (delay (con integer 1))) ])")
+                .unwrap()
+                .try_into()
+                .unwrap();
+
+        let mut machine = Machine::default();
+
+        let mut state = machine.start(program.term).unwrap();
+        let mut deepest = Vec::new();
+
+        while !matches!(state, MachineState::Done(_)) {
+            state = machine.step(state).unwrap();
+
+            if machine.frames().len() > deepest.len() {
+                deepest = machine.frames().iter().map(|f| f.to_string()).collect();
+            }
+        }
+
+        assert_eq!(deepest, vec!["[ (lam x_0 x_1) _ ]", "(force _)"]);
+        assert_eq!(state.to_string(), "done (con integer 1)");
+    }
+
+    #[test]
+    fn breakpoints() {
+        let program: Program<NamedDeBruijn> = parser::program(
+            "(program 1.0.0 [ (lam x [ (builtin addInteger) x x ]) [ (builtin addInteger) (con integer 1) (con integer 2) ] ])",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let mut machine = Machine::default();
+        machine.breakpoints.push(DefaultFunction::AddInteger);
+
+        let mut state = machine.start(program.term).unwrap();
+        let mut hits = Vec::new();
+
+        while !matches!(state, MachineState::Done(_)) {
+            state = machine.step(state).unwrap();
+
+            if let Some(call) = machine.breakpoint() {
+                hits.push(call.to_pretty());
+            }
+        }
+
+        // only once all the arguments are there
+        assert_eq!(
+            hits,
+            vec![
+                "[ (builtin addInteger) (con integer 1) (con integer 2) ]",
+                "[ (builtin addInteger) (con integer 3) (con integer 3) ]"
+            ]
+        );
+        assert_eq!(state.to_string(), "done (con integer 6)");
+    }
+
+    #[test]
+    fn trace_logs() {
+        let program: Program<NamedDeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                [
+                    (force (builtin trace))
+                    (con string "second")
+                    [ (force (builtin trace)) (con string "first") (error) ]
+                ]
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let result = program.eval_with_budget(ExBudget::default());
+
+        assert!(matches!(result.term, Err(Error::EvaluationFailure)));
+        assert!(result.logs.is_empty());
+
+        let program: Program<NamedDeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                [
+                    (force (builtin trace))
+                    [ (force (builtin trace)) (con string "first") (con string "second") ]
+                    (con unit ())
+                ]
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let result = program.eval_with_budget(ExBudget::default());
+
+        assert_eq!(result.term.unwrap(), Term::Constant(Constant::Unit));
+        assert_eq!(result.logs, vec!["first", "second"]);
+    }
+}
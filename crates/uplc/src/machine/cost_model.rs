@@ -0,0 +1,744 @@
+use std::ops::{Add, Sub};
+
+use num_traits::Zero;
+
+use crate::{
+    ast::{Constant, PlutusVersion},
+    builtins::DefaultFunction,
+    data::PlutusData,
+};
+
+use super::Value;
+
+/// An amount of execution units, either available or consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExBudget {
+    pub mem: i64,
+    pub cpu: i64,
+}
+
+impl ExBudget {
+    pub fn new(mem: i64, cpu: i64) -> Self {
+        ExBudget { mem, cpu }
+    }
+
+    /// Multiply this budget by a number of occurrences.
+    pub fn occurrences(&mut self, n: i64) {
+        self.mem *= n;
+        self.cpu *= n;
+    }
+}
+
+/// The maximum execution units a single transaction can use on mainnet.
+impl Default for ExBudget {
+    fn default() -> Self {
+        ExBudget {
+            mem: 14_000_000,
+            cpu: 10_000_000_000,
+        }
+    }
+}
+
+impl Add for ExBudget {
+    type Output = ExBudget;
+
+    fn add(self, rhs: ExBudget) -> ExBudget {
+        ExBudget {
+            mem: self.mem + rhs.mem,
+            cpu: self.cpu + rhs.cpu,
+        }
+    }
+}
+
+impl Sub for ExBudget {
+    type Output = ExBudget;
+
+    fn sub(self, rhs: ExBudget) -> ExBudget {
+        ExBudget {
+            mem: self.mem - rhs.mem,
+            cpu: self.cpu - rhs.cpu,
+        }
+    }
+}
+
+impl Constant {
+    /// The size of a constant in machine words, as used by the
+    /// costing functions of builtins.
+    pub fn to_ex_mem(&self) -> i64 {
+        match self {
+            Constant::Integer(i) => {
+                if i.is_zero() {
+                    1
+                } else {
+                    ((i.bits() as i64 - 1) / 64) + 1
+                }
+            }
+            Constant::ByteString(bytes) => {
+                if bytes.is_empty() {
+                    1
+                } else {
+                    ((bytes.len() as i64 - 1) / 8) + 1
+                }
+            }
+            Constant::String(s) => s.chars().count() as i64,
+            Constant::Char(_) | Constant::Unit | Constant::Bool(_) => 1,
+            Constant::ProtoList(_, items) => items.iter().map(Constant::to_ex_mem).sum(),
+            Constant::ProtoPair(_, _, left, right) => left.to_ex_mem() + right.to_ex_mem(),
+            Constant::Data(data) => data.to_ex_mem(),
+        }
+    }
+}
+
+impl PlutusData {
+    /// Every node of a data value costs four words on top of the
+    /// size of the integers and bytestrings it contains.
+    pub fn to_ex_mem(&self) -> i64 {
+        4 + match self {
+            PlutusData::Constr { fields, .. } => fields.iter().map(PlutusData::to_ex_mem).sum(),
+            PlutusData::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| key.to_ex_mem() + value.to_ex_mem())
+                .sum(),
+            PlutusData::List(items) => items.iter().map(PlutusData::to_ex_mem).sum(),
+            PlutusData::Integer(i) => Constant::Integer(i.clone()).to_ex_mem(),
+            PlutusData::ByteString(bytes) => Constant::ByteString(bytes.clone()).to_ex_mem(),
+        }
+    }
+}
+
+/// The kinds of steps the machine is charged for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Constant = 0,
+    Var = 1,
+    Lambda = 2,
+    Apply = 3,
+    Delay = 4,
+    Force = 5,
+    Builtin = 6,
+}
+
+/// Everything needed to price the evaluation of a program. The
+/// default parameters are the ones used on mainnet since the Vasil
+/// hard fork, which are shared by Plutus V1 and V2.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CostModel {
+    pub machine_costs: MachineCosts,
+    pub builtin_costs: BuiltinCosts,
+}
+
+/// The ledger keeps a cost model for each Plutus version, and
+/// scripts are priced with the one of the version they're written
+/// for. Both start out with the default parameters.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CostModels {
+    pub v1: CostModel,
+    pub v2: CostModel,
+}
+
+impl CostModels {
+    pub fn get(&self, version: PlutusVersion) -> &CostModel {
+        match version {
+            PlutusVersion::V1 => &self.v1,
+            PlutusVersion::V2 => &self.v2,
+        }
+    }
+}
+
+/// The cost of each kind of machine step, plus a one off startup cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineCosts {
+    pub startup: ExBudget,
+    pub var: ExBudget,
+    pub constant: ExBudget,
+    pub lambda: ExBudget,
+    pub delay: ExBudget,
+    pub force: ExBudget,
+    pub apply: ExBudget,
+    pub builtin: ExBudget,
+}
+
+impl MachineCosts {
+    pub fn get(&self, step: StepKind) -> ExBudget {
+        match step {
+            StepKind::Constant => self.constant,
+            StepKind::Var => self.var,
+            StepKind::Lambda => self.lambda,
+            StepKind::Apply => self.apply,
+            StepKind::Delay => self.delay,
+            StepKind::Force => self.force,
+            StepKind::Builtin => self.builtin,
+        }
+    }
+}
+
+impl Default for MachineCosts {
+    fn default() -> Self {
+        let step = ExBudget::new(100, 23000);
+
+        MachineCosts {
+            startup: ExBudget::new(100, 100),
+            var: step,
+            constant: step,
+            lambda: step,
+            delay: step,
+            force: step,
+            apply: step,
+            builtin: step,
+        }
+    }
+}
+
+/// A cost that grows linearly with the size of an argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearSize {
+    pub intercept: i64,
+    pub slope: i64,
+}
+
+impl LinearSize {
+    fn cost(&self, size: i64) -> i64 {
+        self.intercept + self.slope * size
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubtractedSizes {
+    pub intercept: i64,
+    pub slope: i64,
+    pub minimum: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstantOrLinear {
+    pub constant: i64,
+    pub intercept: i64,
+    pub slope: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstantOrTwoArguments {
+    pub constant: i64,
+    pub model: MultipliedSizes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultipliedSizes {
+    pub intercept: i64,
+    pub slope: i64,
+}
+
+/// The cost of a builtin, split between memory and cpu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostingFun<T> {
+    pub mem: T,
+    pub cpu: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OneArgument {
+    ConstantCost(i64),
+    LinearCost(LinearSize),
+}
+
+impl OneArgument {
+    /// How the cost grows with the size of the argument.
+    pub fn class(&self) -> &'static str {
+        match self {
+            OneArgument::ConstantCost(_) => "constant",
+            OneArgument::LinearCost(_) => "linear in the size of the argument",
+        }
+    }
+
+    pub fn cost(&self, x: i64) -> i64 {
+        match self {
+            OneArgument::ConstantCost(c) => *c,
+            OneArgument::LinearCost(m) => m.cost(x),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TwoArguments {
+    ConstantCost(i64),
+    LinearInX(LinearSize),
+    LinearInY(LinearSize),
+    AddedSizes(LinearSize),
+    SubtractedSizes(SubtractedSizes),
+    MultipliedSizes(MultipliedSizes),
+    MinSize(LinearSize),
+    MaxSize(LinearSize),
+    LinearOnDiagonal(ConstantOrLinear),
+    ConstAboveDiagonal(ConstantOrTwoArguments),
+}
+
+impl TwoArguments {
+    /// How the cost grows with the sizes `x` and `y` of the arguments.
+    pub fn class(&self) -> &'static str {
+        match self {
+            TwoArguments::ConstantCost(_) => "constant",
+            TwoArguments::LinearInX(_) => "linear in the size of x",
+            TwoArguments::LinearInY(_) => "linear in the size of y",
+            TwoArguments::AddedSizes(_) => "linear in the sum of the sizes",
+            TwoArguments::SubtractedSizes(_) => "linear in the difference of the sizes",
+            TwoArguments::MultipliedSizes(_) => "linear in the product of the sizes",
+            TwoArguments::MinSize(_) => "linear in the smaller size",
+            TwoArguments::MaxSize(_) => "linear in the larger size",
+            TwoArguments::LinearOnDiagonal(_) => {
+                "linear in the size if both are the same size, constant otherwise"
+            }
+            TwoArguments::ConstAboveDiagonal(_) => {
+                "constant if x is smaller than y, linear in the product of the sizes otherwise"
+            }
+        }
+    }
+
+    pub fn cost(&self, x: i64, y: i64) -> i64 {
+        match self {
+            TwoArguments::ConstantCost(c) => *c,
+            TwoArguments::LinearInX(m) => m.cost(x),
+            TwoArguments::LinearInY(m) => m.cost(y),
+            TwoArguments::AddedSizes(m) => m.cost(x + y),
+            TwoArguments::SubtractedSizes(m) => m.intercept + m.slope * m.minimum.max(x - y),
+            TwoArguments::MultipliedSizes(m) => m.intercept + m.slope * (x * y),
+            TwoArguments::MinSize(m) => m.cost(x.min(y)),
+            TwoArguments::MaxSize(m) => m.cost(x.max(y)),
+            TwoArguments::LinearOnDiagonal(m) => {
+                if x == y {
+                    m.intercept + m.slope * x
+                } else {
+                    m.constant
+                }
+            }
+            TwoArguments::ConstAboveDiagonal(m) => {
+                if x < y {
+                    m.constant
+                } else {
+                    m.model.intercept + m.model.slope * (x * y)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThreeArguments {
+    ConstantCost(i64),
+    AddedSizes(LinearSize),
+    LinearInX(LinearSize),
+    LinearInY(LinearSize),
+    LinearInZ(LinearSize),
+}
+
+impl ThreeArguments {
+    /// How the cost grows with the sizes `x`, `y` and `z` of the arguments.
+    pub fn class(&self) -> &'static str {
+        match self {
+            ThreeArguments::ConstantCost(_) => "constant",
+            ThreeArguments::AddedSizes(_) => "linear in the sum of the sizes",
+            ThreeArguments::LinearInX(_) => "linear in the size of x",
+            ThreeArguments::LinearInY(_) => "linear in the size of y",
+            ThreeArguments::LinearInZ(_) => "linear in the size of z",
+        }
+    }
+
+    pub fn cost(&self, x: i64, y: i64, z: i64) -> i64 {
+        match self {
+            ThreeArguments::ConstantCost(c) => *c,
+            ThreeArguments::AddedSizes(m) => m.cost(x + y + z),
+            ThreeArguments::LinearInX(m) => m.cost(x),
+            ThreeArguments::LinearInY(m) => m.cost(y),
+            ThreeArguments::LinearInZ(m) => m.cost(z),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SixArguments {
+    ConstantCost(i64),
+}
+
+impl SixArguments {
+    pub fn class(&self) -> &'static str {
+        match self {
+            SixArguments::ConstantCost(_) => "constant",
+        }
+    }
+
+    pub fn cost(&self) -> i64 {
+        match self {
+            SixArguments::ConstantCost(c) => *c,
+        }
+    }
+}
+
+/// The costing function of a builtin, by the number of arguments
+/// it's cost depends on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Costing<'a> {
+    One(&'a CostingFun<OneArgument>),
+    Two(&'a CostingFun<TwoArguments>),
+    Three(&'a CostingFun<ThreeArguments>),
+    Six(&'a CostingFun<SixArguments>),
+}
+
+impl Costing<'_> {
+    /// How the memory and cpu costs grow with the sizes of the
+    /// arguments, e.g. `linear in the size of x`.
+    pub fn class(&self) -> CostingFun<&'static str> {
+        match self {
+            Costing::One(costing) => CostingFun {
+                mem: costing.mem.class(),
+                cpu: costing.cpu.class(),
+            },
+            Costing::Two(costing) => CostingFun {
+                mem: costing.mem.class(),
+                cpu: costing.cpu.class(),
+            },
+            Costing::Three(costing) => CostingFun {
+                mem: costing.mem.class(),
+                cpu: costing.cpu.class(),
+            },
+            Costing::Six(costing) => CostingFun {
+                mem: costing.mem.class(),
+                cpu: costing.cpu.class(),
+            },
+        }
+    }
+}
+
+/// The costing function of every builtin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinCosts {
+    pub add_integer: CostingFun<TwoArguments>,
+    pub subtract_integer: CostingFun<TwoArguments>,
+    pub multiply_integer: CostingFun<TwoArguments>,
+    pub divide_integer: CostingFun<TwoArguments>,
+    pub quotient_integer: CostingFun<TwoArguments>,
+    pub remainder_integer: CostingFun<TwoArguments>,
+    pub mod_integer: CostingFun<TwoArguments>,
+    pub equals_integer: CostingFun<TwoArguments>,
+    pub less_than_integer: CostingFun<TwoArguments>,
+    pub less_than_equals_integer: CostingFun<TwoArguments>,
+    pub append_byte_string: CostingFun<TwoArguments>,
+    pub cons_byte_string: CostingFun<TwoArguments>,
+    pub slice_byte_string: CostingFun<ThreeArguments>,
+    pub length_of_byte_string: CostingFun<OneArgument>,
+    pub index_byte_string: CostingFun<TwoArguments>,
+    pub equals_byte_string: CostingFun<TwoArguments>,
+    pub less_than_byte_string: CostingFun<TwoArguments>,
+    pub less_than_equals_byte_string: CostingFun<TwoArguments>,
+    pub sha2_256: CostingFun<OneArgument>,
+    pub sha3_256: CostingFun<OneArgument>,
+    pub blake2b_256: CostingFun<OneArgument>,
+    pub verify_ed25519_signature: CostingFun<ThreeArguments>,
+    pub verify_ecdsa_secp256k1_signature: CostingFun<ThreeArguments>,
+    pub verify_schnorr_secp256k1_signature: CostingFun<ThreeArguments>,
+    pub append_string: CostingFun<TwoArguments>,
+    pub equals_string: CostingFun<TwoArguments>,
+    pub encode_utf8: CostingFun<OneArgument>,
+    pub decode_utf8: CostingFun<OneArgument>,
+    pub if_then_else: CostingFun<ThreeArguments>,
+    pub choose_unit: CostingFun<TwoArguments>,
+    pub trace: CostingFun<TwoArguments>,
+    pub fst_pair: CostingFun<OneArgument>,
+    pub snd_pair: CostingFun<OneArgument>,
+    pub choose_list: CostingFun<ThreeArguments>,
+    pub mk_cons: CostingFun<TwoArguments>,
+    pub head_list: CostingFun<OneArgument>,
+    pub tail_list: CostingFun<OneArgument>,
+    pub null_list: CostingFun<OneArgument>,
+    pub choose_data: CostingFun<SixArguments>,
+    pub constr_data: CostingFun<TwoArguments>,
+    pub map_data: CostingFun<OneArgument>,
+    pub list_data: CostingFun<OneArgument>,
+    pub i_data: CostingFun<OneArgument>,
+    pub b_data: CostingFun<OneArgument>,
+    pub un_constr_data: CostingFun<OneArgument>,
+    pub un_map_data: CostingFun<OneArgument>,
+    pub un_list_data: CostingFun<OneArgument>,
+    pub un_i_data: CostingFun<OneArgument>,
+    pub un_b_data: CostingFun<OneArgument>,
+    pub equals_data: CostingFun<TwoArguments>,
+    pub serialise_data: CostingFun<OneArgument>,
+    pub mk_pair_data: CostingFun<TwoArguments>,
+    pub mk_nil_data: CostingFun<OneArgument>,
+    pub mk_nil_pair_data: CostingFun<OneArgument>,
+}
+
+fn linear(intercept: i64, slope: i64) -> LinearSize {
+    LinearSize { intercept, slope }
+}
+
+fn constant_one(mem: i64, cpu: i64) -> CostingFun<OneArgument> {
+    CostingFun {
+        mem: OneArgument::ConstantCost(mem),
+        cpu: OneArgument::ConstantCost(cpu),
+    }
+}
+
+fn constant_two(mem: i64, cpu: i64) -> CostingFun<TwoArguments> {
+    CostingFun {
+        mem: TwoArguments::ConstantCost(mem),
+        cpu: TwoArguments::ConstantCost(cpu),
+    }
+}
+
+fn division() -> CostingFun<TwoArguments> {
+    CostingFun {
+        mem: TwoArguments::SubtractedSizes(SubtractedSizes {
+            intercept: 0,
+            slope: 1,
+            minimum: 1,
+        }),
+        cpu: TwoArguments::ConstAboveDiagonal(ConstantOrTwoArguments {
+            constant: 196500,
+            model: MultipliedSizes {
+                intercept: 453240,
+                slope: 220,
+            },
+        }),
+    }
+}
+
+impl Default for BuiltinCosts {
+    fn default() -> Self {
+        BuiltinCosts {
+            add_integer: CostingFun {
+                mem: TwoArguments::MaxSize(linear(1, 1)),
+                cpu: TwoArguments::MaxSize(linear(205665, 812)),
+            },
+            subtract_integer: CostingFun {
+                mem: TwoArguments::MaxSize(linear(1, 1)),
+                cpu: TwoArguments::MaxSize(linear(205665, 812)),
+            },
+            multiply_integer: CostingFun {
+                mem: TwoArguments::AddedSizes(linear(0, 1)),
+                cpu: TwoArguments::AddedSizes(linear(69522, 11687)),
+            },
+            divide_integer: division(),
+            quotient_integer: division(),
+            remainder_integer: division(),
+            mod_integer: division(),
+            equals_integer: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(208512, 421)),
+            },
+            less_than_integer: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(208896, 511)),
+            },
+            less_than_equals_integer: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(204924, 473)),
+            },
+            append_byte_string: CostingFun {
+                mem: TwoArguments::AddedSizes(linear(0, 1)),
+                cpu: TwoArguments::AddedSizes(linear(1000, 571)),
+            },
+            cons_byte_string: CostingFun {
+                mem: TwoArguments::AddedSizes(linear(0, 1)),
+                cpu: TwoArguments::LinearInY(linear(221973, 511)),
+            },
+            slice_byte_string: CostingFun {
+                mem: ThreeArguments::LinearInZ(linear(4, 0)),
+                cpu: ThreeArguments::LinearInZ(linear(265318, 0)),
+            },
+            length_of_byte_string: constant_one(10, 1000),
+            index_byte_string: constant_two(4, 57667),
+            equals_byte_string: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::LinearOnDiagonal(ConstantOrLinear {
+                    constant: 245000,
+                    intercept: 216773,
+                    slope: 62,
+                }),
+            },
+            less_than_byte_string: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(197145, 156)),
+            },
+            less_than_equals_byte_string: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(197145, 156)),
+            },
+            sha2_256: CostingFun {
+                mem: OneArgument::ConstantCost(4),
+                cpu: OneArgument::LinearCost(linear(806990, 30482)),
+            },
+            sha3_256: CostingFun {
+                mem: OneArgument::ConstantCost(4),
+                cpu: OneArgument::LinearCost(linear(1927926, 82523)),
+            },
+            blake2b_256: CostingFun {
+                mem: OneArgument::ConstantCost(4),
+                cpu: OneArgument::LinearCost(linear(117366, 10475)),
+            },
+            verify_ed25519_signature: CostingFun {
+                mem: ThreeArguments::ConstantCost(10),
+                cpu: ThreeArguments::LinearInY(linear(57996947, 18975)),
+            },
+            verify_ecdsa_secp256k1_signature: CostingFun {
+                mem: ThreeArguments::ConstantCost(10),
+                cpu: ThreeArguments::ConstantCost(35892428),
+            },
+            verify_schnorr_secp256k1_signature: CostingFun {
+                mem: ThreeArguments::ConstantCost(10),
+                cpu: ThreeArguments::LinearInY(linear(38887044, 32947)),
+            },
+            append_string: CostingFun {
+                mem: TwoArguments::AddedSizes(linear(4, 1)),
+                cpu: TwoArguments::AddedSizes(linear(1000, 24177)),
+            },
+            equals_string: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::LinearOnDiagonal(ConstantOrLinear {
+                    constant: 187000,
+                    intercept: 1000,
+                    slope: 52998,
+                }),
+            },
+            encode_utf8: CostingFun {
+                mem: OneArgument::LinearCost(linear(4, 2)),
+                cpu: OneArgument::LinearCost(linear(1000, 28662)),
+            },
+            decode_utf8: CostingFun {
+                mem: OneArgument::LinearCost(linear(4, 2)),
+                cpu: OneArgument::LinearCost(linear(497525, 14068)),
+            },
+            if_then_else: CostingFun {
+                mem: ThreeArguments::ConstantCost(1),
+                cpu: ThreeArguments::ConstantCost(80556),
+            },
+            choose_unit: constant_two(4, 46417),
+            trace: constant_two(32, 212342),
+            fst_pair: constant_one(32, 80436),
+            snd_pair: constant_one(32, 85931),
+            choose_list: CostingFun {
+                mem: ThreeArguments::ConstantCost(32),
+                cpu: ThreeArguments::ConstantCost(175354),
+            },
+            mk_cons: constant_two(32, 65493),
+            head_list: constant_one(32, 43249),
+            tail_list: constant_one(32, 41182),
+            null_list: constant_one(32, 60091),
+            choose_data: CostingFun {
+                mem: SixArguments::ConstantCost(32),
+                cpu: SixArguments::ConstantCost(19537),
+            },
+            constr_data: constant_two(32, 89141),
+            map_data: constant_one(32, 64832),
+            list_data: constant_one(32, 52467),
+            i_data: constant_one(32, 1000),
+            b_data: constant_one(32, 1000),
+            un_constr_data: constant_one(32, 32696),
+            un_map_data: constant_one(32, 38314),
+            un_list_data: constant_one(32, 32247),
+            un_i_data: constant_one(32, 43357),
+            un_b_data: constant_one(32, 31220),
+            equals_data: CostingFun {
+                mem: TwoArguments::ConstantCost(1),
+                cpu: TwoArguments::MinSize(linear(1060367, 12586)),
+            },
+            serialise_data: CostingFun {
+                mem: OneArgument::LinearCost(linear(0, 2)),
+                cpu: OneArgument::LinearCost(linear(1159724, 392670)),
+            },
+            mk_pair_data: constant_two(32, 76511),
+            mk_nil_data: constant_one(32, 22558),
+            mk_nil_pair_data: constant_one(32, 16563),
+        }
+    }
+}
+
+impl BuiltinCosts {
+    /// Price a saturated builtin call based on the sizes of it's arguments.
+    pub fn to_ex_budget(&self, fun: DefaultFunction, args: &[Value]) -> ExBudget {
+        let sizes: Vec<i64> = args.iter().map(|arg| arg.to_ex_mem()).collect();
+
+        match self.costing(fun) {
+            Costing::One(costing) => ExBudget {
+                mem: costing.mem.cost(sizes[0]),
+                cpu: costing.cpu.cost(sizes[0]),
+            },
+            Costing::Two(costing) => ExBudget {
+                mem: costing.mem.cost(sizes[0], sizes[1]),
+                cpu: costing.cpu.cost(sizes[0], sizes[1]),
+            },
+            Costing::Three(costing) => ExBudget {
+                mem: costing.mem.cost(sizes[0], sizes[1], sizes[2]),
+                cpu: costing.cpu.cost(sizes[0], sizes[1], sizes[2]),
+            },
+            Costing::Six(costing) => ExBudget {
+                mem: costing.mem.cost(),
+                cpu: costing.cpu.cost(),
+            },
+        }
+    }
+
+    /// The costing function of a builtin.
+    pub fn costing(&self, fun: DefaultFunction) -> Costing<'_> {
+        match fun {
+            DefaultFunction::AddInteger => Costing::Two(&self.add_integer),
+            DefaultFunction::SubtractInteger => Costing::Two(&self.subtract_integer),
+            DefaultFunction::MultiplyInteger => Costing::Two(&self.multiply_integer),
+            DefaultFunction::DivideInteger => Costing::Two(&self.divide_integer),
+            DefaultFunction::QuotientInteger => Costing::Two(&self.quotient_integer),
+            DefaultFunction::RemainderInteger => Costing::Two(&self.remainder_integer),
+            DefaultFunction::ModInteger => Costing::Two(&self.mod_integer),
+            DefaultFunction::EqualsInteger => Costing::Two(&self.equals_integer),
+            DefaultFunction::LessThanInteger => Costing::Two(&self.less_than_integer),
+            DefaultFunction::LessThanEqualsInteger => Costing::Two(&self.less_than_equals_integer),
+            DefaultFunction::AppendByteString => Costing::Two(&self.append_byte_string),
+            DefaultFunction::ConsByteString => Costing::Two(&self.cons_byte_string),
+            DefaultFunction::SliceByteString => Costing::Three(&self.slice_byte_string),
+            DefaultFunction::LengthOfByteString => Costing::One(&self.length_of_byte_string),
+            DefaultFunction::IndexByteString => Costing::Two(&self.index_byte_string),
+            DefaultFunction::EqualsByteString => Costing::Two(&self.equals_byte_string),
+            DefaultFunction::LessThanByteString => Costing::Two(&self.less_than_byte_string),
+            DefaultFunction::LessThanEqualsByteString => {
+                Costing::Two(&self.less_than_equals_byte_string)
+            }
+            DefaultFunction::Sha2_256 => Costing::One(&self.sha2_256),
+            DefaultFunction::Sha3_256 => Costing::One(&self.sha3_256),
+            DefaultFunction::Blake2b_256 => Costing::One(&self.blake2b_256),
+            DefaultFunction::VerifySignature => Costing::Three(&self.verify_ed25519_signature),
+            DefaultFunction::VerifyEcdsaSecp256k1Signature => {
+                Costing::Three(&self.verify_ecdsa_secp256k1_signature)
+            }
+            DefaultFunction::VerifySchnorrSecp256k1Signature => {
+                Costing::Three(&self.verify_schnorr_secp256k1_signature)
+            }
+            DefaultFunction::AppendString => Costing::Two(&self.append_string),
+            DefaultFunction::EqualsString => Costing::Two(&self.equals_string),
+            DefaultFunction::EncodeUtf8 => Costing::One(&self.encode_utf8),
+            DefaultFunction::DecodeUtf8 => Costing::One(&self.decode_utf8),
+            DefaultFunction::IfThenElse => Costing::Three(&self.if_then_else),
+            DefaultFunction::ChooseUnit => Costing::Two(&self.choose_unit),
+            DefaultFunction::Trace => Costing::Two(&self.trace),
+            DefaultFunction::FstPair => Costing::One(&self.fst_pair),
+            DefaultFunction::SndPair => Costing::One(&self.snd_pair),
+            DefaultFunction::ChooseList => Costing::Three(&self.choose_list),
+            DefaultFunction::MkCons => Costing::Two(&self.mk_cons),
+            DefaultFunction::HeadList => Costing::One(&self.head_list),
+            DefaultFunction::TailList => Costing::One(&self.tail_list),
+            DefaultFunction::NullList => Costing::One(&self.null_list),
+            DefaultFunction::ChooseData => Costing::Six(&self.choose_data),
+            DefaultFunction::ConstrData => Costing::Two(&self.constr_data),
+            DefaultFunction::MapData => Costing::One(&self.map_data),
+            DefaultFunction::ListData => Costing::One(&self.list_data),
+            DefaultFunction::IData => Costing::One(&self.i_data),
+            DefaultFunction::BData => Costing::One(&self.b_data),
+            DefaultFunction::UnConstrData => Costing::One(&self.un_constr_data),
+            DefaultFunction::UnMapData => Costing::One(&self.un_map_data),
+            DefaultFunction::UnListData => Costing::One(&self.un_list_data),
+            DefaultFunction::UnIData => Costing::One(&self.un_i_data),
+            DefaultFunction::UnBData => Costing::One(&self.un_b_data),
+            DefaultFunction::EqualsData => Costing::Two(&self.equals_data),
+            DefaultFunction::SerialiseData => Costing::One(&self.serialise_data),
+            DefaultFunction::MkPairData => Costing::Two(&self.mk_pair_data),
+            DefaultFunction::MkNilData => Costing::One(&self.mk_nil_data),
+            DefaultFunction::MkNilPairData => Costing::One(&self.mk_nil_pair_data),
+        }
+    }
+}
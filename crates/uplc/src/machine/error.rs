@@ -0,0 +1,54 @@
+use num_bigint::BigInt;
+use thiserror::Error;
+
+use crate::{
+    ast::{Constant, PlutusVersion, Type},
+    builtins::DefaultFunction,
+    data::PlutusData,
+};
+
+use super::cost_model::ExBudget;
+
+/// Terms are kept pretty printed, errors have to be `Send` and terms
+/// share their subterms with `Rc`.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Evaluation failure")]
+    EvaluationFailure,
+    #[error("Over budget mem: {} & cpu: {}", .0.mem, .0.cpu)]
+    OutOfExError(ExBudget),
+    #[error("Attempted to evaluate an open term:\n\n{0}")]
+    OpenTermEvaluated(String),
+    #[error("Attempted to instantiate a non-polymorphic term:\n\n{0}")]
+    NonPolymorphicInstantiation(String),
+    #[error("Attempted to apply a non-function:\n\n{0}")]
+    NonFunctionalApplication(String),
+    #[error("Expected a constant but got:\n\n{0}")]
+    NotAConstant(String),
+    #[error("Type mismatch expected '{0:?}' got '{1:?}'")]
+    TypeMismatch(Type, Constant),
+    #[error("A builtin received a term argument when something else was expected:\n\n{0}")]
+    UnexpectedBuiltinTermArgument(String),
+    #[error("A builtin expected a term argument, but something else was received:\n\n{0}")]
+    BuiltinTermArgumentExpected(String),
+    #[error("Builtin '{0:?}' is not available in Plutus {1:?}")]
+    UnavailableBuiltin(DefaultFunction, PlutusVersion),
+    #[error("Division by zero while evaluating builtin '{0:?}'")]
+    DivideByZero(DefaultFunction),
+    #[error("Index {0} out of bounds for a bytestring of length {1}")]
+    ByteStringOutOfBounds(BigInt, usize),
+    #[error("Builtin '{0:?}' was given an empty list")]
+    EmptyList(DefaultFunction),
+    #[error("Builtin '{0:?}' can't deconstruct data:\n\n{1:#?}")]
+    DeserialisationError(DefaultFunction, PlutusData),
+    #[error("Constructor tag {0} is out of range")]
+    InvalidConstrTag(BigInt),
+    #[error("Ed25519 public keys are 32 bytes long, got {0} bytes")]
+    UnexpectedEd25519PublicKeyLength(usize),
+    #[error("Ed25519 signatures are 64 bytes long, got {0} bytes")]
+    UnexpectedEd25519SignatureLength(usize),
+    #[error("Invalid secp256k1 {0}")]
+    InvalidSecp256k1(&'static str),
+    #[error(transparent)]
+    DecodeUtf8(#[from] std::string::FromUtf8Error),
+}
@@ -0,0 +1,837 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use blake2::{digest::consts::U32, Blake2b};
+use ed25519_dalek::Verifier;
+use k256::{
+    ecdsa::{self, signature::hazmat::PrehashVerifier},
+    schnorr,
+};
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+
+use crate::{
+    ast::{Constant, NamedDeBruijn, Term, Type},
+    builtins::DefaultFunction,
+    data::PlutusData,
+};
+
+use super::{cost_model::BuiltinCosts, cost_model::ExBudget, Error, Value};
+
+/// Keeps track of the forces and arguments a builtin has received
+/// until it is saturated and can be called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinRuntime {
+    fun: DefaultFunction,
+    forces: usize,
+    args: Vec<Value>,
+}
+
+impl BuiltinRuntime {
+    pub fn new(fun: DefaultFunction) -> Self {
+        BuiltinRuntime {
+            fun,
+            forces: 0,
+            args: Vec::new(),
+        }
+    }
+
+    /// All the forces and arguments have been received.
+    pub fn is_ready(&self) -> bool {
+        self.forces == self.fun.force_count() && self.args.len() == self.fun.arity()
+    }
+
+    /// The builtin is still waiting to be forced.
+    pub fn needs_force(&self) -> bool {
+        self.forces < self.fun.force_count()
+    }
+
+    /// The builtin has been fully forced and is waiting for arguments.
+    pub fn is_arrow(&self) -> bool {
+        !self.needs_force() && self.args.len() < self.fun.arity()
+    }
+
+    pub fn consume_force(&mut self) {
+        self.forces += 1;
+    }
+
+    pub fn push(&mut self, arg: Value) {
+        self.args.push(arg);
+    }
+
+    /// Run the builtin once it is saturated.
+    pub fn call(&self, logs: &mut Vec<String>) -> Result<Value, Error> {
+        self.fun.call(&self.args, logs)
+    }
+
+    /// The cost of calling the builtin with the arguments it received.
+    pub fn to_ex_budget(&self, costs: &BuiltinCosts) -> ExBudget {
+        costs.to_ex_budget(self.fun, &self.args)
+    }
+
+    /// Turn a partially applied builtin back into a term.
+    pub fn to_term(&self) -> Term<NamedDeBruijn> {
+        let mut term = Term::Builtin(self.fun);
+
+        for _ in 0..self.forces {
+            term = Term::Force(Rc::new(term));
+        }
+
+        for arg in &self.args {
+            term = Term::Apply {
+                function: Rc::new(term),
+                argument: Rc::new(arg.to_term()),
+            };
+        }
+
+        term
+    }
+}
+
+impl DefaultFunction {
+    /// Apply the semantics of this builtin to a saturated list of arguments.
+    /// Messages emitted by `trace` are appended to `logs`.
+    pub fn call(&self, args: &[Value], logs: &mut Vec<String>) -> Result<Value, Error> {
+        match self {
+            DefaultFunction::AddInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Integer(a + b)))
+            }
+            DefaultFunction::SubtractInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Integer(a - b)))
+            }
+            DefaultFunction::MultiplyInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Integer(a * b)))
+            }
+            DefaultFunction::DivideInteger
+            | DefaultFunction::QuotientInteger
+            | DefaultFunction::RemainderInteger
+            | DefaultFunction::ModInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                if b.is_zero() {
+                    return Err(Error::DivideByZero(*self));
+                }
+
+                // `div` and `mod` round towards negative infinity like
+                // Haskell, `quot` and `rem` round towards zero.
+                let result = match self {
+                    DefaultFunction::DivideInteger => a.div_floor(b),
+                    DefaultFunction::QuotientInteger => a / b,
+                    DefaultFunction::RemainderInteger => a % b,
+                    _ => a.mod_floor(b),
+                };
+
+                Ok(Value::Con(Constant::Integer(result)))
+            }
+            DefaultFunction::EqualsInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a == b)))
+            }
+            DefaultFunction::LessThanInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a < b)))
+            }
+            DefaultFunction::LessThanEqualsInteger => {
+                let (a, b) = (unwrap_integer(&args[0])?, unwrap_integer(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a <= b)))
+            }
+            DefaultFunction::AppendByteString => {
+                let (a, b) = (unwrap_bytestring(&args[0])?, unwrap_bytestring(&args[1])?);
+
+                Ok(Value::Con(Constant::ByteString([a, b].concat())))
+            }
+            DefaultFunction::ConsByteString => {
+                let (byte, bytes) = (unwrap_integer(&args[0])?, unwrap_bytestring(&args[1])?);
+
+                let byte = byte.mod_floor(&BigInt::from(256)).to_u8().unwrap();
+
+                let mut result = vec![byte];
+
+                result.extend(bytes);
+
+                Ok(Value::Con(Constant::ByteString(result)))
+            }
+            DefaultFunction::SliceByteString => {
+                let (skip, take, bytes) = (
+                    unwrap_integer(&args[0])?,
+                    unwrap_integer(&args[1])?,
+                    unwrap_bytestring(&args[2])?,
+                );
+
+                let skip = to_length(skip);
+                let take = to_length(take);
+
+                let result = bytes.iter().skip(skip).take(take).cloned().collect();
+
+                Ok(Value::Con(Constant::ByteString(result)))
+            }
+            DefaultFunction::LengthOfByteString => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                Ok(Value::Con(Constant::Integer(bytes.len().into())))
+            }
+            DefaultFunction::IndexByteString => {
+                let (bytes, index) = (unwrap_bytestring(&args[0])?, unwrap_integer(&args[1])?);
+
+                match index.to_usize().and_then(|i| bytes.get(i)) {
+                    Some(byte) => Ok(Value::Con(Constant::Integer((*byte).into()))),
+                    None => Err(Error::ByteStringOutOfBounds(index.clone(), bytes.len())),
+                }
+            }
+            DefaultFunction::EqualsByteString => {
+                let (a, b) = (unwrap_bytestring(&args[0])?, unwrap_bytestring(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a == b)))
+            }
+            DefaultFunction::LessThanByteString => {
+                let (a, b) = (unwrap_bytestring(&args[0])?, unwrap_bytestring(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a < b)))
+            }
+            DefaultFunction::LessThanEqualsByteString => {
+                let (a, b) = (unwrap_bytestring(&args[0])?, unwrap_bytestring(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a <= b)))
+            }
+            DefaultFunction::AppendString => {
+                let (a, b) = (unwrap_string(&args[0])?, unwrap_string(&args[1])?);
+
+                Ok(Value::Con(Constant::String(format!("{}{}", a, b))))
+            }
+            DefaultFunction::EqualsString => {
+                let (a, b) = (unwrap_string(&args[0])?, unwrap_string(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a == b)))
+            }
+            DefaultFunction::EncodeUtf8 => {
+                let s = unwrap_string(&args[0])?;
+
+                Ok(Value::Con(Constant::ByteString(s.as_bytes().to_vec())))
+            }
+            DefaultFunction::DecodeUtf8 => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                let s = String::from_utf8(bytes.to_vec())?;
+
+                Ok(Value::Con(Constant::String(s)))
+            }
+            DefaultFunction::IfThenElse => {
+                if unwrap_bool(&args[0])? {
+                    Ok(args[1].clone())
+                } else {
+                    Ok(args[2].clone())
+                }
+            }
+            DefaultFunction::ChooseUnit => {
+                unwrap_unit(&args[0])?;
+
+                Ok(args[1].clone())
+            }
+            DefaultFunction::Trace => {
+                logs.push(unwrap_string(&args[0])?.to_string());
+
+                Ok(args[1].clone())
+            }
+            DefaultFunction::Sha2_256 => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                Ok(Value::Con(Constant::ByteString(
+                    Sha256::digest(bytes).to_vec(),
+                )))
+            }
+            DefaultFunction::Sha3_256 => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                Ok(Value::Con(Constant::ByteString(
+                    Sha3_256::digest(bytes).to_vec(),
+                )))
+            }
+            DefaultFunction::Blake2b_256 => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                Ok(Value::Con(Constant::ByteString(
+                    Blake2b::<U32>::digest(bytes).to_vec(),
+                )))
+            }
+            DefaultFunction::VerifySignature => {
+                let (public_key, message, signature) = (
+                    unwrap_bytestring(&args[0])?,
+                    unwrap_bytestring(&args[1])?,
+                    unwrap_bytestring(&args[2])?,
+                );
+
+                let public_key: [u8; 32] = public_key
+                    .try_into()
+                    .map_err(|_| Error::UnexpectedEd25519PublicKeyLength(public_key.len()))?;
+
+                let signature: [u8; 64] = signature
+                    .try_into()
+                    .map_err(|_| Error::UnexpectedEd25519SignatureLength(signature.len()))?;
+
+                // keys that aren't points on the curve can't verify anything
+                let verified = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+                    .map(|key| {
+                        key.verify(message, &ed25519_dalek::Signature::from_bytes(&signature))
+                            .is_ok()
+                    })
+                    .unwrap_or(false);
+
+                Ok(Value::Con(Constant::Bool(verified)))
+            }
+            DefaultFunction::VerifyEcdsaSecp256k1Signature => {
+                let (public_key, message, signature) = (
+                    unwrap_bytestring(&args[0])?,
+                    unwrap_bytestring(&args[1])?,
+                    unwrap_bytestring(&args[2])?,
+                );
+
+                let public_key = ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                    .ok()
+                    .filter(|_| public_key.len() == 33)
+                    .ok_or(Error::InvalidSecp256k1("public key"))?;
+
+                if message.len() != 32 {
+                    return Err(Error::InvalidSecp256k1("message hash"));
+                }
+
+                let signature = ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSecp256k1("signature"))?;
+
+                let verified = public_key.verify_prehash(message, &signature).is_ok();
+
+                Ok(Value::Con(Constant::Bool(verified)))
+            }
+            DefaultFunction::VerifySchnorrSecp256k1Signature => {
+                let (public_key, message, signature) = (
+                    unwrap_bytestring(&args[0])?,
+                    unwrap_bytestring(&args[1])?,
+                    unwrap_bytestring(&args[2])?,
+                );
+
+                let public_key = schnorr::VerifyingKey::from_bytes(public_key)
+                    .map_err(|_| Error::InvalidSecp256k1("public key"))?;
+
+                let signature = schnorr::Signature::try_from(signature)
+                    .map_err(|_| Error::InvalidSecp256k1("signature"))?;
+
+                let verified = public_key.verify_raw(message, &signature).is_ok();
+
+                Ok(Value::Con(Constant::Bool(verified)))
+            }
+            DefaultFunction::FstPair => {
+                let (left, _) = unwrap_pair(&args[0])?;
+
+                Ok(Value::Con(left.clone()))
+            }
+            DefaultFunction::SndPair => {
+                let (_, right) = unwrap_pair(&args[0])?;
+
+                Ok(Value::Con(right.clone()))
+            }
+            DefaultFunction::ChooseList => {
+                let (_, items) = unwrap_list(&args[0])?;
+
+                if items.is_empty() {
+                    Ok(args[1].clone())
+                } else {
+                    Ok(args[2].clone())
+                }
+            }
+            DefaultFunction::MkCons => {
+                let item = unwrap_constant(&args[0])?;
+                let (ty, items) = unwrap_list(&args[1])?;
+
+                if item.ty() != *ty {
+                    return Err(Error::TypeMismatch(ty.clone(), item.clone()));
+                }
+
+                let mut result = vec![item.clone()];
+
+                result.extend(items.iter().cloned());
+
+                Ok(Value::Con(Constant::ProtoList(ty.clone(), result)))
+            }
+            DefaultFunction::HeadList => {
+                let (_, items) = unwrap_list(&args[0])?;
+
+                match items.first() {
+                    Some(head) => Ok(Value::Con(head.clone())),
+                    None => Err(Error::EmptyList(*self)),
+                }
+            }
+            DefaultFunction::TailList => {
+                let (ty, items) = unwrap_list(&args[0])?;
+
+                match items.split_first() {
+                    Some((_, tail)) => {
+                        Ok(Value::Con(Constant::ProtoList(ty.clone(), tail.to_vec())))
+                    }
+                    None => Err(Error::EmptyList(*self)),
+                }
+            }
+            DefaultFunction::NullList => {
+                let (_, items) = unwrap_list(&args[0])?;
+
+                Ok(Value::Con(Constant::Bool(items.is_empty())))
+            }
+            DefaultFunction::ChooseData => {
+                let branch = match unwrap_data(&args[0])? {
+                    PlutusData::Constr { .. } => &args[1],
+                    PlutusData::Map(_) => &args[2],
+                    PlutusData::List(_) => &args[3],
+                    PlutusData::Integer(_) => &args[4],
+                    PlutusData::ByteString(_) => &args[5],
+                };
+
+                Ok(branch.clone())
+            }
+            DefaultFunction::ConstrData => {
+                let (tag, fields) = (unwrap_integer(&args[0])?, unwrap_list(&args[1])?);
+
+                let tag = tag
+                    .to_u64()
+                    .ok_or_else(|| Error::InvalidConstrTag(tag.clone()))?;
+
+                let fields = fields
+                    .1
+                    .iter()
+                    .map(constant_to_data)
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Value::Con(Constant::Data(PlutusData::Constr {
+                    tag,
+                    fields,
+                })))
+            }
+            DefaultFunction::MapData => {
+                let (_, items) = unwrap_list(&args[0])?;
+
+                let entries = items
+                    .iter()
+                    .map(|item| match item {
+                        Constant::ProtoPair(_, _, key, value) => {
+                            Ok((constant_to_data(key)?, constant_to_data(value)?))
+                        }
+                        rest => Err(Error::TypeMismatch(
+                            Type::Pair(Box::new(Type::Data), Box::new(Type::Data)),
+                            rest.clone(),
+                        )),
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Value::Con(Constant::Data(PlutusData::Map(entries))))
+            }
+            DefaultFunction::ListData => {
+                let (_, items) = unwrap_list(&args[0])?;
+
+                let items = items
+                    .iter()
+                    .map(constant_to_data)
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Value::Con(Constant::Data(PlutusData::List(items))))
+            }
+            DefaultFunction::IData => {
+                let i = unwrap_integer(&args[0])?;
+
+                Ok(Value::Con(Constant::Data(PlutusData::Integer(i.clone()))))
+            }
+            DefaultFunction::BData => {
+                let bytes = unwrap_bytestring(&args[0])?;
+
+                Ok(Value::Con(Constant::Data(PlutusData::ByteString(
+                    bytes.to_vec(),
+                ))))
+            }
+            DefaultFunction::UnConstrData => match unwrap_data(&args[0])? {
+                PlutusData::Constr { tag, fields } => Ok(Value::Con(Constant::ProtoPair(
+                    Type::Integer,
+                    Type::List(Box::new(Type::Data)),
+                    Box::new(Constant::Integer((*tag).into())),
+                    Box::new(data_list(
+                        fields.iter().cloned().map(Constant::Data).collect(),
+                    )),
+                ))),
+                rest => Err(Error::DeserialisationError(*self, rest.clone())),
+            },
+            DefaultFunction::UnMapData => match unwrap_data(&args[0])? {
+                PlutusData::Map(entries) => {
+                    let items = entries
+                        .iter()
+                        .map(|(key, value)| {
+                            Constant::ProtoPair(
+                                Type::Data,
+                                Type::Data,
+                                Box::new(Constant::Data(key.clone())),
+                                Box::new(Constant::Data(value.clone())),
+                            )
+                        })
+                        .collect();
+
+                    Ok(Value::Con(Constant::ProtoList(
+                        Type::Pair(Box::new(Type::Data), Box::new(Type::Data)),
+                        items,
+                    )))
+                }
+                rest => Err(Error::DeserialisationError(*self, rest.clone())),
+            },
+            DefaultFunction::UnListData => match unwrap_data(&args[0])? {
+                PlutusData::List(items) => Ok(Value::Con(data_list(
+                    items.iter().cloned().map(Constant::Data).collect(),
+                ))),
+                rest => Err(Error::DeserialisationError(*self, rest.clone())),
+            },
+            DefaultFunction::UnIData => match unwrap_data(&args[0])? {
+                PlutusData::Integer(i) => Ok(Value::Con(Constant::Integer(i.clone()))),
+                rest => Err(Error::DeserialisationError(*self, rest.clone())),
+            },
+            DefaultFunction::UnBData => match unwrap_data(&args[0])? {
+                PlutusData::ByteString(bytes) => {
+                    Ok(Value::Con(Constant::ByteString(bytes.clone())))
+                }
+                rest => Err(Error::DeserialisationError(*self, rest.clone())),
+            },
+            DefaultFunction::EqualsData => {
+                let (a, b) = (unwrap_data(&args[0])?, unwrap_data(&args[1])?);
+
+                Ok(Value::Con(Constant::Bool(a == b)))
+            }
+            DefaultFunction::SerialiseData => {
+                let data = unwrap_data(&args[0])?;
+
+                Ok(Value::Con(Constant::ByteString(data.to_cbor())))
+            }
+            DefaultFunction::MkPairData => {
+                let (a, b) = (unwrap_data(&args[0])?, unwrap_data(&args[1])?);
+
+                Ok(Value::Con(Constant::ProtoPair(
+                    Type::Data,
+                    Type::Data,
+                    Box::new(Constant::Data(a.clone())),
+                    Box::new(Constant::Data(b.clone())),
+                )))
+            }
+            DefaultFunction::MkNilData => {
+                unwrap_unit(&args[0])?;
+
+                Ok(Value::Con(data_list(vec![])))
+            }
+            DefaultFunction::MkNilPairData => {
+                unwrap_unit(&args[0])?;
+
+                Ok(Value::Con(Constant::ProtoList(
+                    Type::Pair(Box::new(Type::Data), Box::new(Type::Data)),
+                    vec![],
+                )))
+            }
+        }
+    }
+}
+
+fn data_list(items: Vec<Constant>) -> Constant {
+    Constant::ProtoList(Type::Data, items)
+}
+
+fn constant_to_data(constant: &Constant) -> Result<PlutusData, Error> {
+    match constant {
+        Constant::Data(data) => Ok(data.clone()),
+        rest => Err(Error::TypeMismatch(Type::Data, rest.clone())),
+    }
+}
+
+fn unwrap_constant(value: &Value) -> Result<&Constant, Error> {
+    match value {
+        Value::Con(constant) => Ok(constant),
+        rest => Err(Error::NotAConstant(rest.to_term().to_pretty())),
+    }
+}
+
+/// Clamp an integer argument to a length, negative values count as zero.
+fn to_length(i: &BigInt) -> usize {
+    if i.is_negative() {
+        0
+    } else {
+        i.to_usize().unwrap_or(usize::MAX)
+    }
+}
+
+fn unwrap_integer(value: &Value) -> Result<&BigInt, Error> {
+    match unwrap_constant(value)? {
+        Constant::Integer(i) => Ok(i),
+        rest => Err(Error::TypeMismatch(Type::Integer, rest.clone())),
+    }
+}
+
+fn unwrap_bytestring(value: &Value) -> Result<&[u8], Error> {
+    match unwrap_constant(value)? {
+        Constant::ByteString(bytes) => Ok(bytes),
+        rest => Err(Error::TypeMismatch(Type::ByteString, rest.clone())),
+    }
+}
+
+fn unwrap_string(value: &Value) -> Result<&str, Error> {
+    match unwrap_constant(value)? {
+        Constant::String(s) => Ok(s),
+        rest => Err(Error::TypeMismatch(Type::String, rest.clone())),
+    }
+}
+
+fn unwrap_bool(value: &Value) -> Result<bool, Error> {
+    match unwrap_constant(value)? {
+        Constant::Bool(b) => Ok(*b),
+        rest => Err(Error::TypeMismatch(Type::Bool, rest.clone())),
+    }
+}
+
+fn unwrap_list(value: &Value) -> Result<(&Type, &[Constant]), Error> {
+    match unwrap_constant(value)? {
+        Constant::ProtoList(ty, items) => Ok((ty, items)),
+        rest => Err(Error::TypeMismatch(
+            Type::List(Box::new(Type::Var("a"))),
+            rest.clone(),
+        )),
+    }
+}
+
+fn unwrap_pair(value: &Value) -> Result<(&Constant, &Constant), Error> {
+    match unwrap_constant(value)? {
+        Constant::ProtoPair(_, _, left, right) => Ok((left, right)),
+        rest => Err(Error::TypeMismatch(
+            Type::Pair(Box::new(Type::Var("a")), Box::new(Type::Var("b"))),
+            rest.clone(),
+        )),
+    }
+}
+
+fn unwrap_data(value: &Value) -> Result<&PlutusData, Error> {
+    match unwrap_constant(value)? {
+        Constant::Data(data) => Ok(data),
+        rest => Err(Error::TypeMismatch(Type::Data, rest.clone())),
+    }
+}
+
+fn unwrap_unit(value: &Value) -> Result<(), Error> {
+    match unwrap_constant(value)? {
+        Constant::Unit => Ok(()),
+        rest => Err(Error::TypeMismatch(Type::Unit, rest.clone())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use k256::{ecdsa, schnorr};
+
+    use crate::{
+        ast::{Constant, Type},
+        builtins::DefaultFunction,
+        data::PlutusData,
+        machine::{Error, Value},
+    };
+
+    fn bytes(s: &str) -> Value {
+        Value::Con(Constant::ByteString(hex::decode(s).unwrap()))
+    }
+
+    fn data(d: PlutusData) -> Value {
+        Value::Con(Constant::Data(d))
+    }
+
+    fn call(fun: DefaultFunction, args: &[Value]) -> Constant {
+        match fun.call(args, &mut Vec::new()).unwrap() {
+            Value::Con(constant) => constant,
+            rest => panic!("expected a constant, got {:?}", rest),
+        }
+    }
+
+    #[test]
+    fn hashes() {
+        assert_eq!(
+            call(DefaultFunction::Sha2_256, &[bytes("")]),
+            Constant::ByteString(
+                hex::decode("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+                    .unwrap()
+            )
+        );
+
+        assert_eq!(
+            call(DefaultFunction::Sha3_256, &[bytes("")]),
+            Constant::ByteString(
+                hex::decode("a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a")
+                    .unwrap()
+            )
+        );
+
+        assert_eq!(
+            call(DefaultFunction::Blake2b_256, &[bytes("")]),
+            Constant::ByteString(
+                hex::decode("0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8")
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn verify_ed25519() {
+        // RFC 8032 test vector 1
+        let public_key = bytes("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a");
+        let signature = bytes(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        );
+
+        assert_eq!(
+            call(
+                DefaultFunction::VerifySignature,
+                &[public_key.clone(), bytes(""), signature.clone()]
+            ),
+            Constant::Bool(true)
+        );
+
+        assert_eq!(
+            call(
+                DefaultFunction::VerifySignature,
+                &[public_key.clone(), bytes("00"), signature]
+            ),
+            Constant::Bool(false)
+        );
+
+        assert!(matches!(
+            DefaultFunction::VerifySignature
+                .call(&[public_key, bytes(""), bytes("00")], &mut Vec::new()),
+            Err(Error::UnexpectedEd25519SignatureLength(1))
+        ));
+    }
+
+    #[test]
+    fn verify_secp256k1() {
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+        let message = [7; 32];
+
+        let signing_key = ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let signature: ecdsa::Signature = signing_key.sign_prehash(&message).unwrap();
+        let public_key = signing_key.verifying_key().to_sec1_bytes();
+
+        assert_eq!(
+            call(
+                DefaultFunction::VerifyEcdsaSecp256k1Signature,
+                &[
+                    Value::Con(Constant::ByteString(public_key.to_vec())),
+                    Value::Con(Constant::ByteString(message.to_vec())),
+                    Value::Con(Constant::ByteString(signature.to_vec())),
+                ]
+            ),
+            Constant::Bool(true)
+        );
+
+        assert!(matches!(
+            DefaultFunction::VerifyEcdsaSecp256k1Signature.call(
+                &[
+                    Value::Con(Constant::ByteString(public_key.to_vec())),
+                    bytes("00"),
+                    Value::Con(Constant::ByteString(signature.to_vec())),
+                ],
+                &mut Vec::new()
+            ),
+            Err(Error::InvalidSecp256k1("message hash"))
+        ));
+
+        let signing_key = schnorr::SigningKey::from_bytes(&[1; 32]).unwrap();
+        let signature = signing_key.sign_raw(b"hello", &[0; 32]).unwrap();
+
+        assert_eq!(
+            call(
+                DefaultFunction::VerifySchnorrSecp256k1Signature,
+                &[
+                    Value::Con(Constant::ByteString(
+                        signing_key.verifying_key().to_bytes().to_vec()
+                    )),
+                    Value::Con(Constant::ByteString(b"hello".to_vec())),
+                    Value::Con(Constant::ByteString(signature.to_bytes().to_vec())),
+                ]
+            ),
+            Constant::Bool(true)
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let list = Value::Con(Constant::ProtoList(
+            Type::Integer,
+            vec![Constant::Integer(1.into())],
+        ));
+
+        assert_eq!(
+            call(
+                DefaultFunction::MkCons,
+                &[Value::Con(Constant::Integer(0.into())), list.clone()]
+            ),
+            Constant::ProtoList(
+                Type::Integer,
+                vec![Constant::Integer(0.into()), Constant::Integer(1.into())]
+            )
+        );
+
+        assert!(matches!(
+            DefaultFunction::MkCons
+                .call(&[Value::Con(Constant::Unit), list.clone()], &mut Vec::new()),
+            Err(Error::TypeMismatch(..))
+        ));
+
+        let empty = call(DefaultFunction::TailList, &[list]);
+
+        assert_eq!(empty, Constant::ProtoList(Type::Integer, vec![]));
+
+        assert!(matches!(
+            DefaultFunction::HeadList.call(&[Value::Con(empty)], &mut Vec::new()),
+            Err(Error::EmptyList(DefaultFunction::HeadList))
+        ));
+    }
+
+    #[test]
+    fn construct_and_deconstruct_data() {
+        let fields = Value::Con(Constant::ProtoList(
+            Type::Data,
+            vec![Constant::Data(PlutusData::Integer(42.into()))],
+        ));
+
+        let constr = call(
+            DefaultFunction::ConstrData,
+            &[Value::Con(Constant::Integer(3.into())), fields],
+        );
+
+        assert_eq!(
+            call(DefaultFunction::UnConstrData, &[Value::Con(constr.clone())]),
+            Constant::ProtoPair(
+                Type::Integer,
+                Type::List(Box::new(Type::Data)),
+                Box::new(Constant::Integer(3.into())),
+                Box::new(Constant::ProtoList(
+                    Type::Data,
+                    vec![Constant::Data(PlutusData::Integer(42.into()))]
+                )),
+            )
+        );
+
+        assert_eq!(
+            call(DefaultFunction::SerialiseData, &[Value::Con(constr)]),
+            Constant::ByteString(hex::decode("d87c9f182aff").unwrap())
+        );
+
+        assert!(matches!(
+            DefaultFunction::UnIData.call(&[data(PlutusData::List(vec![]))], &mut Vec::new()),
+            Err(Error::DeserialisationError(DefaultFunction::UnIData, _))
+        ));
+    }
+}
@@ -0,0 +1,509 @@
+//! Rewrites making programs smaller and cheaper without changing what
+//! they evaluate to.
+//!
+//! Passes only move or drop terms that can't fail or trace, so a
+//! program fails, and logs, exactly when it did before.
+
+use std::{rc::Rc, str::FromStr};
+
+use crate::{
+    ast::{
+        visit::{Folded, Visitor},
+        Name, NamedDeBruijn, PlutusVersion, Program, Term,
+    },
+    builtins::DefaultFunction,
+    machine::cost_model::ExBudget,
+};
+
+/// How many times the passes are run at most, each run possibly
+/// uncovering more to simplify.
+const MAX_ROUNDS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// `(force (delay t))` to `t`.
+    ForceDelay,
+    /// `[(lam x body) v]` to `body` with `v` in place of `x`, when
+    /// `x` is used once and `v` is a value. Free variables aren't
+    /// values, looking them up fails.
+    InlineSingleUse,
+    /// `[(lam x body) v]` to `body`, when `x` is unused and `v` is a
+    /// value.
+    DeadParameters,
+    /// Saturated builtins applied to constants to their result, for
+    /// builtins the Plutus version has.
+    ConstantFolding,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 4] = [
+        Pass::ForceDelay,
+        Pass::DeadParameters,
+        Pass::InlineSingleUse,
+        Pass::ConstantFolding,
+    ];
+
+    /// Run the pass over a term of a program meant for `version`.
+    pub fn run(&self, term: Term<Name>, version: PlutusVersion) -> Term<Name> {
+        let mut bound = vec![];
+
+        match self {
+            Pass::ForceDelay => rewrite(term, &mut bound, &mut |term, _| force_delay(term)),
+            Pass::InlineSingleUse => rewrite(term, &mut bound, &mut inline_single_use),
+            Pass::DeadParameters => rewrite(term, &mut bound, &mut dead_parameter),
+            Pass::ConstantFolding => rewrite(term, &mut bound, &mut |term, _| {
+                fold_constants(term, version)
+            }),
+        }
+    }
+}
+
+impl FromStr for Pass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "force-delay" => Ok(Pass::ForceDelay),
+            "inline" => Ok(Pass::InlineSingleUse),
+            "dead-parameters" => Ok(Pass::DeadParameters),
+            "constant-folding" => Ok(Pass::ConstantFolding),
+            _ => Err(format!("unknown optimization pass `{s}`")),
+        }
+    }
+}
+
+impl Program<Name> {
+    /// Run `passes` in order, again and again until the program
+    /// stops changing. `version` is the Plutus version the program is
+    /// meant for.
+    pub fn optimize(self, passes: &[Pass], version: PlutusVersion) -> Program<Name> {
+        let mut term = self.term;
+
+        for _ in 0..MAX_ROUNDS {
+            let optimized = passes
+                .iter()
+                .fold(term.clone(), |term, pass| pass.run(term, version));
+
+            if optimized == term {
+                break;
+            }
+
+            term = optimized;
+        }
+
+        Program {
+            version: self.version,
+            term,
+        }
+    }
+}
+
+/// A rewrite of a term, given the parameters of the lambdas around it.
+type Rewrite<'a> = dyn FnMut(Term<Name>, &[Name]) -> Term<Name> + 'a;
+
+/// Apply `f` to every sub term, children first.
+fn rewrite(term: Term<Name>, bound: &mut Vec<Name>, f: &mut Rewrite) -> Term<Name> {
+    let term = match term {
+        Term::Delay(term) => Term::Delay(rewrite(Rc::unwrap_or_clone(term), bound, f).into()),
+        Term::Force(term) => Term::Force(rewrite(Rc::unwrap_or_clone(term), bound, f).into()),
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => {
+            bound.push(parameter_name);
+
+            let body = rewrite(Rc::unwrap_or_clone(body), bound, f).into();
+
+            Term::Lambda {
+                parameter_name: bound.pop().expect("pushed above"),
+                body,
+            }
+        }
+        Term::Apply { function, argument } => Term::Apply {
+            function: rewrite(Rc::unwrap_or_clone(function), bound, f).into(),
+            argument: rewrite(Rc::unwrap_or_clone(argument), bound, f).into(),
+        },
+        Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => term,
+    };
+
+    f(term, bound)
+}
+
+fn force_delay(term: Term<Name>) -> Term<Name> {
+    match term {
+        Term::Force(inner) => match Rc::unwrap_or_clone(inner) {
+            Term::Delay(term) => Rc::unwrap_or_clone(term),
+            inner => Term::Force(inner.into()),
+        },
+        term => term,
+    }
+}
+
+fn dead_parameter(term: Term<Name>, bound: &[Name]) -> Term<Name> {
+    match applied_lambda(term) {
+        Ok((parameter_name, body, argument)) if is_value(&argument, bound) => {
+            if occurrences(&parameter_name, &body) == 0 {
+                body
+            } else {
+                apply_lambda(parameter_name, body, argument)
+            }
+        }
+        Ok((parameter_name, body, argument)) => apply_lambda(parameter_name, body, argument),
+        Err(term) => term,
+    }
+}
+
+fn inline_single_use(term: Term<Name>, bound: &[Name]) -> Term<Name> {
+    match applied_lambda(term) {
+        Ok((parameter_name, body, argument))
+            if is_value(&argument, bound) && occurrences(&parameter_name, &body) == 1 =>
+        {
+            let free = free_variables(&argument);
+
+            match substitute(&body, &parameter_name, &argument, &free) {
+                Some(body) => body,
+                None => apply_lambda(parameter_name, body, argument),
+            }
+        }
+        Ok((parameter_name, body, argument)) => apply_lambda(parameter_name, body, argument),
+        Err(term) => term,
+    }
+}
+
+fn fold_constants(term: Term<Name>, version: PlutusVersion) -> Term<Name> {
+    let mut arguments = 0;
+    let mut head = &term;
+
+    while let Term::Apply { function, argument } = head {
+        if !matches!(argument.as_ref(), Term::Constant(_)) {
+            return term;
+        }
+
+        arguments += 1;
+        head = function;
+    }
+
+    let mut forces = 0;
+
+    while let Term::Force(inner) = head {
+        forces += 1;
+        head = inner;
+    }
+
+    let Term::Builtin(fun) = head else {
+        return term;
+    };
+
+    // folding a trace would lose it's message, and a builtin the
+    // version doesn't have must still fail
+    if *fun == DefaultFunction::Trace
+        || !fun.is_available_in(version)
+        || forces != fun.force_count()
+        || arguments != fun.arity()
+    {
+        return term;
+    }
+
+    let program = Program {
+        version: (1, 0, 0),
+        term: term.clone(),
+    };
+
+    let Ok(program) = Program::<NamedDeBruijn>::try_from(program) else {
+        return term;
+    };
+
+    // failing builtins are left for the program to fail on
+    match program.eval_with_version(version, ExBudget::default()).term {
+        Ok(Term::Constant(constant)) => Term::Constant(constant),
+        _ => term,
+    }
+}
+
+/// Split `[(lam x body) argument]`, giving the term back otherwise.
+fn applied_lambda(term: Term<Name>) -> Result<(Name, Term<Name>, Term<Name>), Term<Name>> {
+    match term {
+        Term::Apply { function, argument } => match Rc::unwrap_or_clone(function) {
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => Ok((
+                parameter_name,
+                Rc::unwrap_or_clone(body),
+                Rc::unwrap_or_clone(argument),
+            )),
+            function => Err(Term::Apply {
+                function: function.into(),
+                argument,
+            }),
+        },
+        term => Err(term),
+    }
+}
+
+fn apply_lambda(parameter_name: Name, body: Term<Name>, argument: Term<Name>) -> Term<Name> {
+    Term::Apply {
+        function: Term::Lambda {
+            parameter_name,
+            body: body.into(),
+        }
+        .into(),
+        argument: argument.into(),
+    }
+}
+
+/// Terms evaluating to themselves, without failing or tracing, with
+/// `bound` the variables in scope.
+fn is_value(term: &Term<Name>, bound: &[Name]) -> bool {
+    match term {
+        Term::Var(name) => bound.contains(name),
+        Term::Constant(_) | Term::Lambda { .. } | Term::Delay(_) | Term::Builtin(_) => true,
+        Term::Apply { .. } | Term::Force(_) | Term::Error => false,
+    }
+}
+
+/// How many times `name` appears free in `term`.
+fn occurrences(name: &Name, term: &Term<Name>) -> usize {
+    term.fold(|term| match term {
+        Folded::Var(var) => usize::from(var == name),
+        Folded::Lambda {
+            parameter_name,
+            body,
+        } => {
+            if parameter_name == name {
+                0
+            } else {
+                body
+            }
+        }
+        Folded::Delay(count) | Folded::Force(count) => count,
+        Folded::Apply { function, argument } => function + argument,
+        Folded::Constant(_) | Folded::Error | Folded::Builtin(_) => 0,
+    })
+}
+
+fn free_variables(term: &Term<Name>) -> Vec<&Name> {
+    #[derive(Default)]
+    struct FreeVariables<'a> {
+        bound: Vec<&'a Name>,
+        free: Vec<&'a Name>,
+    }
+
+    impl<'a> Visitor<'a, Name> for FreeVariables<'a> {
+        fn enter(&mut self, term: &'a Term<Name>) -> bool {
+            match term {
+                Term::Var(name) if !self.bound.contains(&name) && !self.free.contains(&name) => {
+                    self.free.push(name)
+                }
+                Term::Lambda { parameter_name, .. } => self.bound.push(parameter_name),
+                _ => (),
+            }
+
+            true
+        }
+
+        fn leave(&mut self, term: &'a Term<Name>) {
+            if let Term::Lambda { .. } = term {
+                self.bound.pop();
+            }
+        }
+    }
+
+    let mut variables = FreeVariables::default();
+    term.traverse(&mut variables);
+    variables.free
+}
+
+/// `term` with `value` in place of the free occurrences of `name`,
+/// or `None` if a lambda would capture one of the `free` variables
+/// of `value`.
+fn substitute(
+    term: &Term<Name>,
+    name: &Name,
+    value: &Term<Name>,
+    free: &[&Name],
+) -> Option<Term<Name>> {
+    Some(match term {
+        Term::Var(var) if var == name => value.clone(),
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => {
+            if parameter_name == name {
+                return Some(term.clone());
+            }
+
+            if free.contains(&parameter_name) && occurrences(name, body) > 0 {
+                return None;
+            }
+
+            Term::Lambda {
+                parameter_name: parameter_name.clone(),
+                body: substitute(body, name, value, free)?.into(),
+            }
+        }
+        Term::Delay(inner) => Term::Delay(substitute(inner, name, value, free)?.into()),
+        Term::Force(inner) => Term::Force(substitute(inner, name, value, free)?.into()),
+        Term::Apply { function, argument } => Term::Apply {
+            function: substitute(function, name, value, free)?.into(),
+            argument: substitute(argument, name, value, free)?.into(),
+        },
+        Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => term.clone(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::Pass;
+    use crate::{
+        arbitrary,
+        ast::{NamedDeBruijn, PlutusVersion, Program, Term},
+        machine::Error,
+        parser,
+    };
+
+    fn optimize(src: &str, passes: &[Pass]) -> String {
+        parser::program(src)
+            .unwrap()
+            .optimize(passes, PlutusVersion::V2)
+            .to_pretty()
+    }
+
+    fn pretty(src: &str) -> String {
+        parser::program(src).unwrap().to_pretty()
+    }
+
+    #[test]
+    fn force_delay() {
+        assert_eq!(
+            optimize(
+                "(program 1.0.0 (force (delay (force (delay (con integer 1))))))",
+                &[Pass::ForceDelay]
+            ),
+            pretty("(program 1.0.0 (con integer 1))")
+        );
+    }
+
+    #[test]
+    fn inline_single_use() {
+        assert_eq!(
+            optimize(
+                "(program 1.0.0 (lam y [(lam x [x y]) (lam z z)]))",
+                &[Pass::InlineSingleUse]
+            ),
+            pretty("(program 1.0.0 (lam y y))")
+        );
+
+        // `y` would be captured by the inner lambda
+        let captured = "(program 1.0.0 (lam y [(lam x (lam y x)) y]))";
+        assert_eq!(
+            optimize(captured, &[Pass::InlineSingleUse]),
+            pretty(captured)
+        );
+
+        // the error must still happen
+        let failing = "(program 1.0.0 [(lam x (lam y x)) (error)])";
+        assert_eq!(optimize(failing, &[Pass::InlineSingleUse]), pretty(failing));
+
+        // so must looking up the free `y`
+        let open = "(program 1.0.0 [(lam x (lam z x)) y])";
+        assert_eq!(optimize(open, &[Pass::InlineSingleUse]), pretty(open));
+    }
+
+    #[test]
+    fn dead_parameters() {
+        assert_eq!(
+            optimize(
+                "(program 1.0.0 [(lam x (con integer 1)) (delay (error))])",
+                &[Pass::DeadParameters]
+            ),
+            pretty("(program 1.0.0 (con integer 1))")
+        );
+
+        let failing = "(program 1.0.0 [(lam x (con integer 1)) (error)])";
+        assert_eq!(optimize(failing, &[Pass::DeadParameters]), pretty(failing));
+
+        // looking up the free `y` fails
+        let open = "(program 1.0.0 [(lam x (con integer 1)) y])";
+        assert_eq!(optimize(open, &[Pass::DeadParameters]), pretty(open));
+        assert_eq!(optimize(open, &Pass::ALL), pretty(open));
+
+        assert_eq!(
+            optimize(
+                "(program 1.0.0 (lam y [(lam x (con integer 1)) y]))",
+                &[Pass::DeadParameters]
+            ),
+            pretty("(program 1.0.0 (lam y (con integer 1)))")
+        );
+    }
+
+    #[test]
+    fn constant_folding() {
+        assert_eq!(
+            optimize(
+                r#"(program 1.0.0
+                  [(builtin addInteger) (con integer 1) [(builtin multiplyInteger) (con integer 2) (con integer 3)]]
+                )"#,
+                &[Pass::ConstantFolding]
+            ),
+            pretty("(program 1.0.0 (con integer 7))")
+        );
+
+        let failing = "(program 1.0.0 [(builtin divideInteger) (con integer 1) (con integer 0)])";
+        assert_eq!(optimize(failing, &[Pass::ConstantFolding]), pretty(failing));
+
+        let traced = r#"(program 1.0.0 [(force (builtin trace)) (con string "hi") (con unit ())])"#;
+        assert_eq!(optimize(traced, &[Pass::ConstantFolding]), pretty(traced));
+
+        // `serialiseData` came with Plutus V2
+        let serialise = "(program 1.0.0 [(builtin serialiseData) (con data (I 1))])";
+        let program = parser::program(serialise).unwrap();
+
+        assert_eq!(
+            program
+                .clone()
+                .optimize(&[Pass::ConstantFolding], PlutusVersion::V1)
+                .to_pretty(),
+            pretty(serialise)
+        );
+        assert_eq!(
+            program
+                .optimize(&[Pass::ConstantFolding], PlutusVersion::V2)
+                .to_pretty(),
+            pretty("(program 1.0.0 (con bytestring #01))")
+        );
+    }
+
+    #[test]
+    fn pipeline() {
+        assert_eq!(
+            optimize(
+                r#"(program 1.0.0
+                  [(lam f [(lam unused (force [f (con integer 2)])) (lam a a)]) (lam n (delay [(builtin addInteger) n (con integer 1)]))]
+                )"#,
+                &Pass::ALL
+            ),
+            pretty("(program 1.0.0 (con integer 3))")
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn preserves_evaluation(program in arbitrary::program()) {
+            let optimized = program.clone().optimize(&Pass::ALL, PlutusVersion::V2);
+
+            let program: Program<NamedDeBruijn> = program.try_into().unwrap();
+            let optimized: Program<NamedDeBruijn> = optimized.try_into().unwrap();
+
+            match (program.eval(), optimized.eval()) {
+                (Ok(Term::Constant(expected)), result) => {
+                    prop_assert_eq!(result.unwrap(), Term::Constant(expected))
+                }
+                (Err(Error::OutOfExError(_)), _) | (Ok(_), _) => (),
+                (Err(_), result) => prop_assert!(result.is_err()),
+            }
+        }
+    }
+}
@@ -1,115 +1,308 @@
-use std::str::FromStr;
+use std::{cell::RefCell, rc::Rc, str::FromStr};
+
+use num_bigint::BigInt;
 
 use crate::{
-    ast::{Constant, Name, Program, Term},
+    ast::{Constant, Name, Program, Term, Type},
     builtins::DefaultFunction,
+    data::PlutusData,
+    limits::Limits,
+    typed,
 };
 
-use interner::Interner;
-use peg::{error::ParseError, str::LineCol};
+mod error;
+pub mod interner;
+mod spans;
+
+pub use error::Error;
+pub use interner::Interner;
+pub use spans::{Comment, Spans};
+
+use spans::Collected;
+
+/// A constant's value before it has been checked against it's type.
+enum Literal {
+    Integer(BigInt),
+    ByteString(Vec<u8>),
+    String(String),
+    Bool(bool),
+    Unit,
+    List(Vec<Literal>),
+    Pair(Box<Literal>, Box<Literal>),
+    Data(PlutusData),
+}
+
+fn typed_literal(ty: &Type, literal: Literal) -> Result<Constant, &'static str> {
+    match (ty, literal) {
+        (Type::Integer, Literal::Integer(i)) => Ok(Constant::Integer(i)),
+        (Type::ByteString, Literal::ByteString(bytes)) => Ok(Constant::ByteString(bytes)),
+        (Type::String, Literal::String(s)) => Ok(Constant::String(s)),
+        (Type::Bool, Literal::Bool(b)) => Ok(Constant::Bool(b)),
+        (Type::Unit, Literal::Unit) => Ok(Constant::Unit),
+        (Type::Data, Literal::Data(data)) => Ok(Constant::Data(data)),
+        (Type::List(elem), Literal::List(items)) => {
+            let items = items
+                .into_iter()
+                .map(|item| typed_literal(elem, item))
+                .collect::<Result<_, _>>()?;
+
+            Ok(Constant::ProtoList(elem.as_ref().clone(), items))
+        }
+        (Type::Pair(l_ty, r_ty), Literal::Pair(l, r)) => Ok(Constant::ProtoPair(
+            l_ty.as_ref().clone(),
+            r_ty.as_ref().clone(),
+            Box::new(typed_literal(l_ty, *l)?),
+            Box::new(typed_literal(r_ty, *r)?),
+        )),
+        _ => Err("a value matching the constant's type"),
+    }
+}
 
-mod interner;
+/// Parse a `Program` from a str, within the default `Limits`.
+pub fn program(src: &str) -> Result<Program<Name>, Error> {
+    program_with_limits(src, Limits::default())
+}
+
+/// Parse a `Program` from a str, failing instead of overflowing the
+/// stack on terms nested deeper than `limits` allow.
+pub fn program_with_limits(src: &str, limits: Limits) -> Result<Program<Name>, Error> {
+    parse(src, limits, &mut Interner::new()).map(|(program, _)| program)
+}
+
+/// Parse a `Program` from a str, also returning where it's parts
+/// and comments are in the source.
+pub fn program_with_spans(src: &str) -> Result<(Program<Name>, Spans), Error> {
+    parse(src, Limits::default(), &mut Interner::new())
+}
+
+/// Parse a `Program` from a str, taking the uniques of it's names from
+/// `interner`. Programs parsed with the same interner can be combined,
+/// names with the same text are the same name in all of them.
+pub fn program_with_interner(src: &str, interner: &mut Interner) -> Result<Program<Name>, Error> {
+    parse(src, Limits::default(), interner).map(|(program, _)| program)
+}
+
+fn parse(
+    src: &str,
+    limits: Limits,
+    interner: &mut Interner,
+) -> Result<(Program<Name>, Spans), Error> {
+    if src.len() > limits.max_size {
+        return Err(Error::too_large(src, limits.max_size));
+    }
 
-/// Parse a `Program` from a str.
-pub fn program(src: &str) -> Result<Program<Name>, ParseError<LineCol>> {
-    // initialize the string interner to get unique name
-    let mut interner = Interner::new();
+    let spans = RefCell::new(Collected::default());
 
     // run the generated parser
-    let mut program = uplc::program(src)?;
+    let mut program =
+        uplc::program(src, limits.max_depth, &spans).map_err(|error| Error::new(src, error))?;
 
     // assign proper unique ids in place
     interner.program(&mut program);
 
-    Ok(program)
+    Ok((program, spans.into_inner().finish()))
+}
+
+/// Parse a single `Term` from a str, as written inside a program.
+pub fn term(src: &str) -> Result<Term<Name>, Error> {
+    let spans = RefCell::new(Collected::default());
+
+    let mut term = uplc::standalone_term(src, Limits::MAX_DEPTH, &spans)
+        .map_err(|error| Error::new(src, error))?;
+
+    Interner::new().term(&mut term);
+
+    Ok(term)
+}
+
+/// Parse a Typed Plutus Core `Program` from a str.
+/// Use `typed::Program::erase` to get an untyped `Program<Name>`.
+pub fn typed_program(src: &str) -> Result<typed::Program, Error> {
+    let spans = RefCell::new(Collected::default());
+
+    uplc::typed_program(src, Limits::MAX_DEPTH, &spans).map_err(|error| Error::new(src, error))
 }
 
 peg::parser! {
-    grammar uplc() for str {
+    grammar uplc(max_depth: usize, spans: &RefCell<Collected>) for str {
         pub rule program() -> Program<Name>
-          = _* "(" _* "program" _+ v:version() _+ t:term() _* ")" _* {
+          = _* start:position!() "(" _* "program" _+ v:version() _+ t:term(1) _* ")" end:position!() _* {
+            spans.borrow_mut().program(start, end);
+
             Program {version: v, term: t}
           }
 
+        pub rule standalone_term() -> Term<Name>
+          = _* t:term(1) _* { t }
+
         rule version() -> (usize, usize, usize)
           = major:number() "." minor:number() "." patch:number()  {
             (major as usize, minor as usize, patch as usize)
           }
 
-        rule term() -> Term<Name>
-          = constant()
-          / builtin()
-          / var()
-          / lambda()
-          / apply()
-          / delay()
-          / force()
-          / error()
+        // fails once `depth` exceeds the maximum, keeping the
+        // recursive descent from overflowing the stack
+        rule nested(depth: usize)
+          = {? if depth <= max_depth { Ok(()) } else { Err("less deeply nested terms") } }
 
-        rule constant() -> Term<Name>
-          = "(" _* "con" _+ con:(
-            constant_integer()
-            / constant_bytestring()
-            / constant_string()
-            / constant_unit()
-            / constant_bool()
-            ) _* ")" {
-            Term::Constant(con)
+        rule term(depth: usize) -> Term<Name>
+          = nested(depth) start:position!() t:(
+              constant(depth)
+              / builtin()
+              / var()
+              / lambda(depth)
+              / apply(depth)
+              / delay(depth)
+              / force(depth)
+              / error()
+            ) end:position!() {
+            spans.borrow_mut().term(start, end);
+
+            t
           }
 
-        rule builtin() -> Term<Name>
-          = "(" _* "builtin" _+ b:ident() _* ")" {
-            Term::Builtin(DefaultFunction::from_str(&b).unwrap())
+        rule constant(depth: usize) -> Term<Name>
+          = con:constant_value(depth) { Term::Constant(con) }
+
+        rule constant_value(depth: usize) -> Constant
+          = "(" _* "con" _+ ty:constant_type(depth) _+ l:literal(depth) _* ")" {?
+            typed_literal(&ty, l)
           }
 
-        rule var() -> Term<Name>
-          = n:name() { Term::Var(n) }
+        rule constant_type(depth: usize) -> Type
+          = nested(depth) t:constant_type_inner(depth) { t }
 
-        rule lambda() -> Term<Name>
-          = "(" _* "lam" _+ parameter_name:name() _+ t:term() _* ")" {
-            Term::Lambda { parameter_name, body: Box::new(t) }
+        rule constant_type_inner(depth: usize) -> Type
+          = "integer" { Type::Integer }
+          / "bytestring" { Type::ByteString }
+          / "string" { Type::String }
+          / "unit" { Type::Unit }
+          / "bool" { Type::Bool }
+          / "data" { Type::Data }
+          / "(" _* "list" _+ t:constant_type(depth + 1) _* ")" { Type::List(Box::new(t)) }
+          / "(" _* "pair" _+ l:constant_type(depth + 1) _+ r:constant_type(depth + 1) _* ")" {
+            Type::Pair(Box::new(l), Box::new(r))
           }
 
-        #[cache_left_rec]
-        rule apply() -> Term<Name>
-          = "[" _* initial:term() _+ terms:(t:term() _* { t })+ "]" {
-            terms
-                .into_iter()
-                .fold(initial, |lhs, rhs| Term::Apply {
-                    function: Box::new(lhs),
-                    argument: Box::new(rhs)
-                })
+        rule literal(depth: usize) -> Literal
+          = nested(depth) l:literal_inner(depth) { l }
+
+        rule literal_inner(depth: usize) -> Literal
+          = i:big_number() { Literal::Integer(i) }
+          / "#" h:$(['0'..='9' | 'a'..='f' | 'A'..='F']*) {?
+            hex::decode(h).map(Literal::ByteString).or(Err("hex"))
+          }
+          / "\"" s:string_char()* "\"" { Literal::String(String::from_iter(s)) }
+          / b:$("True" / "False") { Literal::Bool(b == "True") }
+          / "()" { Literal::Unit }
+          / "[" _* ls:(literal(depth + 1) ** (_* "," _*)) _* "]" { Literal::List(ls) }
+          / "(" _* d:data(depth + 1) _* ")" { Literal::Data(d) }
+          / "(" _* l:literal(depth + 1) _* "," _* r:literal(depth + 1) _* ")" {
+            Literal::Pair(Box::new(l), Box::new(r))
+          }
+
+        rule string_char() -> char
+          = "\\" c:['"' | '\\' | 'n' | 'r' | 't'] {
+            match c {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                c => c,
+            }
           }
+          / [^ '"' | '\\']
 
-        rule delay() -> Term<Name>
-          = "(" _* "delay" _+ t:term() _* ")" { Term::Delay(Box::new(t)) }
+        // the syntax Plutus writes data in, e.g. `Constr 0 [I 1, B #ab]`
+        rule data(depth: usize) -> PlutusData
+          = nested(depth) d:data_inner(depth) { d }
 
-        rule force() -> Term<Name>
-          = "(" _* "force" _+ t:term() _* ")" { Term::Force(Box::new(t)) }
+        rule data_inner(depth: usize) -> PlutusData
+          = "Constr" _+ tag:$(['0'..='9']+) _* fields:data_list(depth) {?
+            tag.parse()
+                .map(|tag| PlutusData::Constr { tag, fields })
+                .or(Err("constructor tag"))
+          }
+          / "Map" _* "[" _* entries:(data_entry(depth) ** (_* "," _*)) _* "]" {
+            PlutusData::Map(entries)
+          }
+          / "List" _* items:data_list(depth) { PlutusData::List(items) }
+          / "I" _+ i:big_number() { PlutusData::Integer(i) }
+          / "B" _+ "#" h:$(['0'..='9' | 'a'..='f' | 'A'..='F']*) {?
+            hex::decode(h).map(PlutusData::ByteString).or(Err("hex"))
+          }
 
-        rule error() -> Term<Name>
-          = "(" _* "error" _* ")" { Term::Error }
+        rule data_list(depth: usize) -> Vec<PlutusData>
+          = "[" _* items:(data_item(depth + 1) ** (_* "," _*)) _* "]" { items }
+
+        rule data_entry(depth: usize) -> (PlutusData, PlutusData)
+          = "(" _* k:data_item(depth + 1) _* "," _* v:data_item(depth + 1) _* ")" { (k, v) }
+
+        // nested data may be in parentheses or not
+        rule data_item(depth: usize) -> PlutusData
+          = "(" _* d:data(depth) _* ")" { d }
+          / data(depth)
+
+        rule builtin() -> Term<Name>
+          = b:builtin_name() { Term::Builtin(b) }
+
+        rule builtin_name() -> DefaultFunction
+          = "(" _* "builtin" _+ start:position!() b:ident() end:position!() _* ")" {?
+            let builtin = DefaultFunction::from_str(&b).or(Err("builtin name"))?;
+
+            spans.borrow_mut().builtin(start, end);
+
+            Ok(builtin)
+          }
+
+        rule var() -> Term<Name>
+          = start:position!() n:name() end:position!() {
+            spans.borrow_mut().var(start, end);
+
+            Term::Var(n)
+          }
+
+        rule lambda(depth: usize) -> Term<Name>
+          = "(" _* "lam" _+ start:position!() parameter_name:name() end:position!() _+ t:term(depth + 1) _* ")" {
+            spans.borrow_mut().binder(start, end);
 
-        rule constant_integer() -> Constant
-          = "integer" _+ i:number() { Constant::Integer(i as isize) }
+            Term::Lambda { parameter_name, body: Rc::new(t) }
+          }
+
+        // each argument nests the application one level deeper
+        rule apply(depth: usize) -> Term<Name>
+          = start:position!() "[" _* initial:term(depth + 1) _+ terms:(t:term(depth + 1) end:position!() _* { (t, end) })+ "]" {?
+            if depth + terms.len() > max_depth {
+                return Err("less deeply nested terms");
+            }
+
+            // the outermost application is the whole term
+            for (_, end) in &terms[..terms.len() - 1] {
+                spans.borrow_mut().term(start, *end);
+            }
 
-        rule constant_bytestring() -> Constant
-          = "bytestring" _+ "#" i:ident()* {
-            Constant::ByteString(hex::decode(String::from_iter(i)).unwrap())
+            Ok(terms
+                .into_iter()
+                .fold(initial, |lhs, (rhs, _)| Term::Apply {
+                    function: Rc::new(lhs),
+                    argument: Rc::new(rhs)
+                }))
           }
 
-        rule constant_string() -> Constant
-          = "string" _+ "\"" s:[^ '"']* "\"" { Constant::String(String::from_iter(s)) }
+        rule delay(depth: usize) -> Term<Name>
+          = "(" _* "delay" _+ t:term(depth + 1) _* ")" { Term::Delay(Rc::new(t)) }
 
-        rule constant_bool() -> Constant
-          = "bool" _+ b:$("True" / "False") { Constant::Bool(b == "True") }
+        rule force(depth: usize) -> Term<Name>
+          = "(" _* "force" _+ t:term(depth + 1) _* ")" { Term::Force(Rc::new(t)) }
 
-        rule constant_unit() -> Constant
-          = "unit" _+ "()" { Constant::Unit }
+        rule error() -> Term<Name>
+          = "(" _* "error" _* ")" { Term::Error }
 
         rule number() -> isize
           = n:$("-"* ['0'..='9']+) {? n.parse().or(Err("isize")) }
 
+        rule big_number() -> BigInt
+          = n:$("-"* ['0'..='9']+) {? n.parse().or(Err("integer")) }
+
         rule name() -> Name
           = text:ident() { Name { text, unique: 0.into() } }
 
@@ -118,13 +311,134 @@ peg::parser! {
             String::from_iter(i)
           }
 
-        rule _ = [' ' | '\n']
+        pub rule typed_program() -> typed::Program
+          = _* "(" _* "program" _+ v:version() _+ t:typed_term(1) _* ")" _* {
+            typed::Program {version: v, term: t}
+          }
+
+        rule typed_term(depth: usize) -> typed::Term
+          = nested(depth) t:(
+              con:constant_value(depth) { typed::Term::Constant(con) }
+              / b:builtin_name() { typed::Term::Builtin(b) }
+              / n:ident() { typed::Term::Var(n) }
+              / typed_lambda(depth)
+              / typed_apply(depth)
+              / type_abstraction(depth)
+              / type_instantiation(depth)
+              / iwrap(depth)
+              / unwrap(depth)
+              / typed_error(depth)
+            ) { t }
+
+        rule typed_lambda(depth: usize) -> typed::Term
+          = "(" _* "lam" _+ parameter_name:ident() _+ parameter_type:ty(depth + 1) _+ t:typed_term(depth + 1) _* ")" {
+            typed::Term::Lambda { parameter_name, parameter_type, body: Box::new(t) }
+          }
+
+        rule typed_apply(depth: usize) -> typed::Term
+          = "[" _* initial:typed_term(depth + 1) _+ terms:(t:typed_term(depth + 1) _* { t })+ "]" {?
+            if depth + terms.len() > max_depth {
+                Err("less deeply nested terms")
+            } else {
+                Ok(terms
+                    .into_iter()
+                    .fold(initial, |lhs, rhs| typed::Term::Apply {
+                        function: Box::new(lhs),
+                        argument: Box::new(rhs)
+                    }))
+            }
+          }
+
+        rule type_abstraction(depth: usize) -> typed::Term
+          = "(" _* "abs" _+ parameter_name:ident() _+ kind:kind(depth + 1) _+ t:typed_term(depth + 1) _* ")" {
+            typed::Term::TyAbs { parameter_name, kind, body: Box::new(t) }
+          }
+
+        rule type_instantiation(depth: usize) -> typed::Term
+          = "{" _* initial:typed_term(depth + 1) _+ types:(t:ty(depth + 1) _* { t })+ "}" {?
+            if depth + types.len() > max_depth {
+                Err("less deeply nested terms")
+            } else {
+                Ok(types
+                    .into_iter()
+                    .fold(initial, |term, ty| typed::Term::TyInst {
+                        term: Box::new(term),
+                        ty
+                    }))
+            }
+          }
+
+        rule iwrap(depth: usize) -> typed::Term
+          = "(" _* "iwrap" _+ pattern:ty(depth + 1) _+ argument:ty(depth + 1) _+ t:typed_term(depth + 1) _* ")" {
+            typed::Term::IWrap { pattern, argument, term: Box::new(t) }
+          }
+
+        rule unwrap(depth: usize) -> typed::Term
+          = "(" _* "unwrap" _+ t:typed_term(depth + 1) _* ")" { typed::Term::Unwrap(Box::new(t)) }
+
+        rule typed_error(depth: usize) -> typed::Term
+          = "(" _* "error" _+ ty:ty(depth + 1) _* ")" { typed::Term::Error(ty) }
+
+        rule ty(depth: usize) -> typed::Type
+          = nested(depth) t:ty_inner(depth) { t }
+
+        rule ty_inner(depth: usize) -> typed::Type
+          = "(" _* "con" _+ b:builtin_type(depth + 1) _* ")" { typed::Type::Builtin(b) }
+          / "(" _* "fun" _+ a:ty(depth + 1) _+ b:ty(depth + 1) _* ")" {
+            typed::Type::Fun(Box::new(a), Box::new(b))
+          }
+          / "(" _* "all" _+ parameter_name:ident() _+ kind:kind(depth + 1) _+ t:ty(depth + 1) _* ")" {
+            typed::Type::Forall { parameter_name, kind, body: Box::new(t) }
+          }
+          / "(" _* "ifix" _+ a:ty(depth + 1) _+ b:ty(depth + 1) _* ")" {
+            typed::Type::IFix(Box::new(a), Box::new(b))
+          }
+          / "(" _* "lam" _+ parameter_name:ident() _+ kind:kind(depth + 1) _+ t:ty(depth + 1) _* ")" {
+            typed::Type::Lambda { parameter_name, kind, body: Box::new(t) }
+          }
+          / "[" _* initial:ty(depth + 1) _+ types:(t:ty(depth + 1) _* { t })+ "]" {?
+            if depth + types.len() > max_depth {
+                Err("less deeply nested terms")
+            } else {
+                Ok(types
+                    .into_iter()
+                    .fold(initial, |lhs, rhs| typed::Type::Apply(Box::new(lhs), Box::new(rhs))))
+            }
+          }
+          / n:ident() { typed::Type::Var(n) }
+
+        rule builtin_type(depth: usize) -> String
+          = nested(depth) t:(
+              ident()
+              / "(" _* parts:(builtin_type(depth + 1) ++ (_+)) _* ")" {
+                format!("({})", parts.join(" "))
+              }
+            ) { t }
+
+        rule kind(depth: usize) -> typed::Kind
+          = nested(depth) k:(
+              "(" _* "type" _* ")" { typed::Kind::Type }
+              / "(" _* "fun" _+ a:kind(depth + 1) _+ b:kind(depth + 1) _* ")" {
+                typed::Kind::Arrow(Box::new(a), Box::new(b))
+              }
+            ) { k }
+
+        rule _ = [' ' | '\n' | '\t' | '\r'] / comment()
+
+        rule comment()
+          = start:position!() text:$("--" [^ '\n']* / "{-" (!"-}" [_])* "-}") {
+            spans.borrow_mut().comment(start, text)
+          }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::ast::{Constant, Name, Program, Term};
+    use crate::{
+        ast::{Constant, Name, Program, Term, Type},
+        data::PlutusData,
+        limits::Limits,
+    };
 
     #[test]
     fn parse_program() {
@@ -139,8 +453,162 @@ mod test {
             program,
             Program::<Name> {
                 version: (11, 22, 33),
-                term: Term::Constant(Constant::Integer(11)),
+                term: Term::Constant(Constant::Integer(11.into())),
             }
         );
     }
+
+    #[test]
+    fn parse_list_pair_and_data() {
+        let code = r#"
+        (program 1.0.0
+            (con (list (pair integer data)) [(1, (Constr 0 [])), (-2, (B #ab))])
+        )
+        "#;
+        let program = super::program(code).unwrap();
+
+        let pair = |i: i64, data: PlutusData| {
+            Constant::ProtoPair(
+                Type::Integer,
+                Type::Data,
+                Box::new(Constant::Integer(i.into())),
+                Box::new(Constant::Data(data)),
+            )
+        };
+
+        assert_eq!(
+            program.term,
+            Term::Constant(Constant::ProtoList(
+                Type::Pair(Box::new(Type::Integer), Box::new(Type::Data)),
+                vec![
+                    pair(
+                        1,
+                        PlutusData::Constr {
+                            tag: 0,
+                            fields: vec![]
+                        }
+                    ),
+                    pair(-2, PlutusData::ByteString(vec![0xab])),
+                ]
+            ))
+        );
+
+        assert!(super::program("(program 1.0.0 (con (list integer) [1, #ab]))").is_err());
+    }
+
+    #[test]
+    fn comments() {
+        let code =
+            "{- a\n block -}\n(program 1.0.0 -- the version\n\t[(lam x x) {--} (con integer 1)])";
+
+        let (program, spans) = super::program_with_spans(code).unwrap();
+
+        assert_eq!(
+            program,
+            super::program("(program 1.0.0 [(lam x x) (con integer 1)])").unwrap()
+        );
+
+        let comments: Vec<_> = spans.comments.iter().map(|c| c.text.as_str()).collect();
+
+        assert_eq!(comments, vec!["{- a\n block -}", "-- the version", "{--}"]);
+    }
+
+    #[test]
+    fn spans() {
+        let code = "(program 1.0.0 (lam x [(builtin addInteger) x (lam y y)]))";
+
+        let (_, spans) = super::program_with_spans(code).unwrap();
+
+        let text = |spans: &[miette::SourceSpan]| -> Vec<&str> {
+            spans
+                .iter()
+                .map(|span| &code[span.offset()..span.offset() + span.len()])
+                .collect()
+        };
+
+        assert_eq!(text(&spans.binders), vec!["x", "y"]);
+        assert_eq!(spans.binders[0], (20, 1).into());
+        assert_eq!(text(&spans.vars), vec!["x", "y"]);
+        assert_eq!(spans.vars[0], (44, 1).into());
+        assert_eq!(text(&spans.builtins), vec!["addInteger"]);
+        assert_eq!(
+            text(&spans.program.into_iter().collect::<Vec<_>>()),
+            vec![code]
+        );
+        assert_eq!(
+            text(&spans.terms),
+            vec![
+                "(lam x [(builtin addInteger) x (lam y y)])",
+                "[(builtin addInteger) x (lam y y)]",
+                "[(builtin addInteger) x",
+                "(builtin addInteger)",
+                "x",
+                "(lam y y)",
+                "y"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_builtin() {
+        let error = super::program("(program 1.0.0 (builtin addInt))").unwrap_err();
+
+        assert_eq!(error.expected, vec!["builtin name"]);
+    }
+
+    #[test]
+    fn parse_term() {
+        let term = super::term("  [(lam x x) (con unit ())]\n").unwrap();
+
+        assert_eq!(
+            term,
+            super::program("(program 1.0.0 [(lam x x) (con unit ())])")
+                .unwrap()
+                .term
+        );
+
+        let error = super::term("(program 1.0.0 (con unit ()))").unwrap_err();
+
+        assert_eq!(error.span, (1, 1).into());
+    }
+
+    #[test]
+    fn limits() {
+        let limits = Limits {
+            max_depth: 3,
+            max_size: 100,
+        };
+
+        let parse = |code: &str| super::program_with_limits(code, limits);
+
+        assert!(parse("(program 1.0.0 (lam x (delay x)))").is_ok());
+        assert!(parse("(program 1.0.0 (lam x (delay (force x))))").is_err());
+
+        // arguments count towards the depth of an application
+        assert!(parse("(program 1.0.0 [x x x])").is_ok());
+        assert!(parse("(program 1.0.0 [x x x x])").is_err());
+
+        assert!(parse("(program 1.0.0 (con (list (list integer)) [[1]]))").is_ok());
+        assert!(parse("(program 1.0.0 (con (list (list (list integer))) []))").is_err());
+
+        let error = parse(&format!(
+            "(program 1.0.0\n(con string \"{}\"))",
+            "a".repeat(100)
+        ))
+        .unwrap_err();
+
+        assert_eq!(error.expected, vec![String::from("at most 100 bytes")]);
+        assert_eq!(error.span, (100, 1).into());
+        assert_eq!((error.line, error.column), (2, 86));
+    }
+
+    #[test]
+    fn error_location() {
+        let error = super::program("(program 1.0.0\n  (lam x ])").unwrap_err();
+
+        assert_eq!((error.line, error.column), (2, 10));
+        assert_eq!(error.found, "']'");
+        assert_eq!(error.span, (24, 1).into());
+        assert!(error.expected.contains(&String::from("\"(\"")));
+    }
 }
@@ -0,0 +1,81 @@
+use miette::{Diagnostic, SourceSpan};
+use peg::{error::ParseError, str::LineCol};
+use thiserror::Error;
+
+/// A parse error pointing at where in the source things went wrong.
+/// It renders as a diagnostic with a snippet of the source when
+/// reported through `miette`.
+#[derive(Error, Debug, Diagnostic, PartialEq)]
+#[error("Unexpected {found} at line {line}, column {column}")]
+#[diagnostic(code(uplc::parser))]
+pub struct Error {
+    #[source_code]
+    pub src: String,
+    #[label("expected {}", expected_message(.expected))]
+    pub span: SourceSpan,
+    pub line: usize,
+    pub column: usize,
+    /// What the parser would have accepted instead.
+    pub expected: Vec<String>,
+    pub found: String,
+}
+
+impl Error {
+    pub(super) fn new(src: &str, error: ParseError<LineCol>) -> Self {
+        let LineCol {
+            line,
+            column,
+            offset,
+        } = error.location;
+
+        let found_char = src[offset..].chars().next();
+
+        let mut expected: Vec<String> = error.expected.tokens().map(String::from).collect();
+
+        expected.sort();
+
+        Error {
+            src: src.to_string(),
+            span: (offset, found_char.map(char::len_utf8).unwrap_or(0)).into(),
+            line,
+            column,
+            expected,
+            found: match found_char {
+                Some(c) => format!("{:?}", c),
+                None => String::from("end of input"),
+            },
+        }
+    }
+
+    /// An error pointing at the first character past `max_size`.
+    pub(super) fn too_large(src: &str, max_size: usize) -> Self {
+        let offset = (max_size..src.len())
+            .find(|offset| src.is_char_boundary(*offset))
+            .unwrap_or(src.len());
+
+        let before = &src[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        let found_char = src[offset..].chars().next();
+
+        Error {
+            src: src.to_string(),
+            span: (offset, found_char.map(char::len_utf8).unwrap_or(0)).into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: vec![format!("at most {} bytes", max_size)],
+            found: match found_char {
+                Some(c) => format!("{:?}", c),
+                None => String::from("end of input"),
+            },
+        }
+    }
+}
+
+fn expected_message(expected: &[String]) -> String {
+    match expected {
+        [] => String::from("something else"),
+        [token] => token.clone(),
+        [init @ .., last] => format!("{} or {}", init.join(", "), last),
+    }
+}
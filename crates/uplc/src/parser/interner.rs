@@ -1,7 +1,11 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 use crate::ast::{Name, Program, Term, Unique};
 
+/// Assigns the unique ids of names. Programs parsed or renamed with
+/// the same interner draw from the same supply of uniques, so their
+/// terms can be combined without their names colliding.
+#[derive(Debug, Clone)]
 pub struct Interner {
     identifiers: HashMap<String, Unique>,
     current: Unique,
@@ -19,25 +23,81 @@ impl Interner {
         self.term(&mut program.term);
     }
 
+    /// Intern names in the order they are written, with a stack
+    /// rather than recursion so deep terms can't overflow the stack.
     pub fn term(&mut self, term: &mut Term<Name>) {
-        match term {
-            Term::Var(name) => name.unique = self.intern(&name.text),
-            Term::Delay(term) => self.term(term),
-            Term::Lambda {
-                parameter_name,
-                body,
-            } => {
-                parameter_name.unique = self.intern(&parameter_name.text);
-                self.term(body);
+        let mut stack = vec![term];
+
+        while let Some(term) = stack.pop() {
+            match term {
+                Term::Var(name) => name.unique = self.intern(&name.text),
+                Term::Delay(term) => stack.push(Rc::make_mut(term)),
+                Term::Lambda {
+                    parameter_name,
+                    body,
+                } => {
+                    parameter_name.unique = self.intern(&parameter_name.text);
+                    stack.push(Rc::make_mut(body));
+                }
+                Term::Apply { function, argument } => {
+                    stack.push(Rc::make_mut(argument));
+                    stack.push(Rc::make_mut(function));
+                }
+                Term::Constant(_) => (),
+                Term::Force(term) => stack.push(Rc::make_mut(term)),
+                Term::Error => (),
+                Term::Builtin(_) => (),
             }
-            Term::Apply { function, argument } => {
-                self.term(function);
-                self.term(argument);
+        }
+    }
+
+    /// Alpha rename a term interned elsewhere, e.g. by another parse,
+    /// into this interner. Every lambda gets a unique no other name has,
+    /// free variables are interned by their text so they refer to the
+    /// same names as in terms interned here.
+    pub fn rename(&mut self, term: &mut Term<Name>) {
+        enum Step<'a> {
+            Enter(&'a mut Term<Name>),
+            Leave(Unique),
+        }
+
+        // the new uniques of the lambdas in scope, by their old unique
+        let mut scopes: HashMap<Unique, Vec<Unique>> = HashMap::new();
+        let mut stack = vec![Step::Enter(term)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(Term::Var(name)) => {
+                    name.unique = match scopes.get(&name.unique).and_then(|scope| scope.last()) {
+                        Some(unique) => *unique,
+                        None => self.intern(&name.text),
+                    };
+                }
+                Step::Enter(Term::Lambda {
+                    parameter_name,
+                    body,
+                }) => {
+                    let fresh = self.fresh();
+
+                    scopes.entry(parameter_name.unique).or_default().push(fresh);
+
+                    stack.push(Step::Leave(parameter_name.unique));
+                    stack.push(Step::Enter(Rc::make_mut(body)));
+
+                    parameter_name.unique = fresh;
+                }
+                Step::Enter(Term::Delay(term) | Term::Force(term)) => {
+                    stack.push(Step::Enter(Rc::make_mut(term)))
+                }
+                Step::Enter(Term::Apply { function, argument }) => {
+                    stack.push(Step::Enter(Rc::make_mut(argument)));
+                    stack.push(Step::Enter(Rc::make_mut(function)));
+                }
+                Step::Enter(Term::Constant(_) | Term::Error | Term::Builtin(_)) => (),
+                Step::Leave(unique) => {
+                    scopes.get_mut(&unique).and_then(Vec::pop);
+                }
             }
-            Term::Constant(_) => (),
-            Term::Force(term) => self.term(term),
-            Term::Error => (),
-            Term::Builtin(_) => (),
         }
     }
 
@@ -45,13 +105,87 @@ impl Interner {
         if let Some(u) = self.identifiers.get(text) {
             *u
         } else {
-            let unique = self.current;
+            let unique = self.fresh();
 
             self.identifiers.insert(text.to_string(), unique);
 
-            self.current.increment();
-
             unique
         }
     }
+
+    fn fresh(&mut self) -> Unique {
+        let unique = self.current;
+
+        self.current.increment();
+
+        unique
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{visit::Folded, Name, Term, Unique},
+        parser,
+    };
+
+    use super::Interner;
+
+    /// The uniques of the names in `term`, in the order they are written.
+    fn uniques(term: &Term<Name>) -> Vec<isize> {
+        term.fold(|term| match term {
+            Folded::Var(name) => vec![name.unique.into()],
+            Folded::Lambda {
+                parameter_name,
+                body,
+            } => [vec![parameter_name.unique.into()], body].concat(),
+            Folded::Apply { function, argument } => [function, argument].concat(),
+            Folded::Delay(uniques) | Folded::Force(uniques) => uniques,
+            Folded::Constant(_) | Folded::Error | Folded::Builtin(_) => vec![],
+        })
+    }
+
+    #[test]
+    fn shared_between_parses() {
+        let mut interner = Interner::new();
+
+        let first = parser::program_with_interner("(program 1.0.0 (lam x x))", &mut interner);
+        let second = parser::program_with_interner("(program 1.0.0 (lam y x))", &mut interner);
+
+        // `x` is the same name in both, `y` got a unique of it's own
+        assert_eq!(uniques(&first.unwrap().term), vec![0, 0]);
+        assert_eq!(uniques(&second.unwrap().term), vec![1, 0]);
+        assert_eq!(interner.fresh(), Unique::new(2));
+    }
+
+    #[test]
+    fn rename() {
+        let mut interner = Interner::new();
+
+        let function =
+            parser::program_with_interner("(program 1.0.0 (lam y [x y]))", &mut interner);
+
+        // interned on it's own, `x` gets the unique `y` has above
+        let mut argument = parser::program("(program 1.0.0 (lam x [(lam x x) x z]))").unwrap();
+
+        assert_eq!(uniques(&argument.term), vec![0, 0, 0, 0, 1]);
+
+        interner.rename(&mut argument.term);
+
+        // fresh uniques for the lambdas, even shadowed ones, and the
+        // free `z` interned by it's text
+        assert_eq!(uniques(&argument.term), vec![2, 3, 3, 2, 4]);
+        assert_eq!(
+            argument.to_pretty(),
+            "(program 1.0.0 (lam x [ (lam x x) x z ]))"
+        );
+
+        assert_eq!(uniques(&function.unwrap().term), vec![0, 1, 0]);
+    }
 }
@@ -0,0 +1,88 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use miette::SourceSpan;
+
+/// Where the parts of a program are in the source it was parsed from,
+/// which terms don't keep. Each list is in the order the parts appear
+/// in, which is the order a pre-order walk of the term reaches them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Spans {
+    /// The whole program, from it's opening parenthesis.
+    pub program: Option<SourceSpan>,
+    /// Every term. The applications an application to several
+    /// arguments stands for each span up to their last argument.
+    pub terms: Vec<SourceSpan>,
+    /// The names of lambda parameters.
+    pub binders: Vec<SourceSpan>,
+    /// The names of variables.
+    pub vars: Vec<SourceSpan>,
+    /// The names of builtins, without the `(builtin ...)` around them.
+    pub builtins: Vec<SourceSpan>,
+    pub comments: Vec<Comment>,
+}
+
+/// A `-- line` or `{- block -}` comment, as written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub span: SourceSpan,
+    pub text: String,
+}
+
+/// Spans recorded while parsing. The parser may go over the same
+/// source more than once while trying alternatives, so they are keyed
+/// by where they start.
+#[derive(Debug, Default)]
+pub(super) struct Collected {
+    program: Option<SourceSpan>,
+    // terms that start together are ordered outermost first, the way
+    // a pre-order walk reaches them
+    terms: BTreeMap<(usize, Reverse<usize>), SourceSpan>,
+    binders: BTreeMap<usize, SourceSpan>,
+    vars: BTreeMap<usize, SourceSpan>,
+    builtins: BTreeMap<usize, SourceSpan>,
+    comments: BTreeMap<usize, Comment>,
+}
+
+impl Collected {
+    pub(super) fn program(&mut self, start: usize, end: usize) {
+        self.program = Some((start, end - start).into());
+    }
+
+    pub(super) fn term(&mut self, start: usize, end: usize) {
+        self.terms
+            .insert((start, Reverse(end)), (start, end - start).into());
+    }
+
+    pub(super) fn binder(&mut self, start: usize, end: usize) {
+        self.binders.insert(start, (start, end - start).into());
+    }
+
+    pub(super) fn var(&mut self, start: usize, end: usize) {
+        self.vars.insert(start, (start, end - start).into());
+    }
+
+    pub(super) fn builtin(&mut self, start: usize, end: usize) {
+        self.builtins.insert(start, (start, end - start).into());
+    }
+
+    pub(super) fn comment(&mut self, start: usize, text: &str) {
+        self.comments.insert(
+            start,
+            Comment {
+                span: (start, text.len()).into(),
+                text: text.to_string(),
+            },
+        );
+    }
+
+    pub(super) fn finish(self) -> Spans {
+        Spans {
+            program: self.program,
+            terms: self.terms.into_values().collect(),
+            binders: self.binders.into_values().collect(),
+            vars: self.vars.into_values().collect(),
+            builtins: self.builtins.into_values().collect(),
+            comments: self.comments.into_values().collect(),
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use pretty::RcDoc;
+
+use crate::{
+    annotations::Placement,
+    ast::{Constant, Program, Term, Type},
+    data::PlutusData,
+    flat::Binder,
+};
+
+/// The width pretty printed programs try to fit in.
+const WIDTH: usize = 80;
+
+impl<'a, T> Program<T>
+where
+    T: Binder<'a>,
+{
+    /// Render the program in the textual UPLC syntax.
+    pub fn to_pretty(&self) -> String {
+        self.to_pretty_with_width(WIDTH)
+    }
+
+    /// Like `to_pretty` but trying to fit lines in `width` columns.
+    pub fn to_pretty_with_width(&self, width: usize) -> String {
+        render(self.to_doc(&mut Comments::default()), width)
+    }
+
+    /// Like `to_pretty` but with `comments` put back where they were.
+    pub(crate) fn to_pretty_with_comments<'c>(
+        &'c self,
+        comments: impl IntoIterator<Item = (Placement, &'c str)>,
+    ) -> String {
+        let mut leading = Vec::new();
+        let mut trailing = Vec::new();
+        let mut before = Comments::default();
+
+        for (placement, text) in comments {
+            match placement {
+                Placement::Leading => leading.push(text),
+                Placement::Before(index) => before.by_term.entry(index).or_default().push(text),
+                Placement::Trailing => trailing.push(text),
+            }
+        }
+
+        let doc = with_comments(leading, self.to_doc(&mut before));
+
+        let doc = trailing.into_iter().fold(doc, |doc, text| {
+            doc.append(RcDoc::hardline()).append(RcDoc::text(text))
+        });
+
+        render(doc, WIDTH)
+    }
+
+    fn to_doc<'c>(&'c self, comments: &mut Comments<'c>) -> RcDoc<'c, ()> {
+        let (major, minor, patch) = self.version;
+
+        RcDoc::text(format!("(program {}.{}.{}", major, minor, patch))
+            .append(RcDoc::line())
+            .append(self.term.to_doc(comments))
+            .nest(2)
+            .append(RcDoc::line_())
+            .append(RcDoc::text(")"))
+            .group()
+    }
+}
+
+impl<'a, T> Display for Program<T>
+where
+    T: Binder<'a>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_pretty())
+    }
+}
+
+/// Comments to print before terms, by the position of the term in a
+/// pre-order walk, which is the order terms are printed in.
+#[derive(Default)]
+struct Comments<'c> {
+    by_term: BTreeMap<usize, Vec<&'c str>>,
+    next: usize,
+}
+
+impl<'c> Comments<'c> {
+    /// The comments before the next term.
+    fn next(&mut self) -> Vec<&'c str> {
+        let comments = self.by_term.remove(&self.next).unwrap_or_default();
+
+        self.next += 1;
+
+        comments
+    }
+}
+
+fn render(doc: RcDoc<'_, ()>, width: usize) -> String {
+    let mut w = Vec::new();
+
+    doc.render(width, &mut w)
+        .expect("writing to a vec can't fail");
+
+    String::from_utf8(w).expect("pretty printed programs are valid utf8")
+}
+
+impl<'a, T> Term<T>
+where
+    T: Binder<'a>,
+{
+    /// Render the term in the textual UPLC syntax.
+    pub fn to_pretty(&self) -> String {
+        render(self.to_doc(&mut Comments::default()), WIDTH)
+    }
+
+    fn to_doc<'c>(&'c self, comments: &mut Comments<'c>) -> RcDoc<'c, ()> {
+        let before = comments.next();
+
+        let doc = match self {
+            Term::Var(name) => RcDoc::text(name.text()),
+            Term::Delay(term) => keyword_block("delay", term.to_doc(comments)),
+            Term::Lambda {
+                parameter_name,
+                body,
+            } => RcDoc::text("(")
+                .append(RcDoc::text("lam"))
+                .append(RcDoc::line())
+                .append(RcDoc::text(parameter_name.text()))
+                .group()
+                .append(RcDoc::line())
+                .append(body.to_doc(comments))
+                .nest(2)
+                .append(RcDoc::line_())
+                .append(RcDoc::text(")"))
+                .group(),
+            Term::Apply { .. } => {
+                // print nested applications as a single `[ f x y ]`,
+                // the comments of the inner ones go before it too
+                let mut inner_comments = Vec::new();
+                let mut arguments = Vec::new();
+                let mut function = self;
+
+                while let Term::Apply {
+                    function: inner,
+                    argument,
+                } = function
+                {
+                    arguments.push(argument.as_ref());
+                    function = inner;
+                }
+
+                for _ in 1..arguments.len() {
+                    inner_comments.extend(comments.next());
+                }
+
+                let mut docs = vec![function.to_doc(comments)];
+
+                docs.extend(
+                    arguments
+                        .into_iter()
+                        .rev()
+                        .map(|argument| argument.to_doc(comments)),
+                );
+
+                with_comments(
+                    inner_comments,
+                    RcDoc::text("[")
+                        .append(RcDoc::line())
+                        .append(RcDoc::intersperse(docs, RcDoc::line()))
+                        .nest(2)
+                        .append(RcDoc::line())
+                        .append(RcDoc::text("]"))
+                        .group(),
+                )
+            }
+            Term::Constant(constant) => RcDoc::text("(")
+                .append(RcDoc::text("con"))
+                .append(RcDoc::line())
+                .append(RcDoc::text(constant.ty().to_string()))
+                .append(RcDoc::line())
+                .append(constant.to_doc())
+                .nest(2)
+                .append(RcDoc::line_())
+                .append(RcDoc::text(")"))
+                .group(),
+            Term::Force(term) => keyword_block("force", term.to_doc(comments)),
+            Term::Error => RcDoc::text("(error)"),
+            Term::Builtin(builtin) => RcDoc::text(format!("(builtin {})", builtin)),
+        };
+
+        with_comments(before, doc)
+    }
+}
+
+/// `doc` preceded by `comments`, each on it's own line.
+fn with_comments<'c>(comments: Vec<&'c str>, doc: RcDoc<'c, ()>) -> RcDoc<'c, ()> {
+    comments.into_iter().rev().fold(doc, |doc, text| {
+        RcDoc::text(text).append(RcDoc::hardline()).append(doc)
+    })
+}
+
+fn keyword_block<'a>(keyword: &'a str, body: RcDoc<'a, ()>) -> RcDoc<'a, ()> {
+    RcDoc::text("(")
+        .append(RcDoc::text(keyword))
+        .append(RcDoc::line())
+        .append(body)
+        .nest(2)
+        .append(RcDoc::line_())
+        .append(RcDoc::text(")"))
+        .group()
+}
+
+impl Constant {
+    /// The value of a constant, without it's type.
+    fn to_doc(&self) -> RcDoc<'_, ()> {
+        match self {
+            Constant::Integer(i) => RcDoc::as_string(i),
+            Constant::ByteString(bytes) => RcDoc::text(format!("#{}", hex::encode(bytes))),
+            Constant::String(s) => RcDoc::text(format!("\"{}\"", escape(s))),
+            Constant::Char(c) => RcDoc::text(format!("\"{}\"", escape(&c.to_string()))),
+            Constant::Unit => RcDoc::text("()"),
+            Constant::Bool(b) => RcDoc::text(if *b { "True" } else { "False" }),
+            Constant::ProtoList(_, items) => RcDoc::text("[")
+                .append(RcDoc::line())
+                .append(RcDoc::intersperse(
+                    items.iter().map(|item| item.to_doc()),
+                    RcDoc::text(",").append(RcDoc::line()),
+                ))
+                .nest(2)
+                .append(RcDoc::line())
+                .append(RcDoc::text("]"))
+                .group(),
+            Constant::ProtoPair(_, _, left, right) => RcDoc::text("(")
+                .append(left.to_doc())
+                .append(RcDoc::text(","))
+                .append(RcDoc::line())
+                .append(right.to_doc())
+                .append(RcDoc::text(")"))
+                .group(),
+            Constant::Data(data) => RcDoc::text("(")
+                .append(data_doc(data))
+                .append(RcDoc::text(")"))
+                .group(),
+        }
+    }
+}
+
+/// Quotes, backslashes and line breaks written the way the parser
+/// reads them back.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Data the way Plutus writes it, e.g. `Constr 0 [I 1, B #ab]`.
+fn data_doc(data: &PlutusData) -> RcDoc<'_, ()> {
+    match data {
+        PlutusData::Constr { tag, fields } => {
+            RcDoc::text(format!("Constr {tag} ")).append(data_list(fields.iter().map(data_doc)))
+        }
+        PlutusData::Map(entries) => {
+            RcDoc::text("Map ").append(data_list(entries.iter().map(|(key, value)| {
+                RcDoc::text("(")
+                    .append(data_doc(key))
+                    .append(RcDoc::text(","))
+                    .append(RcDoc::line())
+                    .append(data_doc(value))
+                    .append(RcDoc::text(")"))
+                    .group()
+            })))
+        }
+        PlutusData::List(items) => {
+            RcDoc::text("List ").append(data_list(items.iter().map(data_doc)))
+        }
+        PlutusData::Integer(i) => RcDoc::text("I ").append(RcDoc::as_string(i)),
+        PlutusData::ByteString(bytes) => RcDoc::text(format!("B #{}", hex::encode(bytes))),
+    }
+}
+
+fn data_list<'a>(items: impl Iterator<Item = RcDoc<'a, ()>>) -> RcDoc<'a, ()> {
+    RcDoc::text("[")
+        .append(RcDoc::line_())
+        .append(RcDoc::intersperse(
+            items,
+            RcDoc::text(",").append(RcDoc::line()),
+        ))
+        .nest(2)
+        .append(RcDoc::line_())
+        .append(RcDoc::text("]"))
+        .group()
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Bool => write!(f, "bool"),
+            Type::Integer => write!(f, "integer"),
+            Type::String => write!(f, "string"),
+            Type::ByteString => write!(f, "bytestring"),
+            Type::Unit => write!(f, "unit"),
+            Type::List(elem) => write!(f, "(list {})", elem),
+            Type::Pair(left, right) => write!(f, "(pair {} {})", left, right),
+            Type::Data => write!(f, "data"),
+            Type::Var(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{Constant, DeBruijn, Program, Term},
+        parser,
+    };
+
+    #[test]
+    fn pretty_program() {
+        let code = r#"
+        (program 1.0.0
+            [ (lam x (lam y [ (builtin addInteger) x y ])) (con integer 1)
+            (con (list (pair integer bytestring)) [ (1, #ab) ]) ]
+        )
+        "#;
+
+        let program = parser::program(code).unwrap();
+
+        assert_eq!(
+            program.to_pretty_with_width(40),
+            r#"(program 1.0.0
+  [
+    (lam x
+      (lam y
+        [ (builtin addInteger) x y ]
+      )
+    )
+    (con integer 1)
+    (con
+      (list (pair integer bytestring))
+      [ (1, #ab) ]
+    )
+  ]
+)"#
+        );
+    }
+
+    #[test]
+    fn pretty_data() {
+        let code = r#"
+        (program 1.0.0
+            (con (list data) [(Constr 0 [(I 1), Map [(B #ab, List [I -2])]]), (B #)])
+        )
+        "#;
+
+        let program = parser::program(code).unwrap();
+
+        let pretty = program.to_pretty();
+
+        assert_eq!(
+            pretty,
+            "(program 1.0.0\n  (con (list data) [ (Constr 0 [I 1, Map [(B #ab, List [I -2])]]), (B #) ])\n)"
+        );
+
+        assert_eq!(parser::program(&pretty).unwrap(), program);
+    }
+
+    #[test]
+    fn pretty_escaped_string() {
+        let code = r#"(program 1.0.0 (con string "say \"hi\"\n\\ \t"))"#;
+
+        let program = parser::program(code).unwrap();
+
+        assert_eq!(
+            program.term,
+            Term::Constant(Constant::String("say \"hi\"\n\\ \t".to_string()))
+        );
+
+        assert_eq!(program.to_pretty(), code);
+    }
+
+    #[test]
+    fn pretty_round_trip() {
+        let code = include_str!("../test_data/jpg/jpg.uplc");
+
+        let program = parser::program(code).unwrap();
+
+        assert_eq!(parser::program(&program.to_pretty()).unwrap(), program);
+
+        let debruijn_program: Program<DeBruijn> = program.try_into().unwrap();
+
+        assert!(debruijn_program.to_pretty().starts_with("(program 1.0.0"));
+    }
+}
@@ -1,6 +1,18 @@
 /// e2e encoding/decoding tests
+use num_bigint::BigInt;
+use proptest::prelude::*;
+
 use crate::{
-    ast::{DeBruijn, Program},
+    annotations::Annotations,
+    arbitrary,
+    ast::{Constant, DeBruijn, Name, NamedDeBruijn, PlutusVersion, Program, Term},
+    builtins::DefaultFunction,
+    data::PlutusData,
+    limits::Limits,
+    machine::{
+        self,
+        cost_model::{CostModels, ExBudget, TwoArguments},
+    },
     parser,
 };
 
@@ -39,3 +51,229 @@ fn jpg() {
 
     assert_eq!(encoded_program, bytes);
 }
+
+#[test]
+fn jpg_annotations() {
+    let code = include_str!("../test_data/jpg/jpg.uplc");
+
+    let parsed_program = parser::program(code).unwrap();
+
+    let annotations = Annotations::from_program(&parsed_program);
+
+    let debruijn_program: Program<DeBruijn> = parsed_program.clone().try_into().unwrap();
+
+    let bytes = debruijn_program.to_flat().unwrap();
+
+    let annotation_bytes = annotations.to_flat().unwrap();
+
+    let decoded_program: Program<DeBruijn> = Program::from_flat(&bytes).unwrap();
+
+    let decoded_annotations = Annotations::from_flat(&annotation_bytes).unwrap();
+
+    let restored_program = decoded_annotations.restore(decoded_program).unwrap();
+
+    assert_eq!(restored_program, parsed_program);
+}
+
+#[test]
+fn big_integer() {
+    let code = r#"
+    (program 1.0.0
+        [ (builtin addInteger) (con integer 170141183460469231731687303715884105728) (con integer -1) ]
+    )
+    "#;
+
+    let parsed_program = parser::program(code).unwrap();
+
+    let debruijn_program: Program<DeBruijn> = parsed_program.try_into().unwrap();
+
+    let bytes = debruijn_program.to_flat().unwrap();
+
+    let decoded_program: Program<DeBruijn> = Program::from_flat(&bytes).unwrap();
+
+    assert_eq!(debruijn_program, decoded_program);
+
+    let program: Program<NamedDeBruijn> = decoded_program.into();
+
+    let expected: BigInt = "170141183460469231731687303715884105727".parse().unwrap();
+
+    assert_eq!(
+        program.eval().unwrap(),
+        Term::Constant(Constant::Integer(expected))
+    );
+}
+
+#[test]
+fn apply_data() {
+    let code = r#"
+    (program 1.0.0
+        (lam x [ (builtin unIData) x ])
+    )
+    "#;
+
+    let program: Program<NamedDeBruijn> = parser::program(code).unwrap().try_into().unwrap();
+
+    let applied = program.apply_data(PlutusData::Integer(42.into()));
+
+    assert_eq!(
+        applied.eval().unwrap(),
+        Term::Constant(Constant::Integer(42.into()))
+    );
+}
+
+#[test]
+fn builtins_unavailable_in_v1() {
+    let code = r#"
+    (program 1.0.0
+        [ (builtin serialiseData) (con data (I 1)) ]
+    )
+    "#;
+
+    let program: Program<DeBruijn> = parser::program(code).unwrap().try_into().unwrap();
+
+    assert!(program.to_flat_with_version(PlutusVersion::V1).is_err());
+
+    let bytes = program.to_flat_with_version(PlutusVersion::V2).unwrap();
+
+    assert!(Program::<DeBruijn>::from_flat_with_version(&bytes, PlutusVersion::V1).is_err());
+
+    let program: Program<NamedDeBruijn> = program.into();
+
+    assert!(matches!(
+        program
+            .eval_with_version(PlutusVersion::V1, ExBudget::default())
+            .term,
+        Err(machine::Error::UnavailableBuiltin(
+            DefaultFunction::SerialiseData,
+            PlutusVersion::V1
+        ))
+    ));
+
+    assert!(program
+        .eval_with_version(PlutusVersion::V2, ExBudget::default())
+        .term
+        .is_ok());
+}
+
+#[test]
+fn priced_per_version() {
+    let program: Program<NamedDeBruijn> =
+        parser::program("(program 1.0.0 [ (lam x [ (builtin addInteger) x x ]) (con integer 1) ])")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+    let mut cost_models = CostModels::default();
+
+    cost_models.v1.machine_costs.apply = ExBudget::new(100, 29773);
+    cost_models.v1.builtin_costs.add_integer.cpu = TwoArguments::ConstantCost(197209);
+
+    let eval = |version| {
+        program
+            .eval_with_cost_models(version, &cost_models, ExBudget::default())
+            .budget
+    };
+
+    let v1 = eval(PlutusVersion::V1);
+    let v2 = eval(PlutusVersion::V2);
+
+    // three applications dearer and an addition cheaper in V1
+    assert_eq!(v1.mem, v2.mem);
+    assert_eq!(
+        v1.cpu - v2.cpu,
+        3 * (29773 - 23000) + 197209 - (205665 + 812)
+    );
+
+    assert_eq!(
+        program
+            .eval_with_version(PlutusVersion::V2, ExBudget::default())
+            .budget,
+        v2
+    );
+}
+
+/// A program nesting lambdas, forces, delays and applications about
+/// `depth` terms deep.
+fn deep_program(depth: usize) -> String {
+    let layers = (depth - 1) / 4;
+
+    format!(
+        "(program 1.0.0 {}(con integer 1){})",
+        "(lam x (force (delay [ ".repeat(layers),
+        " x ])))".repeat(layers)
+    )
+}
+
+#[test]
+fn depth_limits() {
+    // the main thread's stack size, tests get smaller ones
+    std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(check_depth_limits)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn check_depth_limits() {
+    // the default limit must leave room for everything done with a program
+    let program = parser::program(&deep_program(Limits::MAX_DEPTH)).unwrap();
+
+    assert_eq!(parser::program(&program.to_pretty()).unwrap(), program);
+
+    let debruijn: Program<DeBruijn> = program.try_into().unwrap();
+    let bytes = debruijn.to_flat().unwrap();
+
+    assert_eq!(Program::<DeBruijn>::from_flat(&bytes).unwrap(), debruijn);
+
+    let named_debruijn: Program<NamedDeBruijn> = debruijn.into();
+
+    assert!(named_debruijn.eval().is_ok());
+
+    // deep enough to overflow the stack
+    let error = parser::program(&deep_program(100_000)).unwrap_err();
+
+    assert!(error
+        .expected
+        .contains(&String::from("less deeply nested terms")));
+
+    // the version, then the tags of nested delays
+    let mut bytes = vec![1, 0, 0];
+    bytes.extend([0b0001_0001; 50_000]);
+
+    assert!(matches!(
+        Program::<DeBruijn>::from_flat(&bytes),
+        Err(flat::de::Error::MaxDepth(Limits::MAX_DEPTH))
+    ));
+}
+
+proptest! {
+    #[test]
+    fn flat_round_trip(program in arbitrary::debruijn_program()) {
+        let bytes = program.to_flat().unwrap();
+
+        prop_assert_eq!(Program::<DeBruijn>::from_flat(&bytes).unwrap(), program);
+    }
+
+    #[test]
+    fn pretty_round_trip(program in arbitrary::program()) {
+        prop_assert_eq!(parser::program(&program.to_pretty()).unwrap(), program);
+    }
+
+    #[test]
+    fn debruijn_round_trip(program in arbitrary::program()) {
+        let debruijn: Program<DeBruijn> = program.clone().try_into().unwrap();
+        let named_debruijn: Program<NamedDeBruijn> = program.try_into().unwrap();
+
+        prop_assert_eq!(Program::<DeBruijn>::from(named_debruijn.clone()), debruijn.clone());
+
+        let name: Program<Name> = named_debruijn.clone().try_into().unwrap();
+        prop_assert_eq!(Program::<NamedDeBruijn>::try_from(name).unwrap(), named_debruijn);
+
+        let name: Program<Name> = debruijn.clone().try_into().unwrap();
+        prop_assert_eq!(Program::<DeBruijn>::try_from(name).unwrap(), debruijn.clone());
+
+        let named_debruijn: Program<NamedDeBruijn> = debruijn.clone().into();
+        prop_assert_eq!(Program::<DeBruijn>::from(named_debruijn), debruijn);
+    }
+}
@@ -0,0 +1,875 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    ast::{DeBruijn, NamedDeBruijn, PlutusVersion, Program},
+    data::PlutusData,
+    machine::{
+        cost_model::{CostModels, ExBudget},
+        Machine,
+    },
+};
+
+mod error;
+mod script_context;
+mod transaction;
+
+pub use error::Error;
+pub use script_context::{
+    payment_credential, reward_credential, ResolvedTransaction, ScriptPurpose, SlotConfig,
+};
+pub use transaction::{
+    Certificate, Credential, DatumOption, MultiAsset, Redeemer, RedeemerTag, Script, Transaction,
+    TransactionBody, TransactionInput, TransactionOutput, Value, WitnessSet,
+};
+
+/// A redeemer with the execution units it's script actually consumed,
+/// and the messages the script traced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluatedRedeemer {
+    pub redeemer: Redeemer,
+    pub logs: Vec<String>,
+}
+
+/// Run every script of a transaction the way the ledger does during
+/// phase two validation, starting from CBOR encoded bytes. The resolved
+/// inputs are given as two CBOR arrays, the inputs and the outputs
+/// they point to.
+pub fn eval_phase_two_raw(
+    tx_bytes: &[u8],
+    inputs_bytes: &[u8],
+    outputs_bytes: &[u8],
+    cost_models: &CostModels,
+    max_tx_ex_units: ExBudget,
+    slot_config: &SlotConfig,
+) -> Result<Vec<EvaluatedRedeemer>, Error> {
+    let tx = Transaction::from_cbor(tx_bytes)?;
+
+    let inputs = TransactionInput::list_from_cbor(inputs_bytes)?;
+    let outputs = TransactionOutput::list_from_cbor(outputs_bytes)?;
+
+    if inputs.len() != outputs.len() {
+        return Err(Error::ResolvedInputsMismatch(inputs.len(), outputs.len()));
+    }
+
+    let utxos = inputs.into_iter().zip(outputs).collect();
+
+    eval_phase_two(&tx, &utxos, cost_models, max_tx_ex_units, slot_config)
+}
+
+/// Build the script context for each redeemer of `tx`, apply it to the
+/// script the redeemer points to and evaluate it. The redeemers are
+/// returned with their execution units set to what was actually consumed,
+/// along with what their scripts traced. A failing script's traces are
+/// in the error.
+///
+/// Scripts share `max_tx_ex_units` like they share the transaction's budget,
+/// each is priced with the cost model of the Plutus version it's written for.
+pub fn eval_phase_two(
+    tx: &Transaction,
+    utxos: &BTreeMap<TransactionInput, TransactionOutput>,
+    cost_models: &CostModels,
+    max_tx_ex_units: ExBudget,
+    slot_config: &SlotConfig,
+) -> Result<Vec<EvaluatedRedeemer>, Error> {
+    let resolved = ResolvedTransaction {
+        tx,
+        utxos,
+        slot_config,
+    };
+
+    let mut scripts = BTreeMap::new();
+
+    let witness_scripts = tx
+        .witness_set
+        .plutus_v1_scripts
+        .iter()
+        .map(|script| Script::PlutusV1(script.clone()))
+        .chain(
+            tx.witness_set
+                .plutus_v2_scripts
+                .iter()
+                .map(|script| Script::PlutusV2(script.clone())),
+        );
+
+    let reference_scripts = tx
+        .body
+        .inputs
+        .iter()
+        .chain(&tx.body.reference_inputs)
+        .filter_map(|input| utxos.get(input)?.script_ref.clone());
+
+    for script in witness_scripts.chain(reference_scripts) {
+        scripts.insert(script.hash(), script);
+    }
+
+    let mut remaining = max_tx_ex_units;
+
+    tx.witness_set
+        .redeemers
+        .iter()
+        .map(|redeemer| {
+            let purpose = resolved.script_purpose(redeemer)?;
+
+            let (script_hash, datum) = script_and_datum(&resolved, redeemer, &purpose)?;
+
+            let (version, script) = match scripts.get(&script_hash) {
+                Some(Script::PlutusV1(script)) => (PlutusVersion::V1, script),
+                Some(Script::PlutusV2(script)) => (PlutusVersion::V2, script),
+                _ => return Err(Error::MissingScript(hex::encode(&script_hash))),
+            };
+
+            let script_context = resolved.script_context(version, &purpose)?;
+
+            let arguments = datum
+                .into_iter()
+                .chain([redeemer.data.clone(), script_context]);
+
+            let program: Program<NamedDeBruijn> =
+                Program::<DeBruijn>::from_cbor_with_version(script, version)?.into();
+
+            let program = arguments.fold(program, |program, argument| program.apply_data(argument));
+
+            let cost_model = cost_models.get(version).clone();
+
+            let mut machine = Machine::new(version, cost_model, remaining, 200);
+
+            let result = machine.run(program.term);
+
+            let ex_units = remaining - machine.ex_budget;
+
+            remaining = machine.ex_budget;
+
+            if let Err(error) = result {
+                return Err(Error::Machine(
+                    redeemer.tag,
+                    redeemer.index,
+                    error,
+                    machine.logs,
+                ));
+            }
+
+            Ok(EvaluatedRedeemer {
+                redeemer: Redeemer {
+                    ex_units,
+                    ..redeemer.clone()
+                },
+                logs: machine.logs,
+            })
+        })
+        .collect()
+}
+
+/// The hash of the script a redeemer is for and, when spending,
+/// the datum of the output being spent.
+fn script_and_datum(
+    resolved: &ResolvedTransaction,
+    redeemer: &Redeemer,
+    purpose: &ScriptPurpose,
+) -> Result<(Vec<u8>, Option<PlutusData>), Error> {
+    let not_a_script = || Error::NotAScript(redeemer.tag, redeemer.index);
+
+    let script_hash = |credential: &Credential| match credential {
+        Credential::ScriptHash(hash) => Ok(hash.clone()),
+        Credential::KeyHash(_) => Err(not_a_script()),
+    };
+
+    match purpose {
+        ScriptPurpose::Spending(input) => {
+            let output = resolved.resolve(input)?;
+
+            let hash = script_hash(&payment_credential(&output.address)?)?;
+
+            let datum = match &output.datum {
+                Some(DatumOption::Data(data)) => data.clone(),
+                Some(DatumOption::Hash(hash)) => resolved
+                    .tx
+                    .witness_set
+                    .plutus_data
+                    .get(hash)
+                    .cloned()
+                    .ok_or(Error::MissingDatum(redeemer.tag, redeemer.index))?,
+                None => return Err(Error::MissingDatum(redeemer.tag, redeemer.index)),
+            };
+
+            Ok((hash, Some(datum)))
+        }
+        ScriptPurpose::Minting(policy_id) => Ok((policy_id.clone(), None)),
+        ScriptPurpose::Rewarding(credential) => Ok((script_hash(credential)?, None)),
+        ScriptPurpose::Certifying(
+            Certificate::StakeRegistration(credential)
+            | Certificate::StakeDeregistration(credential)
+            | Certificate::StakeDelegation(credential, _),
+        ) => Ok((script_hash(credential)?, None)),
+        ScriptPurpose::Certifying(_) => Err(not_a_script()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        ast::{Constant, DeBruijn, PlutusVersion, Program, Term},
+        data::PlutusData,
+        machine::cost_model::{CostModels, ExBudget},
+        parser,
+    };
+
+    use super::{
+        eval_phase_two, Certificate, Credential, DatumOption, Error, Redeemer, RedeemerTag,
+        ResolvedTransaction, Script, SlotConfig, Transaction, TransactionBody, TransactionInput,
+        TransactionOutput, Value, WitnessSet,
+    };
+
+    /// Spends an output locked by a script accepting redeemers equal to the datum.
+    fn spend(redeemer: PlutusData) -> Result<Vec<super::EvaluatedRedeemer>, Error> {
+        let program: Program<DeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                (lam d (lam r (lam ctx
+                    (force [
+                        (force (builtin ifThenElse))
+                        [ (builtin equalsData) d r ]
+                        (delay (con unit ()))
+                        (delay (error))
+                    ])
+                )))
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let script = program.to_cbor().unwrap();
+
+        let mut address = vec![0x71];
+        address.extend(Script::PlutusV2(script.clone()).hash());
+
+        let input = TransactionInput {
+            transaction_id: vec![0; 32],
+            index: 0,
+        };
+
+        let mut e = minicbor::Encoder::new(Vec::new());
+
+        e.array(4).unwrap();
+
+        e.map(3).unwrap();
+        e.u8(0).unwrap().array(1).unwrap();
+        e.array(2).unwrap().bytes(&[0; 32]).unwrap().u8(0).unwrap();
+        e.u8(1).unwrap().array(1).unwrap();
+        e.array(2)
+            .unwrap()
+            .bytes(&address)
+            .unwrap()
+            .u64(1_800_000)
+            .unwrap();
+        e.u8(2).unwrap().u64(200_000).unwrap();
+
+        e.map(2).unwrap();
+        e.u8(5).unwrap().array(1).unwrap();
+        e.array(4).unwrap().u8(0).unwrap().u8(0).unwrap();
+        e.encode(&redeemer).unwrap();
+        e.array(2).unwrap().u8(0).unwrap().u8(0).unwrap();
+        e.u8(6).unwrap().array(1).unwrap().bytes(&script).unwrap();
+
+        e.bool(true).unwrap().null().unwrap();
+
+        let tx = Transaction::from_cbor(e.writer()).unwrap();
+
+        let utxos = BTreeMap::from([(
+            input,
+            TransactionOutput {
+                address,
+                value: Value {
+                    coin: 2_000_000,
+                    ..Value::default()
+                },
+                datum: Some(DatumOption::Data(PlutusData::Integer(42.into()))),
+                script_ref: None,
+            },
+        )]);
+
+        eval_phase_two(
+            &tx,
+            &utxos,
+            &CostModels::default(),
+            ExBudget::default(),
+            &SlotConfig::default(),
+        )
+    }
+
+    #[test]
+    fn eval_spending_script() {
+        let redeemers = spend(PlutusData::Integer(42.into())).unwrap();
+
+        assert_eq!(redeemers.len(), 1);
+        assert_eq!(redeemers[0].redeemer.tag, RedeemerTag::Spend);
+        assert!(redeemers[0].redeemer.ex_units.mem > 0 && redeemers[0].redeemer.ex_units.cpu > 0);
+        assert!(redeemers[0].logs.is_empty());
+
+        assert!(matches!(
+            spend(PlutusData::Integer(0.into())),
+            Err(Error::Machine(RedeemerTag::Spend, 0, _, _))
+        ));
+    }
+
+    /// Data written the way the pretty printer writes it.
+    fn data(text: &str) -> PlutusData {
+        let program = parser::program(&format!("(program 1.0.0 (con data ({text})))")).unwrap();
+
+        match program.term {
+            Term::Constant(Constant::Data(data)) => data,
+            term => panic!("expected data, got {term:?}"),
+        }
+    }
+
+    fn input(byte: u8, index: u64) -> TransactionInput {
+        TransactionInput {
+            transaction_id: vec![byte; 32],
+            index,
+        }
+    }
+
+    fn output(address: Vec<u8>, coin: u64, datum: Option<DatumOption>) -> TransactionOutput {
+        TransactionOutput {
+            address,
+            value: Value {
+                coin,
+                ..Value::default()
+            },
+            datum,
+            script_ref: None,
+        }
+    }
+
+    /// A header byte followed by the hashes of an address.
+    fn address(header: u8, hashes: &[&[u8]]) -> Vec<u8> {
+        let mut address = vec![header];
+
+        for hash in hashes {
+            address.extend(*hash);
+        }
+
+        address
+    }
+
+    fn redeemer(tag: RedeemerTag, index: u64, data: PlutusData) -> Redeemer {
+        Redeemer {
+            tag,
+            index,
+            data,
+            ex_units: ExBudget::new(0, 0),
+        }
+    }
+
+    const KEY: [u8; 28] = [0x11; 28];
+    const SCRIPT: [u8; 28] = [0x22; 28];
+    const STAKE_KEY: [u8; 28] = [0x44; 28];
+    const POLICY: [u8; 28] = [0x55; 28];
+    const POOL: [u8; 28] = [0x66; 28];
+    const DATUM_HASH: [u8; 32] = [0x33; 32];
+
+    /// A transaction using everything a script context has room for,
+    /// along with the outputs it spends and references.
+    fn everything() -> (Transaction, BTreeMap<TransactionInput, TransactionOutput>) {
+        let tx = Transaction {
+            id: vec![0xee; 32],
+            body: TransactionBody {
+                // out of order, the ledger sorts them
+                inputs: vec![input(0xbb, 1), input(0xaa, 0)],
+                outputs: vec![output(
+                    address(0x01, &[&KEY, &STAKE_KEY]),
+                    1_000_000,
+                    Some(DatumOption::Data(PlutusData::Constr {
+                        tag: 0,
+                        fields: vec![],
+                    })),
+                )],
+                fee: 170_000,
+                ttl: Some(200),
+                certificates: vec![Certificate::StakeDelegation(
+                    Credential::ScriptHash(SCRIPT.to_vec()),
+                    POOL.to_vec(),
+                )],
+                withdrawals: BTreeMap::from([(address(0xf0, &[&SCRIPT]), 7)]),
+                validity_start: Some(100),
+                mint: BTreeMap::from([(POLICY.to_vec(), BTreeMap::from([(b"ab".to_vec(), 1)]))]),
+                required_signers: vec![KEY.to_vec()],
+                reference_inputs: vec![input(0xcc, 0)],
+            },
+            witness_set: WitnessSet {
+                plutus_data: BTreeMap::from([(DATUM_HASH.to_vec(), PlutusData::Integer(7.into()))]),
+                // out of order too, the V2 context has them by purpose
+                redeemers: vec![
+                    redeemer(RedeemerTag::Cert, 0, PlutusData::Integer(3.into())),
+                    redeemer(RedeemerTag::Spend, 1, PlutusData::Integer(1.into())),
+                    redeemer(RedeemerTag::Reward, 0, PlutusData::Integer(2.into())),
+                    redeemer(RedeemerTag::Mint, 0, PlutusData::Integer(0.into())),
+                ],
+                ..WitnessSet::default()
+            },
+        };
+
+        let utxos = BTreeMap::from([
+            (input(0xaa, 0), output(address(0x61, &[&KEY]), 5, None)),
+            (
+                input(0xbb, 1),
+                output(
+                    address(0x71, &[&SCRIPT]),
+                    2,
+                    Some(DatumOption::Hash(DATUM_HASH.to_vec())),
+                ),
+            ),
+            (
+                input(0xcc, 0),
+                TransactionOutput {
+                    script_ref: Some(Script::PlutusV2(vec![0x01])),
+                    ..output(
+                        address(0x71, &[&SCRIPT]),
+                        3,
+                        Some(DatumOption::Data(PlutusData::Integer(42.into()))),
+                    )
+                },
+            ),
+        ]);
+
+        (tx, utxos)
+    }
+
+    /// Everything `everything` has that V1 scripts can see.
+    fn everything_v1() -> (Transaction, BTreeMap<TransactionInput, TransactionOutput>) {
+        let (mut tx, mut utxos) = everything();
+
+        tx.body.reference_inputs.clear();
+        tx.body.outputs[0].datum = Some(DatumOption::Hash(DATUM_HASH.to_vec()));
+
+        utxos.remove(&input(0xcc, 0));
+
+        (tx, utxos)
+    }
+
+    fn script_context(
+        tx: &Transaction,
+        utxos: &BTreeMap<TransactionInput, TransactionOutput>,
+        version: PlutusVersion,
+        tag: RedeemerTag,
+    ) -> Result<PlutusData, Error> {
+        let resolved = ResolvedTransaction {
+            tx,
+            utxos,
+            slot_config: &SlotConfig {
+                zero_time: 0,
+                zero_slot: 0,
+                slot_length: 1000,
+            },
+        };
+
+        let redeemer = tx
+            .witness_set
+            .redeemers
+            .iter()
+            .find(|redeemer| redeemer.tag == tag)
+            .unwrap();
+
+        resolved.script_context(version, &resolved.script_purpose(redeemer)?)
+    }
+
+    /// Values the way the ledger gives them to scripts, ada first.
+    fn value(coin: u64) -> String {
+        format!("Map [(B #, Map [(B #, I {coin})])]")
+    }
+
+    #[test]
+    fn script_context_v2() {
+        let (tx, utxos) = everything();
+
+        let [aa, bb, cc, ee] = ["aa", "bb", "cc", "ee"].map(|byte| byte.repeat(32));
+        let [key, script, stake_key, policy, pool] =
+            [KEY, SCRIPT, STAKE_KEY, POLICY, POOL].map(hex::encode);
+        let datum_hash = hex::encode(DATUM_HASH);
+
+        let script_credential = format!("Constr 0 [Constr 1 [B #{script}]]");
+        let spent = format!("Constr 0 [Constr 0 [B #{bb}], I 1]");
+        let delegation = format!("Constr 2 [{script_credential}, B #{pool}]");
+
+        let tx_info = format!(
+            "Constr 0 [
+                List [
+                    Constr 0 [
+                        Constr 0 [Constr 0 [B #{aa}], I 0],
+                        Constr 0 [Constr 0 [Constr 0 [B #{key}], Constr 1 []], {}, Constr 0 [], Constr 1 []]
+                    ],
+                    Constr 0 [
+                        {spent},
+                        Constr 0 [Constr 0 [Constr 1 [B #{script}], Constr 1 []], {}, Constr 1 [B #{datum_hash}], Constr 1 []]
+                    ]
+                ],
+                List [
+                    Constr 0 [
+                        Constr 0 [Constr 0 [B #{cc}], I 0],
+                        Constr 0 [
+                            Constr 0 [Constr 1 [B #{script}], Constr 1 []],
+                            {},
+                            Constr 2 [I 42],
+                            Constr 0 [B #{}]
+                        ]
+                    ]
+                ],
+                List [
+                    Constr 0 [
+                        Constr 0 [Constr 0 [B #{key}], Constr 0 [Constr 0 [Constr 0 [B #{stake_key}]]]],
+                        {},
+                        Constr 2 [Constr 0 []],
+                        Constr 1 []
+                    ]
+                ],
+                {},
+                Map [(B #, Map [(B #, I 0)]), (B #{policy}, Map [(B #6162, I 1)])],
+                List [{delegation}],
+                Map [({script_credential}, I 7)],
+                Constr 0 [Constr 0 [Constr 1 [I 100000], Constr 1 []], Constr 0 [Constr 1 [I 200000], Constr 0 []]],
+                List [B #{key}],
+                Map [
+                    (Constr 0 [B #{policy}], I 0),
+                    (Constr 1 [{spent}], I 1),
+                    (Constr 2 [{script_credential}], I 2),
+                    (Constr 3 [{delegation}], I 3)
+                ],
+                Map [(B #{datum_hash}, I 7)],
+                Constr 0 [B #{ee}]
+            ]",
+            value(5),
+            value(2),
+            value(3),
+            hex::encode(Script::PlutusV2(vec![0x01]).hash()),
+            value(1_000_000),
+            value(170_000),
+        );
+
+        let cases = [
+            (RedeemerTag::Mint, format!("Constr 0 [B #{policy}]")),
+            (RedeemerTag::Spend, format!("Constr 1 [{spent}]")),
+            (
+                RedeemerTag::Reward,
+                format!("Constr 2 [{script_credential}]"),
+            ),
+            (RedeemerTag::Cert, format!("Constr 3 [{delegation}]")),
+        ];
+
+        for (tag, purpose) in cases {
+            assert_eq!(
+                script_context(&tx, &utxos, PlutusVersion::V2, tag).unwrap(),
+                data(&format!("Constr 0 [{tx_info}, {purpose}]")),
+                "{tag:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn script_context_v1() {
+        let (tx, utxos) = everything_v1();
+
+        let [aa, bb, ee] = ["aa", "bb", "ee"].map(|byte| byte.repeat(32));
+        let [key, script, stake_key, policy, pool] =
+            [KEY, SCRIPT, STAKE_KEY, POLICY, POOL].map(hex::encode);
+        let datum_hash = hex::encode(DATUM_HASH);
+
+        let script_credential = format!("Constr 0 [Constr 1 [B #{script}]]");
+        let spent = format!("Constr 0 [Constr 0 [B #{bb}], I 1]");
+        let delegation = format!("Constr 2 [{script_credential}, B #{pool}]");
+
+        // no reference inputs or redeemers, withdrawals and datums
+        // are lists of pairs and outputs only have datum hashes
+        let tx_info = format!(
+            "Constr 0 [
+                List [
+                    Constr 0 [
+                        Constr 0 [Constr 0 [B #{aa}], I 0],
+                        Constr 0 [Constr 0 [Constr 0 [B #{key}], Constr 1 []], {}, Constr 1 []]
+                    ],
+                    Constr 0 [
+                        {spent},
+                        Constr 0 [Constr 0 [Constr 1 [B #{script}], Constr 1 []], {}, Constr 0 [B #{datum_hash}]]
+                    ]
+                ],
+                List [
+                    Constr 0 [
+                        Constr 0 [Constr 0 [B #{key}], Constr 0 [Constr 0 [Constr 0 [B #{stake_key}]]]],
+                        {},
+                        Constr 0 [B #{datum_hash}]
+                    ]
+                ],
+                {},
+                Map [(B #, Map [(B #, I 0)]), (B #{policy}, Map [(B #6162, I 1)])],
+                List [{delegation}],
+                List [Constr 0 [{script_credential}, I 7]],
+                Constr 0 [Constr 0 [Constr 1 [I 100000], Constr 1 []], Constr 0 [Constr 1 [I 200000], Constr 0 []]],
+                List [B #{key}],
+                List [Constr 0 [B #{datum_hash}, I 7]],
+                Constr 0 [B #{ee}]
+            ]",
+            value(5),
+            value(2),
+            value(1_000_000),
+            value(170_000),
+        );
+
+        let cases = [
+            (RedeemerTag::Mint, format!("Constr 0 [B #{policy}]")),
+            (RedeemerTag::Spend, format!("Constr 1 [{spent}]")),
+            (
+                RedeemerTag::Reward,
+                format!("Constr 2 [{script_credential}]"),
+            ),
+            (RedeemerTag::Cert, format!("Constr 3 [{delegation}]")),
+        ];
+
+        for (tag, purpose) in cases {
+            assert_eq!(
+                script_context(&tx, &utxos, PlutusVersion::V1, tag).unwrap(),
+                data(&format!("Constr 0 [{tx_info}, {purpose}]")),
+                "{tag:?}"
+            );
+        }
+
+        // what only V2 scripts can see
+        let (tx, utxos) = everything();
+
+        assert!(matches!(
+            script_context(&tx, &utxos, PlutusVersion::V1, RedeemerTag::Mint),
+            Err(Error::UnsupportedByPlutusV1("reference inputs"))
+        ));
+
+        let (mut tx, utxos) = everything();
+        tx.body.reference_inputs.clear();
+
+        assert!(matches!(
+            script_context(&tx, &utxos, PlutusVersion::V1, RedeemerTag::Mint),
+            Err(Error::UnsupportedByPlutusV1("inline datums"))
+        ));
+    }
+
+    #[test]
+    fn validity_range() {
+        let (mut tx, utxos) = everything();
+
+        let range = |tx: &Transaction| match script_context(
+            tx,
+            &utxos,
+            PlutusVersion::V2,
+            RedeemerTag::Mint,
+        ) {
+            Ok(PlutusData::Constr { fields, .. }) => match &fields[0] {
+                PlutusData::Constr { fields, .. } => fields[7].clone(),
+                _ => unreachable!(),
+            },
+            context => panic!("unexpected context {context:?}"),
+        };
+
+        tx.body.validity_start = None;
+        tx.body.ttl = None;
+
+        assert_eq!(
+            range(&tx),
+            data("Constr 0 [Constr 0 [Constr 0 [], Constr 1 []], Constr 0 [Constr 2 [], Constr 1 []]]")
+        );
+
+        tx.body.ttl = Some(5);
+
+        assert_eq!(
+            range(&tx),
+            data("Constr 0 [Constr 0 [Constr 0 [], Constr 1 []], Constr 0 [Constr 1 [I 5000], Constr 0 []]]")
+        );
+    }
+
+    /// Accepts redeemers equal to the index of the constructor of
+    /// it's purpose.
+    fn check_purpose(version: PlutusVersion) -> (Vec<u8>, Script) {
+        let program: Program<DeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                (lam r (lam ctx
+                    [
+                        (lam purpose
+                            (force [
+                                (force (builtin ifThenElse))
+                                [
+                                    (builtin equalsInteger)
+                                    [ (force (force (builtin fstPair))) [ (builtin unConstrData) purpose ] ]
+                                    [ (builtin unIData) r ]
+                                ]
+                                (delay (con unit ()))
+                                (delay (error))
+                            ])
+                        )
+                        [
+                            (force (builtin headList))
+                            [
+                                (force (builtin tailList))
+                                [ (force (force (builtin sndPair))) [ (builtin unConstrData) ctx ] ]
+                            ]
+                        ]
+                    ]
+                ))
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let cbor = program.to_cbor().unwrap();
+
+        let script = match version {
+            PlutusVersion::V1 => Script::PlutusV1(cbor),
+            PlutusVersion::V2 => Script::PlutusV2(cbor),
+        };
+
+        (script.hash(), script)
+    }
+
+    #[test]
+    fn eval_minting_certifying_and_rewarding() {
+        for version in [PlutusVersion::V1, PlutusVersion::V2] {
+            let (hash, script) = check_purpose(version);
+
+            let (mut tx, utxos) = everything_v1();
+
+            tx.body.mint = BTreeMap::from([(hash.clone(), BTreeMap::from([(vec![], 1)]))]);
+            tx.body.certificates = vec![
+                Certificate::StakeDelegation(Credential::ScriptHash(hash.clone()), POOL.to_vec()),
+                Certificate::StakeRegistration(Credential::KeyHash(KEY.to_vec())),
+            ];
+            tx.body.withdrawals = BTreeMap::from([(address(0xf0, &[&hash]), 7)]);
+
+            tx.witness_set.redeemers = vec![
+                redeemer(RedeemerTag::Mint, 0, PlutusData::Integer(0.into())),
+                redeemer(RedeemerTag::Reward, 0, PlutusData::Integer(2.into())),
+                redeemer(RedeemerTag::Cert, 0, PlutusData::Integer(3.into())),
+            ];
+
+            match script {
+                Script::PlutusV1(script) => tx.witness_set.plutus_v1_scripts = vec![script],
+                Script::PlutusV2(script) => tx.witness_set.plutus_v2_scripts = vec![script],
+                Script::Native => unreachable!(),
+            }
+
+            let eval = |tx: &Transaction| {
+                eval_phase_two(
+                    tx,
+                    &utxos,
+                    &CostModels::default(),
+                    ExBudget::default(),
+                    &SlotConfig::default(),
+                )
+            };
+
+            let redeemers = eval(&tx).unwrap();
+
+            assert_eq!(
+                redeemers
+                    .iter()
+                    .map(|evaluated| evaluated.redeemer.tag)
+                    .collect::<Vec<_>>(),
+                [RedeemerTag::Mint, RedeemerTag::Reward, RedeemerTag::Cert]
+            );
+            assert!(redeemers
+                .iter()
+                .all(|evaluated| evaluated.redeemer.ex_units.cpu > 0));
+
+            // redeemers checked against the wrong purpose
+            tx.witness_set.redeemers[1].data = PlutusData::Integer(3.into());
+
+            assert!(matches!(
+                eval(&tx),
+                Err(Error::Machine(RedeemerTag::Reward, 0, _, _))
+            ));
+
+            // only scripts have redeemers
+            tx.witness_set.redeemers = vec![redeemer(
+                RedeemerTag::Cert,
+                1,
+                PlutusData::Integer(3.into()),
+            )];
+
+            assert!(matches!(
+                eval(&tx),
+                Err(Error::NotAScript(RedeemerTag::Cert, 1))
+            ));
+
+            tx.witness_set.redeemers = vec![redeemer(
+                RedeemerTag::Reward,
+                1,
+                PlutusData::Integer(2.into()),
+            )];
+
+            assert!(matches!(
+                eval(&tx),
+                Err(Error::MissingRedeemerTarget(RedeemerTag::Reward, 1))
+            ));
+        }
+    }
+
+    #[test]
+    fn eval_with_reference_script_and_datum_hash() {
+        let program: Program<DeBruijn> = parser::program(
+            r#"
+            (program 1.0.0
+                (lam d (lam r (lam ctx
+                    (force [
+                        (force (builtin ifThenElse))
+                        [ (builtin equalsData) d r ]
+                        (delay (con unit ()))
+                        (delay (error))
+                    ])
+                )))
+            )
+            "#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let script = Script::PlutusV2(program.to_cbor().unwrap());
+        let hash = script.hash();
+
+        let (mut tx, mut utxos) = everything();
+
+        // the script is only in the referenced output
+        utxos.get_mut(&input(0xcc, 0)).unwrap().script_ref = Some(script);
+        utxos.get_mut(&input(0xbb, 1)).unwrap().address = address(0x71, &[&hash]);
+
+        tx.witness_set.redeemers = vec![redeemer(
+            RedeemerTag::Spend,
+            1,
+            PlutusData::Integer(7.into()),
+        )];
+
+        let eval = |tx: &Transaction| {
+            eval_phase_two(
+                tx,
+                &utxos,
+                &CostModels::default(),
+                ExBudget::default(),
+                &SlotConfig::default(),
+            )
+        };
+
+        assert_eq!(eval(&tx).unwrap().len(), 1);
+
+        // outputs that aren't spent or referenced don't count
+        let mut unreferenced = tx.clone();
+        unreferenced.body.reference_inputs.clear();
+
+        assert!(matches!(eval(&unreferenced), Err(Error::MissingScript(_))));
+
+        tx.witness_set.plutus_data.clear();
+
+        assert!(matches!(
+            eval(&tx),
+            Err(Error::MissingDatum(RedeemerTag::Spend, 1))
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+use crate::machine;
+
+use super::transaction::RedeemerTag;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Couldn't decode the transaction: {0}")]
+    Decode(#[from] minicbor::decode::Error),
+    #[error("Couldn't decode script: {0}")]
+    ScriptDecode(#[from] flat::de::Error),
+    #[error("Got {0} resolved inputs but {1} outputs for them")]
+    ResolvedInputsMismatch(usize, usize),
+    #[error("Input {0}#{1} isn't among the resolved inputs")]
+    ResolvedInputNotFound(String, u64),
+    #[error("Redeemer {0:?}[{1}] doesn't point to anything in the transaction")]
+    MissingRedeemerTarget(RedeemerTag, u64),
+    #[error("Redeemer {0:?}[{1}] points to something that isn't locked by a script")]
+    NotAScript(RedeemerTag, u64),
+    #[error("Missing Plutus script with hash {0}")]
+    MissingScript(String),
+    #[error("Missing datum for the input spent by redeemer {0:?}[{1}]")]
+    MissingDatum(RedeemerTag, u64),
+    #[error("Unsupported address {0}")]
+    UnsupportedAddress(String),
+    #[error("Plutus V1 scripts can't be given a transaction with {0}")]
+    UnsupportedByPlutusV1(&'static str),
+    /// The script failed, after tracing the messages in the last field.
+    #[error("Script for redeemer {0:?}[{1}] failed: {2}")]
+    Machine(RedeemerTag, u64, machine::Error, Vec<String>),
+}
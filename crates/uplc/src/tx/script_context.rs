@@ -0,0 +1,540 @@
+use std::collections::BTreeMap;
+
+use crate::{ast::PlutusVersion, data::PlutusData};
+
+use super::{
+    error::Error,
+    transaction::{
+        Certificate, Credential, DatumOption, MultiAsset, Redeemer, Script, Transaction,
+        TransactionInput, TransactionOutput,
+    },
+};
+
+/// How slots map to POSIX time, which is what scripts see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotConfig {
+    /// POSIX time of `zero_slot` in milliseconds.
+    pub zero_time: u64,
+    pub zero_slot: u64,
+    /// Length of a slot in milliseconds.
+    pub slot_length: u64,
+}
+
+/// The Shelley era start on mainnet.
+impl Default for SlotConfig {
+    fn default() -> Self {
+        SlotConfig {
+            zero_time: 1596059091000,
+            zero_slot: 4492800,
+            slot_length: 1000,
+        }
+    }
+}
+
+impl SlotConfig {
+    pub fn slot_to_posix_time(&self, slot: u64) -> u64 {
+        self.zero_time + slot.saturating_sub(self.zero_slot) * self.slot_length
+    }
+}
+
+/// What a script is run for, ordered like the ledger orders them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScriptPurpose {
+    Minting(Vec<u8>),
+    Spending(TransactionInput),
+    Rewarding(Credential),
+    Certifying(Certificate),
+}
+
+/// A transaction along with the outputs its inputs point to.
+pub struct ResolvedTransaction<'a> {
+    pub tx: &'a Transaction,
+    pub utxos: &'a BTreeMap<TransactionInput, TransactionOutput>,
+    pub slot_config: &'a SlotConfig,
+}
+
+impl<'a> ResolvedTransaction<'a> {
+    pub fn resolve(&self, input: &TransactionInput) -> Result<&'a TransactionOutput, Error> {
+        self.utxos.get(input).ok_or_else(|| {
+            Error::ResolvedInputNotFound(hex::encode(&input.transaction_id), input.index)
+        })
+    }
+
+    /// Find what a redeemer points to, inputs, policies and
+    /// withdrawals are pointed to by their index once sorted.
+    pub fn script_purpose(&self, redeemer: &Redeemer) -> Result<ScriptPurpose, Error> {
+        use super::transaction::RedeemerTag;
+
+        let body = &self.tx.body;
+        let index = redeemer.index as usize;
+
+        let purpose = match redeemer.tag {
+            RedeemerTag::Spend => sorted(&body.inputs)
+                .get(index)
+                .map(|input| ScriptPurpose::Spending((*input).clone())),
+            RedeemerTag::Mint => body
+                .mint
+                .keys()
+                .nth(index)
+                .map(|policy_id| ScriptPurpose::Minting(policy_id.clone())),
+            RedeemerTag::Cert => body
+                .certificates
+                .get(index)
+                .map(|certificate| ScriptPurpose::Certifying(certificate.clone())),
+            RedeemerTag::Reward => match body.withdrawals.keys().nth(index) {
+                Some(address) => Some(ScriptPurpose::Rewarding(reward_credential(address)?)),
+                None => None,
+            },
+        };
+
+        purpose.ok_or(Error::MissingRedeemerTarget(redeemer.tag, redeemer.index))
+    }
+
+    /// The `ScriptContext` handed to a script run for `purpose`,
+    /// it's shape depends on the version the script is written for.
+    pub fn script_context(
+        &self,
+        version: PlutusVersion,
+        purpose: &ScriptPurpose,
+    ) -> Result<PlutusData, Error> {
+        let tx_info = match version {
+            PlutusVersion::V1 => self.tx_info_v1()?,
+            PlutusVersion::V2 => self.tx_info_v2()?,
+        };
+
+        Ok(constr(0, vec![tx_info, purpose.to_plutus_data()?]))
+    }
+
+    fn tx_info_v1(&self) -> Result<PlutusData, Error> {
+        let body = &self.tx.body;
+
+        if !body.reference_inputs.is_empty() {
+            return Err(Error::UnsupportedByPlutusV1("reference inputs"));
+        }
+
+        let inputs = sorted(&body.inputs)
+            .into_iter()
+            .map(|input| {
+                let output = self.resolve(input)?;
+
+                Ok(constr(
+                    0,
+                    vec![input.to_plutus_data(), output.to_plutus_data_v1()?],
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let outputs = body
+            .outputs
+            .iter()
+            .map(TransactionOutput::to_plutus_data_v1)
+            .collect::<Result<_, _>>()?;
+
+        let withdrawals = body
+            .withdrawals
+            .iter()
+            .map(|(address, amount)| {
+                Ok(constr(
+                    0,
+                    vec![
+                        staking_credential(&reward_credential(address)?),
+                        integer(*amount),
+                    ],
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let datums = self
+            .tx
+            .witness_set
+            .plutus_data
+            .iter()
+            .map(|(hash, datum)| constr(0, vec![bytes(hash), datum.clone()]))
+            .collect();
+
+        Ok(constr(
+            0,
+            vec![
+                PlutusData::List(inputs),
+                PlutusData::List(outputs),
+                value(body.fee, &MultiAsset::new()),
+                value(0, &body.mint),
+                self.certificates()?,
+                PlutusData::List(withdrawals),
+                self.valid_range(),
+                self.signatories(),
+                PlutusData::List(datums),
+                constr(0, vec![bytes(&self.tx.id)]),
+            ],
+        ))
+    }
+
+    fn tx_info_v2(&self) -> Result<PlutusData, Error> {
+        let body = &self.tx.body;
+
+        let in_info = |inputs: &[TransactionInput]| {
+            sorted(inputs)
+                .into_iter()
+                .map(|input| {
+                    let output = self.resolve(input)?;
+
+                    Ok(constr(
+                        0,
+                        vec![input.to_plutus_data(), output.to_plutus_data_v2()?],
+                    ))
+                })
+                .collect::<Result<_, Error>>()
+        };
+
+        let outputs = body
+            .outputs
+            .iter()
+            .map(TransactionOutput::to_plutus_data_v2)
+            .collect::<Result<_, _>>()?;
+
+        let withdrawals = body
+            .withdrawals
+            .iter()
+            .map(|(address, amount)| {
+                Ok((
+                    staking_credential(&reward_credential(address)?),
+                    integer(*amount),
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let mut redeemers = self
+            .tx
+            .witness_set
+            .redeemers
+            .iter()
+            .map(|redeemer| Ok((self.script_purpose(redeemer)?, redeemer.data.clone())))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        redeemers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let redeemers = redeemers
+            .into_iter()
+            .map(|(purpose, data)| Ok((purpose.to_plutus_data()?, data)))
+            .collect::<Result<_, Error>>()?;
+
+        let datums = self
+            .tx
+            .witness_set
+            .plutus_data
+            .iter()
+            .map(|(hash, datum)| (bytes(hash), datum.clone()))
+            .collect();
+
+        Ok(constr(
+            0,
+            vec![
+                PlutusData::List(in_info(&body.inputs)?),
+                PlutusData::List(in_info(&body.reference_inputs)?),
+                PlutusData::List(outputs),
+                value(body.fee, &MultiAsset::new()),
+                value(0, &body.mint),
+                self.certificates()?,
+                PlutusData::Map(withdrawals),
+                self.valid_range(),
+                self.signatories(),
+                PlutusData::Map(redeemers),
+                PlutusData::Map(datums),
+                constr(0, vec![bytes(&self.tx.id)]),
+            ],
+        ))
+    }
+
+    fn certificates(&self) -> Result<PlutusData, Error> {
+        let certificates = self
+            .tx
+            .body
+            .certificates
+            .iter()
+            .map(Certificate::to_plutus_data)
+            .collect::<Result<_, _>>()?;
+
+        Ok(PlutusData::List(certificates))
+    }
+
+    /// The validity interval as POSIX time, the lower bound is
+    /// inclusive and the upper bound (the ttl) is exclusive.
+    fn valid_range(&self) -> PlutusData {
+        let bound = |slot: Option<u64>, closed: bool| {
+            let extended = match slot {
+                Some(slot) => constr(1, vec![integer(self.slot_config.slot_to_posix_time(slot))]),
+                None if closed => constr(0, vec![]),
+                None => constr(2, vec![]),
+            };
+
+            constr(0, vec![extended, boolean(closed || slot.is_none())])
+        };
+
+        constr(
+            0,
+            vec![
+                bound(self.tx.body.validity_start, true),
+                bound(self.tx.body.ttl, false),
+            ],
+        )
+    }
+
+    fn signatories(&self) -> PlutusData {
+        PlutusData::List(
+            sorted(&self.tx.body.required_signers)
+                .into_iter()
+                .map(|key_hash| bytes(key_hash))
+                .collect(),
+        )
+    }
+}
+
+impl ScriptPurpose {
+    fn to_plutus_data(&self) -> Result<PlutusData, Error> {
+        Ok(match self {
+            ScriptPurpose::Minting(policy_id) => constr(0, vec![bytes(policy_id)]),
+            ScriptPurpose::Spending(input) => constr(1, vec![input.to_plutus_data()]),
+            ScriptPurpose::Rewarding(credential) => constr(2, vec![staking_credential(credential)]),
+            ScriptPurpose::Certifying(certificate) => {
+                constr(3, vec![certificate.to_plutus_data()?])
+            }
+        })
+    }
+}
+
+impl TransactionInput {
+    fn to_plutus_data(&self) -> PlutusData {
+        constr(
+            0,
+            vec![
+                constr(0, vec![bytes(&self.transaction_id)]),
+                integer(self.index),
+            ],
+        )
+    }
+}
+
+impl TransactionOutput {
+    fn to_plutus_data_v1(&self) -> Result<PlutusData, Error> {
+        let datum_hash = match &self.datum {
+            None => constr(1, vec![]),
+            Some(DatumOption::Hash(hash)) => constr(0, vec![bytes(hash)]),
+            Some(DatumOption::Data(_)) => {
+                return Err(Error::UnsupportedByPlutusV1("inline datums"))
+            }
+        };
+
+        if self.script_ref.is_some() {
+            return Err(Error::UnsupportedByPlutusV1("reference scripts"));
+        }
+
+        Ok(constr(
+            0,
+            vec![
+                address(&self.address)?,
+                value(self.value.coin, &self.value.assets),
+                datum_hash,
+            ],
+        ))
+    }
+
+    fn to_plutus_data_v2(&self) -> Result<PlutusData, Error> {
+        let datum = match &self.datum {
+            None => constr(0, vec![]),
+            Some(DatumOption::Hash(hash)) => constr(1, vec![bytes(hash)]),
+            Some(DatumOption::Data(data)) => constr(2, vec![data.clone()]),
+        };
+
+        let script_hash = match &self.script_ref {
+            Some(script) => constr(0, vec![bytes(&script.hash())]),
+            None => constr(1, vec![]),
+        };
+
+        Ok(constr(
+            0,
+            vec![
+                address(&self.address)?,
+                value(self.value.coin, &self.value.assets),
+                datum,
+                script_hash,
+            ],
+        ))
+    }
+}
+
+impl Certificate {
+    fn to_plutus_data(&self) -> Result<PlutusData, Error> {
+        Ok(match self {
+            Certificate::StakeRegistration(credential) => {
+                constr(0, vec![staking_credential(credential)])
+            }
+            Certificate::StakeDeregistration(credential) => {
+                constr(1, vec![staking_credential(credential)])
+            }
+            Certificate::StakeDelegation(credential, pool) => {
+                constr(2, vec![staking_credential(credential), bytes(pool)])
+            }
+            Certificate::PoolRegistration {
+                operator,
+                vrf_keyhash,
+            } => constr(3, vec![bytes(operator), bytes(vrf_keyhash)]),
+            Certificate::PoolRetirement(pool, epoch) => {
+                constr(4, vec![bytes(pool), integer(*epoch)])
+            }
+            Certificate::GenesisKeyDelegation => constr(5, vec![]),
+            Certificate::MoveInstantaneousRewards => constr(6, vec![]),
+        })
+    }
+}
+
+impl Script {
+    /// The hash scripts are identified by, the blake2b-224 of the
+    /// script prefixed by it's language tag.
+    pub fn hash(&self) -> Vec<u8> {
+        use blake2::{digest::consts::U28, Blake2b, Digest};
+
+        let (tag, script): (u8, &[u8]) = match self {
+            Script::Native => (0, &[]),
+            Script::PlutusV1(script) => (1, script),
+            Script::PlutusV2(script) => (2, script),
+        };
+
+        Blake2b::<U28>::new()
+            .chain_update([tag])
+            .chain_update(script)
+            .finalize()
+            .to_vec()
+    }
+}
+
+/// The payment credential of a shelley address.
+pub fn payment_credential(address: &[u8]) -> Result<Credential, Error> {
+    let header = *address.first().ok_or_else(|| unsupported(address))?;
+
+    if header >> 4 > 7 || address.len() < 29 {
+        return Err(unsupported(address));
+    }
+
+    Ok(credential(header & 0b0001_0000 != 0, &address[1..29]))
+}
+
+/// The credential of a reward address.
+pub fn reward_credential(address: &[u8]) -> Result<Credential, Error> {
+    match address.first().map(|header| header >> 4) {
+        Some(kind @ (14 | 15)) if address.len() == 29 => Ok(credential(kind == 15, &address[1..])),
+        _ => Err(unsupported(address)),
+    }
+}
+
+fn address(address: &[u8]) -> Result<PlutusData, Error> {
+    let payment = payment_credential(address)?;
+
+    let stake = match address[0] >> 4 {
+        kind @ 0..=3 if address.len() == 57 => constr(
+            0,
+            vec![staking_credential(&credential(
+                kind & 0b10 != 0,
+                &address[29..],
+            ))],
+        ),
+        4 | 5 => {
+            let (slot, rest) = variable_nat(&address[29..]).ok_or_else(|| unsupported(address))?;
+            let (tx_index, rest) = variable_nat(rest).ok_or_else(|| unsupported(address))?;
+            let (cert_index, _) = variable_nat(rest).ok_or_else(|| unsupported(address))?;
+
+            constr(
+                0,
+                vec![constr(
+                    1,
+                    vec![integer(slot), integer(tx_index), integer(cert_index)],
+                )],
+            )
+        }
+        6 | 7 => constr(1, vec![]),
+        _ => return Err(unsupported(address)),
+    };
+
+    Ok(constr(0, vec![credential_data(&payment), stake]))
+}
+
+/// Pointer addresses store numbers 7 bits at a time, big endian,
+/// with the high bit set on every byte but the last.
+fn variable_nat(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut n = 0u64;
+
+    for (i, byte) in bytes.iter().enumerate() {
+        n = n.checked_mul(128)? | (byte & 0x7f) as u64;
+
+        if byte & 0x80 == 0 {
+            return Some((n, &bytes[i + 1..]));
+        }
+    }
+
+    None
+}
+
+fn credential(is_script: bool, hash: &[u8]) -> Credential {
+    if is_script {
+        Credential::ScriptHash(hash.to_vec())
+    } else {
+        Credential::KeyHash(hash.to_vec())
+    }
+}
+
+fn credential_data(credential: &Credential) -> PlutusData {
+    match credential {
+        Credential::KeyHash(hash) => constr(0, vec![bytes(hash)]),
+        Credential::ScriptHash(hash) => constr(1, vec![bytes(hash)]),
+    }
+}
+
+fn staking_credential(credential: &Credential) -> PlutusData {
+    constr(0, vec![credential_data(credential)])
+}
+
+/// Values always hold an ada entry, even when there is no ada like in `mint`.
+fn value(coin: u64, assets: &MultiAsset) -> PlutusData {
+    let ada = (
+        bytes(&[]),
+        PlutusData::Map(vec![(bytes(&[]), integer(coin))]),
+    );
+
+    let assets = assets.iter().map(|(policy_id, assets)| {
+        let assets = assets
+            .iter()
+            .map(|(name, quantity)| (bytes(name), PlutusData::Integer((*quantity).into())))
+            .collect();
+
+        (bytes(policy_id), PlutusData::Map(assets))
+    });
+
+    PlutusData::Map(std::iter::once(ada).chain(assets).collect())
+}
+
+fn sorted<T: Ord>(items: &[T]) -> Vec<&T> {
+    let mut items: Vec<&T> = items.iter().collect();
+
+    items.sort();
+
+    items
+}
+
+fn unsupported(address: &[u8]) -> Error {
+    Error::UnsupportedAddress(hex::encode(address))
+}
+
+fn constr(tag: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr { tag, fields }
+}
+
+fn bytes(bytes: &[u8]) -> PlutusData {
+    PlutusData::ByteString(bytes.to_vec())
+}
+
+fn integer(i: u64) -> PlutusData {
+    PlutusData::Integer(i.into())
+}
+
+fn boolean(b: bool) -> PlutusData {
+    constr(b as u64, vec![])
+}
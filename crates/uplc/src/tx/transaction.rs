@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use minicbor::{
+    data::{Tag, Type},
+    decode, Decoder,
+};
+
+use crate::{data::PlutusData, machine::cost_model::ExBudget};
+
+/// The parts of a Babbage era transaction that scripts can observe.
+/// Everything else (witness signatures, metadata, ...) is skipped
+/// while decoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    /// The blake2b-256 hash of the body, exactly as it was serialised.
+    pub id: Vec<u8>,
+    pub body: TransactionBody,
+    pub witness_set: WitnessSet,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransactionBody {
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
+    pub fee: u64,
+    pub ttl: Option<u64>,
+    pub certificates: Vec<Certificate>,
+    /// Reward addresses and the amount withdrawn from them.
+    pub withdrawals: BTreeMap<Vec<u8>, u64>,
+    pub validity_start: Option<u64>,
+    pub mint: MultiAsset,
+    pub required_signers: Vec<Vec<u8>>,
+    pub reference_inputs: Vec<TransactionInput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WitnessSet {
+    pub plutus_v1_scripts: Vec<Vec<u8>>,
+    pub plutus_v2_scripts: Vec<Vec<u8>>,
+    /// Datums keyed by the hash of the bytes they were serialised as.
+    pub plutus_data: BTreeMap<Vec<u8>, PlutusData>,
+    pub redeemers: Vec<Redeemer>,
+}
+
+/// Ordered like the ledger orders inputs, by transaction id then index.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransactionInput {
+    pub transaction_id: Vec<u8>,
+    pub index: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionOutput {
+    pub address: Vec<u8>,
+    pub value: Value,
+    pub datum: Option<DatumOption>,
+    pub script_ref: Option<Script>,
+}
+
+/// Policy ids to asset names to quantities. Quantities are signed
+/// because minting can also burn.
+pub type MultiAsset = BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, i64>>;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Value {
+    pub coin: u64,
+    pub assets: MultiAsset,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatumOption {
+    Hash(Vec<u8>),
+    Data(PlutusData),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Script {
+    Native,
+    PlutusV1(Vec<u8>),
+    PlutusV2(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Credential {
+    KeyHash(Vec<u8>),
+    ScriptHash(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Certificate {
+    StakeRegistration(Credential),
+    StakeDeregistration(Credential),
+    StakeDelegation(Credential, Vec<u8>),
+    PoolRegistration {
+        operator: Vec<u8>,
+        vrf_keyhash: Vec<u8>,
+    },
+    PoolRetirement(Vec<u8>, u64),
+    GenesisKeyDelegation,
+    MoveInstantaneousRewards,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RedeemerTag {
+    Spend,
+    Mint,
+    Cert,
+    Reward,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redeemer {
+    pub tag: RedeemerTag,
+    pub index: u64,
+    pub data: PlutusData,
+    pub ex_units: ExBudget,
+}
+
+impl Transaction {
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, decode::Error> {
+        let mut d = Decoder::new(bytes);
+
+        d.array()?;
+
+        let start = d.position();
+        let body = decode_body(&mut d)?;
+        let id = Blake2b::<U32>::digest(&bytes[start..d.position()]).to_vec();
+
+        let witness_set = decode_witness_set(&mut d)?;
+
+        Ok(Transaction {
+            id,
+            body,
+            witness_set,
+        })
+    }
+}
+
+impl TransactionInput {
+    /// Decode a CBOR array of inputs, as given for resolved inputs.
+    pub fn list_from_cbor(bytes: &[u8]) -> Result<Vec<Self>, decode::Error> {
+        decode_inputs(&mut Decoder::new(bytes))
+    }
+}
+
+impl TransactionOutput {
+    /// Decode a CBOR array of outputs, as given for resolved inputs.
+    pub fn list_from_cbor(bytes: &[u8]) -> Result<Vec<Self>, decode::Error> {
+        decode_array(&mut Decoder::new(bytes), decode_output)
+    }
+}
+
+fn decode_body(d: &mut Decoder) -> Result<TransactionBody, decode::Error> {
+    let mut body = TransactionBody::default();
+
+    for _ in 0..definite(d.map()?)? {
+        match d.u32()? {
+            0 => body.inputs = decode_inputs(d)?,
+            1 => body.outputs = decode_array(d, decode_output)?,
+            2 => body.fee = d.u64()?,
+            3 => body.ttl = Some(d.u64()?),
+            4 => body.certificates = decode_array(d, decode_certificate)?,
+            5 => {
+                for _ in 0..definite(d.map()?)? {
+                    body.withdrawals.insert(d.bytes()?.to_vec(), d.u64()?);
+                }
+            }
+            8 => body.validity_start = Some(d.u64()?),
+            9 => body.mint = decode_multi_asset(d)?,
+            14 => {
+                body.required_signers = decode_array(d, |d| Ok(d.bytes()?.to_vec()))?;
+            }
+            18 => body.reference_inputs = decode_inputs(d)?,
+            _ => d.skip()?,
+        }
+    }
+
+    Ok(body)
+}
+
+fn decode_witness_set(d: &mut Decoder) -> Result<WitnessSet, decode::Error> {
+    let mut witness_set = WitnessSet::default();
+
+    for _ in 0..definite(d.map()?)? {
+        match d.u32()? {
+            3 => witness_set.plutus_v1_scripts = decode_array(d, |d| Ok(d.bytes()?.to_vec()))?,
+            4 => {
+                for (hash, data) in decode_array(d, decode_datum)? {
+                    witness_set.plutus_data.insert(hash, data);
+                }
+            }
+            5 => witness_set.redeemers = decode_redeemers(d)?,
+            6 => witness_set.plutus_v2_scripts = decode_array(d, |d| Ok(d.bytes()?.to_vec()))?,
+            _ => d.skip()?,
+        }
+    }
+
+    Ok(witness_set)
+}
+
+/// A datum along with the hash of the bytes it was decoded from.
+fn decode_datum(d: &mut Decoder) -> Result<(Vec<u8>, PlutusData), decode::Error> {
+    let start = d.position();
+    let data: PlutusData = d.decode()?;
+    let hash = Blake2b::<U32>::digest(&d.input()[start..d.position()]).to_vec();
+
+    Ok((hash, data))
+}
+
+fn decode_redeemers(d: &mut Decoder) -> Result<Vec<Redeemer>, decode::Error> {
+    // Conway transactions key redeemers by their tag and index
+    if let Type::Map | Type::MapIndef = d.datatype()? {
+        let mut redeemers = Vec::new();
+
+        for _ in 0..definite(d.map()?)? {
+            d.array()?;
+            let (tag, index) = (decode_redeemer_tag(d)?, d.u64()?);
+
+            d.array()?;
+            let (data, ex_units) = (d.decode()?, decode_ex_units(d)?);
+
+            redeemers.push(Redeemer {
+                tag,
+                index,
+                data,
+                ex_units,
+            });
+        }
+
+        return Ok(redeemers);
+    }
+
+    decode_array(d, |d| {
+        d.array()?;
+
+        Ok(Redeemer {
+            tag: decode_redeemer_tag(d)?,
+            index: d.u64()?,
+            data: d.decode()?,
+            ex_units: decode_ex_units(d)?,
+        })
+    })
+}
+
+fn decode_redeemer_tag(d: &mut Decoder) -> Result<RedeemerTag, decode::Error> {
+    match d.u8()? {
+        0 => Ok(RedeemerTag::Spend),
+        1 => Ok(RedeemerTag::Mint),
+        2 => Ok(RedeemerTag::Cert),
+        3 => Ok(RedeemerTag::Reward),
+        _ => Err(decode::Error::message("Unknown redeemer tag")),
+    }
+}
+
+fn decode_ex_units(d: &mut Decoder) -> Result<ExBudget, decode::Error> {
+    d.array()?;
+
+    Ok(ExBudget::new(d.i64()?, d.i64()?))
+}
+
+fn decode_inputs(d: &mut Decoder) -> Result<Vec<TransactionInput>, decode::Error> {
+    decode_array(d, |d| {
+        d.array()?;
+
+        Ok(TransactionInput {
+            transaction_id: d.bytes()?.to_vec(),
+            index: d.u64()?,
+        })
+    })
+}
+
+fn decode_output(d: &mut Decoder) -> Result<TransactionOutput, decode::Error> {
+    if let Type::Array | Type::ArrayIndef = d.datatype()? {
+        let len = definite(d.array()?)?;
+
+        let address = d.bytes()?.to_vec();
+        let value = decode_value(d)?;
+        let datum = if len > 2 {
+            Some(DatumOption::Hash(d.bytes()?.to_vec()))
+        } else {
+            None
+        };
+
+        return Ok(TransactionOutput {
+            address,
+            value,
+            datum,
+            script_ref: None,
+        });
+    }
+
+    let mut output = TransactionOutput {
+        address: Vec::new(),
+        value: Value::default(),
+        datum: None,
+        script_ref: None,
+    };
+
+    for _ in 0..definite(d.map()?)? {
+        match d.u32()? {
+            0 => output.address = d.bytes()?.to_vec(),
+            1 => output.value = decode_value(d)?,
+            2 => {
+                d.array()?;
+
+                output.datum = match d.u8()? {
+                    0 => Some(DatumOption::Hash(d.bytes()?.to_vec())),
+                    1 => {
+                        let bytes = decode_embedded_cbor(d)?;
+
+                        Some(DatumOption::Data(PlutusData::from_cbor(bytes)?))
+                    }
+                    _ => return Err(decode::Error::message("Unknown datum option")),
+                };
+            }
+            3 => {
+                let mut script = Decoder::new(decode_embedded_cbor(d)?);
+
+                script.array()?;
+
+                output.script_ref = match script.u8()? {
+                    0 => Some(Script::Native),
+                    1 => Some(Script::PlutusV1(script.bytes()?.to_vec())),
+                    2 => Some(Script::PlutusV2(script.bytes()?.to_vec())),
+                    _ => return Err(decode::Error::message("Unknown script language")),
+                };
+            }
+            _ => d.skip()?,
+        }
+    }
+
+    Ok(output)
+}
+
+fn decode_value(d: &mut Decoder) -> Result<Value, decode::Error> {
+    match d.datatype()? {
+        Type::Array | Type::ArrayIndef => {
+            d.array()?;
+
+            Ok(Value {
+                coin: d.u64()?,
+                assets: decode_multi_asset(d)?,
+            })
+        }
+        _ => Ok(Value {
+            coin: d.u64()?,
+            assets: MultiAsset::new(),
+        }),
+    }
+}
+
+fn decode_multi_asset(d: &mut Decoder) -> Result<MultiAsset, decode::Error> {
+    let mut multi_asset = MultiAsset::new();
+
+    for _ in 0..definite(d.map()?)? {
+        let policy_id = d.bytes()?.to_vec();
+        let assets = multi_asset.entry(policy_id).or_default();
+
+        for _ in 0..definite(d.map()?)? {
+            assets.insert(d.bytes()?.to_vec(), d.i64()?);
+        }
+    }
+
+    Ok(multi_asset)
+}
+
+fn decode_certificate(d: &mut Decoder) -> Result<Certificate, decode::Error> {
+    let len = definite(d.array()?)?;
+
+    let (certificate, read) = match d.u8()? {
+        0 => (Certificate::StakeRegistration(decode_credential(d)?), 2),
+        1 => (Certificate::StakeDeregistration(decode_credential(d)?), 2),
+        2 => (
+            Certificate::StakeDelegation(decode_credential(d)?, d.bytes()?.to_vec()),
+            3,
+        ),
+        3 => (
+            Certificate::PoolRegistration {
+                operator: d.bytes()?.to_vec(),
+                vrf_keyhash: d.bytes()?.to_vec(),
+            },
+            3,
+        ),
+        4 => (
+            Certificate::PoolRetirement(d.bytes()?.to_vec(), d.u64()?),
+            3,
+        ),
+        5 => (Certificate::GenesisKeyDelegation, 1),
+        6 => (Certificate::MoveInstantaneousRewards, 1),
+        _ => return Err(decode::Error::message("Unknown certificate")),
+    };
+
+    for _ in read..len {
+        d.skip()?;
+    }
+
+    Ok(certificate)
+}
+
+fn decode_credential(d: &mut Decoder) -> Result<Credential, decode::Error> {
+    d.array()?;
+
+    match d.u8()? {
+        0 => Ok(Credential::KeyHash(d.bytes()?.to_vec())),
+        1 => Ok(Credential::ScriptHash(d.bytes()?.to_vec())),
+        _ => Err(decode::Error::message("Unknown credential")),
+    }
+}
+
+/// Datums and reference scripts in outputs are CBOR bytestrings
+/// tagged with 24, holding the CBOR of the actual value.
+fn decode_embedded_cbor<'b>(d: &mut Decoder<'b>) -> Result<&'b [u8], decode::Error> {
+    if d.tag()? != Tag::Cbor {
+        return Err(decode::Error::message("Expected embedded CBOR"));
+    }
+
+    d.bytes()
+}
+
+/// Sets may be tagged with 258 since Conway, they're otherwise
+/// plain arrays.
+fn decode_array<T>(
+    d: &mut Decoder,
+    mut decode_item: impl FnMut(&mut Decoder) -> Result<T, decode::Error>,
+) -> Result<Vec<T>, decode::Error> {
+    if d.datatype()? == Type::Tag {
+        d.tag()?;
+    }
+
+    match d.array()? {
+        Some(len) => (0..len).map(|_| decode_item(d)).collect(),
+        None => {
+            let mut items = Vec::new();
+
+            while d.datatype()? != Type::Break {
+                items.push(decode_item(d)?);
+            }
+
+            d.set_position(d.position() + 1);
+
+            Ok(items)
+        }
+    }
+}
+
+fn definite(len: Option<u64>) -> Result<u64, decode::Error> {
+    len.ok_or_else(|| decode::Error::message("Expected a definite length"))
+}
@@ -0,0 +1,192 @@
+use std::rc::Rc;
+
+use crate::{
+    ast::{self, Constant, Name},
+    builtins::DefaultFunction,
+    parser::Interner,
+};
+
+/// This represents a program in Typed Plutus Core, as emitted
+/// by the plutus-tx tooling. It is only used as an input format:
+/// see `erase` to turn it into an Untyped Plutus Core program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub version: (usize, usize, usize),
+    pub term: Term,
+}
+
+/// This represents a term in Typed Plutus Core.
+/// Names are kept as their textual representation since
+/// uniques are only assigned once the program is erased.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Var(String),
+    TyAbs {
+        parameter_name: String,
+        kind: Kind,
+        body: Box<Term>,
+    },
+    Lambda {
+        parameter_name: String,
+        parameter_type: Type,
+        body: Box<Term>,
+    },
+    Apply {
+        function: Box<Term>,
+        argument: Box<Term>,
+    },
+    Constant(Constant),
+    TyInst {
+        term: Box<Term>,
+        ty: Type,
+    },
+    Unwrap(Box<Term>),
+    IWrap {
+        pattern: Type,
+        argument: Type,
+        term: Box<Term>,
+    },
+    Error(Type),
+    Builtin(DefaultFunction),
+}
+
+/// The types of Typed Plutus Core. Builtin types are kept
+/// as their textual representation, e.g. `integer` or `(list bool)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Var(String),
+    Fun(Box<Type>, Box<Type>),
+    IFix(Box<Type>, Box<Type>),
+    Forall {
+        parameter_name: String,
+        kind: Kind,
+        body: Box<Type>,
+    },
+    Builtin(String),
+    Lambda {
+        parameter_name: String,
+        kind: Kind,
+        body: Box<Type>,
+    },
+    Apply(Box<Type>, Box<Type>),
+}
+
+/// The kinds of Typed Plutus Core types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Type,
+    Arrow(Box<Kind>, Box<Kind>),
+}
+
+impl Program {
+    /// Erase all the types from this program, producing
+    /// an Untyped Plutus Core program with interned names.
+    pub fn erase(self) -> ast::Program<Name> {
+        let mut program = ast::Program {
+            version: self.version,
+            term: self.term.erase(),
+        };
+
+        let mut interner = Interner::new();
+
+        interner.program(&mut program);
+
+        program
+    }
+}
+
+impl Term {
+    /// Erase all the types from this term. Type abstractions
+    /// become `Delay`, type instantiations become `Force` and
+    /// `IWrap`/`Unwrap` disappear entirely.
+    /// The returned names still need to be interned.
+    pub fn erase(self) -> ast::Term<Name> {
+        match self {
+            Term::Var(text) => ast::Term::Var(Name {
+                text,
+                unique: 0.into(),
+            }),
+            Term::TyAbs { body, .. } => ast::Term::Delay(Rc::new(body.erase())),
+            Term::Lambda {
+                parameter_name,
+                body,
+                ..
+            } => ast::Term::Lambda {
+                parameter_name: Name {
+                    text: parameter_name,
+                    unique: 0.into(),
+                },
+                body: Rc::new(body.erase()),
+            },
+            Term::Apply { function, argument } => ast::Term::Apply {
+                function: Rc::new(function.erase()),
+                argument: Rc::new(argument.erase()),
+            },
+            Term::Constant(constant) => ast::Term::Constant(constant),
+            Term::TyInst { term, .. } => ast::Term::Force(Rc::new(term.erase())),
+            Term::Unwrap(term) => term.erase(),
+            Term::IWrap { term, .. } => term.erase(),
+            Term::Error(_) => ast::Term::Error,
+            Term::Builtin(builtin) => ast::Term::Builtin(builtin),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn erase_program() {
+        let typed_code = r#"
+        (program 1.0.0
+            [
+                { (abs a (type) (lam x a x)) (con integer) }
+                (iwrap (lam f (fun (type) (type)) f) (con integer) (con integer 42))
+            ]
+        )
+        "#;
+
+        let untyped_code = r#"
+        (program 1.0.0
+            [ (force (delay (lam x x))) (con integer 42) ]
+        )
+        "#;
+
+        let typed_program = parser::typed_program(typed_code).unwrap();
+
+        assert_eq!(
+            typed_program.erase(),
+            parser::program(untyped_code).unwrap()
+        );
+    }
+
+    #[test]
+    fn erase_builtins_and_errors() {
+        let typed_code = r#"
+        (program 1.0.0
+            (lam b (con bool)
+                [
+                    { (builtin ifThenElse) (all r (type) (fun (con (list integer)) r)) }
+                    b
+                    (unwrap (error (con unit)))
+                    (con unit ())
+                ]
+            )
+        )
+        "#;
+
+        let untyped_code = r#"
+        (program 1.0.0
+            (lam b [ (force (builtin ifThenElse)) b (error) (con unit ()) ])
+        )
+        "#;
+
+        let typed_program = parser::typed_program(typed_code).unwrap();
+
+        assert_eq!(
+            typed_program.erase(),
+            parser::program(untyped_code).unwrap()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! JavaScript bindings to the `uplc` crate, for browsers and node.
+//! Build the npm package with `wasm-pack build crates/wasm`.
+//!
+//! Scripts are passed around as bytes: flat encoded for `flatEncode`
+//! and `flatDecode`, or wrapped in CBOR the way they are stored on
+//! chain for `applyParamsToScript` and `evalWithBudget`.
+
+use flat::{de, en};
+use thiserror::Error;
+use uplc::{
+    ast::{DeBruijn, Name, NamedDeBruijn, PlutusVersion, Program},
+    data::PlutusData,
+    machine::cost_model::{CostModels, ExBudget},
+    parser,
+};
+use wasm_bindgen::prelude::*;
+
+/// Thrown to JavaScript as an `Error` named after the variant, e.g.
+/// a `ParseError`, so callers can tell failures apart.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Parse(#[from] parser::Error),
+    #[error("{0}")]
+    Scope(String),
+    #[error(transparent)]
+    Encode(#[from] en::Error),
+    #[error(transparent)]
+    Decode(#[from] de::Error),
+    #[error("Invalid parameters: {0}")]
+    Params(#[from] minicbor::decode::Error),
+    #[error("Expected the parameters to be a list of data")]
+    ParamsNotAList,
+}
+
+impl Error {
+    fn name(&self) -> &'static str {
+        match self {
+            Error::Parse(_) => "ParseError",
+            Error::Scope(_) => "ScopeError",
+            Error::Encode(_) => "EncodeError",
+            Error::Decode(_) => "DecodeError",
+            Error::Params(_) | Error::ParamsNotAList => "ParamsError",
+        }
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+
+        js_error.set_name(error.name());
+
+        js_error.into()
+    }
+}
+
+/// The outcome of `evalWithBudget`, with the execution units that
+/// were consumed even when evaluation failed.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// The term the program evaluated to, in the textual syntax.
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub cpu: i64,
+    pub mem: i64,
+    pub logs: Vec<String>,
+}
+
+/// Parse a program in the textual syntax, returning it's JSON
+/// representation.
+#[wasm_bindgen]
+pub fn parse(source: &str) -> Result<String, Error> {
+    let program = parser::program(source)?;
+
+    Ok(serde_json::to_string(&program).expect("programs serialize to json"))
+}
+
+/// Parse a program in the textual syntax and flat encode it.
+#[wasm_bindgen(js_name = flatEncode)]
+pub fn flat_encode(source: &str) -> Result<Vec<u8>, Error> {
+    let program = Program::<DeBruijn>::try_from(parser::program(source)?)
+        .map_err(|error| Error::Scope(error.to_string()))?;
+
+    Ok(program.to_flat()?)
+}
+
+/// Decode a flat encoded program into the textual syntax.
+#[wasm_bindgen(js_name = flatDecode)]
+pub fn flat_decode(bytes: &[u8]) -> Result<String, Error> {
+    let program = Program::<Name>::try_from(Program::<DeBruijn>::from_flat(bytes)?)
+        .map_err(|error| Error::Scope(error.to_string()))?;
+
+    Ok(program.to_pretty())
+}
+
+/// Apply a parameterized script to `params`, a CBOR list of data,
+/// in order.
+#[wasm_bindgen(js_name = applyParamsToScript)]
+pub fn apply_params_to_script(params: &[u8], script: &[u8]) -> Result<Vec<u8>, Error> {
+    let PlutusData::List(params) = PlutusData::from_cbor(params)? else {
+        return Err(Error::ParamsNotAList);
+    };
+
+    let program = params
+        .into_iter()
+        .fold(Program::<DeBruijn>::from_cbor(script)?, |program, param| {
+            program.apply_data(param)
+        });
+
+    Ok(program.to_cbor()?)
+}
+
+/// Evaluate a script without consuming more than `cpu` and `mem`
+/// execution units, priced like a Plutus V2 script.
+#[wasm_bindgen(js_name = evalWithBudget)]
+pub fn eval_with_budget(script: &[u8], cpu: i64, mem: i64) -> Result<Evaluation, Error> {
+    let program: Program<NamedDeBruijn> = Program::<DeBruijn>::from_cbor(script)?.into();
+
+    let evaluated = program.eval_with_cost_models(
+        PlutusVersion::V2,
+        &CostModels::default(),
+        ExBudget { cpu, mem },
+    );
+
+    let (result, error) = match evaluated.term {
+        Ok(term) => (Some(term.to_pretty()), None),
+        Err(error) => (None, Some(error.to_string())),
+    };
+
+    Ok(Evaluation {
+        result,
+        error,
+        cpu: evaluated.budget.cpu,
+        mem: evaluated.budget.mem,
+        logs: evaluated.logs,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use uplc::{
+        ast::{DeBruijn, Program},
+        data::PlutusData,
+        machine::cost_model::ExBudget,
+        parser,
+    };
+
+    use super::{apply_params_to_script, eval_with_budget, flat_decode, flat_encode, parse, Error};
+
+    fn script(source: &str) -> Vec<u8> {
+        Program::<DeBruijn>::try_from(parser::program(source).unwrap())
+            .unwrap()
+            .to_cbor()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_and_flat_round_trip() {
+        let source = "(program 1.0.0 (lam x [(builtin iData) x]))";
+
+        assert!(parse(source).unwrap().starts_with(r#"{"version":[1,0,0]"#));
+        assert!(matches!(parse("(program 1.0.0"), Err(Error::Parse(_))));
+        assert!(matches!(
+            flat_encode("(program 1.0.0 x)"),
+            Err(Error::Scope(_))
+        ));
+
+        let bytes = flat_encode(source).unwrap();
+
+        assert_eq!(
+            flat_decode(&bytes).unwrap(),
+            "(program 1.0.0 (lam i_0 [ (builtin iData) i_0 ]))"
+        );
+        assert!(matches!(flat_decode(&bytes[..1]), Err(Error::Decode(_))));
+    }
+
+    #[test]
+    fn apply_params_and_eval() {
+        let budget = ExBudget::default();
+
+        let script = script("(program 1.0.0 (lam x (lam y [(builtin unIData) x])))");
+
+        let params = PlutusData::List(vec![
+            PlutusData::Integer(42.into()),
+            PlutusData::ByteString(vec![]),
+        ])
+        .to_cbor();
+
+        let applied = apply_params_to_script(&params, &script).unwrap();
+
+        let evaluation = eval_with_budget(&applied, budget.cpu, budget.mem).unwrap();
+
+        assert_eq!(evaluation.result.as_deref(), Some("(con integer 42)"));
+        assert_eq!(evaluation.error, None);
+        assert!(evaluation.cpu > 0 && evaluation.mem > 0);
+
+        let evaluation = eval_with_budget(&applied, 1, 1).unwrap();
+
+        assert_eq!(evaluation.result, None);
+        assert!(evaluation.error.is_some());
+
+        assert!(matches!(
+            apply_params_to_script(&PlutusData::Integer(1.into()).to_cbor(), &script),
+            Err(Error::ParamsNotAList)
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uplc::ast::{DeBruijn, FakeNamedDeBruijn, Program};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(program) = Program::<DeBruijn>::from_flat(data) {
+        let bytes = program.to_flat().unwrap();
+
+        assert_eq!(Program::<DeBruijn>::from_flat(&bytes).unwrap(), program);
+    }
+
+    let _ = Program::<FakeNamedDeBruijn>::from_cbor(data);
+});
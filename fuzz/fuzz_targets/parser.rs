@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uplc::{
+    ast::{DeBruijn, Program},
+    parser,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(code) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(program) = parser::program(code) else {
+        return;
+    };
+
+    // programs with free variables fail to convert
+    if let Ok(program) = Program::<DeBruijn>::try_from(program) {
+        program.to_flat().unwrap();
+    }
+});